@@ -0,0 +1,69 @@
+//! Sorting a large directory by bytes versus by locale collation, and not at all.
+//!
+//! ```text
+//! cargo bench --bench collate
+//! BESTLS_BENCH_ENTRIES=200000 cargo bench --bench collate
+//! ```
+//!
+//! Names mix cases, accented letters, and digits so the collator cannot take its
+//! ASCII fast path everywhere. Each variant lists the directory with `--compact`
+//! and the report shows the median wall-clock time of the whole run.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+const PREFIXES: [&str; 8] = [
+    "apfel", "Ähnlich", "zebra", "Éclair", "café", "Straße", "ørsted", "Zürich",
+];
+
+fn measure(args: &[&str], dir: &Path) -> Duration {
+    let start = Instant::now();
+    let status = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["--no-color", "--compact"])
+        .args(args)
+        .arg(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .expect("failed to run bestls");
+    assert!(status.success());
+    start.elapsed()
+}
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+fn main() {
+    let entries: usize = std::env::var("BESTLS_BENCH_ENTRIES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(50_000);
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..entries {
+        let prefix = PREFIXES[i % PREFIXES.len()];
+        std::fs::File::create(dir.path().join(format!("{}-{}.txt", prefix, i))).unwrap();
+    }
+
+    println!("{} entries, median of {} runs", entries, RUNS);
+    let variants: [(&str, &[&str]); 3] = [
+        ("byte order", &[]),
+        (
+            "--collate --locale en-US",
+            &["--collate", "--locale", "en-US"],
+        ),
+        ("--sort none", &["--sort", "none"]),
+    ];
+    for (label, args) in variants {
+        let samples = (0..RUNS).map(|_| measure(args, dir.path())).collect();
+        println!(
+            "{:<28} {:>9.1} ms",
+            label,
+            median(samples).as_secs_f64() * 1000.0
+        );
+    }
+}
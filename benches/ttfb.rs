@@ -0,0 +1,71 @@
+//! Time to first byte of a huge directory listing, buffered versus streamed.
+//!
+//! ```text
+//! cargo bench --bench ttfb
+//! BESTLS_BENCH_ENTRIES=500000 cargo bench --bench ttfb
+//! ```
+//!
+//! Each line format runs with the default `--sort name`, which reads and sorts the
+//! whole directory before printing (how every listing worked before streaming), and
+//! with `--sort none`, which writes entries while the directory is read. The report
+//! shows the median time until the first output byte arrives and until the process
+//! exits.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+/// Time until the first byte on stdout, and until exit
+fn measure(args: &[&str], dir: &Path) -> (Duration, Duration) {
+    let start = Instant::now();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .arg("--no-color")
+        .args(args)
+        .arg(dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run bestls");
+    let mut stdout = child.stdout.take().unwrap();
+    let mut byte = [0u8; 1];
+    stdout.read_exact(&mut byte).expect("no output");
+    let first_byte = start.elapsed();
+    std::io::copy(&mut stdout, &mut std::io::sink()).unwrap();
+    child.wait().unwrap();
+    (first_byte, start.elapsed())
+}
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+fn main() {
+    let entries: usize = std::env::var("BESTLS_BENCH_ENTRIES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(100_000);
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..entries {
+        std::fs::File::create(dir.path().join(format!("file{:07}.txt", i))).unwrap();
+    }
+
+    println!("{} entries, median of {} runs", entries, RUNS);
+    println!("{:<28} {:>14} {:>14}", "format", "first byte", "total");
+    for format in [&["-1"][..], &["--compact"], &["--format", "ndjson"]] {
+        for sort in ["name", "none"] {
+            let args = [format, &["--sort", sort]].concat();
+            let (first, total): (Vec<_>, Vec<_>) =
+                (0..RUNS).map(|_| measure(&args, dir.path())).unzip();
+            println!(
+                "{:<28} {:>11.1} ms {:>11.1} ms",
+                args.join(" "),
+                median(first).as_secs_f64() * 1000.0,
+                median(total).as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
@@ -0,0 +1,482 @@
+//! # Archive Listing Module
+//!
+//! This module lists the members of `.zip`, `.tar`, `.tar.gz`, and `.tgz` archives as
+//! [`FileEntry`] rows so that sorting, filtering, and every output format work on them
+//! exactly like on a real directory. It is compiled only with the `archive` cargo feature.
+//!
+//! ## Member Mapping
+//!
+//! - `name`: path of the member inside the archive (without a trailing `/`)
+//! - `path`: the archive path joined with the plain components of the name, so an
+//!   absolute name or `..` never points outside the archive
+//! - `e_type`: derived from the member type (directory, symlink, or file)
+//! - `len_bytes`: uncompressed size
+//! - `modified`, `permissions`, `owner`, `group`: taken from the archive where the
+//!   format stores them, `-` otherwise
+//!
+//! Encrypted zip members are listed with `encrypted: true` instead of failing the
+//! whole archive, since only their metadata is read.
+
+use crate::diag::{self, Code};
+use crate::fsops::{
+    format_mode, is_executable_mode, is_hidden_name, rfc3339, split_name, FileEntry, FileType,
+    TimestampFormat,
+};
+use bytesize::ByteSize;
+use chrono::{DateTime, NaiveDate, Utc};
+use flate2::read::GzDecoder;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// Supported archive formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+/// Detect the archive format from the file extension, falling back to magic bytes.
+pub fn detect_kind(path: &Path) -> io::Result<Option<ArchiveKind>> {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if name.ends_with(".zip") {
+        return Ok(Some(ArchiveKind::Zip));
+    }
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        return Ok(Some(ArchiveKind::TarGz));
+    }
+    if name.ends_with(".tar") {
+        return Ok(Some(ArchiveKind::Tar));
+    }
+
+    // Unknown extension: sniff the header
+    let mut header = [0u8; 262];
+    let mut file = File::open(path)?;
+    let read = file.read(&mut header)?;
+    let header = &header[..read];
+
+    if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+        Ok(Some(ArchiveKind::Zip))
+    } else if header.starts_with(&[0x1f, 0x8b]) {
+        // Any gzip stream starts this way, so only call it a tarball if it holds one
+        let mut inner = [0u8; 262];
+        let read = read_up_to(&mut GzDecoder::new(File::open(path)?), &mut inner);
+        Ok(is_tar_header(&inner[..read]).then_some(ArchiveKind::TarGz))
+    } else if is_tar_header(header) {
+        Ok(Some(ArchiveKind::Tar))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Whether `header` starts with a POSIX tar header
+fn is_tar_header(header: &[u8]) -> bool {
+    header.len() >= 262 && &header[257..262] == b"ustar"
+}
+
+/// Fill as much of `buf` as `reader` allows, treating a read error as the end
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> usize {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => filled += n,
+        }
+    }
+    filled
+}
+
+/// List the members of an archive as file entries.
+///
+/// # Arguments
+///
+/// * `path` - Path to the archive file
+/// * `include_hidden` - Whether to include members whose file name starts with '.'
+/// * `timestamps` - How member modification times are displayed
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened, its format is not recognized,
+/// or the archive index itself is corrupt. Individual unreadable members are
+/// skipped with a warning.
+pub fn list_archive(
+    path: &Path,
+    include_hidden: bool,
+    timestamps: &TimestampFormat,
+) -> io::Result<Vec<FileEntry>> {
+    let kind = detect_kind(path)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("'{}' is not a recognized archive", path.display()),
+        )
+    })?;
+
+    let file = File::open(path)?;
+    let mut entries = match kind {
+        ArchiveKind::Zip => list_zip(path, file)?,
+        ArchiveKind::Tar => list_tar(path, BufReader::new(file))?,
+        ArchiveKind::TarGz => list_tar(path, GzDecoder::new(BufReader::new(file)))?,
+    };
+
+    for entry in &mut entries {
+        if let Some(modified) = entry.modified_time {
+            entry.modified = timestamps.format(modified.into());
+        }
+    }
+
+    if !include_hidden {
+        entries.retain(|e| !e.is_hidden);
+    }
+
+    Ok(entries)
+}
+
+/// Build a [`FileEntry`] for an archive member
+#[allow(clippy::too_many_arguments)]
+fn member_entry(
+    archive: &Path,
+    name: &str,
+    e_type: FileType,
+    len_bytes: u64,
+    modified: Option<DateTime<Utc>>,
+    mode: Option<u32>,
+    owner: Option<String>,
+    group: Option<String>,
+) -> FileEntry {
+    let name = name.trim_end_matches('/').to_string();
+    let (extension, stem) = split_name(&name, &e_type);
+    FileEntry {
+        // The stem keeps the leading dot of a hidden file name
+        is_hidden: is_hidden_name(&stem),
+        is_executable: is_executable_mode(&e_type, mode),
+        extension,
+        stem,
+        path: member_path(archive, &name),
+        name,
+        e_type,
+        len_bytes,
+        human_size: ByteSize(len_bytes).to_string(),
+        // Rendered by `list_archive` once the display format is known
+        modified: String::new(),
+        modified_epoch: modified.map(|m| m.timestamp()),
+        modified_iso: modified.map(|m| rfc3339(m.into())),
+        modified_time: modified.map(SystemTime::from),
+        accessed: String::new(),
+        accessed_epoch: None,
+        accessed_iso: None,
+        accessed_time: None,
+        created: None,
+        created_epoch: None,
+        created_iso: None,
+        created_time: None,
+        permissions: mode.map(format_mode).unwrap_or_else(|| "-".into()),
+        mode,
+        // Members are listed flat, like a non-recursive listing
+        depth: 0,
+        owner: owner.unwrap_or_else(|| "-".into()),
+        group: group.unwrap_or_else(|| "-".into()),
+        uid: None,
+        gid: None,
+        nlink: None,
+        blocks: None,
+        last_commit_hash: None,
+        last_commit_date: None,
+        last_commit_summary: None,
+        encrypted: None,
+        preview: None,
+        is_empty: None,
+        is_broken: None,
+        size_approximate: None,
+        items: None,
+        mime: None,
+        custom_columns: BTreeMap::new(),
+    }
+}
+
+/// The path of a member below `archive`, made of the plain components of its name only.
+///
+/// Names are untrusted: `/etc/hostname` or `../../x` must not make `--exec-column`,
+/// `--full-path`, or an executable check act on a real file outside the archive.
+fn member_path(archive: &Path, name: &str) -> PathBuf {
+    let mut path = archive.to_path_buf();
+    path.extend(Path::new(name).components().filter_map(|c| match c {
+        Component::Normal(part) => Some(part),
+        _ => None,
+    }));
+    path
+}
+
+fn list_zip(path: &Path, file: File) -> io::Result<Vec<FileEntry>> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).map_err(zip_error)?;
+    let mut entries = Vec::with_capacity(archive.len());
+
+    for index in 0..archive.len() {
+        // Raw access reads only the metadata, so encrypted members don't need a password
+        let member = match archive.by_index_raw(index) {
+            Ok(member) => member,
+            Err(e) => {
+                skip_member(path, index, e);
+                continue;
+            }
+        };
+
+        let e_type = if member.is_dir() {
+            FileType::Directory
+        } else if member.is_symlink() {
+            FileType::Symlink
+        } else {
+            FileType::File
+        };
+
+        let modified = member.last_modified().and_then(|dt| {
+            NaiveDate::from_ymd_opt(dt.year().into(), dt.month().into(), dt.day().into())?
+                .and_hms_opt(dt.hour().into(), dt.minute().into(), dt.second().into())
+                .map(|naive| naive.and_utc())
+        });
+
+        let mut entry = member_entry(
+            path,
+            member.name(),
+            e_type,
+            member.size(),
+            modified,
+            member.unix_mode(),
+            None,
+            None,
+        );
+        if member.encrypted() {
+            entry.encrypted = Some(true);
+        }
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+fn list_tar<R: Read>(path: &Path, reader: R) -> io::Result<Vec<FileEntry>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+
+    for (index, member) in archive.entries()?.enumerate() {
+        // A corrupt header ends the stream, so the members before it are still listed
+        let member = match member {
+            Ok(member) => member,
+            Err(e) => {
+                skip_member(path, index, e);
+                continue;
+            }
+        };
+        let header = member.header();
+
+        let entry_type = header.entry_type();
+        let e_type = if entry_type.is_dir() {
+            FileType::Directory
+        } else if entry_type.is_symlink() {
+            FileType::Symlink
+        } else {
+            FileType::File
+        };
+
+        let name = match member.path() {
+            Ok(name) => name.to_string_lossy().into_owned(),
+            Err(e) => {
+                skip_member(path, index, e);
+                continue;
+            }
+        };
+        let modified = header
+            .mtime()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs as i64, 0));
+        let owner = header.username().ok().flatten().map(str::to_string);
+        let group = header.groupname().ok().flatten().map(str::to_string);
+
+        entries.push(member_entry(
+            path,
+            &name,
+            e_type,
+            header.size().unwrap_or(0),
+            modified,
+            header.mode().ok(),
+            owner,
+            group,
+        ));
+    }
+
+    Ok(entries)
+}
+
+/// Warn that the member at `index` cannot be read and is left out of the listing
+fn skip_member(path: &Path, index: usize, e: impl std::fmt::Display) {
+    diag::warning(
+        Code::ArchiveMemberUnreadable,
+        Some(path),
+        format!("skipping archive member #{}: {}", index, e),
+    );
+}
+
+/// Convert a zip error into an `io::Error`, keeping I/O errors intact
+fn zip_error(e: zip::result::ZipError) -> io::Error {
+    match e {
+        zip::result::ZipError::Io(io_err) => io_err,
+        other => io::Error::new(io::ErrorKind::InvalidData, other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn names(entries: &[FileEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.name.as_str()).collect()
+    }
+
+    fn list(path: &Path) -> Vec<FileEntry> {
+        list_archive(path, true, &TimestampFormat::default()).unwrap()
+    }
+
+    /// A tarball with `src/`, `src/main.rs` (13 bytes), and `.hidden`
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_ustar();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "src/", io::empty())
+            .unwrap();
+        for (name, data) in [("src/main.rs", &b"fn main() {}\n"[..]), (".hidden", b"")] {
+            let mut header = tar::Header::new_ustar();
+            header.set_mode(0o644);
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_tar_and_tar_gz() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar = dir.path().join("a.tar");
+        std::fs::write(&tar, tar_bytes()).unwrap();
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&tar_bytes()).unwrap();
+        // No extension, so the kind comes from the contents
+        let tgz = dir.path().join("sniffed");
+        std::fs::write(&tgz, gz.finish().unwrap()).unwrap();
+        assert_eq!(detect_kind(&tgz).unwrap(), Some(ArchiveKind::TarGz));
+
+        for path in [&tar, &tgz] {
+            let entries = list(path);
+            assert_eq!(names(&entries), ["src", "src/main.rs", ".hidden"]);
+            assert_eq!(entries[0].e_type, FileType::Directory);
+            assert_eq!(entries[1].len_bytes, 13);
+            assert_eq!(entries[1].extension.as_deref(), Some("rs"));
+            assert_eq!(entries[1].permissions, "rw-r--r--");
+            assert_eq!(entries[1].path, path.join("src/main.rs"));
+
+            let visible = list_archive(path, false, &TimestampFormat::default()).unwrap();
+            assert_eq!(names(&visible), ["src", "src/main.rs"]);
+        }
+    }
+
+    #[test]
+    fn test_corrupt_tar_member_keeps_earlier_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bytes = tar_bytes();
+        // Break the checksum of the third header (after `src/` and `src/main.rs` with
+        // its one data block)
+        bytes[3 * 512 + 148] ^= 0xff;
+        let path = dir.path().join("broken.tar");
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(names(&list(&path)), ["src", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_zip_and_encrypted_marker() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .unix_permissions(0o644);
+        writer.add_directory("docs/", options).unwrap();
+        writer.start_file("docs/secret.txt", options).unwrap();
+        writer.write_all(b"hello").unwrap();
+        let mut bytes = writer.finish().unwrap().into_inner();
+
+        // The writer cannot encrypt without the aes-crypto feature, so set the
+        // "encrypted" flag of the second central directory record by hand
+        let record = bytes
+            .windows(4)
+            .enumerate()
+            .filter(|(_, w)| *w == b"PK\x01\x02")
+            .nth(1)
+            .unwrap()
+            .0;
+        bytes[record + 8] |= 1;
+        let path = dir.path().join("a.zip");
+        std::fs::write(&path, bytes).unwrap();
+
+        let entries = list(&path);
+        assert_eq!(names(&entries), ["docs", "docs/secret.txt"]);
+        assert_eq!(entries[0].e_type, FileType::Directory);
+        assert_eq!(entries[0].encrypted, None);
+        assert_eq!(entries[1].len_bytes, 5);
+        assert_eq!(entries[1].encrypted, Some(true));
+    }
+
+    #[test]
+    fn test_member_paths_stay_inside_the_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut bytes = Vec::new();
+        {
+            let mut builder = tar::Builder::new(&mut bytes);
+            for name in ["/etc/hostname", "../../x", "./a/../b"] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(0);
+                header.set_mode(0o644);
+                // Write the raw name, which `Builder::append_data` would refuse
+                header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+                header.set_cksum();
+                builder.append(&header, io::empty()).unwrap();
+            }
+            builder.finish().unwrap();
+        }
+        let path = dir.path().join("evil.tar");
+        std::fs::write(&path, &bytes).unwrap();
+
+        let entries = list(&path);
+        // Names are listed as stored
+        assert_eq!(names(&entries), ["/etc/hostname", "../../x", "./a/../b"]);
+        let paths: Vec<PathBuf> = entries.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [path.join("etc/hostname"), path.join("x"), path.join("a/b")]
+        );
+        for entry in &entries {
+            assert!(entry.path.starts_with(&path), "{}", entry.path.display());
+        }
+    }
+
+    #[test]
+    fn test_plain_gzip_is_not_an_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut gz = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(b"just some text\n").unwrap();
+        let path = dir.path().join("notes.txt.gz");
+        std::fs::write(&path, gz.finish().unwrap()).unwrap();
+
+        assert_eq!(detect_kind(&path).unwrap(), None);
+        let err = list_archive(&path, true, &TimestampFormat::default()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -8,6 +8,7 @@
 //!
 //! - [`Cli`]: Main command-line interface structure
 //! - [`SortBy`]: Enumeration for sorting options
+//! - [`SortKey`]: One `--sort` key, optionally descending
 //! - [`Commands`][]: Subcommands (currently just completion generation)
 //!
 //! ## Features
@@ -34,8 +35,11 @@
 //! # Sort by file size
 //! bestls --sort size
 //!
+//! # Directories first, then files by descending size, ties by name
+//! bestls --sort type,-size,name
+//!
 //! # Output as pretty JSON
-//! bestls --json-pretty
+//! bestls --format json-pretty
 //! ```
 //!
 //! ### Shell Completion Generation
@@ -51,10 +55,21 @@
 //! bestls completion fish > ~/.config/fish/completions/bestls.fish
 //! ```
 
-use clap::{CommandFactory, Parser, ValueEnum};
+use clap::{ArgGroup, CommandFactory, Parser, ValueEnum, ValueHint};
 use clap_complete::{generate, Shell};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt;
 use std::io;
 use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::fsops::{
+    parse_duration, parse_size, parse_time_bound, parse_time_style, FileType, TimeBound, TimeStyle,
+};
+use crate::mime::{parse_mime_pattern, MimePattern};
+use crate::perms::{parse_perm, PermSpec};
+use crate::platform::expand_tilde;
 
 /// Main command-line interface structure for bestls.
 ///
@@ -64,10 +79,10 @@ use std::path::PathBuf;
 /// # Arguments
 ///
 /// * `command` - Optional subcommand (currently only completion generation)
-/// * `path` - Directory path to list (defaults to current directory)
+/// * `path` - Directory paths to list (defaults to the current directory)
 /// * `json` - Output in compact JSON format
 /// * `json_pretty` - Output in pretty-printed JSON format
-/// * `sort_by` - Sort files by name, size, or modification date
+/// * `sort_by` - Sort keys (name, size, date, ...), applied in order
 /// * `all` - Include hidden files in the listing
 ///
 /// # Examples
@@ -83,7 +98,7 @@ use std::path::PathBuf;
 ///
 /// // Access parsed values
 /// let include_hidden = cli.all;
-/// let path = cli.path.unwrap_or_else(|| std::path::PathBuf::from("."));
+/// let paths = cli.paths();
 /// ```
 ///
 /// # Shell Integration
@@ -110,6 +125,13 @@ use std::path::PathBuf;
 /// * `Table` - Pretty table format (default)
 /// * `Json` - Compact JSON format
 /// * `JsonPretty` - Pretty-printed JSON format
+/// * `Csv` - A header row and one comma-separated row per entry
+/// * `Tsv` - Like `Csv`, tab-separated and unquoted
+/// * `Yaml` - The JSON documents as YAML
+/// * `Toml` - The JSON documents as TOML, entries as `[[entries]]` tables
+/// * `Ndjson` - One compact JSON object per line
+/// * `Markdown` - GitHub-flavored Markdown table with the table's columns
+/// * `Html` - Standalone HTML `<table>` with the table's columns
 #[derive(Debug, Clone, Copy, ValueEnum)]
 #[clap(rename_all = "kebab-case")]
 pub enum OutputFormat {
@@ -122,6 +144,48 @@ pub enum OutputFormat {
     /// Pretty-printed JSON format
     #[value(name = "json-pretty")]
     JsonPretty,
+    /// `name<TAB>size<TAB>modified` lines for `fzf --ansi`
+    #[value(name = "fzf")]
+    Fzf,
+    /// A header row and one comma-separated row per entry, for spreadsheets
+    #[value(name = "csv")]
+    Csv,
+    /// Tab-separated `csv` columns without quoting; backslashes, tabs, and line
+    /// breaks in fields are escaped (`\\`, `\t`, `\n`, `\r`)
+    #[value(name = "tsv")]
+    Tsv,
+    /// YAML with the same field names and structure as `json`
+    #[value(name = "yaml")]
+    Yaml,
+    /// TOML with the JSON field names: an `[[entries]]` table per entry, unset fields
+    /// left out
+    #[value(name = "toml")]
+    Toml,
+    /// One compact JSON object per entry and line; streamed while reading with
+    /// `--sort none`
+    #[value(name = "ndjson")]
+    Ndjson,
+    /// GitHub-flavored Markdown table with the same columns as `table`, uncolored
+    #[value(name = "markdown", alias = "md")]
+    Markdown,
+    /// Standalone HTML `<table>` with the table's columns and a class per entry type
+    #[value(name = "html")]
+    Html,
+    /// Names only, one per line; chosen by `-1`
+    #[value(skip)]
+    Oneline,
+    /// `ls -l` style columns without borders; chosen by `-l`
+    #[value(skip)]
+    Long,
+    /// Names in columns fitting the terminal width; chosen by `--grid`
+    #[value(skip)]
+    Grid,
+    /// One line per entry from a template; chosen by `--format-string`
+    #[value(skip)]
+    Template,
+    /// Indented tree; chosen by `--tree` in place of the table
+    #[value(skip)]
+    Tree,
 }
 
 #[derive(Debug, Parser)]
@@ -138,11 +202,33 @@ Features:
 
 Usage Examples:
   bestls -p ./src
-  bestls --json --sort size
-  bestls --json-pretty --sort date
+  bestls --format json --sort size
+  bestls --format json-pretty --sort date
   bestls completion bash > ~/.local/share/bash-completion/completions/bestls
+
+fzf:
+  --fzf prints one "name<TAB>size<TAB>modified" line per entry, colored, with no
+  borders, header, or footer. Sorting and filters apply as usual. Pick a file:
+    bestls --fzf --sort date | fzf --ansi --delimiter '\t' --nth 1 | cut -f1
+  or bind it as a shell function:
+    bf() { bestls --fzf "$@" | fzf --ansi --delimiter '\t' --nth 1 | cut -f1; }
+
+Exit status:
+  0    Success; with -q, at least one entry matched
+  1    A path or entry could not be read; with -q, nothing matched
+  2    Invalid arguments or configuration; with -q, nothing matched and a path
+       or entry could not be read
+  3    Stopped at --max-entries
+  130  Interrupted (Ctrl-C)
 "#
 )]
+#[command(group(
+    ArgGroup::new("match_filters")
+        .multiple(true)
+        .args(["glob", "regex", "search", "mime", "filter_name", "filter_ext", "types", "dirs_only", "files_only"])
+))]
+#[command(group(ArgGroup::new("recursion").args(["tree", "recursive"])))]
+#[command(group(ArgGroup::new("null_separated").multiple(true).args(["select", "stdin"])))]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -150,13 +236,32 @@ pub struct Cli {
         short = 'p',
         long = "path",
         value_name = "PATH",
+        value_hint = ValueHint::DirPath,
         help = "Directory path to list files from. Defaults to current directory."
     )]
-    pub path: Option<PathBuf>,
+    pub path: Vec<PathBuf>,
+
+    /// Same as `-p`; appended to `path` by [`Cli::resolve_paths`]
+    #[arg(
+        value_name = "PATH",
+        value_hint = ValueHint::DirPath,
+        help = "Directories to list (same as -p); with several, each listing gets a 'PATH:' header and JSON becomes an object keyed by path. Put -- before names starting with a dash: bestls -- -dir"
+    )]
+    pub path_arg: Vec<PathBuf>,
+
+    #[arg(
+        long = "stdin",
+        conflicts_with_all = ["path", "path_arg"],
+        help = "Read the paths to list from standard input, one per line (NUL-separated with -0), e.g. find . -name node_modules | bestls --stdin. Repeated paths (also as ./dir or dir/) are listed once.",
+        default_value_t = false
+    )]
+    pub stdin: bool,
 
     #[arg(
         short = 'j',
         long = "json",
+        hide = true,
+        conflicts_with_all = ["json_pretty", "format"],
         help = "Output file list in compact JSON format (deprecated, use --format json instead).",
         default_value_t = false
     )]
@@ -164,6 +269,8 @@ pub struct Cli {
 
     #[arg(
         long = "json-pretty",
+        hide = true,
+        conflicts_with = "format",
         help = "Output file list in pretty-printed JSON format (deprecated, use --format json-pretty instead).",
         default_value_t = false
     )]
@@ -172,11 +279,84 @@ pub struct Cli {
     #[arg(
         short = 's',
         long = "sort",
-        value_enum,
+        value_name = "KEYS",
+        value_parser = parse_sort_key,
+        value_delimiter = ',',
+        allow_hyphen_values = true,
         default_value = "name",
-        help = "Sort files by the given attribute."
+        help = "Sort by comma-separated keys, applied in order: name, size, date, depth, extension, type, natural, created, accessed, none. Prefix a key with - to sort it descending (e.g. type,-size)."
+    )]
+    pub sort_by: Vec<SortKey>,
+
+    #[arg(
+        short = 'r',
+        long = "reverse",
+        help = "Reverse the sort order (e.g., largest first with --sort size).",
+        default_value_t = false
+    )]
+    pub reverse: bool,
+
+    #[arg(
+        short = 'i',
+        long = "ignore-case",
+        help = "Compare names case-insensitively when sorting (README.md after cargo.toml).",
+        default_value_t = false
+    )]
+    pub ignore_case: bool,
+
+    #[arg(
+        long = "collate",
+        help = "Sort names in the collation order of the locale (LC_ALL/LC_COLLATE/LANG or --locale) instead of by bytes.",
+        default_value_t = false
+    )]
+    pub collate: bool,
+
+    #[arg(
+        long = "locale",
+        value_name = "LOCALE",
+        requires = "collate",
+        help = "Locale for --collate (e.g. de-DE, sv_SE.UTF-8)."
     )]
-    pub sort_by: SortBy,
+    pub locale: Option<String>,
+
+    #[arg(
+        long = "group-directories-first",
+        help = "List directories before other entries, keeping the sort order within each group.",
+        default_value_t = false
+    )]
+    pub group_directories_first: bool,
+
+    #[arg(
+        long = "dereference-dirs",
+        requires = "group_directories_first",
+        conflicts_with = "dirs_last",
+        help = "With --group-directories-first, group symlinks to directories with the directories.",
+        default_value_t = false
+    )]
+    pub dereference_dirs: bool,
+
+    #[arg(
+        long = "dirs-last",
+        conflicts_with = "group_directories_first",
+        help = "List directories after other entries (symlinks count as files), keeping the sort order within each group.",
+        default_value_t = false
+    )]
+    pub dirs_last: bool,
+
+    #[arg(
+        long = "hidden-first",
+        conflicts_with = "hidden_last",
+        help = "List hidden entries (names starting with .) before the others, keeping the sort order within each group.",
+        default_value_t = false
+    )]
+    pub hidden_first: bool,
+
+    #[arg(
+        long = "hidden-last",
+        help = "List hidden entries (names starting with .) after the others, keeping the sort order within each group.",
+        default_value_t = false
+    )]
+    pub hidden_last: bool,
 
     #[arg(
         short = 'a',
@@ -193,29 +373,123 @@ pub struct Cli {
     )]
     pub compact: bool,
 
+    #[arg(
+        short = '1',
+        long = "oneline",
+        conflicts_with_all = ["json", "json_pretty", "fzf", "format", "tree"],
+        help = "Print only names, one per line like ls -1; colored by type on a terminal.",
+        default_value_t = false
+    )]
+    pub oneline: bool,
+
+    #[arg(
+        short = 'l',
+        long = "long",
+        conflicts_with_all = ["json", "json_pretty", "fzf", "format", "tree", "oneline", "compact"],
+        help = "Print aligned ls -l style columns (mode, links, owner, group, size, date, name) without borders, after a total line.",
+        default_value_t = false
+    )]
+    pub long: bool,
+
+    #[arg(
+        long = "grid",
+        conflicts_with_all = ["json", "json_pretty", "fzf", "format", "tree", "oneline", "long", "compact"],
+        help = "Lay names out in columns fitting the terminal, like plain ls; one per line when stdout is not a terminal.",
+        default_value_t = false
+    )]
+    pub grid: bool,
+
+    #[arg(
+        long = "width",
+        value_name = "COLS",
+        requires = "grid",
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Lay out --grid for this many terminal columns instead of the detected width, also when stdout is not a terminal."
+    )]
+    pub width: Option<u16>,
+
+    #[arg(
+        long = "print0",
+        requires = "oneline",
+        conflicts_with_all = ["summary", "watch", "select"],
+        help = "With -1, end each entry with a NUL byte instead of a newline and write names exactly as stored, uncolored (like find -print0, for xargs -0).",
+        default_value_t = false
+    )]
+    pub print0: bool,
+
+    #[arg(
+        long = "full-path",
+        requires = "oneline",
+        help = "With -1, print each entry's absolute path instead of its name.",
+        default_value_t = false
+    )]
+    pub full_path: bool,
+
     #[arg(
         long = "columns",
         value_name = "COLS",
-        help = "Comma-separated columns to display: name,type,size,date,permissions,owner,group"
+        help = "Comma-separated table columns to display, in this order: name, type, size, size_bar, items, modified (or date), created, permissions, owner, group, depth, last_commit, preview, or an --exec-column label (e.g., name,size,modified)."
     )]
     pub columns: Option<String>,
 
     #[arg(
+        short = 'o',
         long = "out",
+        visible_alias = "output",
+        value_hint = ValueHint::FilePath,
         value_name = "FILE",
-        help = "Export output to file instead of stdout."
+        help = "Export output to file instead of stdout (replaced atomically), uncolored unless --color always."
     )]
     pub output_file: Option<std::path::PathBuf>,
 
+    #[arg(
+        long = "mkdir",
+        requires = "output_file",
+        help = "Create the missing parent directories of the --out file.",
+        default_value_t = false
+    )]
+    pub mkdir: bool,
+
+    #[arg(
+        long = "append",
+        requires = "output_file",
+        help = "Append to the --out file under an advisory lock instead of replacing it (for NDJSON).",
+        default_value_t = false
+    )]
+    pub append: bool,
+
     #[arg(
         long = "format",
         value_name = "FORMAT",
         value_enum,
         default_value = "table",
-        help = "Output format: table, json, or json-pretty (legacy --json/--json-pretty flags override this for backward compatibility)"
+        help = "Output format: table, json, json-pretty, fzf, csv, tsv, yaml, toml, ndjson, markdown, or html"
     )]
     pub format: OutputFormat,
 
+    #[arg(
+        long = "format-string",
+        value_name = "TEMPLATE",
+        conflicts_with_all = ["json", "json_pretty", "fzf", "format", "tree", "oneline", "long", "grid", "compact"],
+        help = "Print one line per entry from a template such as '{name:color}\\t{human_size}\\t{modified}': {field} is any JSON field or {path}, :color colors it like the name, {{ and }} are literal braces, and \\t, \\n, \\\\ are escapes."
+    )]
+    pub format_string: Option<String>,
+
+    #[arg(
+        long = "fzf",
+        conflicts_with_all = ["json", "json_pretty", "format"],
+        help = "Shorthand for --format fzf: one colored name<TAB>size<TAB>modified line per entry, for fzf --ansi.",
+        default_value_t = false
+    )]
+    pub fzf: bool,
+
+    #[arg(
+        long = "no-header",
+        help = "Leave out the header row of tables, CSV, and TSV, and the total line of -l.",
+        default_value_t = false
+    )]
+    pub no_header: bool,
+
     #[arg(
         long = "no-color",
         help = "Disable colored output.",
@@ -223,21 +497,130 @@ pub struct Cli {
     )]
     pub no_color: bool,
 
+    #[arg(
+        long = "color",
+        value_name = "WHEN",
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help = "When to use colors: auto (when standard output is a terminal that supports them), always, never."
+    )]
+    pub color: ColorChoice,
+
+    #[arg(
+        short = 'v',
+        long = "verbose",
+        help = "Print diagnostic details (such as terminal capability detection) to stderr.",
+        default_value_t = false
+    )]
+    pub verbose: bool,
+
+    #[arg(
+        long = "error-format",
+        value_name = "FORMAT",
+        value_enum,
+        global = true,
+        default_value_t = ErrorFormat::Human,
+        help = "Diagnostics on stderr as human text or NDJSON objects (json); `bestls errors` lists the codes."
+    )]
+    pub error_format: ErrorFormat,
+
     #[arg(
         long = "tree",
-        help = "Display directory tree (recursive listing).",
+        help = "Display a recursive listing as an indented tree, like the tree command (JSON output stays a flat list with depths).",
         default_value_t = false
     )]
     pub tree: bool,
 
+    #[arg(
+        long = "sizes",
+        requires = "tree",
+        help = "With --tree, show each entry's size before its name.",
+        default_value_t = false
+    )]
+    pub sizes: bool,
+
+    #[arg(
+        short = 'R',
+        long = "recursive",
+        help = "List subdirectories recursively as one flat listing, naming entries by their path relative to the listed directory. Symlinked directories are not followed unless --follow-symlinks is given.",
+        default_value_t = false
+    )]
+    pub recursive: bool,
+
+    #[arg(
+        long = "follow-symlinks",
+        requires = "recursion",
+        help = "With --tree or -R, descend into symlinked directories (and Windows junctions). Entries keep the path they were reached by; a link leading back into a directory being listed is skipped with a warning.",
+        default_value_t = false
+    )]
+    pub follow_symlinks: bool,
+
+    #[arg(
+        long = "flat",
+        requires = "recursive",
+        conflicts_with = "group_by_dir",
+        help = "With -R, show one table for the whole walk, naming entries by their relative path and sorting them together (the default).",
+        default_value_t = false
+    )]
+    pub flat: bool,
+
+    #[arg(
+        long = "group-by-dir",
+        requires = "recursive",
+        conflicts_with = "watch",
+        help = "With -R, show one table per directory under a 'DIR:' heading, like ls -R; entries are sorted within each directory. JSON stays one flat list.",
+        default_value_t = false
+    )]
+    pub group_by_dir: bool,
+
+    #[arg(
+        long = "one-file-system",
+        requires = "recursion",
+        help = "With --tree or -R, do not descend into directories on another filesystem (mounts such as /proc or network shares, other volumes on Windows); mount points are still listed.",
+        default_value_t = false
+    )]
+    pub one_file_system: bool,
+
     #[arg(
         long = "depth",
         value_name = "N",
-        requires = "tree",
-        help = "Maximum depth for tree traversal (requires --tree)."
+        requires = "recursion",
+        help = "Maximum depth for tree traversal (requires --tree or -R)."
     )]
     pub depth: Option<usize>,
 
+    #[arg(
+        long = "min-depth",
+        value_name = "N",
+        requires = "recursion",
+        help = "With --tree or -R, hide entries shallower than N (0 = the directory's own entries)."
+    )]
+    pub min_depth: Option<usize>,
+
+    #[arg(
+        long = "max-depth",
+        value_name = "N",
+        requires = "recursion",
+        help = "With --tree or -R, hide entries deeper than N (0 = the directory's own entries)."
+    )]
+    pub max_depth: Option<usize>,
+
+    #[arg(
+        long = "max-entries",
+        value_name = "N",
+        default_value_t = 1_000_000,
+        help = "Stop gathering after N entries to bound memory (0 = unlimited)."
+    )]
+    pub max_entries: usize,
+
+    #[arg(
+        long = "limit",
+        value_name = "N",
+        default_value_t = 0,
+        help = "Only show the first N entries after sorting and filtering, and note how many were left out (0 = all)."
+    )]
+    pub limit: usize,
+
     #[arg(
         long = "filter-ext",
         value_name = "EXT",
@@ -252,19 +635,464 @@ pub struct Cli {
     )]
     pub filter_name: Option<String>,
 
+    #[arg(
+        long = "glob",
+        value_name = "PATTERN",
+        help = "Only list entries whose name matches PATTERN (e.g. '*.rs'). Repeat to match any of several patterns."
+    )]
+    pub glob: Vec<String>,
+
+    #[arg(
+        long = "case-sensitive",
+        help = "Match --glob patterns case-sensitively on Windows too (the default elsewhere).",
+        default_value_t = false
+    )]
+    pub case_sensitive: bool,
+
+    #[arg(
+        long = "exclude",
+        value_name = "PATTERN",
+        help = "Leave out entries matching the glob PATTERN, and everything below excluded directories. Patterns with '/' match the relative path, others the name. Repeatable."
+    )]
+    pub exclude: Vec<String>,
+
+    #[arg(
+        long = "regex",
+        value_name = "EXPR",
+        help = "Only list entries whose name matches the regular expression EXPR (unanchored; use ^ and $ to match the whole name)."
+    )]
+    pub regex: Option<String>,
+
+    #[arg(
+        long = "search",
+        value_name = "TEXT",
+        value_parser = clap::builder::NonEmptyStringValueParser::new(),
+        help = "Only list entries whose name contains TEXT, ignoring case; matches are highlighted in colored tables. Repeat to match any of several."
+    )]
+    pub search: Vec<String>,
+
+    #[arg(
+        long = "mime",
+        value_name = "TYPE",
+        value_parser = parse_mime_pattern,
+        help = "Only list entries of the MIME type TYPE, exact (text/plain) or a whole top-level type (image/*); detected from the name unless --deep-mime. Repeat to match any of several."
+    )]
+    pub mime: Vec<MimePattern>,
+
+    #[arg(
+        long = "deep-mime",
+        requires = "mime",
+        help = "Detect MIME types of names the extension table does not know from their first bytes (magic numbers, #! lines, text).",
+        default_value_t = false
+    )]
+    pub deep_mime: bool,
+
+    #[arg(
+        long = "invert-match",
+        requires = "match_filters",
+        help = "Flip the combined result of --glob, --regex, --search, --mime, --filter-name, --filter-ext, --type, --dirs-only, and --files-only, like grep -v. Size, time, and other attribute filters still apply as given.",
+        default_value_t = false
+    )]
+    pub invert_match: bool,
+
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        value_enum,
+        value_delimiter = ',',
+        help = "Only list entries of these types (file, dir, symlink). Repeatable or comma-separated."
+    )]
+    pub types: Vec<EntryType>,
+
+    #[arg(
+        long = "dirs-only",
+        conflicts_with = "files_only",
+        help = "Only list directories (shorthand for --type dir).",
+        default_value_t = false
+    )]
+    pub dirs_only: bool,
+
+    #[arg(
+        long = "files-only",
+        help = "Only list files, counting symlinks as files unless --type is given.",
+        default_value_t = false
+    )]
+    pub files_only: bool,
+
+    #[arg(
+        long = "executable",
+        help = "Only list executable files: any execute bit set on Unix, an .exe/.bat/.cmd/.ps1/.com extension on Windows.",
+        default_value_t = false
+    )]
+    pub executable: bool,
+
+    #[arg(
+        long = "dereference-executable",
+        requires = "executable",
+        help = "With --executable, also list symlinks whose target is an executable file.",
+        default_value_t = false
+    )]
+    pub dereference_executable: bool,
+
+    #[arg(
+        long = "empty",
+        help = "Only list zero-byte files and directories without entries (hidden ones count).",
+        default_value_t = false
+    )]
+    pub empty: bool,
+
+    #[arg(
+        long = "broken-only",
+        help = "Only list symlinks whose target does not exist.",
+        default_value_t = false
+    )]
+    pub broken_only: bool,
+
+    #[arg(
+        long = "no-backups",
+        help = "Hide editor backup and temp files: names ending in ~, .swp, .swo, .bak, or .tmp, and #autosave# files. More suffixes can be added under [filters] backup_patterns in the config.",
+        default_value_t = false
+    )]
+    pub no_backups: bool,
+
+    #[arg(
+        long = "owner",
+        value_name = "USER",
+        help = "Only list entries owned by USER (a user name or numeric uid)."
+    )]
+    pub owner: Option<String>,
+
+    #[arg(
+        long = "group",
+        value_name = "GROUP",
+        help = "Only list entries whose group is GROUP (a group name or numeric gid)."
+    )]
+    pub group: Option<String>,
+
     #[arg(
         long = "min-size",
         value_name = "SIZE",
-        help = "Filter files with minimum size (e.g., 1KB, 1MB, 100B)."
+        value_parser = parse_size,
+        help = "Filter files with minimum size (e.g., 100B, 10K, 1MB, 2GiB)."
     )]
-    pub min_size: Option<String>,
+    pub min_size: Option<u64>,
 
     #[arg(
         long = "max-size",
         value_name = "SIZE",
-        help = "Filter files with maximum size (e.g., 1KB, 1MB, 100B)."
+        value_parser = parse_size,
+        help = "Filter files with maximum size (e.g., 100B, 10K, 1MB, 2GiB)."
+    )]
+    pub max_size: Option<u64>,
+
+    #[arg(
+        long = "total-size",
+        help = "Show the total size of the files below each directory instead of the directory's own size (like du), so --sort size finds what is big. Symlinks are not followed; a total missing unreadable parts is shown with a leading ~.",
+        default_value_t = false
+    )]
+    pub total_size: bool,
+
+    #[arg(
+        long = "count-items",
+        help = "Add an Items column with the number of entries directly inside each directory (hidden ones only with -a); ? marks directories that cannot be read.",
+        default_value_t = false
     )]
-    pub max_size: Option<String>,
+    pub count_items: bool,
+
+    #[arg(
+        long = "size-filter-dirs",
+        help = "Apply --min-size and --max-size to directories too (by default directories always pass, since their size is not their contents).",
+        default_value_t = false
+    )]
+    pub size_filter_dirs: bool,
+
+    #[arg(
+        long = "perm",
+        value_name = "SPEC",
+        value_parser = parse_perm,
+        allow_hyphen_values = true,
+        help = "Filter by permission bits like find -perm: 644 (exact), -4000 (all of), /o+w (any of), or o+w / g-x (just the bits mentioned). Unix only."
+    )]
+    pub perm: Option<PermSpec>,
+
+    #[arg(
+        long = "newer-than-file",
+        value_hint = ValueHint::AnyPath,
+        value_name = "PATH",
+        help = "Only show entries modified after the reference file at PATH."
+    )]
+    pub newer_than_file: Option<PathBuf>,
+
+    #[arg(
+        long = "older-than-file",
+        value_hint = ValueHint::AnyPath,
+        value_name = "PATH",
+        help = "Only show entries modified before the reference file at PATH."
+    )]
+    pub older_than_file: Option<PathBuf>,
+
+    #[arg(
+        long = "newer-than",
+        value_name = "WHEN",
+        value_parser = parse_time_bound,
+        conflicts_with = "newer_than_file",
+        help = "Only show entries modified after WHEN: a duration ago (30m, 2h, 7d, 1w) or a date (2024-01-01)."
+    )]
+    pub newer_than: Option<TimeBound>,
+
+    #[arg(
+        long = "older-than",
+        value_name = "WHEN",
+        value_parser = parse_time_bound,
+        conflicts_with = "older_than_file",
+        help = "Only show entries modified before WHEN: a duration ago (30m, 2h, 7d, 1w) or a date (2024-01-01)."
+    )]
+    pub older_than: Option<TimeBound>,
+
+    #[arg(
+        long = "git-log",
+        help = "Show the last commit (hash, relative date, summary) touching each entry.",
+        default_value_t = false
+    )]
+    pub git_log: bool,
+
+    #[arg(
+        long = "created",
+        help = "Add a Created column with each entry's creation (birth) time, or - where the filesystem does not record it.",
+        default_value_t = false
+    )]
+    pub created: bool,
+
+    #[arg(
+        long = "git-log-depth",
+        value_name = "N",
+        requires = "git_log",
+        default_value_t = 1000,
+        help = "Maximum number of commits to inspect for --git-log."
+    )]
+    pub git_log_depth: usize,
+
+    #[arg(
+        long = "archive",
+        help = "Treat PATH as an archive (.zip, .tar, .tar.gz, .tgz) and list its members.",
+        default_value_t = false
+    )]
+    pub archive: bool,
+
+    #[arg(
+        long = "exec-column",
+        value_name = "LABEL:CMD",
+        help = "Add a column filled with the trimmed stdout of CMD run per entry ({} is replaced by the path). Repeatable."
+    )]
+    pub exec_columns: Vec<String>,
+
+    #[arg(
+        long = "exec-jobs",
+        value_name = "N",
+        default_value_t = 4,
+        help = "Maximum number of --exec-column commands running at once."
+    )]
+    pub exec_jobs: usize,
+
+    #[arg(
+        long = "exec-timeout",
+        value_name = "SECS",
+        default_value_t = 5,
+        help = "Per-command timeout in seconds for --exec-column."
+    )]
+    pub exec_timeout: u64,
+
+    #[arg(
+        long = "allow-local-exec",
+        help = "Allow exec columns defined in a per-directory .bestls.toml to run.",
+        default_value_t = false
+    )]
+    pub allow_local_exec: bool,
+
+    #[arg(
+        long = "preview",
+        help = "Show the first line (up to 120 bytes) of text files in a Preview column.",
+        default_value_t = false
+    )]
+    pub preview: bool,
+
+    #[arg(
+        long = "watch",
+        help = "Keep running and redraw the listing when entries change (200 ms after the last change), highlighting what changed. Sorting and filters apply on every redraw; Ctrl-C exits.",
+        default_value_t = false
+    )]
+    pub watch: bool,
+
+    #[arg(
+        long = "select",
+        conflicts_with_all = ["watch", "output_file", "json", "json_pretty", "fzf"],
+        help = "Pick entries interactively (on stderr) and print their paths to stdout, e.g. vim $(bestls --select).",
+        default_value_t = false
+    )]
+    pub select: bool,
+
+    #[arg(
+        short = '0',
+        long = "null",
+        requires = "null_separated",
+        help = "Use NUL instead of newline to terminate --select output paths and to separate --stdin input paths (for find -print0 and xargs -0).",
+        default_value_t = false
+    )]
+    pub null: bool,
+
+    #[arg(
+        long = "watch-log",
+        requires = "watch",
+        help = "With --watch, append one NDJSON event per change instead of redrawing.",
+        default_value_t = false
+    )]
+    pub watch_log: bool,
+
+    #[arg(
+        long = "size-bars",
+        value_name = "SCALE",
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "max",
+        help = "Add a bar column scaled against the largest entry (max) or the listing total (total)."
+    )]
+    pub size_bars: Option<SizeBarScale>,
+
+    #[arg(
+        short = 'H',
+        long = "dereference-command-line",
+        conflicts_with = "no_dereference",
+        help = "Follow PATH when it is a symlink and list its target's contents (the default).",
+        default_value_t = false
+    )]
+    pub dereference_command_line: bool,
+
+    #[arg(
+        short = 'd',
+        long = "directory",
+        conflicts_with_all = ["recursive", "tree", "watch"],
+        help = "List each PATH itself as a single entry instead of its contents, like ls -d (bestls -d */).",
+        default_value_t = false
+    )]
+    pub directory: bool,
+
+    #[arg(
+        long = "no-dereference",
+        help = "When PATH is a symlink, list the link itself as a single entry.",
+        default_value_t = false
+    )]
+    pub no_dereference: bool,
+
+    #[arg(
+        long = "fields",
+        value_name = "FIELDS",
+        help = "Only include these comma-separated JSON fields, in this order, in JSON, NDJSON, YAML, TOML, and CSV/TSV output (e.g., name,len_bytes). Tables ignore it; use --columns."
+    )]
+    pub fields: Option<String>,
+
+    #[arg(
+        long = "envelope",
+        help = "Wrap JSON output in an object with listing metadata: {\"entries\": [...], \"partial\": false, \"truncated\": false, \"errors\": [...]}, where errors lists what could not be read.",
+        default_value_t = false
+    )]
+    pub envelope: bool,
+
+    #[arg(
+        long = "disk-usage-footer",
+        help = "Show total, used, and available space of the listed path's filesystem below the table (and under \"filesystem\" in --envelope JSON).",
+        default_value_t = false
+    )]
+    pub disk_usage_footer: bool,
+
+    #[arg(
+        long = "summary",
+        help = "Print only the totals of the listing: \"128 files, 14 directories, 3 symlinks, 2.4 GB total\" (a JSON object with --json). Filters, -a, and -R apply; the size is that of the files and symlinks.",
+        conflicts_with_all = ["watch", "select", "limit", "fields", "envelope", "disk_usage_footer", "group_by_dir"],
+        default_value_t = false
+    )]
+    pub summary: bool,
+
+    #[arg(
+        short = 'q',
+        long = "quiet",
+        help = "Print nothing and answer through the exit status: 0 if at least one entry matched the filters, 1 if none did, 2 if none did and a path or entry could not be read. For shell conditionals: if bestls -q --glob '*.lock'; then ...",
+        conflicts_with_all = ["watch", "select", "output_file"],
+        default_value_t = false
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        long = "indent",
+        value_name = "N",
+        default_value_t = 2,
+        help = "Indentation width for pretty JSON output."
+    )]
+    pub indent: usize,
+
+    #[arg(
+        long = "owner-format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = OwnerFormat::Separate,
+        help = "Owner/Group table columns: separate, combined (owner:group), or owner-only."
+    )]
+    pub owner_format: OwnerFormat,
+
+    #[arg(
+        long = "style",
+        value_name = "STYLE",
+        value_enum,
+        help = "Table borders: rounded (default), ascii, modern, psql, markdown, blank, or compact. The default can be set under [table] in the config file."
+    )]
+    pub style: Option<TableStyle>,
+
+    #[arg(
+        long = "max-name-width",
+        value_name = "N",
+        num_args = 0..=1,
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Cut table names longer than N terminal columns, ending them with '…'. Without N, on a terminal, names are cut as far as the table needs to fit it. Other formats keep full names."
+    )]
+    pub max_name_width: Option<Option<u16>>,
+
+    #[arg(
+        long = "wrap",
+        value_name = "N",
+        num_args = 0..=1,
+        value_parser = clap::value_parser!(u16).range(1..),
+        conflicts_with = "max_name_width",
+        help = "Wrap table names longer than N terminal columns onto further lines of their row. Without N, on a terminal, names are wrapped as far as the table needs to fit it."
+    )]
+    pub wrap: Option<Option<u16>>,
+
+    #[arg(
+        long = "omit-same-group",
+        help = "With --owner-format combined, show only the owner when the group has the same name.",
+        default_value_t = false
+    )]
+    pub omit_same_group: bool,
+
+    #[arg(
+        long = "date-locale",
+        value_name = "LOCALE",
+        help = "Month and weekday names in the Modified column for LOCALE (e.g. de_DE, fr-FR), or auto to use LC_ALL/LC_TIME/LANG."
+    )]
+    pub date_locale: Option<String>,
+
+    #[arg(
+        long = "time-style",
+        value_name = "STYLE",
+        value_parser = parse_time_style,
+        help = "Modified column style: full-iso, long-iso, iso, locale, relative, or +FORMAT (strftime)."
+    )]
+    pub time_style: Option<TimeStyle>,
+
+    #[arg(
+        long = "respect-dot-hidden",
+        help = "Treat names listed in a directory's .hidden file as hidden (--all still shows them).",
+        default_value_t = false
+    )]
+    pub respect_dot_hidden: bool,
 }
 
 /// Enumeration of available sorting options for file listings.
@@ -278,6 +1106,7 @@ pub struct Cli {
 /// * `Name` - Sort files alphabetically by filename (default)
 /// * `Size` - Sort files by size in bytes (smallest to largest)
 /// * `Date` - Sort files by modification date (oldest to newest)
+/// * `Depth` - Sort entries by nesting depth below the listed directory, then by name
 ///
 /// # Examples
 ///
@@ -315,6 +1144,156 @@ pub enum SortBy {
     Size,
     /// Sort files by modification date (oldest to newest)
     Date,
+    /// Sort entries by nesting depth (shallowest first), then by name
+    Depth,
+    /// Group by extension (case-insensitive): directories, then files without an
+    /// extension, then by extension; ties by name
+    #[value(alias = "ext")]
+    Extension,
+    /// Group by entry type: directories, then symlinks, then files; ties by name
+    Type,
+    /// Sort by name with digit runs compared numerically (`file2` before `file10`)
+    Natural,
+    /// Sort by creation (birth) time (oldest to newest); entries without one go last
+    Created,
+    /// Sort by last access time (least recent first); entries without one go last
+    Accessed,
+    /// Don't sort: keep the order the operating system returns (`--reverse` has no
+    /// effect)
+    None,
+}
+
+/// One `--sort` key: an attribute, in descending order with a `-` prefix
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    pub by: SortBy,
+    pub descending: bool,
+}
+
+/// Error returned when a `--sort` key names no [`SortBy`] value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortKeyParseError(String);
+
+impl fmt::Display for SortKeyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = SortBy::value_variants()
+            .iter()
+            .filter_map(|v| v.to_possible_value())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        write!(
+            f,
+            "unknown sort key '{}' (expected {}, optionally prefixed with -)",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for SortKeyParseError {}
+
+/// Parse one `--sort` key such as `size` or `-size`
+pub fn parse_sort_key(spec: &str) -> Result<SortKey, SortKeyParseError> {
+    let (name, descending) = match spec.strip_prefix('-') {
+        Some(name) => (name, true),
+        None => (spec, false),
+    };
+    let by =
+        SortBy::from_str(name.trim(), true).map_err(|_| SortKeyParseError(spec.to_string()))?;
+    Ok(SortKey { by, descending })
+}
+
+/// Entry type selected by `--type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum EntryType {
+    /// Regular files
+    File,
+    /// Directories
+    #[value(alias = "directory")]
+    Dir,
+    /// Symbolic links (not followed)
+    #[value(alias = "link")]
+    Symlink,
+}
+
+impl EntryType {
+    /// The [`FileType`] of the entries this selects
+    pub fn file_type(self) -> FileType {
+        match self {
+            EntryType::File => FileType::File,
+            EntryType::Dir => FileType::Directory,
+            EntryType::Symlink => FileType::Symlink,
+        }
+    }
+}
+
+/// When to emit ANSI colors (`--color`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum ColorChoice {
+    /// Color unless the terminal cannot render ANSI escapes
+    Auto,
+    /// Always color (and keep box-drawing borders)
+    Always,
+    /// Never color
+    Never,
+}
+
+/// Format of diagnostics on stderr (`--error-format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum ErrorFormat {
+    /// `Error: ...` / `Warning: ...` lines
+    #[default]
+    Human,
+    /// One `{"level", "code", "path", "message"}` object per line
+    Json,
+}
+
+/// Layout of the Owner and Group table columns (`--owner-format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+#[clap(rename_all = "kebab-case")]
+pub enum OwnerFormat {
+    /// Separate Owner and Group columns
+    #[default]
+    Separate,
+    /// A single Owner column rendered as "owner:group"
+    Combined,
+    /// Only the Owner column
+    OwnerOnly,
+}
+
+/// Border style of tables (`--style`, or `style` under `[table]` in the config file).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+#[clap(rename_all = "lower")]
+#[serde(rename_all = "lowercase")]
+pub enum TableStyle {
+    /// Box-drawing borders with rounded corners
+    #[default]
+    Rounded,
+    /// Borders of `+`, `-`, and `|`, with a line between all rows
+    Ascii,
+    /// Box-drawing borders with a line between all rows
+    Modern,
+    /// PostgreSQL style: no outer borders, a line under the header
+    Psql,
+    /// Markdown table syntax, with cells escaped
+    Markdown,
+    /// No borders, columns separated by spaces
+    Blank,
+    /// No borders, columns separated by the cell padding alone
+    Compact,
+}
+
+/// Reference used to scale the `--size-bars` column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum SizeBarScale {
+    /// Full bar for the largest entry
+    Max,
+    /// Bar length is the entry's share of the listing total
+    Total,
 }
 
 /// Available subcommands for the bestls CLI.
@@ -377,6 +1356,80 @@ pub enum Commands {
         #[command(subcommand)]
         subcommand: ThemeSubcommand,
     },
+
+    /// Report files not modified within a time window, oldest first.
+    ///
+    /// Shows each entry's age in days and the total reclaimable size, which makes
+    /// it a starting point for cleaning up downloads or build artifacts.
+    Stale {
+        /// Directory to scan
+        #[arg(short = 'p', long = "path", value_name = "PATH", value_hint = ValueHint::DirPath)]
+        path: Option<PathBuf>,
+
+        /// Minimum age of reported entries (e.g., 30d, 12w, 1y)
+        #[arg(
+            long = "older-than",
+            value_name = "AGE",
+            default_value = "180d",
+            value_parser = parse_duration
+        )]
+        older_than: Duration,
+
+        /// Scan subdirectories recursively
+        #[arg(short = 'R', long = "recursive")]
+        recursive: bool,
+
+        /// Include hidden files
+        #[arg(short = 'a', long = "all")]
+        all: bool,
+
+        /// Use the access time instead of the modification time
+        #[arg(long = "access-time")]
+        access_time: bool,
+
+        /// Output the report as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+
+    /// Show the largest or most recently modified files under a directory.
+    ///
+    /// Walks the whole tree and keeps the first `-n` files, largest (or newest)
+    /// first, with their size and modification date.
+    Top {
+        /// Directory to scan
+        #[arg(short = 'p', long = "path", value_name = "PATH", value_hint = ValueHint::DirPath)]
+        path: Option<PathBuf>,
+
+        /// Rank files by size or by modification date
+        #[arg(long = "by", value_enum, default_value = "size")]
+        by: TopBy,
+
+        /// Number of files to show
+        #[arg(short = 'n', long = "count", value_name = "N", default_value_t = 10)]
+        count: usize,
+
+        /// Include hidden files
+        #[arg(short = 'a', long = "all")]
+        all: bool,
+
+        /// Output the report as JSON
+        #[arg(long = "json")]
+        json: bool,
+    },
+
+    /// List the diagnostic codes used by `--error-format json`.
+    Errors,
+}
+
+/// Ranking of `bestls top`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[clap(rename_all = "lower")]
+pub enum TopBy {
+    /// Largest files first
+    Size,
+    /// Most recently modified files first
+    Date,
 }
 
 #[derive(Debug, Parser)]
@@ -396,11 +1449,12 @@ pub enum ThemeSubcommand {
 }
 
 impl Cli {
-    /// Compute the effective output format, honoring legacy flags.
+    /// Compute the effective output format, honoring the shorthand flags.
     ///
-    /// This method resolves the output format by checking legacy flags (`--json`, `--json-pretty`)
-    /// first for backward compatibility, and falling back to the `--format` option if no legacy flags
-    /// are set. This centralizes the logic for handling the "two knobs for one concept" issue.
+    /// The hidden legacy aliases (`--json`, `--json-pretty`) and the shorthands (`--fzf`,
+    /// `-1`, `-l`, `--grid`, `--format-string`) each stand for one format; clap rejects
+    /// them next to an explicit `--format`, so this is the single place a format is
+    /// chosen.
     ///
     /// # Returns
     ///
@@ -410,6 +1464,11 @@ impl Cli {
     ///
     /// - If `--json-pretty` is set, returns `OutputFormat::JsonPretty`
     /// - Else if `--json` is set, returns `OutputFormat::Json`
+    /// - Else if `--fzf` is set, returns `OutputFormat::Fzf`
+    /// - Else if `-1` is set, returns `OutputFormat::Oneline`
+    /// - Else if `-l` is set, returns `OutputFormat::Long`
+    /// - Else if `--grid` is set, returns `OutputFormat::Grid`
+    /// - Else if `--format-string` is set, returns `OutputFormat::Template`
     /// - Otherwise returns the value of `--format`
     ///
     /// # Examples
@@ -420,27 +1479,71 @@ impl Cli {
     ///
     /// // Example with --json flag
     /// // CLI args: bestls --json
-    /// // Result: OutputFormat::Json (legacy flag takes precedence)
+    /// // Result: OutputFormat::Json (hidden alias of --format json)
     ///
     /// // Example with --format option
     /// // CLI args: bestls --format json-pretty
     /// // Result: OutputFormat::JsonPretty
     ///
-    /// // Example with both (legacy takes precedence)
+    /// // Example with both
     /// // CLI args: bestls --format table --json
-    /// // Result: OutputFormat::Json (legacy flag overrides)
+    /// // Result: error, the alias conflicts with --format
     /// ```
     pub fn effective_format(&self) -> OutputFormat {
-        // Legacy flags override `--format` for compatibility
+        // The shorthand flags conflict with `--format`, so at most one of them is set
         if self.json_pretty {
             OutputFormat::JsonPretty
         } else if self.json {
             OutputFormat::Json
+        } else if self.fzf {
+            OutputFormat::Fzf
+        } else if self.oneline {
+            OutputFormat::Oneline
+        } else if self.long {
+            OutputFormat::Long
+        } else if self.grid {
+            OutputFormat::Grid
+        } else if self.format_string.is_some() {
+            OutputFormat::Template
+        } else if self.tree && matches!(self.format, OutputFormat::Table) && !self.compact {
+            OutputFormat::Tree
         } else {
             self.format
         }
     }
 
+    /// Append the positional paths to `path` and expand a leading `~` in path values.
+    ///
+    /// Shells expand `~` only at the start of a word, so `-p=~/src` or a quoted value
+    /// would otherwise reach bestls unexpanded.
+    pub fn resolve_paths(&mut self) {
+        self.path.append(&mut self.path_arg);
+        let paths = [
+            self.output_file.as_mut(),
+            self.newer_than_file.as_mut(),
+            self.older_than_file.as_mut(),
+        ];
+        let stale_path = match &mut self.command {
+            Some(Commands::Stale { path, .. } | Commands::Top { path, .. }) => path.as_mut(),
+            _ => None,
+        };
+        let listed = self.path.iter_mut();
+        for path in listed.chain(paths.into_iter().chain([stale_path]).flatten()) {
+            if let Cow::Owned(expanded) = expand_tilde(path) {
+                *path = expanded;
+            }
+        }
+    }
+
+    /// The directories to list, in command line order (`.` when none is given)
+    pub fn paths(&self) -> Vec<PathBuf> {
+        if self.path.is_empty() {
+            vec![PathBuf::from(".")]
+        } else {
+            self.path.clone()
+        }
+    }
+
     /// Generate and output shell completion scripts to stdout.
     ///
     /// This method creates completion scripts for the specified shell using `clap_complete`.
@@ -479,3 +1582,98 @@ impl Cli {
         generate(shell, &mut cmd, name, &mut io::stdout());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_help_lists_every_key() {
+        let cmd = Cli::command();
+        let help = cmd
+            .get_arguments()
+            .find(|arg| arg.get_id() == "sort_by")
+            .and_then(|arg| arg.get_help())
+            .unwrap()
+            .to_string();
+        for key in SortBy::value_variants() {
+            let name = key.to_possible_value().unwrap().get_name().to_string();
+            assert!(
+                help.contains(&format!(" {},", name)) || help.contains(&format!(" {}.", name)),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_dash_path_after_double_dash() {
+        let cli = Cli::try_parse_from(["bestls", "--", "-weird-dir"]).unwrap();
+        assert_eq!(cli.path_arg, [PathBuf::from("-weird-dir")]);
+
+        let cli = Cli::try_parse_from(["bestls", "stale", "--older-than", "1d"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Stale { .. })));
+
+        let cli = Cli::try_parse_from(["bestls", "top", "--by", "date", "-n", "3"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Top {
+                by: TopBy::Date,
+                count: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_legacy_json_flags_are_format_aliases() {
+        let format = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("bestls").chain(args.iter().copied()))
+                .map(|cli| cli.effective_format())
+        };
+        assert!(matches!(format(&["--json"]), Ok(OutputFormat::Json)));
+        assert!(matches!(format(&["-j"]), Ok(OutputFormat::Json)));
+        assert!(matches!(
+            format(&["--json-pretty"]),
+            Ok(OutputFormat::JsonPretty)
+        ));
+        for conflict in [
+            &["--json", "--json-pretty"][..],
+            &["--json", "--format", "table"],
+            &["--json-pretty", "--format", "csv"],
+            &["--fzf", "--format", "json"],
+        ] {
+            let error = format(conflict).unwrap_err();
+            assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+        }
+    }
+
+    #[test]
+    fn test_resolve_paths() {
+        let home = dirs::home_dir().unwrap();
+        let mut cli = Cli::try_parse_from(["bestls", "~/src", "--out", "~/listing.json"]).unwrap();
+        cli.resolve_paths();
+        assert_eq!(cli.path, [home.join("src")]);
+        assert!(cli.path_arg.is_empty());
+        assert_eq!(cli.output_file, Some(home.join("listing.json")));
+    }
+
+    #[test]
+    fn test_multiple_paths_keep_order() {
+        let mut cli = Cli::try_parse_from(["bestls", "-p", "a", "b", "c"]).unwrap();
+        cli.resolve_paths();
+        assert_eq!(cli.paths(), ["a", "b", "c"].map(PathBuf::from));
+
+        let cli = Cli::try_parse_from(["bestls"]).unwrap();
+        assert_eq!(cli.paths(), [PathBuf::from(".")]);
+    }
+
+    #[test]
+    fn test_completions_complete_directories() {
+        let mut cmd = Cli::command();
+        let mut script = Vec::new();
+        generate(Shell::Zsh, &mut cmd, "bestls", &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--path=[Directory path to list files from. Defaults to current directory.]:PATH:_files -/"));
+    }
+}
@@ -0,0 +1,170 @@
+//! # Collation Module
+//!
+//! Locale-aware name ordering for `--collate`. The default name sort compares bytes,
+//! which puts every uppercase letter before every lowercase one and accented letters
+//! after `z` (`Zebra` before `ähnlich`). With `--collate`, names are compared with the
+//! Unicode Collation Algorithm tailored to a locale:
+//!
+//! - `--locale de-DE` selects the locale explicitly (BCP 47 or POSIX style, `de_DE.UTF-8`)
+//! - otherwise `LC_ALL`, then `LC_COLLATE`, then `LANG` is used
+//! - `C`, `POSIX`, and unknown languages get the root (language-neutral) order
+//!
+//! The collation data is bundled with the `collate` feature (enabled by default);
+//! without it `--collate` is ignored with a warning.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// Error returned when a collator cannot be created
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CollateError {
+    /// The locale is not a valid BCP 47 or POSIX locale name
+    #[cfg_attr(not(feature = "collate"), allow(dead_code))]
+    InvalidLocale(String),
+    /// bestls was built without the `collate` feature
+    #[cfg_attr(feature = "collate", allow(dead_code))]
+    Unsupported,
+}
+
+impl fmt::Display for CollateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollateError::InvalidLocale(name) => write!(f, "invalid collation locale '{}'", name),
+            CollateError::Unsupported => {
+                write!(f, "bestls was built without the `collate` feature")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CollateError {}
+
+/// Compares names in the collation order of a locale
+pub struct NameCollator {
+    #[cfg(feature = "collate")]
+    collator: icu_collator::Collator,
+}
+
+impl NameCollator {
+    /// Create a collator for `locale` (a BCP 47 tag such as `de-DE`, or a POSIX name
+    /// such as `sv_SE.UTF-8`). With `ignore_case`, names differing only in case
+    /// compare equal.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CollateError::InvalidLocale`] for malformed locale names and
+    /// [`CollateError::Unsupported`] without the `collate` feature.
+    #[cfg(feature = "collate")]
+    pub fn new(locale: &str, ignore_case: bool) -> Result<Self, CollateError> {
+        use icu_collator::{Collator, CollatorOptions, Strength};
+
+        let tag = bcp47_tag(locale);
+        let parsed: icu_locid::Locale = if tag.is_empty() {
+            icu_locid::Locale::UND
+        } else {
+            tag.parse()
+                .map_err(|_| CollateError::InvalidLocale(locale.to_string()))?
+        };
+
+        let mut options = CollatorOptions::new();
+        if ignore_case {
+            // Secondary strength still tells accents apart, but not case
+            options.strength = Some(Strength::Secondary);
+        }
+        // The baked data falls back to the root order for languages without tailoring
+        let collator = Collator::try_new(&(&parsed).into(), options)
+            .map_err(|_| CollateError::InvalidLocale(locale.to_string()))?;
+        Ok(Self { collator })
+    }
+
+    /// Collation is not available without the `collate` feature.
+    #[cfg(not(feature = "collate"))]
+    pub fn new(_locale: &str, _ignore_case: bool) -> Result<Self, CollateError> {
+        Err(CollateError::Unsupported)
+    }
+
+    /// Compare two names; names the collation considers equal compare by bytes so the
+    /// order stays deterministic.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "collate")]
+        let ordering = self.collator.compare(a, b);
+        #[cfg(not(feature = "collate"))]
+        let ordering = Ordering::Equal;
+        ordering.then_with(|| a.cmp(b))
+    }
+}
+
+/// Locale name for collation from the environment (`LC_ALL`, then `LC_COLLATE`,
+/// then `LANG`).
+pub fn collate_locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_COLLATE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Turn a POSIX locale name into a BCP 47 tag (`de_DE.UTF-8@euro` -> `de-DE`).
+///
+/// `C` and `POSIX` map to the empty string, meaning the root order.
+#[cfg_attr(not(feature = "collate"), allow(dead_code))]
+fn bcp47_tag(name: &str) -> String {
+    let base = name.split(['.', '@']).next().unwrap_or_default();
+    if base == "C" || base == "POSIX" {
+        return String::new();
+    }
+    base.replace('_', "-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcp47_tag() {
+        assert_eq!(bcp47_tag("de_DE.UTF-8@euro"), "de-DE");
+        assert_eq!(bcp47_tag("sv-SE"), "sv-SE");
+        assert_eq!(bcp47_tag("C.UTF-8"), "");
+        assert_eq!(bcp47_tag("POSIX"), "");
+    }
+
+    #[cfg(feature = "collate")]
+    #[test]
+    fn test_collation_orders() {
+        fn sorted<'a>(collator: &NameCollator, names: &[&'a str]) -> Vec<&'a str> {
+            let mut names = names.to_vec();
+            names.sort_by(|a, b| collator.compare(a, b));
+            names
+        }
+        let names = ["Zebra", "ähnlich", "apfel", "Öl", "oben", "Äpfel", "zoo"];
+
+        let german = NameCollator::new("de_DE.UTF-8", false).unwrap();
+        assert_eq!(
+            sorted(&german, &names),
+            ["ähnlich", "apfel", "Äpfel", "oben", "Öl", "Zebra", "zoo"]
+        );
+
+        // Swedish sorts å, ä, ö as separate letters after z
+        let swedish = NameCollator::new("sv-SE", false).unwrap();
+        assert_eq!(
+            sorted(&swedish, &["öl", "åsa", "ara", "zeta", "äng"]),
+            ["ara", "zeta", "åsa", "äng", "öl"]
+        );
+        let danish = NameCollator::new("da", false).unwrap();
+        assert_eq!(
+            sorted(&danish, &["ørn", "æble", "ål", "zulu", "abe"]),
+            ["abe", "zulu", "æble", "ørn", "ål"]
+        );
+
+        // Case-only differences: lowercase first, or byte order with ignore_case
+        let root = NameCollator::new("C", false).unwrap();
+        assert_eq!(sorted(&root, &["Apple", "apple"]), ["apple", "Apple"]);
+        let folded = NameCollator::new("C", true).unwrap();
+        assert_eq!(sorted(&folded, &["apple", "Apple"]), ["Apple", "apple"]);
+        assert_eq!(folded.compare("Apple", "apple"), Ordering::Less);
+
+        assert!(matches!(
+            NameCollator::new("not a locale!", false),
+            Err(CollateError::InvalidLocale(_))
+        ));
+    }
+}
@@ -15,6 +15,7 @@
 //! file = "bright_cyan"
 //! directory = "bright_blue"
 //! symlink = "bright_magenta"
+//! broken_symlink = "red"
 //!
 //! # Extension-based colors (optional)
 //! [colors.extensions]
@@ -30,7 +31,7 @@
 //! - `bright_black`, `bright_red`, `bright_green`, `bright_yellow`
 //! - `bright_blue`, `bright_magenta`, `bright_cyan`, `bright_white`
 
-use crate::fsops::FileType;
+use crate::fsops::{FileEntry, FileType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -136,6 +137,8 @@ pub struct FileTypeColors {
     pub file: ColorValue,
     pub directory: ColorValue,
     pub symlink: ColorValue,
+    /// Symlinks whose target does not exist
+    pub broken_symlink: ColorValue,
 }
 
 impl Default for FileTypeColors {
@@ -144,6 +147,7 @@ impl Default for FileTypeColors {
             file: ColorValue::BrightCyan,
             directory: ColorValue::BrightBlue,
             symlink: ColorValue::BrightMagenta,
+            broken_symlink: ColorValue::Red,
         }
     }
 }
@@ -158,6 +162,8 @@ pub struct Theme {
     pub extensions: HashMap<String, ColorValue>,
     /// Table column colors
     pub table: TableColors,
+    /// `--disk-usage-footer` colors
+    pub disk_usage: DiskUsageColors,
 }
 
 impl Default for Theme {
@@ -166,6 +172,7 @@ impl Default for Theme {
             file_types: FileTypeColors::default(),
             extensions: default_extension_colors(),
             table: TableColors::default(),
+            disk_usage: DiskUsageColors::default(),
         }
     }
 }
@@ -178,6 +185,8 @@ pub struct TableColors {
     pub size: ColorValue,
     pub date: ColorValue,
     pub header: ColorValue,
+    /// Color of the `--size-bars` column
+    pub size_bar: ColorValue,
 }
 
 impl Default for TableColors {
@@ -187,6 +196,46 @@ impl Default for TableColors {
             size: ColorValue::BrightMagenta,
             date: ColorValue::BrightYellow,
             header: ColorValue::BrightGreen,
+            size_bar: ColorValue::BrightBlue,
+        }
+    }
+}
+
+/// Footer colors by filesystem fullness (`--disk-usage-footer`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DiskUsageColors {
+    /// Below `warning_percent`
+    pub normal: ColorValue,
+    /// From `warning_percent` up to `critical_percent`
+    pub warning: ColorValue,
+    /// From `critical_percent` up
+    pub critical: ColorValue,
+    pub warning_percent: u8,
+    pub critical_percent: u8,
+}
+
+impl Default for DiskUsageColors {
+    fn default() -> Self {
+        Self {
+            normal: ColorValue::BrightGreen,
+            warning: ColorValue::BrightYellow,
+            critical: ColorValue::BrightRed,
+            warning_percent: 75,
+            critical_percent: 90,
+        }
+    }
+}
+
+impl DiskUsageColors {
+    /// Color for a filesystem that is `percent_used` full
+    pub fn for_percent(&self, percent_used: u8) -> ColorValue {
+        if percent_used >= self.critical_percent {
+            self.critical
+        } else if percent_used >= self.warning_percent {
+            self.warning
+        } else {
+            self.normal
         }
     }
 }
@@ -270,6 +319,8 @@ struct ColorConfig {
     extensions: Option<HashMap<String, String>>,
     #[serde(default)]
     table: Option<TableColors>,
+    #[serde(default)]
+    disk_usage: Option<DiskUsageColors>,
 }
 
 impl ThemeConfig {
@@ -292,29 +343,29 @@ impl ThemeConfig {
             theme.table = tc;
         }
 
+        if let Some(du) = self.colors.disk_usage {
+            theme.disk_usage = du;
+        }
+
         theme
     }
 }
 
 /// Get color for a file based on type and extension
-///
-/// Used by library consumers and in tests. Marked with #[allow(dead_code)]
-/// because the binary doesn't use it directly, but it's part of the public API.
-#[allow(dead_code)]
-pub fn get_file_color(file_type: &FileType, filename: &str, theme: &Theme) -> ColorValue {
-    match file_type {
+pub fn get_file_color(entry: &FileEntry, theme: &Theme) -> ColorValue {
+    match entry.e_type {
         FileType::File => {
             // Check extension-based coloring first
-            if let Some(pos) = filename.rfind('.') {
-                let ext = &filename[pos + 1..].to_lowercase();
-                if let Some(color) = theme.extensions.get(ext) {
-                    return *color;
-                }
-            }
-            // Fall back to default file color
-            theme.file_types.file
+            entry
+                .extension
+                .as_ref()
+                .and_then(|ext| theme.extensions.get(ext))
+                .copied()
+                // Fall back to default file color
+                .unwrap_or(theme.file_types.file)
         }
         FileType::Directory => theme.file_types.directory,
+        FileType::Symlink if entry.is_broken == Some(true) => theme.file_types.broken_symlink,
         FileType::Symlink => theme.file_types.symlink,
     }
 }
@@ -338,6 +389,7 @@ pub fn create_sample_config() -> Result<PathBuf, Box<dyn std::error::Error>> {
 file = "bright_cyan"
 directory = "bright_blue"
 symlink = "bright_magenta"
+broken_symlink = "red"
 
 [colors.table]
 # Table column colors
@@ -345,6 +397,15 @@ name = "bright_cyan"
 size = "bright_magenta"
 date = "bright_yellow"
 header = "bright_green"
+size_bar = "bright_blue"
+
+[colors.disk_usage]
+# --disk-usage-footer colors by how full the filesystem is (percent used)
+normal = "bright_green"
+warning = "bright_yellow"
+critical = "bright_red"
+warning_percent = 75
+critical_percent = 90
 
 [colors.extensions]
 # Extension-based file colors (case-insensitive)
@@ -383,18 +444,43 @@ mod tests {
     #[test]
     fn test_get_file_color() {
         let theme = Theme::default();
+        let entry = |e_type, extension: Option<&str>| FileEntry {
+            e_type,
+            extension: extension.map(str::to_string),
+            ..Default::default()
+        };
 
         // Test extension-based coloring
-        let color = get_file_color(&FileType::File, "test.rs", &theme);
+        let color = get_file_color(&entry(FileType::File, Some("rs")), &theme);
         assert_eq!(color, ColorValue::Yellow);
 
         // Test default file color
-        let color = get_file_color(&FileType::File, "test.unknown", &theme);
+        let color = get_file_color(&entry(FileType::File, Some("unknown")), &theme);
+        assert_eq!(color, theme.file_types.file);
+        let color = get_file_color(&entry(FileType::File, None), &theme);
         assert_eq!(color, theme.file_types.file);
 
         // Test directory color
-        let color = get_file_color(&FileType::Directory, "src", &theme);
+        let color = get_file_color(&entry(FileType::Directory, None), &theme);
         assert_eq!(color, theme.file_types.directory);
+
+        // Broken symlinks stand out from working ones
+        let mut link = entry(FileType::Symlink, None);
+        link.is_broken = Some(false);
+        assert_eq!(get_file_color(&link, &theme), theme.file_types.symlink);
+        link.is_broken = Some(true);
+        assert_eq!(get_file_color(&link, &theme), ColorValue::Red);
+    }
+
+    #[test]
+    fn test_disk_usage_thresholds() {
+        let config: ThemeConfig =
+            toml::from_str("[colors.disk_usage]\ncritical_percent = 95\n").unwrap();
+        let colors = config.into_theme().disk_usage;
+        assert_eq!(colors.for_percent(10), ColorValue::BrightGreen);
+        assert_eq!(colors.for_percent(75), ColorValue::BrightYellow);
+        assert_eq!(colors.for_percent(94), ColorValue::BrightYellow);
+        assert_eq!(colors.for_percent(95), ColorValue::BrightRed);
     }
 
     #[test]
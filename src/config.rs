@@ -0,0 +1,155 @@
+//! # Settings Module
+//!
+//! This module loads non-color settings for bestls. Settings come from two places:
+//!
+//! - The user config file `~/.config/bestls/config.toml` (shared with the theme)
+//! - An optional per-directory `.bestls.toml` in the listed directory
+//!
+//! Both files are kept separate rather than merged so callers can decide how much to
+//! trust each source; a local file can come from any checked-out repository.
+//!
+//! ## Example
+//!
+//! ```toml
+//! [listing]
+//! # Treat names listed in a directory's `.hidden` file as hidden
+//! respect_dot_hidden = true
+//!
+//! [exec]
+//! # Custom columns populated by an external command ({} is replaced by the path)
+//! columns = ["Lines:wc -l < {}"]
+//!
+//! [filters]
+//! # Extra name suffixes hidden by --no-backups
+//! backup_patterns = [".orig", ".rej"]
+//!
+//! [table]
+//! # Default for --style
+//! style = "ascii"
+//! ```
+
+use crate::cli::TableStyle;
+use crate::diag::{self, Code};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the per-directory config file
+pub const LOCAL_CONFIG_NAME: &str = ".bestls.toml";
+
+/// Settings read from a single config file
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct FileSettings {
+    /// Listing defaults
+    pub listing: ListingSettings,
+    /// External command column settings
+    pub exec: ExecSettings,
+    /// Filter settings
+    pub filters: FilterSettings,
+    /// Table settings
+    pub table: TableSettings,
+}
+
+/// `[listing]` section: defaults for listing flags
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListingSettings {
+    /// Default for `--respect-dot-hidden`
+    pub respect_dot_hidden: Option<bool>,
+}
+
+/// `[exec]` section: custom columns populated by external commands
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct ExecSettings {
+    /// Column specs in `Label:command {}` form
+    pub columns: Vec<String>,
+}
+
+/// `[filters]` section: additions to the built-in filters
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct FilterSettings {
+    /// Name suffixes hidden by `--no-backups` on top of the built-in ones
+    pub backup_patterns: Vec<String>,
+}
+
+/// `[table]` section: table defaults
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct TableSettings {
+    /// Default for `--style`
+    pub style: Option<TableStyle>,
+}
+
+/// Settings from the user config and the per-directory config
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    /// Settings from `~/.config/bestls/config.toml`
+    pub global: FileSettings,
+    /// Settings from `.bestls.toml` in the listed directory, with its path
+    pub local: Option<(PathBuf, FileSettings)>,
+}
+
+impl Settings {
+    /// Whether `.hidden` files are honored by default (the local file wins over the global one)
+    pub fn respect_dot_hidden(&self) -> bool {
+        self.local
+            .as_ref()
+            .and_then(|(_, local)| local.listing.respect_dot_hidden)
+            .or(self.global.listing.respect_dot_hidden)
+            .unwrap_or(false)
+    }
+
+    /// Default table style, if either file sets one (the local file wins over the global one)
+    pub fn table_style(&self) -> Option<TableStyle> {
+        self.local
+            .as_ref()
+            .and_then(|(_, local)| local.table.style)
+            .or(self.global.table.style)
+    }
+
+    /// Extra `--no-backups` suffixes from both files (the lists are combined)
+    pub fn backup_patterns(&self) -> impl Iterator<Item = &String> {
+        self.global.filters.backup_patterns.iter().chain(
+            self.local
+                .iter()
+                .flat_map(|(_, local)| local.filters.backup_patterns.iter()),
+        )
+    }
+}
+
+/// Path of the user config file (`~/.config/bestls/config.toml`)
+pub fn config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("bestls").join("config.toml"))
+}
+
+/// Load settings for a listing of `dir`.
+///
+/// Missing files yield defaults. Unparseable files are reported as a warning and
+/// otherwise ignored so a broken config never prevents a listing.
+pub fn load_settings(dir: &Path) -> Settings {
+    let global = config_path()
+        .and_then(|path| read_settings(&path))
+        .unwrap_or_default();
+
+    let local_path = dir.join(LOCAL_CONFIG_NAME);
+    let local = read_settings(&local_path).map(|settings| (local_path, settings));
+
+    Settings { global, local }
+}
+
+fn read_settings(path: &Path) -> Option<FileSettings> {
+    let content = std::fs::read_to_string(path).ok()?;
+    match toml::from_str(&content) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            diag::warning(
+                Code::ConfigInvalid,
+                Some(path),
+                format!("ignoring invalid config '{}': {}", path.display(), e),
+            );
+            None
+        }
+    }
+}
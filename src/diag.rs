@@ -0,0 +1,295 @@
+//! # Diagnostics Module
+//!
+//! Every warning and error bestls prints goes through [`emit`], so the output format
+//! is chosen in one place:
+//!
+//! - `--error-format human` (default): `Error: ...`, `Warning: ...`, `Note: ...` lines;
+//!   errors are red when stderr is a terminal
+//! - `--error-format json`: one NDJSON object per diagnostic on stderr
+//!
+//! ## JSON Records
+//!
+//! ```json
+//! {"level":"error","code":"path-unreadable","path":"/root/secret","message":"..."}
+//! ```
+//!
+//! - `level`: `error`, `warning`, `note`, or `info` (`--verbose` details)
+//! - `code`: a stable identifier from [`Code`]; `bestls errors` lists them all
+//! - `path`: the file or directory concerned, or `null`
+//! - `message`: the same text the human format shows
+
+use crate::cli::ErrorFormat;
+use crate::platform::display_path;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::sync::OnceLock;
+use strum::{EnumIter, EnumMessage, IntoEnumIterator};
+
+/// Format selected by `--error-format`, set once at startup
+static FORMAT: OnceLock<ErrorFormat> = OnceLock::new();
+
+/// Whether human-format errors are colored, set once at startup
+static COLOR: OnceLock<bool> = OnceLock::new();
+
+/// Select the diagnostics format for the rest of the process.
+pub fn set_format(format: ErrorFormat) {
+    let _ = FORMAT.set(format);
+}
+
+/// Color human-format errors red for the rest of the process.
+pub fn set_color(enabled: bool) {
+    let _ = COLOR.set(enabled);
+}
+
+/// Severity of a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Error,
+    Warning,
+    Note,
+    /// Details only printed with `--verbose`
+    Info,
+}
+
+/// Stable diagnostic codes.
+///
+/// Codes are part of the JSON interface: existing ones are never renamed or reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, strum::Display, EnumIter, EnumMessage)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Code {
+    #[strum(message = "A command line argument was rejected")]
+    InvalidArgument,
+    #[strum(message = "The listed path or a subdirectory cannot be read")]
+    PathUnreadable,
+    #[strum(message = "The metadata of a single entry cannot be read; the entry is skipped")]
+    EntryUnreadable,
+    #[strum(message = "An archive member cannot be read; the member is skipped")]
+    ArchiveMemberUnreadable,
+    #[strum(message = "--filter-name or --glob is not a valid glob pattern")]
+    InvalidGlobPattern,
+    #[strum(message = "--regex is not a valid regular expression")]
+    InvalidRegex,
+    #[strum(message = "Two filters exclude each other, so nothing can be listed")]
+    FiltersNeverMatch,
+    #[strum(message = "--min-size is not a valid size")]
+    InvalidMinSize,
+    #[strum(message = "--max-size is not a valid size")]
+    InvalidMaxSize,
+    #[strum(message = "--min-size is larger than --max-size")]
+    InvalidSizeRange,
+    #[strum(message = "An --exec-column spec is malformed or uses a reserved label")]
+    InvalidExecColumn,
+    #[strum(message = "--fields names an unknown field")]
+    InvalidFields,
+    #[strum(message = "The --newer-than-file/--older-than-file reference cannot be read")]
+    ReferenceFileUnreadable,
+    #[strum(message = "The --newer-than time is not before the --older-than time")]
+    InvalidTimeWindow,
+    #[strum(message = "A config file cannot be parsed and is ignored")]
+    ConfigInvalid,
+    #[strum(message = "Exec columns from a per-directory .bestls.toml were not run")]
+    LocalExecIgnored,
+    #[strum(message = "Exec columns cannot run")]
+    ExecUnavailable,
+    #[strum(message = "--git-log cannot open a repository")]
+    GitUnavailable,
+    #[strum(message = "An option is not supported on this platform and is ignored (--perm fails)")]
+    UnsupportedOption,
+    #[strum(message = "The theme config file cannot be created, read, or removed")]
+    ThemeConfigFailed,
+    #[strum(message = "The --out file cannot be written")]
+    OutputWriteFailed,
+    #[strum(message = "A --watch-log event cannot be encoded")]
+    EventEncodingFailed,
+    #[strum(message = "The listed path is on a virtual filesystem; sizes are not read")]
+    VirtualFilesystem,
+    #[strum(message = "--date-locale names a locale without data; English is used")]
+    UnknownLocale,
+    #[strum(message = "Terminal color detection details (--verbose)")]
+    TerminalColors,
+    #[strum(message = "Ctrl-C stopped gathering; the output is partial (exit status 130)")]
+    Interrupted,
+    #[strum(message = "--max-entries stopped gathering; the output is partial (exit status 3)")]
+    EntryLimitReached,
+    #[strum(
+        message = "--disk-usage-footer cannot query the filesystem; the footer is omitted (--verbose)"
+    )]
+    DiskUsageUnavailable,
+    #[strum(message = "--select cannot control the terminal (stderr must be a terminal)")]
+    SelectUnavailable,
+    #[strum(message = "--collate cannot use the locale; names sort by bytes")]
+    CollationUnavailable,
+    #[strum(message = "No entry matched the --glob patterns")]
+    NoGlobMatches,
+    #[strum(message = "--limit left out entries; the note says how many")]
+    ListingLimited,
+    #[strum(message = "A path read with --stdin does not exist; it is skipped (exit status 1)")]
+    PathNotFound,
+    #[strum(message = "--stdin cannot read standard input")]
+    StdinUnreadable,
+    #[strum(message = "A symlink leads back into a directory being walked; it is not followed")]
+    SymlinkLoop,
+    #[strum(message = "--watch gets no change notifications; it re-lists every second")]
+    WatchUnavailable,
+    #[strum(message = "--min-depth is larger than --max-depth")]
+    InvalidDepthRange,
+    #[strum(message = "--format-string has an unknown placeholder or unbalanced braces")]
+    InvalidFormatString,
+    #[strum(message = "--columns names an unknown column or one whose flag is missing")]
+    InvalidColumns,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    level: Level,
+    code: Code,
+    path: Option<String>,
+    message: &'a str,
+}
+
+/// Render a diagnostic in the given format (without a trailing newline).
+///
+/// `color` makes human-format errors red.
+fn render(
+    format: ErrorFormat,
+    level: Level,
+    code: Code,
+    path: Option<&Path>,
+    message: &str,
+    color: bool,
+) -> String {
+    match format {
+        ErrorFormat::Human => match level {
+            Level::Error if color => format!("\x1b[31mError: {}\x1b[0m", message),
+            Level::Error => format!("Error: {}", message),
+            Level::Warning => format!("Warning: {}", message),
+            Level::Note => format!("Note: {}", message),
+            Level::Info => message.to_string(),
+        },
+        ErrorFormat::Json => {
+            let record = Record {
+                level,
+                code,
+                path: path.map(|p| display_path(p).to_string_lossy().into_owned()),
+                message,
+            };
+            serde_json::to_string(&record).unwrap_or_else(|_| message.to_string())
+        }
+    }
+}
+
+/// Print a diagnostic to stderr in the selected format.
+pub fn emit(level: Level, code: Code, path: Option<&Path>, message: impl fmt::Display) {
+    let format = FORMAT.get().copied().unwrap_or_default();
+    let color = COLOR.get().copied().unwrap_or(false);
+    eprintln!(
+        "{}",
+        render(format, level, code, path, &message.to_string(), color)
+    );
+}
+
+/// Print an error diagnostic.
+pub fn error(code: Code, path: Option<&Path>, message: impl fmt::Display) {
+    emit(Level::Error, code, path, message);
+}
+
+/// Print a warning diagnostic.
+pub fn warning(code: Code, path: Option<&Path>, message: impl fmt::Display) {
+    emit(Level::Warning, code, path, message);
+}
+
+/// Print a note diagnostic.
+pub fn note(code: Code, path: Option<&Path>, message: impl fmt::Display) {
+    emit(Level::Note, code, path, message);
+}
+
+/// Print a `--verbose` detail.
+pub fn info(code: Code, message: impl fmt::Display) {
+    emit(Level::Info, code, None, message);
+}
+
+/// Table of all codes with their meaning (`bestls errors`).
+pub fn code_listing() -> String {
+    let width = Code::iter()
+        .map(|code| code.to_string().len())
+        .max()
+        .unwrap_or(0);
+    Code::iter()
+        .map(|code| {
+            format!(
+                "{:<width$}  {}",
+                code.to_string(),
+                code.get_message().unwrap_or_default(),
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_json_record() {
+        let line = render(
+            ErrorFormat::Json,
+            Level::Error,
+            Code::PathUnreadable,
+            Some(Path::new("/missing")),
+            "cannot read directory",
+            true,
+        );
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "error");
+        assert_eq!(value["code"], "path-unreadable");
+        assert_eq!(value["path"], "/missing");
+        assert_eq!(value["message"], "cannot read directory");
+
+        let line = render(
+            ErrorFormat::Json,
+            Level::Info,
+            Code::TerminalColors,
+            None,
+            "x",
+            false,
+        );
+        assert!(line.contains("\"path\":null"));
+    }
+
+    #[test]
+    fn test_render_human() {
+        let render_human = |level, color| {
+            render(
+                ErrorFormat::Human,
+                level,
+                Code::ConfigInvalid,
+                None,
+                "bad config",
+                color,
+            )
+        };
+        assert_eq!(render_human(Level::Warning, false), "Warning: bad config");
+        assert_eq!(render_human(Level::Info, false), "bad config");
+        assert_eq!(render_human(Level::Error, false), "Error: bad config");
+        assert_eq!(
+            render_human(Level::Error, true),
+            "\x1b[31mError: bad config\x1b[0m"
+        );
+        // Only errors are colored
+        assert_eq!(render_human(Level::Warning, true), "Warning: bad config");
+    }
+
+    #[test]
+    fn test_codes_match_serialized_names() {
+        for code in Code::iter() {
+            let serialized = serde_json::to_value(code).unwrap();
+            assert_eq!(serialized, code.to_string());
+            assert!(code.get_message().is_some(), "{} has no description", code);
+        }
+    }
+}
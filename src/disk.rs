@@ -0,0 +1,170 @@
+//! # Disk Usage Module
+//!
+//! Reports the size and fill level of the filesystem holding the listed path for
+//! `--disk-usage-footer`, like a one-line `df`:
+//!
+//! ```text
+//! Filesystem: 465.6 GB total, 301.2 GB used, 164.4 GB available (65% used)
+//! ```
+//!
+//! ## Numbers
+//!
+//! - **Unix**: `statvfs`; "used" excludes the blocks reserved for root, and the
+//!   percentage is `used / (used + available)` rounded up, as `df` computes it
+//! - **Windows**: `GetDiskFreeSpaceExW`; "available" honors per-user quotas
+//! - **Other platforms**: not available
+//!
+//! The footer color follows the `[colors.disk_usage]` thresholds of the theme.
+
+use crate::color::DiskUsageColors;
+use bytesize::ByteSize;
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+/// Size and fill level of a filesystem, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FilesystemUsage {
+    pub total_bytes: u64,
+    pub used_bytes: u64,
+    pub available_bytes: u64,
+    pub percent_used: u8,
+}
+
+impl FilesystemUsage {
+    fn new(total_bytes: u64, used_bytes: u64, available_bytes: u64) -> Self {
+        let usable = used_bytes.saturating_add(available_bytes);
+        let percent_used = if usable == 0 {
+            0
+        } else {
+            (used_bytes.saturating_mul(100).div_ceil(usable)).min(100) as u8
+        };
+        Self {
+            total_bytes,
+            used_bytes,
+            available_bytes,
+            percent_used,
+        }
+    }
+}
+
+/// Query the filesystem containing `path`.
+///
+/// # Errors
+///
+/// Returns the OS error when the filesystem cannot be queried (common on network
+/// filesystems), or an error when it reports no size at all.
+#[cfg(unix)]
+pub fn filesystem_usage(path: &Path) -> io::Result<FilesystemUsage> {
+    let stat = nix::sys::statvfs::statvfs(path).map_err(io::Error::from)?;
+    let fragment = stat.fragment_size() as u64;
+    let blocks = stat.blocks() as u64;
+    let free = stat.blocks_free() as u64;
+    let available = stat.blocks_available() as u64;
+    // Pseudo filesystems (proc, sysfs) report zero blocks
+    if blocks == 0 {
+        return Err(io::Error::other("the filesystem reports no size"));
+    }
+
+    Ok(FilesystemUsage::new(
+        blocks.saturating_mul(fragment),
+        blocks.saturating_sub(free).saturating_mul(fragment),
+        available.saturating_mul(fragment),
+    ))
+}
+
+/// Query the filesystem containing `path`.
+///
+/// # Errors
+///
+/// Returns the OS error when the volume cannot be queried (common on network shares).
+#[cfg(windows)]
+pub fn filesystem_usage(path: &Path) -> io::Result<FilesystemUsage> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    // The call wants a directory, so a listed file is queried through its parent
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+    let wide: Vec<u16> = crate::platform::long_path(dir)
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+
+    let (mut available, mut total, mut free) = (0u64, 0u64, 0u64);
+    // SAFETY: `wide` is NUL-terminated and the out pointers are valid for the call
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(FilesystemUsage::new(
+        total,
+        total.saturating_sub(free),
+        available,
+    ))
+}
+
+/// Disk usage is not available on this platform.
+#[cfg(not(any(unix, windows)))]
+pub fn filesystem_usage(_path: &Path) -> io::Result<FilesystemUsage> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "disk usage is not supported on this platform",
+    ))
+}
+
+/// Render the footer line, colored by fullness when `use_color` is set
+pub fn format_footer(usage: &FilesystemUsage, use_color: bool, colors: &DiskUsageColors) -> String {
+    let line = format!(
+        "Filesystem: {} total, {} used, {} available ({}% used)",
+        ByteSize(usage.total_bytes),
+        ByteSize(usage.used_bytes),
+        ByteSize(usage.available_bytes),
+        usage.percent_used
+    );
+    if !use_color {
+        return line;
+    }
+    colors
+        .for_percent(usage.percent_used)
+        .to_tabled_color()
+        .colorize(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_used_rounds_up_like_df() {
+        assert_eq!(FilesystemUsage::new(100, 0, 100).percent_used, 0);
+        assert_eq!(FilesystemUsage::new(100, 1, 199).percent_used, 1);
+        // Reserved blocks count as neither used nor available
+        assert_eq!(FilesystemUsage::new(100, 50, 45).percent_used, 53);
+        assert_eq!(FilesystemUsage::new(100, 100, 0).percent_used, 100);
+        assert_eq!(FilesystemUsage::new(0, 0, 0).percent_used, 0);
+    }
+
+    #[test]
+    fn test_footer() {
+        let usage = FilesystemUsage::new(2048, 1024, 1024);
+        let colors = DiskUsageColors::default();
+        assert_eq!(
+            format_footer(&usage, false, &colors),
+            "Filesystem: 2.0 KB total, 1.0 KB used, 1.0 KB available (50% used)"
+        );
+        assert!(format_footer(&usage, true, &colors).starts_with('\u{1b}'));
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_usage_of_temp_dir() {
+        let usage = filesystem_usage(&std::env::temp_dir()).unwrap();
+        assert!(usage.total_bytes > 0);
+        assert!(usage.used_bytes <= usage.total_bytes);
+    }
+}
@@ -0,0 +1,265 @@
+//! # External Command Columns Module
+//!
+//! This module implements `--exec-column 'Label:command {}'`, which runs a command per
+//! entry and shows its trimmed stdout in a custom column (and a JSON field named after
+//! the label). It is meant for niche metadata such as media duration or EXIF dates.
+//!
+//! ## Execution Model
+//!
+//! - `{}` in the command is replaced by the shell-escaped entry path (appended when absent)
+//! - Commands run through `sh -c` (`cmd /C` on Windows) in a dedicated rayon pool,
+//!   so at most `jobs` commands run at the same time
+//! - Each command is killed after the timeout; failures, timeouts, and non-zero exits
+//!   produce no value (rendered as `-`)
+
+use crate::diag::{self, Code};
+use crate::fsops::FileEntry;
+use crate::interrupt;
+use crate::json::ENTRY_FIELDS;
+use rayon::prelude::*;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A custom column populated by an external command
+#[derive(Debug, Clone)]
+pub struct ExecColumn {
+    /// Column header and JSON field name
+    pub label: String,
+    /// Command template, `{}` is replaced by the entry path
+    pub command: String,
+}
+
+impl ExecColumn {
+    /// Parse a `Label:command {}` spec.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (label, command) = spec
+            .split_once(':')
+            .ok_or_else(|| format!("invalid exec column '{}' (expected LABEL:COMMAND)", spec))?;
+
+        let label = label.trim();
+        let command = command.trim();
+
+        if label.is_empty() {
+            return Err(format!("exec column '{}' has an empty label", spec));
+        }
+        if command.is_empty() {
+            return Err(format!("exec column '{}' has an empty command", label));
+        }
+        // Labels become JSON keys next to the built-in fields
+        if ENTRY_FIELDS.contains(&label.to_lowercase().as_str()) {
+            return Err(format!(
+                "exec column label '{}' clashes with a built-in field",
+                label
+            ));
+        }
+
+        Ok(ExecColumn {
+            label: label.to_string(),
+            command: command.to_string(),
+        })
+    }
+
+    /// Build the shell command line for a given path
+    fn command_line(&self, path: &Path) -> String {
+        let quoted = shell_quote(&path.to_string_lossy());
+        if self.command.contains("{}") {
+            self.command.replace("{}", &quoted)
+        } else {
+            format!("{} {}", self.command, quoted)
+        }
+    }
+}
+
+/// Quote a string so the shell passes it through as a single literal argument
+#[cfg(not(windows))]
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Quote a string so `cmd.exe` passes it through as a single argument
+#[cfg(windows)]
+fn shell_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+/// Run every exec column for every entry and store the results on the entries.
+///
+/// # Arguments
+///
+/// * `entries` - Entries to annotate (results go into `custom_columns`)
+/// * `columns` - Custom columns to compute
+/// * `jobs` - Maximum number of commands running concurrently
+/// * `timeout` - Per-command time limit
+pub fn run_exec_columns(
+    entries: &mut [FileEntry],
+    columns: &[ExecColumn],
+    jobs: usize,
+    timeout: Duration,
+) {
+    if columns.is_empty() {
+        return;
+    }
+
+    let pool = match rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs.max(1))
+        .build()
+    {
+        Ok(pool) => pool,
+        Err(e) => {
+            diag::warning(
+                Code::ExecUnavailable,
+                None,
+                format!("cannot start exec column workers: {}", e),
+            );
+            return;
+        }
+    };
+
+    pool.install(|| {
+        entries.par_iter_mut().for_each(|entry| {
+            for column in columns {
+                // After Ctrl-C the remaining cells stay empty
+                if interrupt::is_cancelled() {
+                    return;
+                }
+                let value = run_with_timeout(&column.command_line(&entry.path), timeout);
+                entry.custom_columns.insert(column.label.clone(), value);
+            }
+        });
+    });
+}
+
+/// Run a shell command, returning its trimmed stdout on success
+fn run_with_timeout(command_line: &str, timeout: Duration) -> Option<String> {
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(command_line);
+        c
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(command_line);
+        c
+    };
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Drain stdout on a separate thread so a chatty command can't block on a full pipe
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf).map(|_| buf)
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(_) => return None,
+        }
+    };
+
+    let output = reader.join().ok()?.ok()?;
+    if !status.success() {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output).trim().to_string();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exec_column() {
+        let column = ExecColumn::parse("Duration: ffprobe {}").unwrap();
+        assert_eq!(column.label, "Duration");
+        assert_eq!(column.command, "ffprobe {}");
+
+        assert!(ExecColumn::parse("no separator").is_err());
+        assert!(ExecColumn::parse(":cmd").is_err());
+        assert!(ExecColumn::parse("Label:").is_err());
+        assert!(ExecColumn::parse("size:du {}").is_ok());
+        assert!(ExecColumn::parse("Name:echo {}").is_err());
+    }
+
+    #[test]
+    fn test_labels_never_shadow_serialized_fields() {
+        // Every optional field set, so the entry serializes all of its keys
+        let entry = FileEntry {
+            last_commit_hash: Some(String::new()),
+            last_commit_date: Some(String::new()),
+            last_commit_summary: Some(String::new()),
+            encrypted: Some(false),
+            preview: Some(String::new()),
+            is_empty: Some(false),
+            is_broken: Some(false),
+            size_approximate: Some(false),
+            items: Some(0),
+            mime: Some(String::new()),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&entry).unwrap();
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut fields = ENTRY_FIELDS.to_vec();
+        keys.sort_unstable();
+        fields.sort_unstable();
+        assert_eq!(keys, fields);
+        for key in keys {
+            let spec = format!("{}:echo hi", key);
+            assert!(ExecColumn::parse(&spec).is_err(), "{}", key);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_line_escapes_path() {
+        let column = ExecColumn::parse("X:cat {}").unwrap();
+        assert_eq!(
+            column.command_line(Path::new("it's here.txt")),
+            r"cat 'it'\''s here.txt'"
+        );
+
+        let column = ExecColumn::parse("X:wc -c").unwrap();
+        assert_eq!(column.command_line(Path::new("a b")), "wc -c 'a b'");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_timeout() {
+        let timeout = Duration::from_secs(5);
+        assert_eq!(
+            run_with_timeout("echo '  hello  '", timeout),
+            Some("hello".to_string())
+        );
+        assert_eq!(run_with_timeout("exit 1", timeout), None);
+        assert_eq!(run_with_timeout("sleep 5", Duration::from_millis(50)), None);
+    }
+}
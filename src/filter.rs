@@ -0,0 +1,1111 @@
+//! # Filter Module
+//!
+//! Decides which entries a listing keeps ([`Filter::matches`]). The filters fall
+//! into two kinds:
+//!
+//! - **Match filters** select entries by name or type: `--glob`, `--regex`,
+//!   `--search`, `--mime`, `--filter-name`, `--filter-ext`, `--type`, `--dirs-only`,
+//!   and `--files-only`.
+//!   They are ANDed together, and `--invert-match` flips their combined result,
+//!   like `grep -v`.
+//! - **Attribute filters** narrow the listing by metadata: sizes, modification
+//!   times, depth, permissions, owner and group, `--executable`, `--empty`,
+//!   `--broken-only`, and `--no-backups`. They are never inverted, so
+//!   `--invert-match --glob '*.rs' --min-size 1K` lists everything but Rust files
+//!   that is at least 1 KiB.
+//!
+//! `--exclude` is applied earlier, while directories are read (see
+//! [`ExcludePatterns`]), and is not inverted either.
+
+use crate::cli::Cli;
+use crate::config::Settings;
+use crate::diag::{self, Code};
+use crate::fsops::{
+    is_executable_target, matches_any_glob, matches_extension, matches_owner, matches_pattern,
+    search_matches, ExcludePatterns, FileEntry, FileType,
+};
+use crate::mime::MimePattern;
+use crate::perms::PermSpec;
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Error returned when filter options are invalid
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum FilterError {
+    /// The full message, naming the option and the pattern
+    InvalidGlobPattern(String),
+    InvalidRegex(String),
+    SizeRangeInvalid(String),
+    DepthRangeInvalid(String),
+    InvalidReferenceFile(String),
+    InvalidTimeWindow(String),
+    /// `--perm` on a platform without Unix mode bits
+    PermUnsupported,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterError::InvalidGlobPattern(e) => write!(f, "{}", e),
+            FilterError::InvalidRegex(e) => write!(f, "invalid --regex value: {}", e),
+            FilterError::SizeRangeInvalid(e) => write!(f, "{}", e),
+            FilterError::DepthRangeInvalid(e) => write!(f, "{}", e),
+            FilterError::InvalidReferenceFile(e) => write!(f, "cannot read reference file {}", e),
+            FilterError::InvalidTimeWindow(e) => write!(f, "{}", e),
+            FilterError::PermUnsupported => write!(
+                f,
+                "--perm is not supported on this platform (entries have no Unix permission bits)"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+impl FilterError {
+    /// Stable diagnostic code for `--error-format json`
+    pub fn code(&self) -> Code {
+        match self {
+            FilterError::InvalidGlobPattern(_) => Code::InvalidGlobPattern,
+            FilterError::InvalidRegex(_) => Code::InvalidRegex,
+            FilterError::SizeRangeInvalid(_) => Code::InvalidSizeRange,
+            FilterError::DepthRangeInvalid(_) => Code::InvalidDepthRange,
+            FilterError::InvalidReferenceFile(_) => Code::ReferenceFileUnreadable,
+            FilterError::InvalidTimeWindow(_) => Code::InvalidTimeWindow,
+            FilterError::PermUnsupported => Code::UnsupportedOption,
+        }
+    }
+}
+
+/// Precomputed filters, so nothing is parsed per entry
+pub struct Filter {
+    /// `--filter-ext`, pre-normalized
+    exts: Option<Vec<String>>,
+    name_pattern: Option<Pattern>,
+    /// `--glob` patterns; an entry must match one of them
+    globs: Vec<Pattern>,
+    glob_options: MatchOptions,
+    /// `--exclude` patterns, applied while reading directories
+    exclude: ExcludePatterns,
+    /// `--regex`, searched anywhere in the name
+    name_regex: Option<Regex>,
+    /// `--search` terms; an entry must contain one of them
+    search: Vec<String>,
+    /// `--mime` patterns; an entry's detected type must match one of them
+    mime: Vec<MimePattern>,
+    /// `--type`; `None` lists every type
+    types: Option<Vec<FileType>>,
+    /// `--executable`, judging symlinks by their target with `--dereference-executable`
+    executable: bool,
+    dereference_executable: bool,
+    /// `--empty`
+    empty: bool,
+    /// `--broken-only`
+    broken_only: bool,
+    /// `--owner` and `--group`, by name or numeric ID
+    owner: Option<String>,
+    group: Option<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    /// Whether the size range applies to directories (`--size-filter-dirs`)
+    size_filter_dirs: bool,
+    perm: Option<PermSpec>,
+    /// Entries must be modified strictly after this time
+    modified_after: Option<SystemTime>,
+    /// Entries must be modified strictly before this time
+    modified_before: Option<SystemTime>,
+    min_depth: Option<usize>,
+    max_depth: Option<usize>,
+    /// `--no-backups` name suffixes, built-in and configured
+    backup_suffixes: Option<Vec<String>>,
+    /// `--invert-match`: flip the name and type filters
+    invert: bool,
+}
+
+/// Suffixes of editor backup and temp files hidden by `--no-backups`
+pub const BACKUP_SUFFIXES: &[&str] = &["~", ".swp", ".swo", ".bak", ".tmp"];
+
+/// Whether `name` looks like an editor backup or temp file (`--no-backups`).
+///
+/// Suffixes compare ASCII case-insensitively (`NOTES.BAK`) and must end the name:
+/// `README~` is a backup, `tilde~file.txt.real` is not. Emacs autosave files
+/// (`#notes.txt#`) are recognized by their surrounding `#`.
+pub fn is_backup_name(name: &str, suffixes: &[String]) -> bool {
+    let autosave = name.len() > 2 && name.starts_with('#') && name.ends_with('#');
+    autosave
+        || suffixes.iter().any(|suffix| {
+            name.len() > suffix.len()
+                && name
+                    .get(name.len() - suffix.len()..)
+                    .is_some_and(|end| end.eq_ignore_ascii_case(suffix))
+        })
+}
+
+impl Filter {
+    /// Build the filter from CLI options, compiling patterns and resolving times once.
+    /// `settings` supplies the configured `--no-backups` suffixes.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`FilterError`] for invalid patterns, size ranges, reference files, or
+    /// time windows.
+    pub fn from_cli(cli: &Cli, settings: &Settings) -> Result<Self, FilterError> {
+        // Pre-normalize extensions: lowercase and strip leading '.'
+        let exts = cli.filter_ext.as_ref().map(|ext_filter| {
+            ext_filter
+                .split(',')
+                .map(|s| s.trim().trim_start_matches('.').to_lowercase())
+                .collect::<Vec<_>>()
+        });
+
+        // Compile and validate glob pattern once
+        let name_pattern = match cli.filter_name.as_deref() {
+            Some(pattern_str) => match Pattern::new(pattern_str) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    return Err(FilterError::InvalidGlobPattern(format!(
+                        "invalid glob pattern '{}': {}",
+                        pattern_str, e
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        let globs = cli
+            .glob
+            .iter()
+            .map(|pattern_str| {
+                Pattern::new(pattern_str).map_err(|e| {
+                    FilterError::InvalidGlobPattern(format!(
+                        "invalid glob pattern '{}': {}",
+                        pattern_str, e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // Windows file names are case-insensitive, so patterns are too unless asked
+        let glob_options = MatchOptions {
+            case_sensitive: cfg!(not(windows)) || cli.case_sensitive,
+            ..MatchOptions::new()
+        };
+
+        let exclude = cli
+            .exclude
+            .iter()
+            .map(|pattern_str| {
+                Pattern::new(pattern_str).map_err(|e| {
+                    FilterError::InvalidGlobPattern(format!(
+                        "invalid --exclude pattern '{}': {}",
+                        pattern_str, e
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let exclude = ExcludePatterns::new(exclude, glob_options);
+
+        let name_regex = cli
+            .regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .map_err(|e| FilterError::InvalidRegex(e.to_string()))?;
+
+        let types = (!cli.types.is_empty())
+            .then(|| cli.types.iter().map(|t| t.file_type()).collect::<Vec<_>>());
+        // --files-only counts symlinks as files unless --type says otherwise
+        let shortcut = if cli.dirs_only {
+            Some(vec![FileType::Directory])
+        } else if cli.files_only && types.is_none() {
+            Some(vec![FileType::File, FileType::Symlink])
+        } else if cli.files_only {
+            Some(vec![FileType::File])
+        } else {
+            None
+        };
+        let types = match (types, shortcut) {
+            (Some(types), Some(shortcut)) => {
+                let common: Vec<FileType> =
+                    types.into_iter().filter(|t| shortcut.contains(t)).collect();
+                if common.is_empty() {
+                    diag::warning(
+                        Code::FiltersNeverMatch,
+                        None,
+                        "--type has no type in common with --dirs-only/--files-only",
+                    );
+                }
+                Some(common)
+            }
+            (types, shortcut) => types.or(shortcut),
+        };
+        // Directories never have an extension, so --filter-ext rejects them all
+        if let (Some(types), Some(_)) = (&types, &exts) {
+            if !types.is_empty() && types.iter().all(|t| *t == FileType::Directory) {
+                diag::warning(
+                    Code::FiltersNeverMatch,
+                    None,
+                    "--type dir never matches --filter-ext (directories have no extension)",
+                );
+            }
+        }
+
+        // Sizes were parsed by clap
+        let (min_size, max_size) = (cli.min_size, cli.max_size);
+
+        // Validate that min_size <= max_size
+        if let (Some(min), Some(max)) = (min_size, max_size) {
+            if min > max {
+                return Err(FilterError::SizeRangeInvalid(format!(
+                    "--min-size ({}) must be less than or equal to --max-size ({})",
+                    min, max
+                )));
+            }
+        }
+
+        if let (Some(min), Some(max)) = (cli.min_depth, cli.max_depth) {
+            if min > max {
+                return Err(FilterError::DepthRangeInvalid(format!(
+                    "--min-depth ({}) must be less than or equal to --max-depth ({})",
+                    min, max
+                )));
+            }
+        }
+
+        // Mode bits don't exist on Windows, so the filter would reject everything
+        if cli.perm.is_some() && !cfg!(unix) {
+            return Err(FilterError::PermUnsupported);
+        }
+        let perm = cli.perm;
+
+        // Windows reports placeholder owners until SID lookup exists
+        let (owner, group) = if cfg!(unix) {
+            (cli.owner.clone(), cli.group.clone())
+        } else {
+            if cli.owner.is_some() || cli.group.is_some() {
+                diag::warning(
+                    Code::UnsupportedOption,
+                    None,
+                    "--owner and --group need owner lookup, which is limited on this platform; ignoring them",
+                );
+            }
+            (None, None)
+        };
+
+        // Stat reference files and resolve relative times once, before listing starts
+        let now = SystemTime::now();
+        let modified_after = match (&cli.newer_than_file, cli.newer_than) {
+            (Some(path), _) => Some(reference_mtime(path)?),
+            (None, bound) => bound.map(|bound| bound.resolve(now)),
+        };
+        let modified_before = match (&cli.older_than_file, cli.older_than) {
+            (Some(path), _) => Some(reference_mtime(path)?),
+            (None, bound) => bound.map(|bound| bound.resolve(now)),
+        };
+        if let (Some(after), Some(before)) = (modified_after, modified_before) {
+            if after >= before {
+                return Err(FilterError::InvalidTimeWindow(format!(
+                    "the --newer-than time ({}) must be before the --older-than time ({})",
+                    chrono::DateTime::<chrono::Local>::from(after).format("%Y-%m-%d %H:%M:%S"),
+                    chrono::DateTime::<chrono::Local>::from(before).format("%Y-%m-%d %H:%M:%S")
+                )));
+            }
+        }
+
+        Ok(Filter {
+            exts,
+            name_pattern,
+            globs,
+            glob_options,
+            exclude,
+            name_regex,
+            search: cli.search.clone(),
+            mime: cli.mime.clone(),
+            types,
+            executable: cli.executable,
+            dereference_executable: cli.dereference_executable,
+            empty: cli.empty,
+            broken_only: cli.broken_only,
+            owner,
+            group,
+            min_size,
+            max_size,
+            size_filter_dirs: cli.size_filter_dirs,
+            perm,
+            modified_after,
+            modified_before,
+            min_depth: cli.min_depth,
+            max_depth: cli.max_depth,
+            backup_suffixes: cli.no_backups.then(|| {
+                BACKUP_SUFFIXES
+                    .iter()
+                    .map(|s| s.to_string())
+                    .chain(settings.backup_patterns().cloned())
+                    .collect()
+            }),
+            invert: cli.invert_match,
+        })
+    }
+
+    /// Whether the listing keeps `f`
+    pub fn matches(&self, f: &FileEntry) -> bool {
+        self.selects(f) != self.invert && self.passes_attributes(f)
+    }
+
+    /// `--exclude` patterns, applied while directories are read
+    pub fn exclude(&self) -> &ExcludePatterns {
+        &self.exclude
+    }
+
+    /// Whether `--glob` patterns were given
+    pub fn has_globs(&self) -> bool {
+        !self.globs.is_empty()
+    }
+
+    /// The match filters (name and type), which `--invert-match` flips
+    fn selects(&self, f: &FileEntry) -> bool {
+        if let Some(ref types) = self.types {
+            if !types.contains(&f.e_type) {
+                return false;
+            }
+        }
+
+        // Extension filter
+        // Names without an extension (directories included) never match, even when
+        // they end in `.ext`; the name itself is still checked so `tar.gz` works
+        if let Some(ref exts) = self.exts {
+            if f.extension.is_none() || !matches_extension(&f.name, exts) {
+                return false;
+            }
+        }
+
+        // Name pattern filter
+        if let Some(ref name_pattern) = self.name_pattern {
+            if !matches_pattern(&f.name, name_pattern) {
+                return false;
+            }
+        }
+
+        // --glob filter: any pattern may match
+        if !self.globs.is_empty() && !matches_any_glob(&f.name, &self.globs, self.glob_options) {
+            return false;
+        }
+
+        if let Some(ref name_regex) = self.name_regex {
+            if !name_regex.is_match(&f.name) {
+                return false;
+            }
+        }
+
+        if !self.search.is_empty() && search_matches(&f.name, &self.search).is_empty() {
+            return false;
+        }
+
+        if !self.mime.is_empty() {
+            let detected = f.mime.as_deref().unwrap_or_default();
+            if !self.mime.iter().any(|pattern| pattern.matches(detected)) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// The attribute filters, which are never inverted
+    fn passes_attributes(&self, f: &FileEntry) -> bool {
+        if self.executable {
+            let executable = match f.e_type {
+                FileType::File => f.is_executable,
+                FileType::Symlink => self.dereference_executable && is_executable_target(&f.path),
+                FileType::Directory => false,
+            };
+            if !executable {
+                return false;
+            }
+        }
+
+        if self.empty && f.is_empty != Some(true) {
+            return false;
+        }
+
+        if self.broken_only && f.is_broken != Some(true) {
+            return false;
+        }
+
+        if let Some(ref suffixes) = self.backup_suffixes {
+            if is_backup_name(&f.name, suffixes) {
+                return false;
+            }
+        }
+
+        if let Some(ref owner) = self.owner {
+            if !matches_owner(&f.owner, f.uid, owner) {
+                return false;
+            }
+        }
+        if let Some(ref group) = self.group {
+            if !matches_owner(&f.group, f.gid, group) {
+                return false;
+            }
+        }
+
+        // Size range filter (inclusive); a directory's own size says nothing about its contents
+        if f.e_type != FileType::Directory || self.size_filter_dirs {
+            if self.min_size.is_some_and(|min| f.len_bytes < min) {
+                return false;
+            }
+            if self.max_size.is_some_and(|max| f.len_bytes > max) {
+                return false;
+            }
+        }
+
+        // Modification time window (full precision, not the formatted string)
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            let Some(mtime) = f.modified_time else {
+                return false;
+            };
+            if self.modified_after.is_some_and(|after| mtime <= after) {
+                return false;
+            }
+            if self.modified_before.is_some_and(|before| mtime >= before) {
+                return false;
+            }
+        }
+
+        // Depth filters (0 = the listed directory's own entries)
+        if self.min_depth.is_some_and(|min| f.depth < min) {
+            return false;
+        }
+        if self.max_depth.is_some_and(|max| f.depth > max) {
+            return false;
+        }
+
+        // Permission bits filter
+        if let Some(spec) = self.perm {
+            if !f.mode.is_some_and(|mode| spec.matches(mode)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Modification time of a `--newer-than-file`/`--older-than-file` reference
+fn reference_mtime(path: &Path) -> Result<SystemTime, FilterError> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| FilterError::InvalidReferenceFile(format!("'{}': {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsops::{get_files, ListOptions};
+    use clap::Parser;
+    use std::fs::File;
+    use std::time::Duration;
+
+    fn filter(after: Option<u64>, before: Option<u64>) -> Filter {
+        Filter {
+            exts: None,
+            name_pattern: None,
+            globs: Vec::new(),
+            glob_options: MatchOptions::new(),
+            exclude: ExcludePatterns::default(),
+            name_regex: None,
+            search: Vec::new(),
+            mime: Vec::new(),
+            types: None,
+            executable: false,
+            dereference_executable: false,
+            empty: false,
+            broken_only: false,
+            owner: None,
+            group: None,
+            min_size: None,
+            max_size: None,
+            size_filter_dirs: false,
+            perm: None,
+            modified_after: after.map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s)),
+            modified_before: before.map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s)),
+            min_depth: None,
+            max_depth: None,
+            backup_suffixes: None,
+            invert: false,
+        }
+    }
+
+    fn modified_at(secs: u64, nanos: u32) -> FileEntry {
+        FileEntry {
+            modified_time: Some(SystemTime::UNIX_EPOCH + Duration::new(secs, nanos)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_modified_time_window() {
+        let newer = filter(Some(100), None);
+        assert!(newer.matches(&modified_at(100, 1)));
+        assert!(!newer.matches(&modified_at(100, 0)));
+        assert!(!newer.matches(&FileEntry::default()));
+
+        let window = filter(Some(100), Some(200));
+        assert!(window.matches(&modified_at(150, 0)));
+        assert!(!window.matches(&modified_at(200, 0)));
+        assert!(!window.matches(&modified_at(50, 0)));
+    }
+
+    #[test]
+    fn test_depth_filters() {
+        let cfg = Filter {
+            min_depth: Some(1),
+            max_depth: Some(2),
+            ..filter(None, None)
+        };
+        let at_depth = |depth| FileEntry {
+            depth,
+            ..Default::default()
+        };
+        assert!(!cfg.matches(&at_depth(0)));
+        assert!(cfg.matches(&at_depth(1)));
+        assert!(cfg.matches(&at_depth(2)));
+        assert!(!cfg.matches(&at_depth(3)));
+
+        // A single level is fine, an inverted range is rejected
+        let range = |min: &str, max: &str| {
+            let cli = Cli::parse_from(["bestls", "-R", "--min-depth", min, "--max-depth", max]);
+            Filter::from_cli(&cli, &Settings::default())
+        };
+        assert!(range("2", "2").is_ok());
+        assert!(matches!(
+            range("3", "2"),
+            Err(FilterError::DepthRangeInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_glob_filters() {
+        let named = |name: &str| FileEntry {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let mut cfg = Filter {
+            globs: vec![
+                Pattern::new("*.rs").unwrap(),
+                Pattern::new("Cargo.*").unwrap(),
+            ],
+            ..filter(None, None)
+        };
+        assert!(cfg.matches(&named("main.rs")));
+        assert!(cfg.matches(&named("Cargo.toml")));
+        assert!(!cfg.matches(&named("README.md")));
+        assert!(!cfg.matches(&named("MAIN.RS")));
+
+        cfg.glob_options.case_sensitive = false;
+        assert!(cfg.matches(&named("MAIN.RS")));
+    }
+
+    #[test]
+    fn test_invalid_glob_messages() {
+        let message = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            match Filter::from_cli(&cli, &Settings::default()) {
+                Err(e) => e.to_string(),
+                Ok(_) => panic!("{:?} should be rejected", args),
+            }
+        };
+
+        assert!(message(&["--filter-name", "["]).starts_with("invalid glob pattern '[': "));
+        assert!(message(&["--glob", "["]).starts_with("invalid glob pattern '[': "));
+        assert!(message(&["--exclude", "["]).starts_with("invalid --exclude pattern '[': "));
+    }
+
+    #[test]
+    fn test_regex_filter() {
+        let named = |name: &str| FileEntry {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let with_regex = |expr: &str| Filter {
+            name_regex: Some(Regex::new(expr).unwrap()),
+            ..filter(None, None)
+        };
+
+        // Unanchored: matches anywhere unless ^ or $ says otherwise
+        let dated = with_regex(r"\d{4}-\d{2}-\d{2}");
+        assert!(dated.matches(&named("backup-2024-08-22.tar")));
+        assert!(!dated.matches(&named("backup.tar")));
+        let suffix = with_regex(r"\d{4}-\d{2}-\d{2}$");
+        assert!(suffix.matches(&named("log-2024-08-22")));
+        assert!(!suffix.matches(&named("log-2024-08-22.gz")));
+
+        // Character classes and . work on characters, not bytes
+        let unicode = with_regex(r"^\p{Greek}+\.txt$");
+        assert!(unicode.matches(&named("αβγ.txt")));
+        assert!(!unicode.matches(&named("abc.txt")));
+        assert!(with_regex("^..\\.md$").matches(&named("日本.md")));
+
+        // ANDed with --glob
+        let both = Filter {
+            globs: vec![Pattern::new("*.gz").unwrap()],
+            ..dated
+        };
+        assert!(both.matches(&named("backup-2024-08-22.gz")));
+        assert!(!both.matches(&named("backup-2024-08-22.tar")));
+        assert!(!both.matches(&named("backup.gz")));
+    }
+
+    #[test]
+    fn test_size_range_is_inclusive() {
+        let sized = |e_type, len_bytes| FileEntry {
+            e_type,
+            len_bytes,
+            ..Default::default()
+        };
+        let cli = Cli::parse_from(["bestls", "--min-size", "1K", "--max-size", "2KiB"]);
+        let mut cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+        assert!(!cfg.matches(&sized(FileType::File, 1023)));
+        assert!(cfg.matches(&sized(FileType::File, 1024)));
+        assert!(cfg.matches(&sized(FileType::File, 2048)));
+        assert!(!cfg.matches(&sized(FileType::File, 2049)));
+
+        // Directories pass unless --size-filter-dirs
+        assert!(cfg.matches(&sized(FileType::Directory, 4096)));
+        cfg.size_filter_dirs = true;
+        assert!(!cfg.matches(&sized(FileType::Directory, 4096)));
+
+        assert!(Cli::try_parse_from(["bestls", "--min-size", "banana"]).is_err());
+    }
+
+    #[test]
+    fn test_type_filter() {
+        let typed = |e_type| FileEntry {
+            e_type,
+            ..Default::default()
+        };
+        let dirs = Filter {
+            types: Some(vec![FileType::Directory]),
+            ..filter(None, None)
+        };
+        assert!(dirs.matches(&typed(FileType::Directory)));
+        assert!(!dirs.matches(&typed(FileType::File)));
+        assert!(!dirs.matches(&typed(FileType::Symlink)));
+
+        let cli = Cli::parse_from(["bestls", "--type", "file,symlink", "--type", "directory"]);
+        let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+        assert_eq!(
+            cfg.types,
+            Some(vec![FileType::File, FileType::Symlink, FileType::Directory])
+        );
+    }
+
+    #[test]
+    fn test_newer_and_older_than() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (name, days_ago) in [("today", 0), ("last-week", 7), ("last-month", 30)] {
+            let file = File::create(dir.path().join(name)).unwrap();
+            file.set_modified(now - Duration::from_secs(days_ago * 86_400 + 60))
+                .unwrap();
+        }
+        let listed = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            let mut names: Vec<String> = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .into_iter()
+                .filter(|f| cfg.matches(f))
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(listed(&["--newer-than", "2d"]), ["today"]);
+        assert_eq!(listed(&["--older-than", "1w"]), ["last-month", "last-week"]);
+        assert_eq!(
+            listed(&["--newer-than", "14d", "--older-than", "1d"]),
+            ["last-week"]
+        );
+        assert_eq!(
+            listed(&["--newer-than", "2000-01-01", "--older-than", "1d"]),
+            ["last-month", "last-week"]
+        );
+
+        // An inverted window is rejected
+        let cli = Cli::parse_from(["bestls", "--newer-than", "1d", "--older-than", "2d"]);
+        assert!(matches!(
+            Filter::from_cli(&cli, &Settings::default()),
+            Err(FilterError::InvalidTimeWindow(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_owner_and_group_filters() {
+        use nix::unistd::{getgid, getuid, Group, User};
+
+        let dir = tempfile::tempdir().unwrap();
+        File::create(dir.path().join("mine")).unwrap();
+        let uid = getuid();
+        let user = User::from_uid(uid).unwrap().map(|u| u.name);
+        let group = Group::from_gid(getgid()).unwrap().map(|g| g.name);
+        let listed = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .iter()
+                .filter(|f| cfg.matches(f))
+                .count()
+        };
+
+        let uid = uid.to_string();
+        let gid = getgid().to_string();
+        assert_eq!(listed(&["--owner", &uid, "--group", &gid]), 1);
+        if let (Some(user), Some(group)) = (user, group) {
+            assert_eq!(listed(&["--owner", &user, "--group", &group]), 1);
+        }
+        assert_eq!(listed(&["--owner", "no-such-user-bestls"]), 0);
+        assert_eq!(listed(&["--group", "4294967294"]), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_executable_filter() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o744)).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "x").unwrap();
+        std::fs::create_dir(dir.path().join("bin")).unwrap();
+        symlink(&script, dir.path().join("run")).unwrap();
+
+        let listed = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls", "--executable"], args].concat());
+            let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            let mut names: Vec<String> = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .into_iter()
+                .filter(|f| cfg.matches(f))
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+        // Directories have execute bits but are not programs
+        assert_eq!(listed(&[]), ["run.sh"]);
+        assert_eq!(listed(&["--dereference-executable"]), ["run", "run.sh"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_broken_only_filter() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        std::fs::write(&target, "x").unwrap();
+        std::fs::write(dir.path().join("kept.txt"), "x").unwrap();
+        symlink(&target, dir.path().join("dangling")).unwrap();
+        symlink(dir.path().join("kept.txt"), dir.path().join("working")).unwrap();
+        std::fs::remove_file(&target).unwrap();
+
+        let files = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        let find = |name: &str| files.iter().find(|f| f.name == name).unwrap();
+        assert_eq!(find("dangling").is_broken, Some(true));
+        assert_eq!(find("working").is_broken, Some(false));
+        assert_eq!(find("kept.txt").is_broken, None);
+        // The link's own metadata is still reported
+        assert!(find("dangling").modified_time.is_some());
+
+        let cfg = Filter::from_cli(
+            &Cli::parse_from(["bestls", "--broken-only"]),
+            &Settings::default(),
+        )
+        .unwrap();
+        let names: Vec<&str> = files
+            .iter()
+            .filter(|f| cfg.matches(f))
+            .map(|f| f.name.as_str())
+            .collect();
+        assert_eq!(names, ["dangling"]);
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let names = ["Invoice-2024.pdf", "INVOICES", "receipt.pdf", "Èté.txt"];
+        let listed = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            names
+                .iter()
+                .filter(|name| {
+                    cfg.matches(&FileEntry {
+                        name: name.to_string(),
+                        ..Default::default()
+                    })
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            listed(&["--search", "invoice"]),
+            ["Invoice-2024.pdf", "INVOICES"]
+        );
+        // Any term may match
+        assert_eq!(
+            listed(&["--search", "receipt", "--search", "èTÉ"]),
+            ["receipt.pdf", "Èté.txt"]
+        );
+        assert_eq!(
+            listed(&["--search", "pdf", "--invert-match"]),
+            ["INVOICES", "Èté.txt"]
+        );
+        assert!(Cli::try_parse_from(["bestls", "--search", ""]).is_err());
+    }
+
+    #[test]
+    fn test_no_backups() {
+        let names = [
+            "README~",
+            "tilde~file.txt.real",
+            ".main.rs.swp",
+            ".main.rs.swo",
+            "NOTES.BAK",
+            "build.tmp",
+            "#notes.txt#",
+            "#hashtag",
+            "patch.orig",
+            "main.rs",
+        ];
+        let listed = |settings: &Settings| {
+            let cli = Cli::parse_from(["bestls", "--no-backups"]);
+            let cfg = Filter::from_cli(&cli, settings).unwrap();
+            names
+                .iter()
+                .filter(|name| {
+                    cfg.matches(&FileEntry {
+                        name: name.to_string(),
+                        ..Default::default()
+                    })
+                })
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        // Only a trailing ~ marks a backup
+        assert_eq!(
+            listed(&Settings::default()),
+            ["tilde~file.txt.real", "#hashtag", "patch.orig", "main.rs"]
+        );
+
+        let mut settings = Settings::default();
+        settings.global.filters.backup_patterns = vec![".orig".to_string()];
+        assert_eq!(
+            listed(&settings),
+            ["tilde~file.txt.real", "#hashtag", "main.rs"]
+        );
+    }
+
+    #[test]
+    fn test_mime_filter() {
+        use crate::mime::MimeDetection;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("site.tar.gz"), "archive").unwrap();
+        std::fs::write(dir.path().join("photo.png"), "png").unwrap();
+        std::fs::write(dir.path().join("deploy"), "#!/usr/bin/env bash\n").unwrap();
+        std::fs::write(dir.path().join("blob"), [0xde, 0xad, 0x00, 0xef]).unwrap();
+
+        let listed = |args: &[&str], detection| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let cfg = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            let opts = ListOptions {
+                mime: Some(detection),
+                ..Default::default()
+            };
+            let mut names: Vec<String> = get_files(dir.path(), &opts)
+                .unwrap()
+                .entries
+                .into_iter()
+                .filter(|f| cfg.matches(f))
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            listed(&["--mime", "image/*"], MimeDetection::Name),
+            ["photo.png"]
+        );
+        assert_eq!(
+            listed(
+                &["--mime", "application/x-compressed-tar"],
+                MimeDetection::Name
+            ),
+            ["site.tar.gz"]
+        );
+        // Without sniffing, names the table does not know are all unknown
+        assert_eq!(
+            listed(&["--mime", "application/octet-stream"], MimeDetection::Name),
+            ["blob", "deploy"]
+        );
+        assert_eq!(
+            listed(&["--mime", "text/*"], MimeDetection::Content),
+            ["deploy"]
+        );
+        assert_eq!(
+            listed(
+                &["--mime", "application/octet-stream"],
+                MimeDetection::Content
+            ),
+            ["blob"]
+        );
+        assert!(Cli::try_parse_from(["bestls", "--mime", "image"]).is_err());
+        assert!(Cli::try_parse_from(["bestls", "--deep-mime"]).is_err());
+    }
+
+    #[test]
+    fn test_invert_match_flips_only_match_filters() {
+        let entry = |name: &str, e_type, len_bytes| FileEntry {
+            name: name.to_string(),
+            e_type,
+            len_bytes,
+            ..Default::default()
+        };
+        let cli = Cli::parse_from([
+            "bestls",
+            "--invert-match",
+            "--glob",
+            "*.rs",
+            "--type",
+            "file",
+            "--min-size",
+            "1K",
+        ]);
+        let filter = Filter::from_cli(&cli, &Settings::default()).unwrap();
+
+        // Not (a .rs file): other files and all directories are selected...
+        assert!(!filter.matches(&entry("main.rs", FileType::File, 4096)));
+        assert!(filter.matches(&entry("README.md", FileType::File, 4096)));
+        assert!(filter.matches(&entry("src.rs", FileType::Directory, 0)));
+        // ...but the size filter still applies as given
+        assert!(!filter.matches(&entry("notes.txt", FileType::File, 10)));
+
+        // Without a match filter there is nothing to invert
+        assert!(Cli::try_parse_from(["bestls", "--invert-match", "--min-size", "1K"]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dirs_only_and_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        File::create(dir.path().join("a.txt")).unwrap();
+        File::create(dir.path().join("b.txt")).unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::os::unix::fs::symlink("a.txt", dir.path().join("link")).unwrap();
+        let count = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let filter = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .iter()
+                .filter(|f| filter.matches(f))
+                .count()
+        };
+
+        assert_eq!(count(&["--dirs-only"]), 1);
+        assert_eq!(count(&["--files-only"]), 3);
+        // With --type, symlinks are no longer counted as files
+        assert_eq!(count(&["--files-only", "--type", "file,symlink"]), 2);
+        assert_eq!(count(&["--files-only", "--type", "dir"]), 0);
+        assert_eq!(count(&["--files-only", "--invert-match"]), 1);
+        assert!(Cli::try_parse_from(["bestls", "--dirs-only", "--files-only"]).is_err());
+    }
+
+    #[test]
+    fn test_extension_filter_skips_dotted_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        File::create(dir.path().join("main.rs")).unwrap();
+        File::create(dir.path().join("backup.tar.gz")).unwrap();
+        std::fs::create_dir(dir.path().join("pkg.rs")).unwrap();
+        let names = |args: &[&str]| {
+            let cli = Cli::parse_from([&["bestls"], args].concat());
+            let filter = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            let mut names: Vec<String> = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .into_iter()
+                .filter(|f| filter.matches(f))
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(&["--filter-ext", "rs"]), ["main.rs"]);
+        assert!(names(&["--filter-ext", "rs", "--type", "dir"]).is_empty());
+        assert_eq!(names(&["--filter-ext", "tar.gz"]), ["backup.tar.gz"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_perm_filter_on_files() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        for (name, mode) in [("shared", 0o666), ("private", 0o600), ("suid", 0o4755)] {
+            let path = dir.path().join(name);
+            File::create(&path).unwrap();
+            let mut permissions = std::fs::metadata(&path).unwrap().permissions();
+            permissions.set_mode(mode);
+            std::fs::set_permissions(&path, permissions).unwrap();
+        }
+        let listed = |spec: &str| {
+            let cli = Cli::parse_from(["bestls", "--perm", spec]);
+            let filter = Filter::from_cli(&cli, &Settings::default()).unwrap();
+            let mut names: Vec<String> = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries
+                .into_iter()
+                .filter(|f| filter.matches(f))
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(listed("o+w"), ["shared"]);
+        assert_eq!(listed("-4000"), ["suid"]);
+        assert_eq!(listed("u+s"), ["suid"]);
+        assert_eq!(listed("600"), ["private"]);
+        assert_eq!(listed("g-x"), ["private", "shared"]);
+    }
+
+    #[test]
+    fn test_reference_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("marker");
+        let file = File::create(&marker).unwrap();
+        let when = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        file.set_modified(when).unwrap();
+
+        assert_eq!(reference_mtime(&marker).unwrap(), when);
+        assert!(matches!(
+            reference_mtime(&dir.path().join("missing")),
+            Err(FilterError::InvalidReferenceFile(_))
+        ));
+    }
+}
@@ -39,14 +39,13 @@
 //!
 //! ```rust
 //! use std::path::Path;
-//! use bestls::fsops::get_files;
+//! use bestls::fsops::{get_files, ListOptions};
 //!
 //! let path = Path::new(".");
-//! let include_hidden = false;
 //!
-//! match get_files(&path, include_hidden) {
-//!     Ok(files) => {
-//!         for file in files {
+//! match get_files(&path, &ListOptions::default()) {
+//!     Ok(listing) => {
+//!         for file in listing.entries {
 //!             println!("{}: {} ({})", file.name, file.human_size, file.e_type);
 //!         }
 //!     }
@@ -58,23 +57,40 @@
 //!
 //! ```rust
 //! use std::path::Path;
-//! use bestls::fsops::get_files;
+//! use bestls::fsops::{get_files, ListOptions};
 //!
 //! let path = Path::new("/home/user");
-//! let include_hidden = true; // Include files starting with '.'
+//! let opts = ListOptions {
+//!     include_hidden: true, // Include files starting with '.'
+//!     ..Default::default()
+//! };
 //!
-//! let files = get_files(&path, include_hidden)?;
+//! let files = get_files(&path, &opts)?.entries;
 //! println!("Found {} files (including hidden)", files.len());
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
 use bytesize::ByteSize;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Locale, SecondsFormat, Utc};
 use rayon::prelude::*;
 use serde::Serialize;
-use std::{fmt, fs, io, path::Path};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashSet, VecDeque},
+    fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 use strum::Display;
 
+use crate::diag::{self, Code};
+use crate::dirsize;
+use crate::interrupt;
+use crate::mime::{self, MimeDetection};
+use crate::platform::{self, display_path, long_path, DeviceId, FileId};
+use crate::vfs::virtual_fs_kind;
+
 #[cfg(unix)]
 use nix::unistd::{Group, User};
 #[cfg(unix)]
@@ -123,9 +139,10 @@ use std::os::unix::fs::{MetadataExt, PermissionsExt};
 ///   "e_type": "File"
 /// }
 /// ```
-#[derive(Debug, Display, Serialize, Clone)]
+#[derive(Debug, Display, Serialize, Clone, Copy, PartialEq, Eq, Default)]
 pub enum FileType {
     /// Regular file
+    #[default]
     File,
     /// Directory or folder
     Directory,
@@ -133,6 +150,17 @@ pub enum FileType {
     Symlink,
 }
 
+impl FileType {
+    /// Position in `--sort type` order: directories, then symlinks, then files
+    pub fn sort_rank(self) -> u8 {
+        match self {
+            FileType::Directory => 0,
+            FileType::Symlink => 1,
+            FileType::File => 2,
+        }
+    }
+}
+
 /// Comprehensive file system entry representation with rich metadata.
 ///
 /// This struct contains all the metadata for a file system entry that bestls can extract.
@@ -234,10 +262,14 @@ pub enum FileType {
 ///   "modified": "Mon 15 Jan 2024 14:30:25",
 ///   "permissions": "rw-r--r--",
 ///   "owner": "user",
-///   "group": "staff"
+///   "group": "staff",
+///   "extension": "txt",
+///   "stem": "example",
+///   "is_hidden": false,
+///   "is_executable": false
 /// }
 /// ```
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct FileEntry {
     /// The filename or directory name (without path components)
     pub name: String,
@@ -247,14 +279,365 @@ pub struct FileEntry {
     pub len_bytes: u64,
     /// Human-readable file size (e.g., "1.5 KB", "2.1 MB", "1.2 GB")
     pub human_size: String,
-    /// Formatted modification date and time string
+    /// Formatted modification date and time string, for display (`--time-style`)
     pub modified: String,
+    /// Modification time in whole seconds since the Unix epoch. This is the
+    /// authoritative value: `modified` and `modified_iso` are renderings of it
+    pub modified_epoch: Option<i64>,
+    /// Modification time in RFC 3339 form, in UTC (`2024-02-05T10:11:12Z`)
+    pub modified_iso: Option<String>,
+    /// Raw modification time, used for change detection (not serialized)
+    #[serde(skip)]
+    pub modified_time: Option<SystemTime>,
+    /// Formatted last access time, formatted like `modified` (empty where the
+    /// platform does not record it)
+    pub accessed: String,
+    /// Access time in seconds since the Unix epoch, like `modified_epoch`
+    pub accessed_epoch: Option<i64>,
+    /// Access time in RFC 3339 form, like `modified_iso`
+    pub accessed_iso: Option<String>,
+    /// Raw access time where the filesystem records it (not serialized)
+    #[serde(skip)]
+    pub accessed_time: Option<SystemTime>,
+    /// Formatted creation (birth) time, `None` where the platform or filesystem
+    /// does not record it
+    pub created: Option<String>,
+    /// Creation time in seconds since the Unix epoch, like `modified_epoch`
+    pub created_epoch: Option<i64>,
+    /// Creation time in RFC 3339 form, like `modified_iso`
+    pub created_iso: Option<String>,
+    /// Raw creation time, used by `--sort created` (not serialized)
+    #[serde(skip)]
+    pub created_time: Option<SystemTime>,
     /// File permissions string (format varies by platform)
     pub permissions: String,
+    /// Nesting level below the listed directory: 0 for its own entries, 1 for entries
+    /// of its subdirectories, and so on (always 0 without `--tree`)
+    pub depth: usize,
+    /// Raw Unix mode bits, used by `--perm` (not serialized)
+    #[serde(skip)]
+    pub mode: Option<u32>,
     /// File owner name (platform-dependent format)
     pub owner: String,
     /// File group name (platform-dependent format)
     pub group: String,
+    /// Raw owner user ID, used by `--owner` (not serialized)
+    #[serde(skip)]
+    pub uid: Option<u32>,
+    /// Raw group ID, used by `--group` (not serialized)
+    #[serde(skip)]
+    pub gid: Option<u32>,
+    /// Number of hard links, shown by `-l` (not serialized)
+    #[serde(skip)]
+    pub nlink: Option<u64>,
+    /// Allocated 512-byte blocks, totaled by `-l` (not serialized)
+    #[serde(skip)]
+    pub blocks: Option<u64>,
+    /// Lowercased extension (`None` for directories and names without one)
+    pub extension: Option<String>,
+    /// Name without its extension (the whole name when there is none)
+    pub stem: String,
+    /// Whether the name is hidden by the dot-file convention
+    pub is_hidden: bool,
+    /// Whether a regular file has an execute bit set (on Windows: whether its
+    /// extension is one of [`WINDOWS_EXECUTABLE_EXTENSIONS`])
+    pub is_executable: bool,
+    /// Full path of the entry, used for follow-up lookups (not serialized)
+    #[serde(skip)]
+    pub path: PathBuf,
+    /// Abbreviated hash of the last commit touching this entry (`--git-log`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_hash: Option<String>,
+    /// Relative date of the last commit touching this entry (`--git-log`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_date: Option<String>,
+    /// Summary line of the last commit touching this entry (`--git-log`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_commit_summary: Option<String>,
+    /// Whether an archive member is encrypted (`--archive`, zip only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encrypted: Option<bool>,
+    /// First line of a regular text file (`--preview`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    /// Whether a file has no bytes or a directory has no entries, hidden ones
+    /// included (`--empty`); `None` for symlinks and unreadable directories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_empty: Option<bool>,
+    /// Whether a symlink points at a missing target; `None` for other entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_broken: Option<bool>,
+    /// Whether a directory's `--total-size` misses parts that could not be read;
+    /// `None` for other entries and without `--total-size`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_approximate: Option<bool>,
+    /// Number of entries directly inside a directory, hidden ones only with `-a`
+    /// (`--count-items`); `None` for other entries and unreadable directories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<u64>,
+    /// Detected MIME type (`--mime`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime: Option<String>,
+    /// Values of custom `--exec-column` columns keyed by label (flattened into JSON)
+    #[serde(flatten)]
+    pub custom_columns: BTreeMap<String, Option<String>>,
+}
+
+/// Options controlling which entries are listed and which optional metadata is gathered.
+///
+/// Optional metadata costs extra syscalls or reads per entry, so it is only collected
+/// when the corresponding flag is set.
+#[derive(Debug, Default, Clone)]
+pub struct ListOptions {
+    /// Include entries whose name starts with '.'
+    pub include_hidden: bool,
+    /// Read the first line of regular text files into `preview` (`--preview`)
+    pub preview: bool,
+    /// List a symlinked root path as the link itself instead of its target's
+    /// contents (`--no-dereference`); by default the root is followed like `ls -H`
+    pub no_dereference: bool,
+    /// List the root path itself as a single entry instead of its contents
+    /// (`--directory`, like `ls -d`); a symlink is shown as the link
+    pub directory: bool,
+    /// Also hide the names listed in each directory's `.hidden` file (`--respect-dot-hidden`)
+    pub respect_dot_hidden: bool,
+    /// How the `modified` column is rendered
+    pub timestamps: TimestampFormat,
+    /// Stop gathering after this many entries (`--max-entries`); `None` for no limit
+    pub max_entries: Option<usize>,
+    /// Entries to leave out, with their whole subtree (`--exclude`)
+    pub exclude: ExcludePatterns,
+    /// Fill `is_empty`, which costs a directory read per subdirectory (`--empty`)
+    pub probe_empty: bool,
+    /// Report the total size of the files below each directory, which walks its
+    /// whole subtree (`--total-size`)
+    pub total_size: bool,
+    /// Fill `items`, which costs a directory read per subdirectory (`--count-items`)
+    pub count_items: bool,
+    /// Fill `mime` this way (`--mime`, `--deep-mime`); `None` skips detection
+    pub mime: Option<MimeDetection>,
+    /// Name entries of a recursive listing by their path relative to the listed
+    /// directory (`-R`) instead of their file name
+    pub relative_names: bool,
+    /// Descend into symlinked directories during a recursive walk
+    /// (`--follow-symlinks`); links leading back into the walk are skipped
+    pub follow_symlinks: bool,
+    /// List but do not enter directories on another filesystem than the listed
+    /// directory during a recursive walk (`--one-file-system`)
+    pub one_file_system: bool,
+}
+
+/// Glob patterns removing entries from a listing (`--exclude`).
+///
+/// A pattern containing `/` matches the path relative to the listed directory
+/// (`src/generated`, `**/target`); any other pattern matches the file name alone.
+#[derive(Debug, Default, Clone)]
+pub struct ExcludePatterns {
+    names: Vec<glob::Pattern>,
+    paths: Vec<glob::Pattern>,
+    options: glob::MatchOptions,
+}
+
+impl ExcludePatterns {
+    /// Sort `patterns` into name and path patterns, matched with `options`.
+    pub fn new(patterns: Vec<glob::Pattern>, options: glob::MatchOptions) -> Self {
+        let (paths, names) = patterns
+            .into_iter()
+            .partition(|pattern| pattern.as_str().contains('/'));
+        Self {
+            names,
+            paths,
+            options,
+        }
+    }
+
+    /// Whether the entry `name` at `relative` (`/`-separated, from the listed
+    /// directory) is excluded
+    pub fn matches(&self, name: &str, relative: &str) -> bool {
+        let path_options = glob::MatchOptions {
+            // `*` stays within one component; `**` crosses them
+            require_literal_separator: true,
+            ..self.options
+        };
+        self.names
+            .iter()
+            .any(|pattern| pattern.matches_with(name, self.options))
+            || self
+                .paths
+                .iter()
+                .any(|pattern| pattern.matches_with(relative, path_options))
+    }
+}
+
+/// Name of the per-directory file listing extra hidden names (macOS/file manager convention)
+pub const DOT_HIDDEN_NAME: &str = ".hidden";
+
+/// Read the names listed in `dir/.hidden`, one per line (`#` starts a comment line).
+///
+/// A missing or unreadable file yields no names.
+pub fn read_dot_hidden(dir: &Path) -> HashSet<String> {
+    fs::read_to_string(long_path(&dir.join(DOT_HIDDEN_NAME)))
+        .map(|content| {
+            content
+                .lines()
+                .map(|line| line.trim_end_matches('\r'))
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a file name is hidden by the dot-file convention
+pub fn is_hidden_name(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// Split the last component of `name` into its lowercased extension and its stem.
+///
+/// Like [`Path::extension`], a leading dot does not start an extension (`.bashrc` is
+/// all stem). Directories never have an extension.
+pub fn split_name(name: &str, e_type: &FileType) -> (Option<String>, String) {
+    let path = Path::new(name);
+    let base = path
+        .file_name()
+        .map(|base| base.to_string_lossy().into_owned())
+        .unwrap_or_else(|| name.to_string());
+    if *e_type == FileType::Directory {
+        return (None, base);
+    }
+    match (path.extension(), path.file_stem()) {
+        (Some(ext), Some(stem)) => (
+            Some(ext.to_string_lossy().to_lowercase()),
+            stem.to_string_lossy().into_owned(),
+        ),
+        _ => (None, base),
+    }
+}
+
+/// Whether a regular file with these mode bits is executable by anyone
+pub fn is_executable_mode(e_type: &FileType, mode: Option<u32>) -> bool {
+    *e_type == FileType::File && mode.is_some_and(|mode| mode & 0o111 != 0)
+}
+
+/// Extensions treated as executable where there are no mode bits (Windows)
+#[cfg_attr(unix, allow(dead_code))]
+pub const WINDOWS_EXECUTABLE_EXTENSIONS: &[&str] = &["exe", "bat", "cmd", "ps1", "com"];
+
+/// Whether a lowercased extension is one of [`WINDOWS_EXECUTABLE_EXTENSIONS`]
+#[cfg_attr(unix, allow(dead_code))]
+pub fn has_executable_extension(extension: Option<&str>) -> bool {
+    extension.is_some_and(|ext| WINDOWS_EXECUTABLE_EXTENSIONS.contains(&ext))
+}
+
+/// Whether `path`, following symlinks, is an executable regular file
+pub fn is_executable_target(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(long_path(path)) else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    return metadata.permissions().mode() & 0o111 != 0;
+    #[cfg(not(unix))]
+    return has_executable_extension(
+        path.extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .as_deref(),
+    );
+}
+
+/// Read a directory, dropping hidden entries unless `include_hidden` is set, and
+/// entries matching `--exclude`. `relative` is the directory's `/`-separated path
+/// below the listed one (empty for the listed directory itself).
+///
+/// At most `limit` entries are returned (`--max-entries`). When more exist, the
+/// returned flag is set and the truncation is recorded with [`interrupt::mark_truncated`].
+/// Entries the directory stream fails to return are added to `errors`.
+fn read_visible_entries(
+    path: &Path,
+    relative: &str,
+    opts: &ListOptions,
+    limit: Option<usize>,
+    errors: &mut Vec<ListError>,
+) -> Result<(Vec<fs::DirEntry>, bool), io::Error> {
+    let dot_hidden = dot_hidden_names(path, opts);
+
+    // One extra entry tells a full directory apart from a truncated one
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(long_path(path))?
+        .filter_map(|entry| {
+            entry
+                .map_err(|error| {
+                    errors.push(ListError {
+                        path: path.to_path_buf(),
+                        error,
+                        code: Code::EntryUnreadable,
+                    })
+                })
+                .ok()
+        })
+        .filter(|entry| is_visible(entry, relative, opts, &dot_hidden))
+        .take(limit.map_or(usize::MAX, |limit| limit.saturating_add(1)))
+        .collect();
+
+    let truncated = limit.is_some_and(|limit| entries.len() > limit);
+    if let (true, Some(limit)) = (truncated, limit) {
+        entries.truncate(limit);
+        interrupt::mark_truncated();
+    }
+    Ok((entries, truncated))
+}
+
+/// Names a `.hidden` file in `path` hides, when `--respect-dot-hidden` applies
+fn dot_hidden_names(path: &Path, opts: &ListOptions) -> HashSet<String> {
+    if opts.respect_dot_hidden && !opts.include_hidden {
+        read_dot_hidden(path)
+    } else {
+        HashSet::new()
+    }
+}
+
+/// Whether a directory entry is listed: hidden entries only with `include_hidden`,
+/// and never one matching `--exclude`
+fn is_visible(
+    entry: &fs::DirEntry,
+    relative: &str,
+    opts: &ListOptions,
+    dot_hidden: &HashSet<String>,
+) -> bool {
+    let name = entry.file_name().to_string_lossy().to_string();
+    (opts.include_hidden || !(is_hidden_name(&name) || dot_hidden.contains(&name)))
+        && !opts
+            .exclude
+            .matches(&name, &relative_child(relative, &name))
+}
+
+/// `/`-separated path of `name` inside the directory at `relative`
+fn relative_child(relative: &str, name: &str) -> String {
+    if relative.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", relative, name)
+    }
+}
+
+/// Whether `path` should be listed as a single entry instead of read as a directory
+/// under `opts`: a file, a symlink to one, a broken symlink, with `--no-dereference`
+/// any symlink (like `ls FILE`), and with `--directory` anything
+fn root_as_entry(path: &Path, opts: &ListOptions) -> bool {
+    if opts.directory {
+        return true;
+    }
+    // A missing path is left to read_dir, which reports it
+    let Ok(metadata) = fs::symlink_metadata(long_path(path)) else {
+        return false;
+    };
+    if metadata.file_type().is_symlink() {
+        opts.no_dereference || !fs::metadata(long_path(path)).is_ok_and(|target| target.is_dir())
+    } else {
+        !metadata.is_dir()
+    }
 }
 
 /// Retrieve and process all files in a directory with optional hidden file inclusion.
@@ -266,11 +649,12 @@ pub struct FileEntry {
 /// # Arguments
 ///
 /// * `path` - The directory path to read
-/// * `include_hidden` - Whether to include files starting with '.' (Unix hidden files)
+/// * `opts` - Listing options (hidden file inclusion, optional metadata)
 ///
 /// # Returns
 ///
-/// * `Ok(Vec<FileEntry>)` - Vector of file entries with complete metadata
+/// * `Ok(ListResult)` - File entries with complete metadata, in directory order, and
+///   the entries that could not be read
 /// * `Err(io::Error)` - I/O error if directory cannot be read
 ///
 /// # Performance
@@ -285,12 +669,15 @@ pub struct FileEntry {
 ///
 /// ```rust
 /// use std::path::Path;
-/// use bestls::fsops::get_files;
+/// use bestls::fsops::{get_files, ListOptions};
 ///
 /// let current_dir = Path::new(".");
-/// let files = get_files(&current_dir, false)?;
+/// let listing = get_files(&current_dir, &ListOptions::default())?;
 ///
-/// for file in files {
+/// for error in &listing.errors {
+///     eprintln!("{}", error);
+/// }
+/// for file in listing.entries {
 ///     println!("{}: {}", file.name, file.human_size);
 /// }
 /// # Ok::<(), std::io::Error>(())
@@ -300,10 +687,11 @@ pub struct FileEntry {
 ///
 /// ```rust
 /// use std::path::Path;
-/// use bestls::fsops::get_files;
+/// use bestls::fsops::{get_files, ListOptions};
 ///
 /// let home_dir = Path::new("/home/user");
-/// let all_files = get_files(&home_dir, true)?; // Include .bashrc, .profile, etc.
+/// let opts = ListOptions { include_hidden: true, ..Default::default() }; // Include .bashrc, .profile, etc.
+/// let all_files = get_files(&home_dir, &opts)?.entries;
 ///
 /// let hidden_count = all_files.iter()
 ///     .filter(|f| f.name.starts_with('.'))
@@ -317,11 +705,11 @@ pub struct FileEntry {
 ///
 /// ```rust
 /// use std::path::Path;
-/// use bestls::fsops::get_files;
+/// use bestls::fsops::{get_files, ListOptions};
 ///
 /// let restricted_dir = Path::new("/root");
-/// match get_files(&restricted_dir, false) {
-///     Ok(files) => println!("Found {} files", files.len()),
+/// match get_files(&restricted_dir, &ListOptions::default()) {
+///     Ok(listing) => println!("Found {} files", listing.entries.len()),
 ///     Err(e) => eprintln!("Cannot access directory: {}", e),
 /// }
 /// ```
@@ -331,26 +719,212 @@ pub struct FileEntry {
 /// This function can return errors in several scenarios:
 /// - Directory does not exist
 /// - Permission denied to read directory
-/// - Path points to a file rather than directory
 /// - I/O errors during filesystem access
 ///
-/// Individual file metadata extraction errors are silently ignored to allow partial
-/// directory listings even when some files cannot be accessed.
-pub fn get_files(path: &Path, include_hidden: bool) -> Result<Vec<FileEntry>, io::Error> {
-    let entries: Vec<fs::DirEntry> = fs::read_dir(path)?
-        .filter_map(Result::ok)
-        .filter(|entry: &fs::DirEntry| {
-            include_hidden || !entry.file_name().to_string_lossy().starts_with('.')
-        })
-        .collect();
+/// A path that points to a file (or a symlink to one) is listed as that single entry.
+///
+/// An entry that cannot be read does not fail the listing; it is returned in
+/// [`ListResult::errors`] instead, so callers can report it.
+pub fn get_files(path: &Path, opts: &ListOptions) -> Result<ListResult, io::Error> {
+    if root_as_entry(path, opts) {
+        return Ok(ListResult {
+            entries: vec![get_path_entry(path, opts)?],
+            errors: Vec::new(),
+        });
+    }
 
-    let files: Vec<FileEntry> = entries
+    let mut errors = Vec::new();
+    let (entries, _) = read_visible_entries(path, "", opts, opts.max_entries, &mut errors)?;
+
+    let virtual_fs = virtual_fs_kind(path).is_some();
+    let mut results: Vec<(usize, io::Result<FileEntry>)> = entries
         .par_iter()
-        .map(map_data)
-        .filter_map(Result::ok)
+        .enumerate()
+        // After Ctrl-C, remaining entries are skipped so partial results come back quickly
+        .filter_map(|(index, entry)| {
+            if interrupt::is_cancelled() {
+                return None;
+            }
+            Some((index, map_data(entry, opts, virtual_fs)))
+        })
         .collect();
+    // Keep the directory order regardless of how the work was split across threads
+    results.sort_by_key(|(index, _)| *index);
 
-    Ok(files)
+    let mut files = Vec::with_capacity(results.len());
+    for (index, result) in results {
+        match result {
+            Ok(file) => files.push(file),
+            Err(error) => errors.push(ListError {
+                path: entries[index].path(),
+                error,
+                code: Code::EntryUnreadable,
+            }),
+        }
+    }
+    Ok(ListResult {
+        entries: files,
+        errors,
+    })
+}
+
+/// Directory entries [`FileStream`] reads and maps together: enough to keep the
+/// `rayon` pool busy, few enough that output starts at once and memory stays bounded
+const STREAM_BATCH: usize = 256;
+
+/// List a directory like [`get_files`], yielding entries as they are read instead of
+/// collecting the listing.
+///
+/// `read_dir` is consumed [`STREAM_BATCH`] visible entries at a time; each batch is
+/// mapped in parallel and handed out in the order the operating system returned it,
+/// so a consumer can write a huge directory out while it is still being read, holding
+/// no more than one batch. Entries that cannot be read come out as `Err` in their
+/// place. Reading stops after `opts.max_entries` entries and on Ctrl-C, like
+/// [`get_files`]; dropping the iterator stops it early.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be read.
+pub fn stream_files<'a>(path: &Path, opts: &'a ListOptions) -> Result<FileStream<'a>, io::Error> {
+    if root_as_entry(path, opts) {
+        return Ok(FileStream {
+            read_dir: None,
+            ready: VecDeque::from([Ok(get_path_entry(path, opts)?)]),
+            dir: path.to_path_buf(),
+            opts,
+            dot_hidden: HashSet::new(),
+            virtual_fs: false,
+            listed: 0,
+        });
+    }
+
+    Ok(FileStream {
+        read_dir: Some(fs::read_dir(long_path(path))?),
+        ready: VecDeque::with_capacity(STREAM_BATCH),
+        dir: path.to_path_buf(),
+        opts,
+        dot_hidden: dot_hidden_names(path, opts),
+        virtual_fs: virtual_fs_kind(path).is_some(),
+        listed: 0,
+    })
+}
+
+/// Iterator over a directory's entries, returned by [`stream_files`]
+pub struct FileStream<'a> {
+    /// `None` once the directory is exhausted or reading stopped
+    read_dir: Option<fs::ReadDir>,
+    /// Mapped entries of the current batch, in directory order
+    ready: VecDeque<Result<FileEntry, ListError>>,
+    dir: PathBuf,
+    opts: &'a ListOptions,
+    dot_hidden: HashSet<String>,
+    virtual_fs: bool,
+    /// Visible entries read so far, for `max_entries`
+    listed: usize,
+}
+
+impl FileStream<'_> {
+    /// Whether every entry read so far has been handed out, so a consumer writing them
+    /// can flush before the next batch is read
+    pub fn batch_drained(&self) -> bool {
+        self.ready.is_empty()
+    }
+
+    /// Read the next batch of visible entries and map them in parallel
+    fn fill(&mut self) {
+        let Some(read_dir) = self.read_dir.as_mut() else {
+            return;
+        };
+        let mut batch: Vec<io::Result<fs::DirEntry>> = Vec::with_capacity(STREAM_BATCH);
+        while batch.len() < STREAM_BATCH {
+            if interrupt::is_cancelled() {
+                self.read_dir = None;
+                break;
+            }
+            match read_dir.next() {
+                None => {
+                    self.read_dir = None;
+                    break;
+                }
+                Some(Ok(entry)) if !is_visible(&entry, "", self.opts, &self.dot_hidden) => {}
+                Some(Ok(entry)) => {
+                    if self
+                        .opts
+                        .max_entries
+                        .is_some_and(|limit| self.listed == limit)
+                    {
+                        interrupt::mark_truncated();
+                        self.read_dir = None;
+                        break;
+                    }
+                    self.listed += 1;
+                    batch.push(Ok(entry));
+                }
+                Some(Err(error)) => batch.push(Err(error)),
+            }
+        }
+
+        let (dir, opts, virtual_fs) = (&self.dir, self.opts, self.virtual_fs);
+        let mapped: Vec<Result<FileEntry, ListError>> = batch
+            .into_par_iter()
+            .map(|entry| match entry {
+                Ok(entry) => map_data(&entry, opts, virtual_fs).map_err(|error| ListError {
+                    path: entry.path(),
+                    error,
+                    code: Code::EntryUnreadable,
+                }),
+                Err(error) => Err(ListError {
+                    path: dir.clone(),
+                    error,
+                    code: Code::EntryUnreadable,
+                }),
+            })
+            .collect();
+        self.ready.extend(mapped);
+    }
+}
+
+impl Iterator for FileStream<'_> {
+    type Item = Result<FileEntry, ListError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() && self.read_dir.is_some() {
+            self.fill();
+        }
+        self.ready.pop_front()
+    }
+}
+
+/// Entries of a listing, plus everything that could not be read along the way
+#[derive(Debug, Default)]
+pub struct ListResult {
+    pub entries: Vec<FileEntry>,
+    pub errors: Vec<ListError>,
+}
+
+/// An entry or subdirectory that could not be read during a listing
+#[derive(Debug)]
+pub struct ListError {
+    pub path: PathBuf,
+    pub error: io::Error,
+    /// [`Code::PathUnreadable`] for a subdirectory that cannot be read,
+    /// [`Code::EntryUnreadable`] for a single entry
+    pub code: Code,
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = display_path(&self.path);
+        match self.code {
+            Code::PathUnreadable => write!(
+                f,
+                "failed to read directory '{}': {}",
+                path.display(),
+                self.error
+            ),
+            _ => write!(f, "cannot access '{}': {}", path.display(), self.error),
+        }
+    }
 }
 
 /// Internal function to extract comprehensive metadata from a file system entry.
@@ -362,6 +936,9 @@ pub fn get_files(path: &Path, include_hidden: bool) -> Result<Vec<FileEntry>, io
 /// # Arguments
 ///
 /// * `entry` - A directory entry from `fs::read_dir()`
+/// * `opts` - Listing options selecting optional metadata (e.g. previews)
+/// * `virtual_fs` - Whether the entry lives on a kernel pseudo filesystem (`/proc`,
+///   `/sys`, ...), in which case sizes are not reported and file contents are never read
 ///
 /// # Returns
 ///
@@ -389,11 +966,10 @@ pub fn get_files(path: &Path, include_hidden: bool) -> Result<Vec<FileEntry>, io
 /// This function is called internally by [`get_files`] and typically not used directly:
 ///
 /// ```rust
-/// // Internal usage within get_files()
-/// let files: Vec<FileEntry> = entries
+/// // Internal usage within collect_files_recursive()
+/// let results: Vec<io::Result<FileEntry>> = entries
 ///     .par_iter()
-///     .map(map_data)  // <- This function
-///     .filter_map(Result::ok)
+///     .map(|entry| map_data(entry, opts, virtual_fs))
 ///     .collect();
 /// ```
 ///
@@ -404,37 +980,71 @@ pub fn get_files(path: &Path, include_hidden: bool) -> Result<Vec<FileEntry>, io
 /// - File system access issues
 /// - Platform-specific permission/ownership resolution
 ///
-/// These errors are typically handled by the calling [`get_files`] function,
-/// which filters out failed entries to provide partial results.
-fn map_data(entry: &fs::DirEntry) -> Result<FileEntry, io::Error> {
+/// [`get_files`] and [`get_files_recursive`] return these errors in
+/// [`ListResult::errors`] and leave the failed entries out, so that callers still
+/// get partial results.
+fn map_data(
+    entry: &fs::DirEntry,
+    opts: &ListOptions,
+    virtual_fs: bool,
+) -> Result<FileEntry, io::Error> {
     let metadata: fs::Metadata = entry.metadata()?;
+    Ok(entry_from_metadata(
+        entry.file_name().to_string_lossy().to_string(),
+        entry.path(),
+        &metadata,
+        opts,
+        virtual_fs,
+    ))
+}
+
+/// Build a [`FileEntry`] for the path itself, without following a final symlink.
+///
+/// Used when the listing root is not a directory (`bestls Cargo.toml`), for
+/// `--directory`, and for `--no-dereference`, where a symlink given as the root is
+/// shown as a single entry instead of being listed through.
+pub fn get_path_entry(path: &Path, opts: &ListOptions) -> Result<FileEntry, io::Error> {
+    let metadata: fs::Metadata = fs::symlink_metadata(long_path(path))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| display_path(path).to_string_lossy().to_string());
+    let virtual_fs = virtual_fs_kind(path).is_some();
+    Ok(entry_from_metadata(
+        name,
+        path.to_path_buf(),
+        &metadata,
+        opts,
+        virtual_fs,
+    ))
+}
+
+/// Build a [`FileEntry`] from already fetched (non-followed) metadata
+fn entry_from_metadata(
+    name: String,
+    path: PathBuf,
+    metadata: &fs::Metadata,
+    opts: &ListOptions,
+    virtual_fs: bool,
+) -> FileEntry {
     let file_type: fs::FileType = metadata.file_type();
 
-    let modified: String = metadata
-        .modified()
-        .map(|m: std::time::SystemTime| {
-            let dt: DateTime<Utc> = m.into();
-            dt.format("%a %d %b %Y %H:%M:%S").to_string()
-        })
+    let modified_time: Option<SystemTime> = metadata.modified().ok();
+    let modified: String = modified_time
+        .map(|m: SystemTime| opts.timestamps.format(m.into()))
         .unwrap_or_default();
+    // Mounts with noatime/relatime keep this stale rather than missing
+    let accessed_time: Option<SystemTime> = metadata.accessed().ok();
+    let accessed: String = accessed_time
+        .map(|a| opts.timestamps.format(a.into()))
+        .unwrap_or_default();
+    // Unsupported on some platforms and filesystems (e.g. older Linux kernels, NFS)
+    let created_time: Option<SystemTime> = metadata.created().ok();
+    let created = created_time.map(|c| opts.timestamps.format(c.into()));
 
     // Permissions
     #[cfg(unix)]
-    let permissions: String = {
-        let mode: u32 = metadata.permissions().mode();
-        format!(
-            "{}{}{}{}{}{}{}{}{}",
-            if mode & 0o400 != 0 { 'r' } else { '-' },
-            if mode & 0o200 != 0 { 'w' } else { '-' },
-            if mode & 0o100 != 0 { 'x' } else { '-' },
-            if mode & 0o040 != 0 { 'r' } else { '-' },
-            if mode & 0o020 != 0 { 'w' } else { '-' },
-            if mode & 0o010 != 0 { 'x' } else { '-' },
-            if mode & 0o004 != 0 { 'r' } else { '-' },
-            if mode & 0o002 != 0 { 'w' } else { '-' },
-            if mode & 0o001 != 0 { 'x' } else { '-' },
-        )
-    };
+    let permissions: String = format_mode(metadata.permissions().mode());
 
     #[cfg(windows)]
     let permissions = if metadata.permissions().readonly() {
@@ -446,9 +1056,14 @@ fn map_data(entry: &fs::DirEntry) -> Result<FileEntry, io::Error> {
     #[cfg(not(any(unix, windows)))]
     let permissions = "N/A".to_string();
 
+    #[cfg(unix)]
+    let mode = Some(metadata.permissions().mode());
+    #[cfg(not(unix))]
+    let mode = None;
+
     // Owner / Group - Using nix crate instead of users
     #[cfg(unix)]
-    let (owner_name, group_name) = get_owner_group(&metadata);
+    let (owner_name, group_name) = get_owner_group(metadata);
 
     #[cfg(windows)]
     let (owner_name, group_name) = ("Owner".into(), "Group".into());
@@ -456,24 +1071,418 @@ fn map_data(entry: &fs::DirEntry) -> Result<FileEntry, io::Error> {
     #[cfg(not(any(unix, windows)))]
     let (owner_name, group_name) = ("N/A".into(), "N/A".into());
 
-    Ok(FileEntry {
-        name: entry.file_name().to_string_lossy().to_string(),
-        e_type: if file_type.is_file() {
-            FileType::File
-        } else if file_type.is_dir() {
-            FileType::Directory
-        } else if file_type.is_symlink() {
-            FileType::Symlink
+    #[cfg(unix)]
+    let (uid, gid) = (Some(metadata.uid()), Some(metadata.gid()));
+    #[cfg(not(unix))]
+    let (uid, gid) = (None, None);
+
+    #[cfg(unix)]
+    let (nlink, blocks) = (Some(metadata.nlink()), Some(metadata.blocks()));
+    #[cfg(not(unix))]
+    let (nlink, blocks) = (None, None);
+
+    let e_type = if file_type.is_file() {
+        FileType::File
+    } else if file_type.is_dir() {
+        FileType::Directory
+    } else if file_type.is_symlink() {
+        FileType::Symlink
+    } else {
+        FileType::File
+    };
+    let (extension, stem) = split_name(&name, &e_type);
+    // Special files are shown as files but are never "executable"
+    #[cfg(unix)]
+    let is_executable = file_type.is_file() && is_executable_mode(&e_type, mode);
+    #[cfg(not(unix))]
+    let is_executable = file_type.is_file() && has_executable_extension(extension.as_deref());
+
+    // Virtual files are never read, so they are typed by name alone
+    let mime = opts.mime.map(|detection| {
+        let detection = if virtual_fs {
+            MimeDetection::Name
         } else {
-            FileType::File
-        },
-        len_bytes: metadata.len(),
-        human_size: ByteSize(metadata.len()).to_string(),
+            detection
+        };
+        mime::detect(&name, &path, file_type, detection)
+    });
+
+    // A directory's own size is its entry table; --total-size sums what is inside
+    let total =
+        (opts.total_size && file_type.is_dir() && !virtual_fs).then(|| dirsize::dir_size(&path));
+    let len_bytes = match total {
+        Some(total) => total.bytes,
+        None if virtual_fs => 0,
+        None => metadata.len(),
+    };
+    let human_size = match total {
+        Some(total) if !total.complete => format!("~{}", ByteSize(len_bytes)),
+        _ if virtual_fs => "-".to_string(),
+        _ => ByteSize(len_bytes).to_string(),
+    };
+
+    FileEntry {
+        is_hidden: is_hidden_name(&name),
+        name,
+        e_type,
+        len_bytes,
+        human_size,
         modified,
+        modified_epoch: modified_time.map(epoch_seconds),
+        modified_iso: modified_time.map(rfc3339),
+        modified_time,
+        accessed,
+        accessed_epoch: accessed_time.map(epoch_seconds),
+        accessed_iso: accessed_time.map(rfc3339),
+        accessed_time,
+        created,
+        created_epoch: created_time.map(epoch_seconds),
+        created_iso: created_time.map(rfc3339),
+        created_time,
         permissions,
+        mode,
+        depth: 0,
         owner: owner_name,
         group: group_name,
-    })
+        uid,
+        gid,
+        nlink,
+        blocks,
+        extension,
+        stem,
+        is_executable,
+        last_commit_hash: None,
+        last_commit_date: None,
+        last_commit_summary: None,
+        encrypted: None,
+        preview: if opts.preview && !virtual_fs && file_type.is_file() {
+            read_preview(&path)
+        } else {
+            None
+        },
+        // Virtual files report a zero size whatever they contain
+        is_empty: if opts.probe_empty && !virtual_fs {
+            probe_empty(&path, file_type, metadata.len())
+        } else {
+            None
+        },
+        is_broken: file_type.is_symlink().then(|| is_broken_symlink(&path)),
+        size_approximate: total.map(|total| !total.complete),
+        items: if opts.count_items && file_type.is_dir() {
+            count_items(&path, opts)
+        } else {
+            None
+        },
+        mime,
+        custom_columns: BTreeMap::new(),
+        path,
+    }
+}
+
+/// Whether following the symlink at `path` fails because the target is missing.
+///
+/// Size and times still come from the link itself; only the target is checked.
+fn is_broken_symlink(path: &Path) -> bool {
+    matches!(fs::metadata(long_path(path)), Err(e) if e.kind() == io::ErrorKind::NotFound)
+}
+
+/// Number of entries in the directory at `path` that a listing of it would show
+/// without `--exclude` (`--count-items`); `None` when it cannot be read.
+fn count_items(path: &Path, opts: &ListOptions) -> Option<u64> {
+    let dot_hidden = if opts.respect_dot_hidden && !opts.include_hidden {
+        read_dot_hidden(path)
+    } else {
+        HashSet::new()
+    };
+    let entries = fs::read_dir(long_path(path)).ok()?;
+    let count = entries
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            opts.include_hidden || !(is_hidden_name(&name) || dot_hidden.contains(&name))
+        })
+        .count();
+    Some(count as u64)
+}
+
+/// Whether a file is zero bytes or a directory has no entries (`--empty`).
+///
+/// Symlinks are not followed and yield `None`, as do directories that cannot be read.
+fn probe_empty(path: &Path, file_type: fs::FileType, len: u64) -> Option<bool> {
+    if file_type.is_dir() {
+        fs::read_dir(long_path(path))
+            .ok()
+            .map(|mut entries| entries.next().is_none())
+    } else if file_type.is_symlink() {
+        None
+    } else {
+        Some(len == 0)
+    }
+}
+
+/// Maximum number of bytes read from a file for `--preview`
+pub const PREVIEW_BYTES: usize = 120;
+
+/// Read the first line of a text file for `--preview`.
+///
+/// At most [`PREVIEW_BYTES`] bytes are read. Returns `None` for unreadable, empty,
+/// or binary files (NUL bytes or invalid UTF-8). Control characters are stripped,
+/// and a first line longer than the read limit is truncated with `…`.
+pub fn read_preview(path: &Path) -> Option<String> {
+    use std::io::Read;
+
+    let mut buf = [0u8; PREVIEW_BYTES];
+    let mut file = fs::File::open(long_path(path)).ok()?;
+    let mut len = 0;
+    while len < buf.len() {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return None,
+        }
+    }
+    preview_from_bytes(&buf[..len], len == PREVIEW_BYTES)
+}
+
+/// Build the preview text from the leading bytes of a file
+fn preview_from_bytes(bytes: &[u8], maybe_more: bool) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+
+    let (line, truncated) = match bytes.iter().position(|&b| b == b'\n') {
+        Some(pos) => (&bytes[..pos], false),
+        None => (bytes, maybe_more),
+    };
+
+    // A multi-byte character may be cut at the read limit; anything else is binary
+    let text = match std::str::from_utf8(line) {
+        Ok(text) => text,
+        Err(e) if truncated && e.error_len().is_none() => {
+            std::str::from_utf8(&line[..e.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+
+    let mut cleaned: String = text
+        .chars()
+        .map(|c| if c == '\t' { ' ' } else { c })
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim_end()
+        .to_string();
+
+    if cleaned.is_empty() {
+        return None;
+    }
+    if truncated {
+        cleaned.push('…');
+    }
+    Some(cleaned)
+}
+
+/// Default strftime pattern of the `Modified` column
+pub const DEFAULT_TIMESTAMP_FORMAT: &str = "%a %d %b %Y %H:%M:%S";
+
+/// `--time-style full-iso`: nanoseconds and UTC offset
+const FULL_ISO_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.9f %z";
+/// `--time-style long-iso`
+const LONG_ISO_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// `--time-style iso` for timestamps within [`RECENT_CUTOFF_SECS`]
+const ISO_RECENT_FORMAT: &str = "%m-%d %H:%M";
+/// `--time-style iso` for older (or future) timestamps
+const ISO_OLD_FORMAT: &str = "%Y-%m-%d";
+/// `--time-style locale`: the locale's preferred date and time representation
+const LOCALE_FORMAT: &str = "%c";
+
+/// Age up to which `--time-style iso` shows the time of day: six months, taken as
+/// half an average Gregorian year like coreutils `ls`
+const RECENT_CUTOFF_SECS: i64 = 31_556_952 / 2;
+
+/// Timestamp presets for `--time-style`, modeled on coreutils `ls`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum TimeStyle {
+    /// `Fri 07 Jun 2024 12:00:00`
+    #[default]
+    Default,
+    /// `2024-06-07 12:00:00.000000000 +0000`
+    FullIso,
+    /// `2024-06-07 12:00`
+    LongIso,
+    /// `06-07 12:00` within the last six months, `2024-06-07` otherwise
+    Iso,
+    /// The locale's own date and time format (`%c`)
+    Locale,
+    /// `3 days ago`
+    Relative,
+    /// A custom strftime pattern (`+FORMAT`)
+    Custom(String),
+}
+
+/// Error type for `--time-style` parsing
+#[derive(Debug, Clone)]
+pub enum TimeStyleParseError {
+    Unknown(String),
+    InvalidFormat(String),
+}
+
+impl fmt::Display for TimeStyleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeStyleParseError::Unknown(s) => write!(
+                f,
+                "unknown time style '{}' (expected full-iso, long-iso, iso, locale, relative, or +FORMAT)",
+                s
+            ),
+            TimeStyleParseError::InvalidFormat(s) => {
+                write!(f, "invalid strftime format '{}'", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TimeStyleParseError {}
+
+/// Parse a `--time-style` value (a preset name or `+FORMAT`)
+pub fn parse_time_style(style: &str) -> Result<TimeStyle, TimeStyleParseError> {
+    if let Some(pattern) = style.strip_prefix('+') {
+        // Reject bad specifiers now; chrono would fail while rendering each entry
+        let invalid = chrono::format::StrftimeItems::new(pattern)
+            .any(|item| matches!(item, chrono::format::Item::Error));
+        if pattern.is_empty() || invalid {
+            return Err(TimeStyleParseError::InvalidFormat(pattern.to_string()));
+        }
+        return Ok(TimeStyle::Custom(pattern.to_string()));
+    }
+
+    match style {
+        "default" => Ok(TimeStyle::Default),
+        "full-iso" => Ok(TimeStyle::FullIso),
+        "long-iso" => Ok(TimeStyle::LongIso),
+        "iso" => Ok(TimeStyle::Iso),
+        "locale" => Ok(TimeStyle::Locale),
+        "relative" => Ok(TimeStyle::Relative),
+        _ => Err(TimeStyleParseError::Unknown(style.to_string())),
+    }
+}
+
+/// How timestamps are rendered for display.
+///
+/// Every human-readable timestamp goes through [`TimestampFormat::format_with`], so
+/// month and weekday names (`%a`, `%A`, `%b`, `%B`) follow the configured locale in
+/// every style, custom patterns included.
+#[derive(Debug, Default, Clone)]
+pub struct TimestampFormat {
+    /// Preset or custom pattern (`--time-style`)
+    pub style: TimeStyle,
+    /// Locale for month and weekday names (`--date-locale`); English when `None`
+    pub locale: Option<Locale>,
+}
+
+impl TimestampFormat {
+    /// Format a timestamp the way the `Modified` column displays it.
+    pub fn format(&self, dt: DateTime<Utc>) -> String {
+        self.format_at(dt, Utc::now())
+    }
+
+    /// Format a timestamp relative to `now` (used by the `iso` and `relative` styles).
+    pub fn format_at(&self, dt: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        let pattern = match &self.style {
+            TimeStyle::Default => DEFAULT_TIMESTAMP_FORMAT,
+            TimeStyle::FullIso => FULL_ISO_FORMAT,
+            TimeStyle::LongIso => LONG_ISO_FORMAT,
+            TimeStyle::Iso => {
+                let age = (now - dt).num_seconds();
+                if (0..=RECENT_CUTOFF_SECS).contains(&age) {
+                    ISO_RECENT_FORMAT
+                } else {
+                    ISO_OLD_FORMAT
+                }
+            }
+            TimeStyle::Locale => LOCALE_FORMAT,
+            TimeStyle::Relative => return format_relative_time(dt, now),
+            TimeStyle::Custom(pattern) => pattern,
+        };
+        self.format_with(dt, pattern)
+    }
+
+    /// Format a timestamp with a custom strftime pattern.
+    pub fn format_with(&self, dt: DateTime<Utc>, pattern: &str) -> String {
+        match self.locale {
+            Some(locale) => dt.format_localized(pattern, locale).to_string(),
+            None => dt.format(pattern).to_string(),
+        }
+    }
+}
+
+/// Whole seconds since the Unix epoch (negative before 1970), as serialized in
+/// `modified_epoch` and its siblings
+pub fn epoch_seconds(time: SystemTime) -> i64 {
+    DateTime::<Utc>::from(time).timestamp()
+}
+
+/// RFC 3339 timestamp in UTC to the second, as serialized in `modified_iso` and
+/// its siblings
+pub fn rfc3339(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Parse a locale name such as `de_DE`, `fr-FR`, `pt_BR.UTF-8`, or `de`.
+///
+/// The encoding suffix is ignored, `-` is accepted in place of `_`, and a bare
+/// language code maps to its main territory (`de` → `de_DE`). `C` is the POSIX locale.
+///
+/// # Returns
+///
+/// `None` if no locale data exists for the name.
+pub fn parse_locale(name: &str) -> Option<Locale> {
+    // Drop the encoding ("de_DE.UTF-8@euro" -> "de_DE@euro")
+    let (base, modifier) = match name.split_once('@') {
+        Some((base, modifier)) => (base, Some(modifier)),
+        None => (name, None),
+    };
+    let base = base.split('.').next().unwrap_or_default().replace('-', "_");
+    if base == "C" || base == "POSIX" {
+        return Some(Locale::POSIX);
+    }
+
+    let with_modifier = |base: &str| match modifier {
+        Some(modifier) => format!("{}@{}", base, modifier),
+        None => base.to_string(),
+    };
+    if let Ok(locale) = Locale::try_from(with_modifier(&base).as_str()) {
+        return Some(locale);
+    }
+    if !base.is_empty() && !base.contains('_') {
+        let territory = format!("{}_{}", base, base.to_uppercase());
+        return Locale::try_from(with_modifier(&territory).as_str()).ok();
+    }
+    None
+}
+
+/// Locale name for dates from the environment (`LC_ALL`, then `LC_TIME`, then `LANG`).
+pub fn time_locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Render the permission bits of a Unix mode as an `rwxrwxrwx` string.
+pub fn format_mode(mode: u32) -> String {
+    format!(
+        "{}{}{}{}{}{}{}{}{}",
+        if mode & 0o400 != 0 { 'r' } else { '-' },
+        if mode & 0o200 != 0 { 'w' } else { '-' },
+        if mode & 0o100 != 0 { 'x' } else { '-' },
+        if mode & 0o040 != 0 { 'r' } else { '-' },
+        if mode & 0o020 != 0 { 'w' } else { '-' },
+        if mode & 0o010 != 0 { 'x' } else { '-' },
+        if mode & 0o004 != 0 { 'r' } else { '-' },
+        if mode & 0o002 != 0 { 'w' } else { '-' },
+        if mode & 0o001 != 0 { 'x' } else { '-' },
+    )
 }
 
 /// Extract user and group names from file metadata on Unix systems.
@@ -565,7 +1574,7 @@ impl fmt::Display for SizeParseError {
             SizeParseError::InvalidUnit(u) => {
                 write!(
                     f,
-                    "unknown size unit '{}' (valid units: B, KB, MB, GB, TB)",
+                    "unknown size unit '{}' (valid units: B, K/KB/KiB, M/MB/MiB, G/GB/GiB, T/TB/TiB)",
                     u
                 )
             }
@@ -587,14 +1596,17 @@ impl std::error::Error for SizeParseError {}
 ///
 /// Supports integer and decimal inputs (e.g., "1024", "1.5MB").
 /// Uses integer arithmetic where possible to avoid floating-point precision issues.
+/// Units are case-insensitive and binary: `K`, `KB`, and `KiB` all mean 1024 bytes.
 ///
 /// # Examples
 /// - "1KB" → Ok(1024)
 /// - "1.5MB" → Ok(1572864)
+/// - "2GiB" → Ok(2147483648)
 /// - "100" → Ok(100) (defaults to bytes)
 /// - "invalid" → Err(SizeParseError::InvalidNumber(...))
 pub fn parse_size(size_str: &str) -> Result<u64, SizeParseError> {
-    let size_str = size_str.trim().to_uppercase();
+    let input = size_str.trim();
+    let size_str = input.to_uppercase();
 
     if size_str.is_empty() {
         return Err(SizeParseError::Empty);
@@ -607,13 +1619,17 @@ pub fn parse_size(size_str: &str) -> Result<u64, SizeParseError> {
     };
 
     let num_str = num_part.trim();
+    // "banana" is a bad number, not a bad unit
+    if num_str.is_empty() {
+        return Err(SizeParseError::InvalidNumber(input.to_string()));
+    }
 
-    let multiplier = match unit {
+    let multiplier = match unit.trim() {
         "B" => 1u64,
-        "KB" | "K" => 1024u64,
-        "MB" | "M" => 1024u64 * 1024u64,
-        "GB" | "G" => 1024u64 * 1024u64 * 1024u64,
-        "TB" | "T" => 1024u64 * 1024u64 * 1024u64 * 1024u64,
+        "KB" | "K" | "KIB" => 1024u64,
+        "MB" | "M" | "MIB" => 1024u64 * 1024u64,
+        "GB" | "G" | "GIB" => 1024u64 * 1024u64 * 1024u64,
+        "TB" | "T" | "TIB" => 1024u64 * 1024u64 * 1024u64 * 1024u64,
         _ => return Err(SizeParseError::InvalidUnit(unit.to_string())),
     };
 
@@ -645,6 +1661,212 @@ pub fn parse_size(size_str: &str) -> Result<u64, SizeParseError> {
     Ok(result as u64)
 }
 
+/// Error type for duration parsing operations
+#[derive(Debug, Clone)]
+pub enum DurationParseError {
+    Empty,
+    InvalidNumber(String),
+    InvalidUnit(String),
+    Overflow,
+    /// Looked like a date but is not a valid one
+    InvalidDate(String),
+}
+
+impl fmt::Display for DurationParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DurationParseError::Empty => write!(f, "empty duration string provided"),
+            DurationParseError::InvalidNumber(s) => {
+                write!(
+                    f,
+                    "invalid duration value '{}' (expected a whole number)",
+                    s
+                )
+            }
+            DurationParseError::InvalidUnit(u) => write!(
+                f,
+                "unknown duration unit '{}' (valid units: s, m, h, d, w, y)",
+                u
+            ),
+            DurationParseError::Overflow => write!(f, "duration value is too large"),
+            DurationParseError::InvalidDate(s) => write!(
+                f,
+                "invalid date '{}' (expected YYYY-MM-DD, YYYY-MM-DDTHH:MM:SS, or RFC 3339)",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DurationParseError {}
+
+/// Parse relative durations (e.g., "30m", "12h", "180d", "2w")
+///
+/// A bare number is taken as days. Years are counted as 365 days.
+///
+/// # Examples
+/// - "90s" → 90 seconds
+/// - "180d" → 180 days
+/// - "7" → 7 days
+pub fn parse_duration(duration_str: &str) -> Result<std::time::Duration, DurationParseError> {
+    let duration_str = duration_str.trim().to_lowercase();
+
+    if duration_str.is_empty() {
+        return Err(DurationParseError::Empty);
+    }
+
+    let (num_part, unit) = match duration_str.find(|c: char| c.is_alphabetic()) {
+        Some(pos) => (&duration_str[..pos], &duration_str[pos..]),
+        None => (&duration_str[..], "d"),
+    };
+
+    let seconds_per_unit: u64 = match unit {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        "w" => 7 * 86_400,
+        "y" => 365 * 86_400,
+        _ => return Err(DurationParseError::InvalidUnit(unit.to_string())),
+    };
+
+    let num_str = num_part.trim();
+    let num: u64 = num_str
+        .parse()
+        .map_err(|_| DurationParseError::InvalidNumber(num_str.to_string()))?;
+
+    num.checked_mul(seconds_per_unit)
+        .map(std::time::Duration::from_secs)
+        .ok_or(DurationParseError::Overflow)
+}
+
+/// A point in time given on the command line (`--newer-than`, `--older-than`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBound {
+    /// That long before the listing starts ("7d")
+    Ago(std::time::Duration),
+    /// A fixed instant ("2024-01-01")
+    At(SystemTime),
+}
+
+impl TimeBound {
+    /// The instant this bound stands for when the listing starts at `now`
+    pub fn resolve(self, now: SystemTime) -> SystemTime {
+        match self {
+            TimeBound::Ago(ago) => now.checked_sub(ago).unwrap_or(SystemTime::UNIX_EPOCH),
+            TimeBound::At(at) => at,
+        }
+    }
+}
+
+/// Parse a relative duration (see [`parse_duration`]) or an absolute date.
+///
+/// Dates are ISO 8601 in local time (`2024-01-01` is local midnight,
+/// `2024-01-01T12:30:00`), or RFC 3339 with an offset (`2024-01-01T12:30:00Z`).
+pub fn parse_time_bound(input: &str) -> Result<TimeBound, DurationParseError> {
+    use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+
+    let input = input.trim();
+    // YYYY-... is a date; anything else is a duration
+    let looks_like_date = input.len() >= 5
+        && input.as_bytes()[..4].iter().all(u8::is_ascii_digit)
+        && input.as_bytes()[4] == b'-';
+    if !looks_like_date {
+        return parse_duration(input).map(TimeBound::Ago);
+    }
+
+    let invalid = || DurationParseError::InvalidDate(input.to_string());
+    let local = if let Ok(instant) = DateTime::parse_from_rfc3339(input) {
+        return Ok(TimeBound::At(instant.into()));
+    } else if let Ok(datetime) = NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S") {
+        datetime
+    } else {
+        NaiveDate::parse_from_str(input, "%Y-%m-%d")
+            .map_err(|_| invalid())?
+            .and_hms_opt(0, 0, 0)
+            .ok_or_else(invalid)?
+    };
+    // The earliest reading of a local time skipped or repeated by a DST change
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|instant| TimeBound::At(instant.into()))
+        .ok_or_else(invalid)
+}
+
+/// Format the time elapsed between `then` and `now` in a compact human form.
+///
+/// # Examples
+/// - 30 seconds → "just now"
+/// - 5 minutes → "5 minutes ago"
+/// - 3 days → "3 days ago"
+pub fn format_relative_time(then: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - then).num_seconds();
+    if secs < 0 {
+        return "in the future".to_string();
+    }
+
+    let (value, unit) = match secs {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (secs / 60, "minute"),
+        3_600..=86_399 => (secs / 3_600, "hour"),
+        86_400..=2_591_999 => (secs / 86_400, "day"),
+        2_592_000..=31_535_999 => (secs / 2_592_000, "month"),
+        _ => (secs / 31_536_000, "year"),
+    };
+
+    if value == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", value, unit)
+    }
+}
+
+/// Compare names in natural order, so `file2` sorts before `file10`.
+///
+/// Runs of ASCII digits compare by numeric value (of any length, without parsing
+/// into an integer); other characters compare as usual. Numerically equal runs put
+/// fewer leading zeros first (`a2` before `a02`), and names that still tie fall back
+/// to a plain comparison so the order is total.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut left, mut right) = (a, b);
+    loop {
+        let (Some(l), Some(r)) = (left.chars().next(), right.chars().next()) else {
+            // One name is a prefix of the other
+            return left.len().cmp(&right.len()).then_with(|| a.cmp(b));
+        };
+
+        if l.is_ascii_digit() && r.is_ascii_digit() {
+            let l_len = left
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(left.len());
+            let r_len = right
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(right.len());
+            let (l_run, r_run) = (&left[..l_len], &right[..r_len]);
+            let l_digits = l_run.trim_start_matches('0');
+            let r_digits = r_run.trim_start_matches('0');
+
+            let ordering = l_digits
+                .len()
+                .cmp(&r_digits.len())
+                .then_with(|| l_digits.cmp(r_digits))
+                .then_with(|| l_run.len().cmp(&r_run.len()));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+            left = &left[l_len..];
+            right = &right[r_len..];
+        } else {
+            if l != r {
+                return l.cmp(&r);
+            }
+            left = &left[l.len_utf8()..];
+            right = &right[r.len_utf8()..];
+        }
+    }
+}
+
 /// Check if filename matches extension filter (case-insensitive)
 /// Extensions should be pre-normalized (lowercase, without leading '.')
 pub fn matches_extension(filename: &str, extensions: &[String]) -> bool {
@@ -687,6 +1909,62 @@ pub fn matches_pattern(filename: &str, pattern: &glob::Pattern) -> bool {
     pattern.matches(filename)
 }
 
+/// Check if filename matches any of the glob patterns (`--glob`)
+pub fn matches_any_glob(
+    filename: &str,
+    patterns: &[glob::Pattern],
+    options: glob::MatchOptions,
+) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| pattern.matches_with(filename, options))
+}
+
+/// Byte ranges of `filename` containing any of the `--search` terms, ignoring case.
+///
+/// Names are compared lowercased character by character, so a match on `É` maps back
+/// to the original character even when its lowercase form has a different length.
+/// Overlapping matches of several terms are merged; the ranges are sorted.
+pub fn search_matches(filename: &str, terms: &[String]) -> Vec<Range<usize>> {
+    let mut folded = String::with_capacity(filename.len());
+    // Original character range for every byte of `folded`
+    let mut origin: Vec<Range<usize>> = Vec::with_capacity(filename.len());
+    for (start, c) in filename.char_indices() {
+        for lower in c.to_lowercase() {
+            folded.push(lower);
+            origin.resize(folded.len(), start..start + c.len_utf8());
+        }
+    }
+
+    let mut ranges: Vec<Range<usize>> = terms
+        .iter()
+        .filter(|term| !term.is_empty())
+        .flat_map(|term| {
+            let term = term.to_lowercase();
+            folded
+                .match_indices(&term)
+                .map(|(at, found)| origin[at].start..origin[at + found.len() - 1].end)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    ranges.sort_by_key(|r| r.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+/// Check if an owner or group matches `wanted`, by name or by numeric ID
+/// (`--owner 1000` works when the name cannot be resolved)
+pub fn matches_owner(name: &str, id: Option<u32>, wanted: &str) -> bool {
+    name == wanted || id.is_some_and(|id| wanted.parse::<u32>() == Ok(id))
+}
+
 /// Recursively get files with optional depth limit
 ///
 /// # Depth semantics
@@ -694,68 +1972,1232 @@ pub fn matches_pattern(filename: &str, pattern: &glob::Pattern) -> bool {
 /// - `depth = 1`: Only files in the specified directory (no recursion)
 /// - `depth = 2`: Files in the directory plus one level of subdirectories
 /// - `depth = n`: Files up to n levels deep
+///
+/// Only an unreadable `path` fails the walk; subdirectories and entries below it
+/// that cannot be read are skipped and returned in [`ListResult::errors`].
 pub fn get_files_recursive(
     path: &Path,
-    include_hidden: bool,
+    opts: &ListOptions,
     max_depth: Option<usize>,
-) -> Result<Vec<FileEntry>, io::Error> {
-    let mut files = Vec::new();
-    collect_files_recursive(path, include_hidden, max_depth, 0, &mut files)?;
-    Ok(files)
+) -> Result<ListResult, io::Error> {
+    if root_as_entry(path, opts) {
+        return Ok(ListResult {
+            entries: vec![get_path_entry(path, opts)?],
+            errors: Vec::new(),
+        });
+    }
+
+    let mut listing = ListResult::default();
+    let mut walk = Walk::default();
+    if opts.follow_symlinks {
+        walk.ancestors.extend(platform::file_id(&long_path(path)));
+    }
+    if opts.one_file_system {
+        walk.device = platform::device_id(&long_path(path)).ok();
+    }
+    collect_files_recursive(path, "", opts, max_depth, 0, &mut walk, &mut listing)?;
+    Ok(listing)
+}
+
+/// State of a recursive walk beyond the directory being read
+#[derive(Default)]
+struct Walk {
+    /// With `--follow-symlinks`, the identities of the directories being walked, from
+    /// the listed one down to the current one
+    ancestors: Vec<FileId>,
+    /// With `--one-file-system`, the filesystem of the listed directory
+    device: Option<DeviceId>,
 }
 
+/// Whether a directory on `device` is on another filesystem than the walk's `root`
+/// (`--one-file-system`). When either is unknown, the directory is entered.
+fn leaves_file_system<T: PartialEq>(root: Option<&T>, device: Option<&T>) -> bool {
+    matches!((root, device), (Some(root), Some(device)) if root != device)
+}
+
+/// Walk `path` (at `relative` below the listed directory) into `listing`; returns
+/// `true` once `--max-entries` stopped the walk
 fn collect_files_recursive(
     path: &Path,
-    include_hidden: bool,
+    relative: &str,
+    opts: &ListOptions,
     max_depth: Option<usize>,
     current_depth: usize,
-    files: &mut Vec<FileEntry>,
-) -> Result<(), io::Error> {
+    walk: &mut Walk,
+    listing: &mut ListResult,
+) -> Result<bool, io::Error> {
     // Check depth limit: if current_depth >= max_depth and max_depth > 0, stop recursing
     // max_depth = None or Some(0) means no limit; max_depth = 1 means current level only
     if let Some(max) = max_depth {
         if max > 0 && current_depth >= max {
-            return Ok(());
+            return Ok(false);
         }
     }
+    if interrupt::is_cancelled() {
+        return Ok(false);
+    }
 
-    let entries: Vec<fs::DirEntry> = fs::read_dir(path)?
-        .filter_map(Result::ok)
-        .filter(|entry: &fs::DirEntry| {
-            include_hidden || !entry.file_name().to_string_lossy().starts_with('.')
-        })
-        .collect();
+    // Entries still allowed under --max-entries, counted across the whole walk
+    let remaining = opts
+        .max_entries
+        .map(|max| max.saturating_sub(listing.entries.len()));
+    let (entries, truncated) =
+        read_visible_entries(path, relative, opts, remaining, &mut listing.errors)?;
 
-    let mut file_entries: Vec<FileEntry> = entries
+    let virtual_fs = virtual_fs_kind(path).is_some();
+    let results: Vec<Result<FileEntry, ListError>> = entries
         .par_iter()
-        .map(map_data)
-        .filter_map(Result::ok)
+        // After Ctrl-C, remaining entries are skipped so partial results come back quickly
+        .filter_map(|entry| {
+            if interrupt::is_cancelled() {
+                return None;
+            }
+            Some(
+                map_data(entry, opts, virtual_fs).map_err(|error| ListError {
+                    path: entry.path(),
+                    error,
+                    code: Code::EntryUnreadable,
+                }),
+            )
+        })
         .collect();
 
-    files.append(&mut file_entries);
+    for result in results {
+        match result {
+            Ok(mut file) => {
+                file.depth = current_depth;
+                if opts.relative_names {
+                    file.name = relative_child(relative, &file.name);
+                }
+                listing.entries.push(file);
+            }
+            Err(error) => listing.errors.push(error),
+        }
+    }
+    if truncated {
+        return Ok(true);
+    }
 
     // Recurse into directories if we haven't hit the depth limit
     for entry in entries {
-        if let Ok(metadata) = entry.metadata() {
-            if metadata.is_dir() {
-                // Log subdirectory traversal errors but continue with other directories
-                // This allows collecting as many files as possible even if some subdirs are inaccessible
-                if let Err(e) = collect_files_recursive(
-                    &entry.path(),
-                    include_hidden,
-                    max_depth,
-                    current_depth + 1,
-                    files,
-                ) {
-                    eprintln!(
-                        "Warning: failed to read directory '{}': {}",
-                        entry.path().display(),
-                        e
-                    );
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        let followed = opts.follow_symlinks
+            && metadata.is_symlink()
+            && fs::metadata(long_path(&path)).is_ok_and(|target| target.is_dir());
+        if !metadata.is_dir() && !followed {
+            continue;
+        }
+        // Mount points are listed above but not entered
+        if opts.one_file_system {
+            let device = platform::device_id(&long_path(&path)).ok();
+            if leaves_file_system(walk.device.as_ref(), device.as_ref()) {
+                continue;
+            }
+        }
+
+        // A directory that is also one of its own ancestors would be walked forever
+        let id = if opts.follow_symlinks {
+            platform::file_id(&long_path(&path)).ok()
+        } else {
+            None
+        };
+        let entered = id.is_some();
+        if let Some(id) = id {
+            if walk.ancestors.contains(&id) {
+                diag::warning(
+                    Code::SymlinkLoop,
+                    Some(&path),
+                    format!(
+                        "not following '{}': it leads back into a directory being listed",
+                        display_path(&path).display()
+                    ),
+                );
+                continue;
+            }
+            walk.ancestors.push(id);
+        }
+
+        // Record subdirectory traversal errors but continue with other directories
+        // This allows collecting as many files as possible even if some subdirs are inaccessible
+        // Excluded directories were dropped above, so their subtrees are never read
+        let name = entry.file_name().to_string_lossy().into_owned();
+        match collect_files_recursive(
+            &path,
+            &relative_child(relative, &name),
+            opts,
+            max_depth,
+            current_depth + 1,
+            walk,
+            listing,
+        ) {
+            Ok(true) => return Ok(true),
+            Ok(false) => {}
+            Err(error) => listing.errors.push(ListError {
+                path,
+                error,
+                code: Code::PathUnreadable,
+            }),
+        }
+        if entered {
+            walk.ancestors.pop();
+        }
+    }
+
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create `target/` with one file and a `link` symlink pointing at it
+    #[cfg(unix)]
+    fn symlinked_dirs() -> (tempfile::TempDir, tempfile::TempDir, PathBuf) {
+        let target = tempfile::tempdir().unwrap();
+        fs::write(target.path().join("inside.txt"), "x").unwrap();
+        let links = tempfile::tempdir().unwrap();
+        let link = links.path().join("link");
+        std::os::unix::fs::symlink(target.path(), &link).unwrap();
+        (target, links, link)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_root_is_followed_by_default() {
+        let (_target, _links, link) = symlinked_dirs();
+
+        let files = get_files(&link, &ListOptions::default()).unwrap().entries;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "inside.txt");
+
+        let files = get_files_recursive(&link, &ListOptions::default(), None)
+            .unwrap()
+            .entries;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "inside.txt");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_no_dereference_lists_the_link_itself() {
+        let (_target, _links, link) = symlinked_dirs();
+        let opts = ListOptions {
+            no_dereference: true,
+            ..Default::default()
+        };
+
+        for files in [
+            get_files(&link, &opts).unwrap().entries,
+            get_files_recursive(&link, &opts, None).unwrap().entries,
+        ] {
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].name, "link");
+            assert_eq!(files[0].e_type, FileType::Symlink);
+        }
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        use std::time::Duration;
+
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1_800));
+        assert_eq!(parse_duration("2H").unwrap(), Duration::from_secs(7_200));
+        assert_eq!(
+            parse_duration("180d").unwrap(),
+            Duration::from_secs(180 * 86_400)
+        );
+        assert_eq!(parse_duration("1w").unwrap(), Duration::from_secs(604_800));
+        assert_eq!(
+            parse_duration("7").unwrap(),
+            Duration::from_secs(7 * 86_400)
+        );
+
+        assert!(matches!(parse_duration(""), Err(DurationParseError::Empty)));
+        assert!(matches!(
+            parse_duration("5q"),
+            Err(DurationParseError::InvalidUnit(_))
+        ));
+        assert!(matches!(
+            parse_duration("1.5d"),
+            Err(DurationParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            parse_duration("99999999999999999y"),
+            Err(DurationParseError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(parse_locale("de_DE"), Some(Locale::de_DE));
+        assert_eq!(parse_locale("fr-FR"), Some(Locale::fr_FR));
+        assert_eq!(parse_locale("pt_BR.UTF-8"), Some(Locale::pt_BR));
+        assert_eq!(parse_locale("de"), Some(Locale::de_DE));
+        assert_eq!(parse_locale("C.UTF-8"), Some(Locale::POSIX));
+        assert_eq!(parse_locale("xx_YY"), None);
+        assert_eq!(parse_locale(""), None);
+    }
+
+    #[test]
+    fn test_localized_timestamps() {
+        let dt = DateTime::from_timestamp(1_717_761_600, 0).unwrap(); // 2024-06-07 12:00 UTC
+
+        assert_eq!(
+            TimestampFormat::default().format(dt),
+            "Fri 07 Jun 2024 12:00:00"
+        );
+        let german = TimestampFormat {
+            locale: Some(Locale::de_DE),
+            ..Default::default()
+        };
+        assert_eq!(german.format(dt), "Fr 07 Jun 2024 12:00:00");
+        assert_eq!(german.format_with(dt, "%A %B"), "Freitag Juni");
+    }
+
+    #[test]
+    fn test_parse_time_style() {
+        assert_eq!(parse_time_style("long-iso").unwrap(), TimeStyle::LongIso);
+        assert_eq!(parse_time_style("relative").unwrap(), TimeStyle::Relative);
+        assert_eq!(
+            parse_time_style("+%Y/%m/%d").unwrap(),
+            TimeStyle::Custom("%Y/%m/%d".to_string())
+        );
+
+        assert!(matches!(
+            parse_time_style("posix"),
+            Err(TimeStyleParseError::Unknown(_))
+        ));
+        assert!(matches!(
+            parse_time_style("+"),
+            Err(TimeStyleParseError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            parse_time_style("+%Q"),
+            Err(TimeStyleParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_time_style_presets() {
+        let dt = DateTime::from_timestamp(1_717_761_600, 5).unwrap(); // 2024-06-07 12:00 UTC
+        let now = dt + chrono::Duration::days(1);
+        let style = |style| TimestampFormat {
+            style,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            style(TimeStyle::FullIso).format_at(dt, now),
+            "2024-06-07 12:00:00.000000005 +0000"
+        );
+        assert_eq!(
+            style(TimeStyle::LongIso).format_at(dt, now),
+            "2024-06-07 12:00"
+        );
+        assert_eq!(style(TimeStyle::Relative).format_at(dt, now), "1 day ago");
+        assert_eq!(
+            style(TimeStyle::Custom("%d/%m/%Y".into())).format_at(dt, now),
+            "07/06/2024"
+        );
+    }
+
+    #[test]
+    fn test_iso_style_recent_and_old() {
+        let dt = DateTime::from_timestamp(1_717_761_600, 0).unwrap(); // 2024-06-07 12:00 UTC
+        let iso = TimestampFormat {
+            style: TimeStyle::Iso,
+            ..Default::default()
+        };
+        let after = |secs| dt + chrono::Duration::seconds(secs);
+
+        // Within six months: month, day, and time of day
+        assert_eq!(iso.format_at(dt, after(60)), "06-07 12:00");
+        assert_eq!(iso.format_at(dt, after(RECENT_CUTOFF_SECS)), "06-07 12:00");
+        // Older than six months, or in the future: the full date
+        assert_eq!(
+            iso.format_at(dt, after(RECENT_CUTOFF_SECS + 1)),
+            "2024-06-07"
+        );
+        assert_eq!(iso.format_at(dt, after(-60)), "2024-06-07");
+    }
+
+    #[test]
+    fn test_recursive_depth_convention() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        fs::write(dir.path().join("top.txt"), "x").unwrap();
+        fs::write(dir.path().join("sub/mid.txt"), "x").unwrap();
+        fs::write(dir.path().join("sub/deeper/low.txt"), "x").unwrap();
+
+        let files = get_files_recursive(dir.path(), &ListOptions::default(), None)
+            .unwrap()
+            .entries;
+        let depth_of = |name: &str| files.iter().find(|f| f.name == name).unwrap().depth;
+
+        // The root's own entries are depth 0
+        assert_eq!(depth_of("top.txt"), 0);
+        assert_eq!(depth_of("sub"), 0);
+        assert_eq!(depth_of("mid.txt"), 1);
+        assert_eq!(depth_of("deeper"), 1);
+        assert_eq!(depth_of("low.txt"), 2);
+
+        let flat = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        assert!(flat.iter().all(|f| f.depth == 0));
+    }
+
+    #[test]
+    fn test_recursive_relative_names() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        fs::create_dir(dir.path().join(".hidden")).unwrap();
+        fs::write(dir.path().join(".hidden/secret"), "x").unwrap();
+        fs::write(dir.path().join("top.txt"), "x").unwrap();
+        fs::write(dir.path().join("a/a.txt"), "x").unwrap();
+        fs::write(dir.path().join("a/b/b.txt"), "x").unwrap();
+        fs::write(dir.path().join("a/b/c/c.txt"), "x").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("a"), dir.path().join("link")).unwrap();
+
+        let walk = |opts: ListOptions, levels| {
+            let opts = ListOptions {
+                relative_names: true,
+                ..opts
+            };
+            let mut names: Vec<String> = get_files_recursive(dir.path(), &opts, levels)
+                .unwrap()
+                .entries
+                .into_iter()
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        // Symlinked directories are listed but not followed
+        let expected = |names: &[&'static str]| {
+            names
+                .iter()
+                .filter(|name| cfg!(unix) || **name != "link")
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let all = expected(&[
+            "a",
+            "a/a.txt",
+            "a/b",
+            "a/b/b.txt",
+            "a/b/c",
+            "a/b/c/c.txt",
+            "link",
+            "top.txt",
+        ]);
+        assert_eq!(walk(ListOptions::default(), None), all);
+
+        let with_hidden = walk(
+            ListOptions {
+                include_hidden: true,
+                ..Default::default()
+            },
+            None,
+        );
+        assert_eq!(with_hidden[..2], [".hidden", ".hidden/secret"]);
+        assert_eq!(with_hidden.len(), all.len() + 2);
+
+        // Two levels: the directory's own entries and one level below
+        let shallow = expected(&["a", "a/a.txt", "a/b", "link", "top.txt"]);
+        assert_eq!(walk(ListOptions::default(), Some(2)), shallow);
+
+        // An unreadable subdirectory is reported and skipped; the rest is still listed
+        #[cfg(unix)]
+        if !nix::unistd::geteuid().is_root() {
+            use std::os::unix::fs::PermissionsExt;
+            let locked = dir.path().join("a/b/c");
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+            let names = walk(ListOptions::default(), None);
+            let errors = get_files_recursive(dir.path(), &ListOptions::default(), None)
+                .unwrap()
+                .errors;
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(
+                (&errors[0].path, errors[0].code),
+                (&locked, Code::PathUnreadable)
+            );
+            assert!(names.contains(&"a/b/c".to_string()));
+            assert!(!names.contains(&"a/b/c/c.txt".to_string()));
+            assert!(names.contains(&"top.txt".to_string()));
+        }
+    }
+
+    #[test]
+    fn test_leaves_file_system() {
+        assert!(!leaves_file_system(Some(&7), Some(&7)));
+        assert!(leaves_file_system(Some(&7), Some(&8)));
+        // Unknown devices never stop the walk
+        assert!(!leaves_file_system(None, Some(&8)));
+        assert!(!leaves_file_system::<u64>(Some(&7), None));
+
+        // Directories on the same filesystem are still entered
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("sub/inner"), "x").unwrap();
+        let opts = ListOptions {
+            one_file_system: true,
+            ..Default::default()
+        };
+        let files = get_files_recursive(dir.path(), &opts, None)
+            .unwrap()
+            .entries;
+        assert_eq!(files.len(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_follow_symlinks_stops_at_cycles() {
+        use std::os::unix::fs::symlink;
+
+        // real/up -> the listed directory and real/self -> real are cycles;
+        // link -> real is followed once on its own path
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("real")).unwrap();
+        fs::write(dir.path().join("real/file.txt"), "x").unwrap();
+        symlink(dir.path(), dir.path().join("real/up")).unwrap();
+        symlink(dir.path().join("real"), dir.path().join("real/self")).unwrap();
+        symlink(dir.path().join("real"), dir.path().join("link")).unwrap();
+
+        let opts = ListOptions {
+            relative_names: true,
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let files = get_files_recursive(dir.path(), &opts, None)
+            .unwrap()
+            .entries;
+        let mut names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "link",
+                "link/file.txt",
+                "link/self",
+                "link/up",
+                "real",
+                "real/file.txt",
+                "real/self",
+                "real/up",
+            ]
+        );
+
+        // Entries keep the path they were reached by
+        let through_link = files.iter().find(|f| f.name == "link/file.txt").unwrap();
+        assert_eq!(through_link.path, dir.path().join("link/file.txt"));
+        assert_eq!(through_link.depth, 1);
+    }
+
+    #[test]
+    fn test_search_matches() {
+        // The matched parts of `name`, cut from the original string
+        fn matched<'a>(name: &'a str, terms: &[&str]) -> Vec<&'a str> {
+            let terms: Vec<String> = terms.iter().map(|t| t.to_string()).collect();
+            search_matches(name, &terms)
+                .into_iter()
+                .map(|range| &name[range])
+                .collect()
+        }
+        assert_eq!(
+            matched("Invoice-INVOICE.pdf", &["invoice"]),
+            ["Invoice", "INVOICE"]
+        );
+        assert!(matched("receipt.pdf", &["invoice"]).is_empty());
+
+        // Latin letters outside ASCII fold too, and ranges stay on the original bytes
+        assert_eq!(matched("ÉCOLE.txt", &["école"]), ["ÉCOLE"]);
+        assert_eq!(matched("Straße", &["STRAßE"]), ["Straße"]);
+        assert_eq!(matched("Ñandú", &["NDÚ"]), ["ndú"]);
+
+        // Overlapping and adjacent matches of several terms merge
+        assert_eq!(matched("abcdefg", &["abc", "bcd", "ef"]), ["abcdef"]);
+    }
+
+    #[test]
+    fn test_parse_size_units() {
+        assert_eq!(parse_size("100").unwrap(), 100);
+        assert_eq!(parse_size("10k").unwrap(), 10 * 1024);
+        assert_eq!(parse_size("1KiB").unwrap(), parse_size("1KB").unwrap());
+        assert_eq!(parse_size("2 MiB").unwrap(), 2 * 1024 * 1024);
+        assert_eq!(parse_size("1.5G").unwrap(), 3 * 512 * 1024 * 1024);
+        assert!(matches!(
+            parse_size("banana"),
+            Err(SizeParseError::InvalidNumber(s)) if s == "banana"
+        ));
+        assert!(matches!(
+            parse_size("3XB"),
+            Err(SizeParseError::InvalidUnit(_))
+        ));
+        assert!(matches!(
+            parse_size("-1K"),
+            Err(SizeParseError::NegativeValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_time_bound() {
+        use chrono::{Local, TimeZone};
+        use std::time::Duration;
+
+        assert_eq!(
+            parse_time_bound("2h").unwrap(),
+            TimeBound::Ago(Duration::from_secs(7_200))
+        );
+        assert_eq!(
+            parse_time_bound("1w").unwrap(),
+            TimeBound::Ago(Duration::from_secs(7 * 86_400))
+        );
+        let midnight = Local.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            parse_time_bound("2024-01-01").unwrap(),
+            TimeBound::At(midnight.into())
+        );
+        assert_eq!(
+            parse_time_bound("2024-01-01T00:00:10Z").unwrap(),
+            TimeBound::At(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_210))
+        );
+        assert!(matches!(
+            parse_time_bound("2024-13-01"),
+            Err(DurationParseError::InvalidDate(_))
+        ));
+        assert!(matches!(
+            parse_time_bound("soon"),
+            Err(DurationParseError::InvalidUnit(_))
+        ));
+
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(10_000);
+        assert_eq!(
+            TimeBound::Ago(Duration::from_secs(7_200)).resolve(now),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(2_800)
+        );
+    }
+
+    #[test]
+    fn test_format_relative_time_buckets() {
+        let now = DateTime::from_timestamp(1_000_000_000, 0).unwrap();
+        let ago = |secs: i64| format_relative_time(now - chrono::Duration::seconds(secs), now);
+
+        assert_eq!(ago(0), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(3_599), "59 minutes ago");
+        assert_eq!(ago(3_600), "1 hour ago");
+        assert_eq!(ago(86_399), "23 hours ago");
+        assert_eq!(ago(86_400), "1 day ago");
+        assert_eq!(ago(2_591_999), "29 days ago");
+        assert_eq!(ago(2_592_000), "1 month ago");
+        assert_eq!(ago(31_535_999), "12 months ago");
+        assert_eq!(ago(31_536_000), "1 year ago");
+        assert_eq!(ago(10 * 31_536_000), "10 years ago");
+        // Clock skew or a commit dated ahead
+        assert_eq!(ago(-1), "in the future");
+        assert_eq!(ago(-86_400), "in the future");
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        fn sorted<'a>(names: &[&'a str]) -> Vec<&'a str> {
+            let mut names = names.to_vec();
+            names.sort_by(|a, b| natural_cmp(a, b));
+            names
+        }
+        assert_eq!(
+            sorted(&["a10", "a2", "a02", "a1"]),
+            ["a1", "a2", "a02", "a10"]
+        );
+        assert_eq!(
+            sorted(&[
+                "file10.txt",
+                "file2.txt",
+                "file.txt",
+                "file2b.txt",
+                "file2a.txt"
+            ]),
+            [
+                "file.txt",
+                "file2.txt",
+                "file2a.txt",
+                "file2b.txt",
+                "file10.txt"
+            ]
+        );
+        assert_eq!(
+            sorted(&["v1.10.0", "v1.9.2", "v1.9"]),
+            ["v1.9", "v1.9.2", "v1.10.0"]
+        );
+
+        // Digit runs far beyond u128 compare without overflow
+        let huge = format!("x{}", "9".repeat(60));
+        let huger = format!("x1{}", "0".repeat(60));
+        assert_eq!(natural_cmp(&huge, &huger), Ordering::Less);
+
+        assert_eq!(
+            sorted(&["日記10", "日記9", "Ärger2", "Ärger10"]),
+            ["Ärger2", "Ärger10", "日記9", "日記10"]
+        );
+        assert_eq!(natural_cmp("a2", "a2"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_split_name() {
+        let file = |name| split_name(name, &FileType::File);
+        assert_eq!(
+            file("main.RS"),
+            (Some("rs".to_string()), "main".to_string())
+        );
+        assert_eq!(
+            file("backup.tar.gz"),
+            (Some("gz".to_string()), "backup.tar".to_string())
+        );
+        assert_eq!(file(".bashrc"), (None, ".bashrc".to_string()));
+        assert_eq!(file("Makefile"), (None, "Makefile".to_string()));
+        assert_eq!(
+            file("docs/guide.md"),
+            (Some("md".to_string()), "guide".to_string())
+        );
+        assert_eq!(
+            split_name("site.d", &FileType::Directory),
+            (None, "site.d".to_string())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_derived_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("run.SH"), "#!/bin/sh").unwrap();
+        fs::set_permissions(dir.path().join("run.SH"), fs::Permissions::from_mode(0o750)).unwrap();
+        fs::write(dir.path().join(".env"), "").unwrap();
+        fs::create_dir(dir.path().join("bin")).unwrap();
+
+        let opts = ListOptions {
+            include_hidden: true,
+            ..Default::default()
+        };
+        let files = get_files(dir.path(), &opts).unwrap().entries;
+        let find = |name: &str| files.iter().find(|f| f.name == name).unwrap();
+
+        let script = find("run.SH");
+        assert_eq!(script.extension.as_deref(), Some("sh"));
+        assert_eq!(script.stem, "run");
+        assert!(script.is_executable && !script.is_hidden);
+        assert!(find(".env").is_hidden);
+        assert!(!find("bin").is_executable);
+
+        // Access times use the same display format as modification times
+        let format = TimestampFormat::default();
+        let accessed_time = script.accessed_time.unwrap();
+        assert_eq!(script.accessed, format.format(accessed_time.into()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_entries_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        fs::create_dir(&locked).unwrap();
+        for name in ["c", "a", "b"] {
+            fs::write(locked.join(name), "x").unwrap();
+        }
+        let order: Vec<String> = fs::read_dir(&locked)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+
+        // Names stay readable, but the entries' metadata cannot be read
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o444)).unwrap();
+        let listing = get_files(&locked, &ListOptions::default());
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        let ListResult {
+            entries: files,
+            errors,
+        } = listing.unwrap();
+
+        // Nothing disappears: every entry is either listed or reported
+        assert_eq!(files.len() + errors.len(), 3);
+        // Root bypasses the permission check
+        if !files.is_empty() {
+            return;
+        }
+        let names: Vec<String> = errors
+            .iter()
+            .map(|e| e.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names, order);
+        assert!(errors.iter().all(|e| e.code == Code::EntryUnreadable
+            && e.error.kind() == io::ErrorKind::PermissionDenied));
+    }
+
+    #[test]
+    fn test_exclude_names_and_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "src/lib.rs",
+            "src/test_lib.rs",
+            "test_main.rs",
+            "target/debug/app",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "x").unwrap();
+        }
+        let listed = |patterns: &[&str]| {
+            let opts = ListOptions {
+                exclude: ExcludePatterns::new(
+                    patterns
+                        .iter()
+                        .map(|p| glob::Pattern::new(p).unwrap())
+                        .collect(),
+                    glob::MatchOptions::new(),
+                ),
+                ..Default::default()
+            };
+            let mut names: Vec<String> = get_files_recursive(dir.path(), &opts, None)
+                .unwrap()
+                .entries
+                .into_iter()
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        // A plain name matches at any depth; an excluded directory takes its subtree
+        assert_eq!(listed(&["test_*", "target"]), ["lib.rs", "src"]);
+        // A path pattern only matches that relative path
+        assert_eq!(
+            listed(&["src/test_*", "target/*"]),
+            ["lib.rs", "src", "target", "test_main.rs"]
+        );
+        assert_eq!(
+            listed(&["**/debug"]),
+            ["lib.rs", "src", "target", "test_lib.rs", "test_main.rs"]
+        );
+        assert_eq!(
+            get_files(
+                dir.path(),
+                &ListOptions {
+                    exclude: ExcludePatterns::new(
+                        vec![glob::Pattern::new("*.rs").unwrap()],
+                        glob::MatchOptions::new()
+                    ),
+                    ..Default::default()
                 }
+            )
+            .unwrap()
+            .entries
+            .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_probe_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("zero"), "").unwrap();
+        fs::write(dir.path().join("full"), "x").unwrap();
+        fs::create_dir(dir.path().join("bare")).unwrap();
+        fs::create_dir(dir.path().join("dotted")).unwrap();
+        fs::write(dir.path().join("dotted/.keep"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.path().join("gone"), dir.path().join("dangling")).unwrap();
+
+        let probed = ListOptions {
+            probe_empty: true,
+            ..Default::default()
+        };
+        let files = get_files(dir.path(), &probed).unwrap().entries;
+        let is_empty = |name: &str| files.iter().find(|f| f.name == name).unwrap().is_empty;
+        assert_eq!(is_empty("zero"), Some(true));
+        assert_eq!(is_empty("full"), Some(false));
+        assert_eq!(is_empty("bare"), Some(true));
+        // Hidden entries count even when they are not listed
+        assert_eq!(is_empty("dotted"), Some(false));
+        #[cfg(unix)]
+        assert_eq!(is_empty("dangling"), None);
+
+        // Not probed unless asked
+        let files = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        assert!(files.iter().all(|f| f.is_empty.is_none()));
+    }
+
+    #[test]
+    fn test_count_items_follows_all_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("pkg/src/deep")).unwrap();
+        fs::write(dir.path().join("pkg/Cargo.toml"), "").unwrap();
+        fs::write(dir.path().join("pkg/.gitignore"), "").unwrap();
+        fs::write(dir.path().join("pkg/src/deep/mod.rs"), "").unwrap();
+        fs::write(dir.path().join("file"), "").unwrap();
+
+        let items = |include_hidden| {
+            let opts = ListOptions {
+                count_items: true,
+                include_hidden,
+                ..Default::default()
+            };
+            let files = get_files_recursive(dir.path(), &opts, None)
+                .unwrap()
+                .entries;
+            let mut items: Vec<(PathBuf, Option<u64>)> = files
+                .into_iter()
+                .map(|f| {
+                    (
+                        f.path.strip_prefix(dir.path()).unwrap().to_path_buf(),
+                        f.items,
+                    )
+                })
+                .collect();
+            items.sort();
+            items
+        };
+        let expected = |pkg| {
+            [
+                ("file", None),
+                ("pkg", Some(pkg)),
+                ("pkg/Cargo.toml", None),
+                ("pkg/src", Some(1)),
+                ("pkg/src/deep", Some(1)),
+                ("pkg/src/deep/mod.rs", None),
+            ]
+            .map(|(path, items)| (PathBuf::from(path), items))
+        };
+        // Only immediate children count, hidden ones with -a
+        assert_eq!(items(false), expected(2));
+        let mut with_hidden = expected(3).to_vec();
+        with_hidden.insert(2, (PathBuf::from("pkg/.gitignore"), None));
+        assert_eq!(items(true), with_hidden);
+
+        #[cfg(unix)]
+        if !nix::unistd::geteuid().is_root() {
+            use std::os::unix::fs::PermissionsExt;
+            let locked = dir.path().join("pkg/src");
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+            let opts = ListOptions {
+                count_items: true,
+                ..Default::default()
+            };
+            let files = get_files(&dir.path().join("pkg"), &opts).unwrap().entries;
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            let src = files.iter().find(|f| f.name == "src").unwrap();
+            assert_eq!(src.items, None);
+        }
+    }
+
+    #[test]
+    fn test_max_entries_caps_the_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["a", "b", "c"] {
+            fs::create_dir(dir.path().join(sub)).unwrap();
+            for file in ["1", "2", "3"] {
+                fs::write(dir.path().join(sub).join(file), "x").unwrap();
             }
         }
+        let capped = |max| ListOptions {
+            max_entries: Some(max),
+            ..Default::default()
+        };
+
+        assert_eq!(get_files(dir.path(), &capped(2)).unwrap().entries.len(), 2);
+        assert_eq!(
+            get_files_recursive(dir.path(), &capped(5), None)
+                .unwrap()
+                .entries
+                .len(),
+            5
+        );
+        assert!(interrupt::was_truncated());
+
+        // Exactly at the cap is not a truncation
+        assert_eq!(
+            get_files_recursive(dir.path(), &capped(12), None)
+                .unwrap()
+                .entries
+                .len(),
+            12
+        );
+        let (entries, truncated) = read_visible_entries(
+            &dir.path().join("a"),
+            "",
+            &ListOptions::default(),
+            Some(3),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!((entries.len(), truncated), (3, false));
+    }
+
+    #[test]
+    fn test_respect_dot_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["shown.txt", "secret.txt", "# not a comment"] {
+            fs::write(dir.path().join(name), "x").unwrap();
+        }
+        fs::write(
+            dir.path().join(DOT_HIDDEN_NAME),
+            "# comment\nsecret.txt\n\nmissing.txt\n",
+        )
+        .unwrap();
+
+        let names = |opts: &ListOptions| {
+            let mut names: Vec<String> = get_files(dir.path(), opts)
+                .unwrap()
+                .entries
+                .into_iter()
+                .map(|f| f.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        let opts = ListOptions {
+            respect_dot_hidden: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&opts), vec!["# not a comment", "shown.txt"]);
+        assert_eq!(
+            names(&ListOptions::default()),
+            vec!["# not a comment", "secret.txt", "shown.txt"]
+        );
+
+        let opts = ListOptions {
+            respect_dot_hidden: true,
+            include_hidden: true,
+            ..Default::default()
+        };
+        assert_eq!(names(&opts).len(), 4);
     }
 
-    Ok(())
+    #[test]
+    fn test_stream_files_matches_get_files() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.txt", "b.txt", ".hidden", "c.txt"] {
+            fs::write(dir.path().join(name), "x").unwrap();
+        }
+        fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let opts = ListOptions::default();
+        let streamed: Vec<String> = stream_files(dir.path(), &opts)
+            .unwrap()
+            .map(|entry| entry.unwrap().name)
+            .collect();
+        let listed: Vec<String> = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        // Both keep the order the operating system returns
+        assert_eq!(streamed, listed);
+        assert_eq!(streamed.len(), 4);
+    }
+
+    #[test]
+    fn test_stream_files_across_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..STREAM_BATCH * 2 + 10 {
+            fs::write(dir.path().join(format!("{}.txt", i)), "x").unwrap();
+        }
+        let opts = ListOptions::default();
+
+        let mut stream = stream_files(dir.path(), &opts).unwrap();
+        let first = stream.next().unwrap().unwrap();
+        // The rest of the first batch is mapped and waiting
+        assert!(!stream.batch_drained());
+        let mut streamed = vec![first.name];
+        streamed.extend(stream.map(|entry| entry.unwrap().name));
+
+        let listed: Vec<String> = get_files(dir.path(), &opts)
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        assert_eq!(streamed, listed);
+    }
+
+    #[test]
+    fn test_raw_timestamps_agree_with_formatted() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("dated.txt");
+        fs::write(&file, "x").unwrap();
+        let when = SystemTime::UNIX_EPOCH + std::time::Duration::new(1_707_127_872, 500_000_000);
+        fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(when)
+            .unwrap();
+
+        let entry = get_files(&file, &ListOptions::default())
+            .unwrap()
+            .entries
+            .remove(0);
+        assert_eq!(entry.modified_epoch, Some(1_707_127_872));
+        assert_eq!(entry.modified_iso.as_deref(), Some("2024-02-05T10:11:12Z"));
+        assert_eq!(entry.modified, "Mon 05 Feb 2024 10:11:12");
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["modified_epoch"], 1_707_127_872);
+        let iso = DateTime::parse_from_rfc3339(json["modified_iso"].as_str().unwrap()).unwrap();
+        assert_eq!(Some(iso.timestamp()), entry.modified_epoch);
+
+        // Access and creation times follow the same rules where they are recorded
+        for (epoch, iso, time) in [
+            (
+                &entry.accessed_epoch,
+                &entry.accessed_iso,
+                entry.accessed_time,
+            ),
+            (&entry.created_epoch, &entry.created_iso, entry.created_time),
+        ] {
+            assert_eq!(*epoch, time.map(epoch_seconds));
+            assert_eq!(*iso, time.map(rfc3339));
+        }
+        assert_eq!(entry.created_epoch.is_some(), entry.created.is_some());
+    }
+
+    #[test]
+    fn test_epoch_before_1970_rounds_down() {
+        let when = SystemTime::UNIX_EPOCH - std::time::Duration::from_millis(1500);
+        assert_eq!(epoch_seconds(when), -2);
+        assert_eq!(rfc3339(when), "1969-12-31T23:59:58Z");
+    }
+
+    #[test]
+    fn test_file_root_is_a_single_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, "abc").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&file, dir.path().join("link")).unwrap();
+
+        for files in [
+            get_files(&file, &ListOptions::default()).unwrap().entries,
+            get_files_recursive(&file, &ListOptions::default(), None)
+                .unwrap()
+                .entries,
+        ] {
+            assert_eq!(files.len(), 1);
+            assert_eq!(
+                (files[0].name.as_str(), files[0].len_bytes),
+                ("notes.txt", 3)
+            );
+            assert_eq!(files[0].path, file);
+        }
+
+        // A symlink to a file is shown as the link, like `ls`
+        #[cfg(unix)]
+        {
+            let files = get_files(&dir.path().join("link"), &ListOptions::default())
+                .unwrap()
+                .entries;
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].e_type, FileType::Symlink);
+        }
+
+        let missing = get_files(&dir.path().join("missing"), &ListOptions::default());
+        assert_eq!(missing.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_directory_lists_the_root_itself() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("pkg");
+        fs::create_dir(&root).unwrap();
+        fs::write(root.join("inside.txt"), "x").unwrap();
+        let opts = ListOptions {
+            directory: true,
+            ..Default::default()
+        };
+
+        let files = get_files(&root, &opts).unwrap().entries;
+        assert_eq!(files.len(), 1);
+        let entry = &files[0];
+        let metadata = fs::metadata(&root).unwrap();
+        assert_eq!(entry.name, "pkg");
+        assert_eq!(entry.e_type, FileType::Directory);
+        assert_eq!(entry.len_bytes, metadata.len());
+        assert_eq!(entry.modified_time, metadata.modified().ok());
+        #[cfg(unix)]
+        {
+            assert_eq!(
+                entry.permissions,
+                format_mode(metadata.permissions().mode())
+            );
+            assert_eq!(entry.mode, Some(metadata.permissions().mode()));
+            assert_eq!(
+                (entry.uid, entry.gid),
+                (Some(metadata.uid()), Some(metadata.gid()))
+            );
+            assert_eq!(
+                (entry.owner.clone(), entry.group.clone()),
+                get_owner_group(&metadata)
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_no_dereference_ignores_real_directories() {
+        let (target, _links, _link) = symlinked_dirs();
+        let opts = ListOptions {
+            no_dereference: true,
+            ..Default::default()
+        };
+
+        let files = get_files(target.path(), &opts).unwrap().entries;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "inside.txt");
+    }
+
+    #[test]
+    fn test_preview_first_line() {
+        assert_eq!(
+            preview_from_bytes(b"#!/bin/sh\necho hi\n", false),
+            Some("#!/bin/sh".to_string())
+        );
+        assert_eq!(
+            preview_from_bytes(b"title\r\nbody", false),
+            Some("title".to_string())
+        );
+        assert_eq!(
+            preview_from_bytes(b"a\tb\x1b[31m", false),
+            Some("a b[31m".to_string())
+        );
+        assert_eq!(preview_from_bytes(b"\n\nlater", false), None);
+    }
+
+    #[test]
+    fn test_preview_binary_and_truncation() {
+        assert_eq!(preview_from_bytes(b"\x7fELF\x00\x01", false), None);
+        assert_eq!(preview_from_bytes(b"\xff\xfe bad", false), None);
+
+        let long = "x".repeat(PREVIEW_BYTES);
+        let preview = preview_from_bytes(long.as_bytes(), true).unwrap();
+        assert!(preview.ends_with('…'));
+
+        // A multi-byte character cut at the read limit is dropped, not treated as binary
+        let mut cut = "é".repeat(10).into_bytes();
+        cut.push(0xc3);
+        assert_eq!(
+            preview_from_bytes(&cut, true),
+            Some(format!("{}…", "é".repeat(10)))
+        );
+    }
 }
@@ -0,0 +1,276 @@
+//! # Git Integration Module
+//!
+//! This module resolves "last commit" information for listed entries, similar to
+//! the file browser on GitHub. It is only used when `--git-log` is passed because
+//! walking history is expensive on large repositories.
+//!
+//! ## Strategy
+//!
+//! Instead of running one log query per file, a single revwalk is performed from
+//! `HEAD` (newest first). Each commit is diffed against its first parent and every
+//! touched path (plus its parent directories) is recorded the first time it is
+//! seen, which yields the most recent commit for that path. The walk stops early
+//! once every requested path has been resolved, or after `max_commits` commits.
+
+use chrono::{DateTime, Utc};
+use git2::{DiffOptions, Repository, Sort};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Summary of the last commit that touched a path.
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// Abbreviated commit hash (7 characters)
+    pub short_hash: String,
+    /// Commit time
+    pub time: DateTime<Utc>,
+    /// First line of the commit message
+    pub summary: String,
+}
+
+/// Path → last commit lookup for a single repository.
+pub struct LastCommits {
+    workdir: PathBuf,
+    commits: HashMap<PathBuf, CommitInfo>,
+}
+
+impl LastCommits {
+    /// Look up the last commit for an absolute (canonicalized) path.
+    ///
+    /// Returns `None` for paths outside the repository and for untracked paths.
+    pub fn lookup(&self, path: &Path) -> Option<&CommitInfo> {
+        let rel = path.strip_prefix(&self.workdir).ok()?;
+        self.commits.get(rel)
+    }
+}
+
+/// Walk history once and collect last-commit information for `wanted` paths.
+///
+/// # Arguments
+///
+/// * `start` - Any path inside the repository (used for repository discovery)
+/// * `wanted` - Absolute, canonicalized paths to resolve
+/// * `max_commits` - Maximum number of commits to inspect
+///
+/// # Errors
+///
+/// Returns a `git2::Error` if `start` is not inside a repository or the history
+/// cannot be read (for example, a repository without any commits).
+pub fn collect_last_commits(
+    start: &Path,
+    wanted: &[PathBuf],
+    max_commits: usize,
+) -> Result<LastCommits, git2::Error> {
+    let repo = Repository::discover(start)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("bare repositories are not supported"))?;
+    let workdir = workdir
+        .canonicalize()
+        .unwrap_or_else(|_| workdir.to_path_buf());
+
+    let mut pending: HashSet<PathBuf> = wanted
+        .iter()
+        .filter_map(|p| p.strip_prefix(&workdir).ok())
+        .map(Path::to_path_buf)
+        .collect();
+
+    let mut commits: HashMap<PathBuf, CommitInfo> = HashMap::new();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+
+    for oid in revwalk.take(max_commits) {
+        if pending.is_empty() {
+            break;
+        }
+
+        let commit = repo.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let parent_trees = if commit.parent_count() == 0 {
+            vec![None]
+        } else {
+            commit
+                .parents()
+                .map(|parent| parent.tree().map(Some))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        // Like `git log -- <path>`, a merge only changed the paths that differ from
+        // every parent; the rest came from the branch commit that changed them.
+        let mut changed: Option<HashSet<PathBuf>> = None;
+        for parent_tree in &parent_trees {
+            let mut opts = DiffOptions::new();
+            opts.ignore_submodules(true);
+            let diff =
+                repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+            let paths: HashSet<PathBuf> = diff
+                .deltas()
+                .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+                .map(Path::to_path_buf)
+                .collect();
+            changed = Some(match changed {
+                None => paths,
+                Some(before) => before.intersection(&paths).cloned().collect(),
+            });
+        }
+
+        let info = CommitInfo {
+            short_hash: commit.id().to_string().chars().take(7).collect(),
+            time: DateTime::from_timestamp(commit.time().seconds(), 0).unwrap_or_default(),
+            summary: commit
+                .summary_bytes()
+                .map(|s| String::from_utf8_lossy(s).into_owned())
+                .unwrap_or_default(),
+        };
+
+        for file_path in changed.unwrap_or_default() {
+            // Record the file itself and every ancestor directory so that
+            // directory rows show the newest commit touching their contents.
+            for ancestor in file_path.ancestors() {
+                if ancestor.as_os_str().is_empty() {
+                    break;
+                }
+                if !commits.contains_key(ancestor) {
+                    pending.remove(ancestor);
+                    commits.insert(ancestor.to_path_buf(), info.clone());
+                }
+            }
+        }
+    }
+
+    Ok(LastCommits { workdir, commits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Oid, Signature, Time};
+    use std::fs;
+
+    /// Stage everything in the work tree and commit it at `secs` on top of `HEAD`
+    fn commit(repo: &Repository, secs: i64, message: &str) -> Oid {
+        let head = repo.head().ok().map(|h| h.peel_to_commit().unwrap().id());
+        commit_with_parents(repo, secs, message, head.as_slice())
+    }
+
+    /// Stage everything in the work tree and commit it at `secs` with `parents`,
+    /// moving `HEAD` to the new commit
+    fn commit_with_parents(repo: &Repository, secs: i64, message: &str, parents: &[Oid]) -> Oid {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::new("Test", "test@example.com", &Time::new(secs, 0)).unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let oid = repo
+            .commit(
+                None,
+                &sig,
+                &sig,
+                message,
+                &tree,
+                &parents.iter().collect::<Vec<_>>(),
+            )
+            .unwrap();
+        repo.set_head_detached(oid).unwrap();
+        oid
+    }
+
+    #[test]
+    fn test_last_commit_per_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("clean.txt"), "same").unwrap();
+        fs::write(root.join("src/modified.rs"), "v1").unwrap();
+        commit(&repo, 1_000, "Initial import");
+
+        fs::write(root.join("src/modified.rs"), "v2").unwrap();
+        fs::write(root.join("new.txt"), "added").unwrap();
+        commit(&repo, 2_000, "Second commit\n\nWith a body");
+        fs::write(root.join("untracked.txt"), "").unwrap();
+
+        let paths = [
+            "clean.txt",
+            "src/modified.rs",
+            "new.txt",
+            "src",
+            "untracked.txt",
+        ]
+        .map(|p| root.join(p));
+        let last = collect_last_commits(&root, &paths, 100).unwrap();
+        let summary = |p: &str| last.lookup(&root.join(p)).map(|c| c.summary.as_str());
+
+        assert_eq!(summary("clean.txt"), Some("Initial import"));
+        assert_eq!(summary("src/modified.rs"), Some("Second commit"));
+        assert_eq!(summary("new.txt"), Some("Second commit"));
+        // Directories take the newest commit touching their contents
+        assert_eq!(summary("src"), Some("Second commit"));
+        assert_eq!(summary("untracked.txt"), None);
+
+        let info = last.lookup(&root.join("clean.txt")).unwrap();
+        assert_eq!(info.short_hash.len(), 7);
+        assert_eq!(info.time.timestamp(), 1_000);
+        assert!(last.lookup(Path::new("/elsewhere/clean.txt")).is_none());
+
+        // Only the newest commit is inspected, so older paths stay unresolved
+        let newest = collect_last_commits(&root, &paths, 1).unwrap();
+        assert!(newest.lookup(&root.join("clean.txt")).is_none());
+        assert!(newest.lookup(&root.join("new.txt")).is_some());
+    }
+
+    #[test]
+    fn test_merge_keeps_the_branch_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let repo = Repository::init(&root).unwrap();
+
+        fs::create_dir(root.join("lib")).unwrap();
+        fs::write(root.join("main.txt"), "v1").unwrap();
+        fs::write(root.join("lib/side.txt"), "v1").unwrap();
+        fs::write(root.join("both.txt"), "v1").unwrap();
+        let base = commit(&repo, 1_000, "Base");
+
+        // Side branch changes lib/side.txt and both.txt
+        fs::write(root.join("lib/side.txt"), "side").unwrap();
+        fs::write(root.join("both.txt"), "side").unwrap();
+        let side = commit(&repo, 2_000, "Side change");
+
+        // Main line, started again from base, changes main.txt and both.txt
+        fs::write(root.join("lib/side.txt"), "v1").unwrap();
+        fs::write(root.join("main.txt"), "main").unwrap();
+        fs::write(root.join("both.txt"), "main").unwrap();
+        let main = commit_with_parents(&repo, 3_000, "Main change", &[base]);
+
+        // The merge takes both sides and resolves the conflict in both.txt
+        fs::write(root.join("lib/side.txt"), "side").unwrap();
+        fs::write(root.join("both.txt"), "resolved").unwrap();
+        commit_with_parents(&repo, 4_000, "Merge side", &[main, side]);
+
+        let paths = ["main.txt", "lib/side.txt", "lib", "both.txt"].map(|p| root.join(p));
+        let last = collect_last_commits(&root, &paths, 100).unwrap();
+        let summary = |p: &str| last.lookup(&root.join(p)).map(|c| c.summary.as_str());
+
+        assert_eq!(summary("main.txt"), Some("Main change"));
+        assert_eq!(summary("lib/side.txt"), Some("Side change"));
+        assert_eq!(summary("lib"), Some("Side change"));
+        // Only the conflict resolution is the merge's own change
+        assert_eq!(summary("both.txt"), Some("Merge side"));
+    }
+
+    #[test]
+    fn test_repository_without_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        Repository::init(dir.path()).unwrap();
+        assert!(collect_last_commits(dir.path(), &[], 100).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+//! # Interrupt Handling Module
+//!
+//! Tracks the reasons gathering can stop early. In both cases whatever was collected
+//! so far is still rendered and marked as partial, and the exit status says why.
+//!
+//! ## Ctrl-C
+//!
+//! The first Ctrl-C does not kill bestls: it sets a cancellation flag that the
+//! directory walk checks, and bestls exits with status 130 after rendering. A second
+//! Ctrl-C aborts immediately.
+//!
+//! ## Entry Cap
+//!
+//! The walk stops once `--max-entries` entries were gathered, which bounds memory on
+//! accidental listings of huge trees; bestls then exits with status 3.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Exit status after an interrupt (128 + SIGINT), as shells report it
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Exit status after stopping at `--max-entries`
+pub const EXIT_TRUNCATED: i32 = 3;
+
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+static TRUNCATED: AtomicBool = AtomicBool::new(false);
+
+/// Install the Ctrl-C handler; without it, Ctrl-C keeps its default behavior.
+pub fn install_handler() -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(|| {
+        if CANCELLED.swap(true, Ordering::SeqCst) {
+            std::process::exit(EXIT_INTERRUPTED);
+        }
+    })
+}
+
+/// Whether Ctrl-C was pressed and gathering should stop.
+pub fn is_cancelled() -> bool {
+    CANCELLED.load(Ordering::Relaxed)
+}
+
+/// Record that gathering stopped at the entry cap.
+pub fn mark_truncated() {
+    TRUNCATED.store(true, Ordering::Relaxed);
+}
+
+/// Whether gathering stopped at the entry cap.
+pub fn was_truncated() -> bool {
+    TRUNCATED.load(Ordering::Relaxed)
+}
@@ -0,0 +1,540 @@
+//! # JSON Output Module
+//!
+//! This module serializes file entries for the JSON output formats and applies the
+//! JSON-only controls:
+//!
+//! - `--fields name,len_bytes`: keep only the listed fields, in the listed order
+//! - `--indent N`: indentation width of the pretty printer (`--format json-pretty`, default 2)
+//! - `--envelope`: wrap the entry array in an object with listing metadata,
+//!   `{"entries": [...], "partial": false, "truncated": false, "errors": []}`, plus a `filesystem`
+//!   object (`total_bytes`, `used_bytes`, `available_bytes`, `percent_used`) with
+//!   `--disk-usage-footer` and a `total` entry count with `--limit`. Its `errors`
+//!   array lists what could not be read (`path`, `code`, `message`, as in
+//!   `--error-format json` records)
+//!
+//! Listing several paths (`bestls src tests`) produces one object keyed by path, in
+//! command line order, whose values are the single-path documents described above.
+//!
+//! `--format ndjson` writes each entry as a compact object on its own line (honoring
+//! `--fields`); there is no envelope, and several paths simply follow each other.
+//!
+//! `--format yaml` serializes the same documents as YAML, so field names, `--fields`,
+//! and `--envelope` (including its `errors`) match the JSON output exactly.
+//!
+//! `--format toml` does the same in TOML, whose root must be a table: the entries
+//! become an `entries` array of tables (`[[entries]]`), next to the `--envelope`
+//! metadata, and several paths nest under a table per path. TOML has no null, so unset
+//! fields are left out instead.
+//!
+//! The table output never goes through this module, so `--fields` does not affect it.
+
+use crate::diag::Code;
+use crate::disk::FilesystemUsage;
+use crate::fsops::{FileEntry, ListError};
+use crate::platform::display_path;
+use serde::ser::{Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Field names of a serialized [`FileEntry`] (optional ones are omitted when unset)
+pub const ENTRY_FIELDS: &[&str] = &[
+    "name",
+    "e_type",
+    "len_bytes",
+    "human_size",
+    "modified",
+    "modified_epoch",
+    "modified_iso",
+    "accessed",
+    "accessed_epoch",
+    "accessed_iso",
+    "created",
+    "created_epoch",
+    "created_iso",
+    "permissions",
+    "owner",
+    "group",
+    "depth",
+    "extension",
+    "stem",
+    "is_hidden",
+    "is_executable",
+    "last_commit_hash",
+    "last_commit_date",
+    "last_commit_summary",
+    "encrypted",
+    "preview",
+    "is_empty",
+    "is_broken",
+    "size_approximate",
+    "items",
+    "mime",
+];
+
+/// Fields selected with `--fields`, in output order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection(Vec<String>);
+
+impl FieldSelection {
+    /// Parse a comma-separated field list.
+    ///
+    /// `extra` holds additional valid names, such as `--exec-column` labels.
+    pub fn parse(spec: &str, extra: &[String]) -> Result<Self, String> {
+        let mut fields: Vec<String> = Vec::new();
+        for field in spec.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            if !ENTRY_FIELDS.contains(&field) && !extra.iter().any(|e| e == field) {
+                return Err(format!(
+                    "unknown field '{}' (expected one of: {})",
+                    field,
+                    ENTRY_FIELDS.join(", ")
+                ));
+            }
+            if !fields.iter().any(|f| f == field) {
+                fields.push(field.to_string());
+            }
+        }
+
+        if fields.is_empty() {
+            return Err("--fields needs at least one field name".to_string());
+        }
+        Ok(FieldSelection(fields))
+    }
+
+    /// Selected field names, in output order
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+}
+
+/// The selected fields of an entry as a JSON object, in selection order.
+///
+/// Fields the entry leaves out (such as `preview` without `--preview`) are missing
+/// from the object.
+pub fn project(
+    entry: &FileEntry,
+    fields: &FieldSelection,
+) -> serde_json::Result<Map<String, Value>> {
+    let Value::Object(mut object) = serde_json::to_value(entry)? else {
+        return Ok(Map::new());
+    };
+    Ok(fields
+        .0
+        .iter()
+        .filter_map(|field| object.remove(field).map(|value| (field.clone(), value)))
+        .collect())
+}
+
+/// A field value as plain text, for CSV cells and `--format-string`: strings as they
+/// are, `null` empty, anything else in JSON notation
+pub fn plain_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Serializes an entry, restricted to a field selection when one is given
+pub struct EntryJson<'a> {
+    pub entry: &'a FileEntry,
+    pub fields: Option<&'a FieldSelection>,
+}
+
+impl Serialize for EntryJson<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Some(fields) = self.fields else {
+            return self.entry.serialize(serializer);
+        };
+
+        project(self.entry, fields)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Listing metadata carried next to the entries by `--envelope`
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct ListingMeta {
+    /// Gathering stopped early (Ctrl-C or `--max-entries`), so entries are missing
+    pub partial: bool,
+    /// Gathering stopped at `--max-entries`
+    pub truncated: bool,
+    /// Number of entries before `--limit` cut the listing short
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    /// Usage of the listed path's filesystem (`--disk-usage-footer`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filesystem: Option<FilesystemUsage>,
+    /// Entries and subdirectories that could not be read, so are missing
+    pub errors: Vec<ListingError>,
+}
+
+/// An entry or subdirectory that could not be read, in the shape of a
+/// `--error-format json` record
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ListingError {
+    pub path: String,
+    pub code: Code,
+    pub message: String,
+}
+
+impl From<&ListError> for ListingError {
+    fn from(error: &ListError) -> Self {
+        ListingError {
+            path: display_path(&error.path).to_string_lossy().into_owned(),
+            code: error.code,
+            message: error.to_string(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Envelope<'a> {
+    entries: Vec<EntryJson<'a>>,
+    #[serde(flatten)]
+    meta: &'a ListingMeta,
+}
+
+/// Serialize entries as a JSON array, or as an envelope object when `envelope` is given.
+///
+/// `indent` selects pretty printing with that many spaces (`None` for compact).
+pub fn to_json(
+    entries: &[FileEntry],
+    fields: Option<&FieldSelection>,
+    indent: Option<usize>,
+    envelope: Option<&ListingMeta>,
+) -> serde_json::Result<String> {
+    let entries: Vec<EntryJson> = entries
+        .iter()
+        .map(|entry| EntryJson { entry, fields })
+        .collect();
+    match envelope {
+        Some(meta) => write_json(&Envelope { entries, meta }, indent),
+        None => write_json(&entries, indent),
+    }
+}
+
+/// Combine the JSON documents of several listings into one object keyed by path.
+///
+/// `listings` holds each path with its rendered document, in output order.
+pub fn by_path(listings: &[(String, &str)], indent: Option<usize>) -> serde_json::Result<String> {
+    let mut object = serde_json::Map::new();
+    for (path, document) in listings {
+        object.insert(path.clone(), serde_json::from_str(document)?);
+    }
+    write_json(&object, indent)
+}
+
+/// Serialize entries as NDJSON: one compact object per line, without a final newline.
+pub fn to_ndjson(
+    entries: &[FileEntry],
+    fields: Option<&FieldSelection>,
+) -> serde_json::Result<String> {
+    let lines = entries
+        .iter()
+        .map(|entry| serde_json::to_string(&EntryJson { entry, fields }))
+        .collect::<serde_json::Result<Vec<_>>>()?;
+    Ok(lines.join("\n"))
+}
+
+/// Serialize entries as a YAML sequence, or as an envelope mapping when `envelope` is
+/// given; the YAML counterpart of [`to_json`], without the final newline.
+pub fn to_yaml(
+    entries: &[FileEntry],
+    fields: Option<&FieldSelection>,
+    envelope: Option<&ListingMeta>,
+) -> serde_yaml::Result<String> {
+    let entries: Vec<EntryJson> = entries
+        .iter()
+        .map(|entry| EntryJson { entry, fields })
+        .collect();
+    let yaml = match envelope {
+        Some(meta) => serde_yaml::to_string(&Envelope { entries, meta }),
+        None => serde_yaml::to_string(&entries),
+    }?;
+    Ok(yaml.trim_end_matches('\n').to_string())
+}
+
+/// Combine the YAML documents of several listings into one mapping keyed by path.
+///
+/// `listings` holds each path with its rendered document, in output order.
+pub fn yaml_by_path(listings: &[(String, &str)]) -> serde_yaml::Result<String> {
+    let mut mapping = serde_yaml::Mapping::new();
+    for (path, document) in listings {
+        mapping.insert(path.as_str().into(), serde_yaml::from_str(document)?);
+    }
+    let yaml = serde_yaml::to_string(&mapping)?;
+    Ok(yaml.trim_end_matches('\n').to_string())
+}
+
+#[derive(serde::Serialize)]
+struct TomlDocument<'a> {
+    entries: Vec<EntryJson<'a>>,
+    #[serde(flatten)]
+    meta: Option<&'a ListingMeta>,
+}
+
+/// Serialize entries as a TOML document with an `entries` array of tables, plus the
+/// listing metadata when `envelope` is given; the TOML counterpart of [`to_json`].
+pub fn to_toml(
+    entries: &[FileEntry],
+    fields: Option<&FieldSelection>,
+    envelope: Option<&ListingMeta>,
+) -> Result<String, toml::ser::Error> {
+    let entries: Vec<EntryJson> = entries
+        .iter()
+        .map(|entry| EntryJson { entry, fields })
+        .collect();
+    write_toml(&TomlDocument {
+        entries,
+        meta: envelope,
+    })
+}
+
+/// Combine the TOML documents of several listings into one document with a table
+/// per path.
+///
+/// `listings` holds each path with its rendered document, in output order.
+pub fn toml_by_path(listings: &[(String, &str)]) -> Result<String, toml::ser::Error> {
+    let mut object = Map::new();
+    for (path, document) in listings {
+        // Parsed into JSON values, which keep the field order
+        let value: Value = toml::from_str(document).map_err(serde::ser::Error::custom)?;
+        object.insert(path.clone(), value);
+    }
+    write_toml(&object)
+}
+
+/// Serialize `value` as TOML without the final newline, leaving out `null` fields
+/// and array items, which TOML cannot represent.
+pub fn write_toml<T: Serialize + ?Sized>(value: &T) -> Result<String, toml::ser::Error> {
+    let value = serde_json::to_value(value).map_err(serde::ser::Error::custom)?;
+    let toml = toml::to_string(&without_nulls(value))?;
+    Ok(toml.trim_end_matches('\n').to_string())
+}
+
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .filter(|value| !value.is_null())
+                .map(without_nulls)
+                .collect(),
+        ),
+        value => value,
+    }
+}
+
+/// Serialize `value`, pretty printed with `indent` spaces when given.
+pub fn write_json<T: Serialize + ?Sized>(
+    value: &T,
+    indent: Option<usize>,
+) -> serde_json::Result<String> {
+    let Some(width) = indent else {
+        return serde_json::to_string(value);
+    };
+
+    let indent = " ".repeat(width);
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    value.serialize(&mut serializer)?;
+    // serde_json only ever writes valid UTF-8
+    Ok(String::from_utf8(out).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> FileEntry {
+        FileEntry {
+            name: "a.txt".to_string(),
+            len_bytes: 3,
+            modified: "Mon 15 Jan 2024 14:30:25".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = FieldSelection::parse("name, len_bytes,name", &[]).unwrap();
+        assert_eq!(fields.0, vec!["name", "len_bytes"]);
+
+        assert!(FieldSelection::parse("name,bogus", &[]).is_err());
+        assert!(FieldSelection::parse(" , ", &[]).is_err());
+        assert!(FieldSelection::parse("Lines", &["Lines".to_string()]).is_ok());
+    }
+
+    #[test]
+    fn test_fields_keep_requested_order() {
+        let fields = FieldSelection::parse("len_bytes,name", &[]).unwrap();
+        let json = to_json(&[entry()], Some(&fields), None, None).unwrap();
+        assert_eq!(json, r#"[{"len_bytes":3,"name":"a.txt"}]"#);
+    }
+
+    #[test]
+    fn test_indent_width() {
+        let fields = FieldSelection::parse("name", &[]).unwrap();
+        let json = to_json(&[entry()], Some(&fields), Some(4), None).unwrap();
+        assert_eq!(json, "[\n    {\n        \"name\": \"a.txt\"\n    }\n]");
+    }
+
+    #[test]
+    fn test_envelope() {
+        let fields = FieldSelection::parse("name", &[]).unwrap();
+        let error = ListError {
+            path: std::path::PathBuf::from("/w/locked"),
+            error: std::io::ErrorKind::PermissionDenied.into(),
+            code: Code::PathUnreadable,
+        };
+        let meta = ListingMeta {
+            partial: true,
+            truncated: false,
+            total: Some(3),
+            filesystem: None,
+            errors: vec![ListingError::from(&error)],
+        };
+        let json = to_json(&[entry()], Some(&fields), None, Some(&meta)).unwrap();
+        assert_eq!(
+            json,
+            concat!(
+                r#"{"entries":[{"name":"a.txt"}],"partial":true,"truncated":false,"total":3,"#,
+                r#""errors":[{"path":"/w/locked","code":"path-unreadable","#,
+                r#""message":"failed to read directory '/w/locked': permission denied"}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_by_path_keeps_order() {
+        let src = to_json(&[entry()], None, None, None).unwrap();
+        let docs = to_json(&[], None, None, None).unwrap();
+        let listings = [
+            ("src".to_string(), src.as_str()),
+            ("docs".to_string(), docs.as_str()),
+        ];
+
+        let json = by_path(&listings, None).unwrap();
+        assert!(json.starts_with(r#"{"src":[{"name":"a.txt","e_type":"#));
+        assert!(json.ends_with(r#""docs":[]}"#));
+    }
+
+    #[test]
+    fn test_ndjson_lines() {
+        let fields = FieldSelection::parse("name,len_bytes", &[]).unwrap();
+        let mut second = entry();
+        second.name = "line\nbreak.txt".to_string();
+        let ndjson = to_ndjson(&[entry(), second], Some(&fields)).unwrap();
+        assert_eq!(
+            ndjson,
+            "{\"name\":\"a.txt\",\"len_bytes\":3}\n{\"name\":\"line\\nbreak.txt\",\"len_bytes\":3}"
+        );
+        assert_eq!(to_ndjson(&[], None).unwrap(), "");
+    }
+
+    #[test]
+    fn test_yaml_matches_json() {
+        let meta = ListingMeta {
+            partial: false,
+            truncated: false,
+            total: None,
+            filesystem: None,
+            errors: Vec::new(),
+        };
+        for (fields, envelope) in [(None, None), (Some("len_bytes,name"), Some(&meta))] {
+            let fields = fields.map(|spec| FieldSelection::parse(spec, &[]).unwrap());
+            let json = to_json(&[entry()], fields.as_ref(), None, envelope).unwrap();
+            let yaml = to_yaml(&[entry()], fields.as_ref(), envelope).unwrap();
+            let from_yaml: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+            let from_json: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(from_yaml, from_json);
+        }
+
+        let fields = FieldSelection::parse("len_bytes,name", &[]).unwrap();
+        let yaml = to_yaml(&[entry()], Some(&fields), None).unwrap();
+        assert_eq!(yaml, "- len_bytes: 3\n  name: a.txt");
+    }
+
+    #[test]
+    fn test_yaml_by_path_keeps_order() {
+        let src = to_yaml(&[entry()], None, None).unwrap();
+        let docs = to_yaml(&[], None, None).unwrap();
+        let listings = [
+            ("src".to_string(), src.as_str()),
+            ("docs".to_string(), docs.as_str()),
+        ];
+
+        let yaml = yaml_by_path(&listings).unwrap();
+        assert!(yaml.starts_with("src:\n- name: a.txt\n"));
+        assert!(yaml.ends_with("docs: []"));
+    }
+
+    /// A JSON value with its `null` fields removed, as the TOML output carries it
+    fn json_without_nulls(json: &str) -> serde_json::Value {
+        without_nulls(serde_json::from_str(json).unwrap())
+    }
+
+    #[test]
+    fn test_toml_round_trips() {
+        let meta = ListingMeta {
+            errors: vec![ListingError {
+                path: "src/locked".to_string(),
+                code: Code::PathUnreadable,
+                message: "permission denied".to_string(),
+            }],
+            ..Default::default()
+        };
+        for (fields, envelope) in [(None, None), (Some("len_bytes,name"), Some(&meta))] {
+            let fields = fields.map(|spec| FieldSelection::parse(spec, &[]).unwrap());
+            let json = to_json(&[entry()], fields.as_ref(), None, envelope).unwrap();
+            let toml = to_toml(&[entry()], fields.as_ref(), envelope).unwrap();
+            let from_toml: serde_json::Value = toml::from_str(&toml).unwrap();
+            match envelope {
+                Some(_) => assert_eq!(from_toml, json_without_nulls(&json)),
+                None => assert_eq!(from_toml["entries"], json_without_nulls(&json)),
+            }
+        }
+
+        let fields = FieldSelection::parse("len_bytes,name", &[]).unwrap();
+        let toml = to_toml(&[entry()], Some(&fields), None).unwrap();
+        assert_eq!(toml, "[[entries]]\nlen_bytes = 3\nname = \"a.txt\"");
+        assert_eq!(to_toml(&[], None, None).unwrap(), "entries = []");
+    }
+
+    #[test]
+    fn test_toml_omits_unset_fields() {
+        let toml = to_toml(&[entry()], None, None).unwrap();
+        let from_toml: toml::Table = toml::from_str(&toml).unwrap();
+        let entry = from_toml["entries"][0].as_table().unwrap();
+        assert_eq!(entry["name"].as_str(), Some("a.txt"));
+        // `created` and `extension` are null in JSON
+        assert!(!entry.contains_key("created"));
+        assert!(!entry.contains_key("extension"));
+    }
+
+    #[test]
+    fn test_toml_by_path_nests_listings() {
+        let src = to_toml(&[entry()], None, None).unwrap();
+        let docs = to_toml(&[], None, None).unwrap();
+        let listings = [
+            ("src/main".to_string(), src.as_str()),
+            ("docs".to_string(), docs.as_str()),
+        ];
+
+        let toml = toml_by_path(&listings).unwrap();
+        let from_toml: serde_json::Value = toml::from_str(&toml).unwrap();
+        assert_eq!(from_toml["src/main"]["entries"][0]["name"], "a.txt");
+        assert_eq!(from_toml["docs"]["entries"], serde_json::json!([]));
+        // Paths keep their command line order
+        let keys: Vec<&String> = from_toml.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["src/main", "docs"]);
+    }
+}
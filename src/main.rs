@@ -33,7 +33,7 @@
 //! bestls -p /home/user -a
 //!
 //! # Output as pretty JSON sorted by size
-//! bestls --json-pretty --sort size
+//! bestls --format json-pretty --sort size
 //!
 //! # Generate shell completions
 //! bestls completion bash > ~/.local/share/bash-completion/completions/bestls
@@ -45,16 +45,15 @@
 //!
 //! ```rust
 //! use std::path::PathBuf;
-//! use bestls::fsops::get_files;
+//! use bestls::fsops::{get_files, ListOptions};
 //!
 //! // Get file entries for current directory
 //! let path = PathBuf::from(".");
-//! let include_hidden = false;
 //!
-//! match get_files(&path, include_hidden) {
-//!     Ok(files) => {
-//!         println!("Found {} files", files.len());
-//!         for file in files {
+//! match get_files(&path, &ListOptions::default()) {
+//!     Ok(listing) => {
+//!         println!("Found {} files", listing.entries.len());
+//!         for file in listing.entries {
 //!             println!("{}: {}", file.name, file.human_size);
 //!         }
 //!     }
@@ -62,159 +61,1494 @@
 //! }
 //! ```
 
+#[cfg(feature = "archive")]
+mod archive;
 mod cli;
+mod collate;
 mod color;
+mod config;
+mod diag;
+mod dirsize;
+mod disk;
+mod exec;
+mod filter;
 mod fsops;
+mod git;
+mod interrupt;
+mod json;
+mod mime;
+mod output;
+mod perms;
+mod platform;
+mod select;
+mod stale;
+mod summary;
 mod table;
+mod template;
+mod top;
+mod tree;
+mod vfs;
+mod watch;
 
 use clap::Parser;
-use cli::{Cli, Commands, OutputFormat, SortBy, ThemeSubcommand};
-use color::{create_sample_config, load_theme};
+use cli::{Cli, ColorChoice, Commands, OutputFormat, SortBy, SortKey, TableStyle, ThemeSubcommand};
+use collate::{collate_locale_from_env, NameCollator};
+use color::{create_sample_config, load_theme, Theme};
+use config::{load_settings, Settings};
+use diag::Code;
+use exec::{run_exec_columns, ExecColumn};
+use filter::{Filter, FilterError};
 use fsops::{
-    get_files, get_files_recursive, matches_extension, matches_pattern, parse_size, FileEntry,
+    format_relative_time, get_files, get_files_recursive, natural_cmp, parse_locale,
+    time_locale_from_env, FileEntry, FileType, ListError, ListOptions, ListResult, TimeStyle,
+    TimestampFormat,
 };
-use glob::Pattern;
-use owo_colors::OwoColorize;
-use std::fs::File;
-use std::io::Write;
+use json::{FieldSelection, ListingMeta};
+use mime::MimeDetection;
+use output::RenderContext;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use table::format_table;
+use table::{NameWidth, RowHighlight, TableColumn, TableOptions};
+use template::{Template, TemplateError};
+use tree::TreeOptions;
+use watch::Snapshot;
 
-/// Error type for filter configuration
+/// Error type for listing configuration
 #[derive(Debug)]
-#[allow(clippy::enum_variant_names)]
 enum ConfigError {
-    InvalidGlobPattern(String),
-    InvalidMinSize(String),
-    InvalidMaxSize(String),
-    SizeRangeInvalid(String),
+    Filter(FilterError),
+    InvalidExecColumn(String),
+    InvalidFields(String),
+    InvalidColumns(String),
+    FormatString(TemplateError),
+    /// `--watch` or `--select` was given several paths
+    SinglePathOnly(&'static str),
 }
 
 impl std::fmt::Display for ConfigError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ConfigError::InvalidGlobPattern(e) => write!(f, "invalid glob pattern: {}", e),
-            ConfigError::InvalidMinSize(e) => write!(f, "invalid --min-size value: {}", e),
-            ConfigError::InvalidMaxSize(e) => write!(f, "invalid --max-size value: {}", e),
-            ConfigError::SizeRangeInvalid(e) => write!(f, "{}", e),
+            ConfigError::Filter(e) => write!(f, "{}", e),
+            ConfigError::InvalidExecColumn(e) => write!(f, "{}", e),
+            ConfigError::InvalidFields(e) => write!(f, "invalid --fields value: {}", e),
+            ConfigError::InvalidColumns(e) => write!(f, "invalid --columns value: {}", e),
+            ConfigError::FormatString(e) => write!(f, "{}", e),
+            ConfigError::SinglePathOnly(flag) => write!(f, "{} lists a single path", flag),
         }
     }
 }
 
 impl std::error::Error for ConfigError {}
 
-/// Precomputed filter configuration to avoid repeated parsing per file
-struct FilterConfig {
-    exts: Option<Vec<String>>,
-    name_pattern: Option<Pattern>,
-    min_size: Option<u64>,
-    max_size: Option<u64>,
-}
-
-impl FilterConfig {
-    /// Build filter configuration from CLI options, parsing once
-    /// Returns Err if any configuration is invalid
-    fn from_cli(cli: &Cli) -> Result<Self, ConfigError> {
-        // Pre-normalize extensions: lowercase and strip leading '.'
-        let exts = cli.filter_ext.as_ref().map(|ext_filter| {
-            ext_filter
-                .split(',')
-                .map(|s| s.trim().trim_start_matches('.').to_lowercase())
-                .collect::<Vec<_>>()
-        });
+impl From<FilterError> for ConfigError {
+    fn from(e: FilterError) -> Self {
+        ConfigError::Filter(e)
+    }
+}
 
-        // Compile and validate glob pattern once
-        let name_pattern = match cli.filter_name.as_deref() {
-            Some(pattern_str) => match Pattern::new(pattern_str) {
-                Ok(pattern) => Some(pattern),
-                Err(e) => {
-                    return Err(ConfigError::InvalidGlobPattern(format!(
-                        "invalid glob pattern '{}': {}",
-                        pattern_str, e
-                    )))
-                }
+impl ConfigError {
+    /// Stable diagnostic code for `--error-format json`
+    fn code(&self) -> Code {
+        match self {
+            ConfigError::Filter(e) => e.code(),
+            ConfigError::InvalidExecColumn(_) => Code::InvalidExecColumn,
+            ConfigError::InvalidFields(_) => Code::InvalidFields,
+            ConfigError::InvalidColumns(_) => Code::InvalidColumns,
+            ConfigError::FormatString(e) => e.code(),
+            ConfigError::SinglePathOnly(_) => Code::InvalidArgument,
+        }
+    }
+
+    /// Report the error and exit with the usage error status
+    fn exit(&self) -> ! {
+        diag::error(self.code(), None, self);
+        std::process::exit(2);
+    }
+}
+
+/// Load files from the specified path (tree or flat)
+fn load_files(cli: &Cli, path: &Path, opts: &ListOptions) -> std::io::Result<ListResult> {
+    if cli.archive {
+        load_archive(path, opts).map(|entries| ListResult {
+            entries,
+            errors: Vec::new(),
+        })
+    } else if cli.tree || cli.recursive {
+        // --depth counts levels from 1 while --max-depth counts from 0; don't walk
+        // below the shallower of the two
+        let levels = match (cli.depth.filter(|d| *d > 0), cli.max_depth) {
+            (Some(depth), Some(max)) => Some(depth.min(max + 1)),
+            (depth, max) => depth.or(max.map(|m| m + 1)),
+        };
+        get_files_recursive(path, opts, levels)
+    } else {
+        get_files(path, opts)
+    }
+}
+
+/// List archive members as file entries (`--archive`)
+#[cfg(feature = "archive")]
+fn load_archive(path: &Path, opts: &ListOptions) -> std::io::Result<Vec<FileEntry>> {
+    archive::list_archive(path, opts.include_hidden, &opts.timestamps)
+}
+
+#[cfg(not(feature = "archive"))]
+fn load_archive(_path: &Path, _opts: &ListOptions) -> std::io::Result<Vec<FileEntry>> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "bestls was built without the `archive` feature",
+    ))
+}
+
+/// Collect exec column specs from the command line and config files.
+///
+/// Columns from a per-directory `.bestls.toml` can run arbitrary commands from any
+/// checked-out repository, so they are skipped with a warning unless
+/// `--allow-local-exec` is passed.
+fn exec_columns(cli: &Cli, settings: &Settings) -> Result<Vec<ExecColumn>, ConfigError> {
+    let mut specs: Vec<&String> = cli
+        .exec_columns
+        .iter()
+        .chain(settings.global.exec.columns.iter())
+        .collect();
+
+    if let Some((local_path, local)) = &settings.local {
+        if !local.exec.columns.is_empty() {
+            if cli.allow_local_exec {
+                specs.extend(local.exec.columns.iter());
+            } else {
+                diag::warning(
+                    Code::LocalExecIgnored,
+                    Some(local_path),
+                    format!(
+                        "ignoring exec columns from '{}' (pass --allow-local-exec to run them)",
+                        local_path.display()
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut columns: Vec<ExecColumn> = Vec::new();
+    for spec in specs {
+        let column = ExecColumn::parse(spec).map_err(ConfigError::InvalidExecColumn)?;
+        if columns.iter().any(|c| c.label == column.label) {
+            return Err(ConfigError::InvalidExecColumn(format!(
+                "duplicate exec column label '{}'",
+                column.label
+            )));
+        }
+        columns.push(column);
+    }
+    Ok(columns)
+}
+
+/// Attach last-commit information to entries (`--git-log`)
+///
+/// Failures (not a repository, empty history) are reported as a warning and
+/// leave the entries untouched so the listing itself still succeeds.
+fn attach_git_log(files: &mut [FileEntry], path: &Path, max_commits: usize) {
+    let wanted: Vec<PathBuf> = files
+        .iter()
+        .filter_map(|f| f.path.canonicalize().ok())
+        .collect();
+
+    let commits = match git::collect_last_commits(path, &wanted, max_commits) {
+        Ok(commits) => commits,
+        Err(e) => {
+            diag::warning(
+                Code::GitUnavailable,
+                Some(path),
+                format!("--git-log unavailable: {}", e.message()),
+            );
+            return;
+        }
+    };
+
+    let now = chrono::Utc::now();
+    for file in files.iter_mut() {
+        let Ok(abs) = file.path.canonicalize() else {
+            continue;
+        };
+        if let Some(info) = commits.lookup(&abs) {
+            file.last_commit_hash = Some(info.short_hash.clone());
+            file.last_commit_date = Some(format_relative_time(info.time, now));
+            file.last_commit_summary = Some(info.summary.clone());
+        }
+    }
+}
+
+/// Terminal styling decided once at startup
+#[derive(Debug, Clone, Copy)]
+struct Styling {
+    /// Emit ANSI colors
+    color: bool,
+    /// Draw table borders with ASCII instead of box-drawing characters
+    ascii: bool,
+}
+
+/// Resolve `--date-locale` (with `auto` read from the environment).
+///
+/// `--time-style locale` without `--date-locale` implies `auto`. Only tables (also
+/// Markdown and `-l`), `--format-string` lines, and fzf lines are localized; JSON and
+/// `--watch-log` keep English timestamps so scripts parsing them don't depend on the
+/// user's locale. Unknown locales fall back to English; `--verbose` says so.
+fn date_locale(cli: &Cli) -> Option<chrono::Locale> {
+    let requested = match (&cli.date_locale, &cli.time_style) {
+        (Some(locale), _) => locale.as_str(),
+        (None, Some(TimeStyle::Locale)) => "auto",
+        (None, _) => return None,
+    };
+    if !matches!(
+        cli.effective_format(),
+        OutputFormat::Table
+            | OutputFormat::Markdown
+            | OutputFormat::Long
+            | OutputFormat::Template
+            | OutputFormat::Fzf
+    ) || cli.watch_log
+    {
+        return None;
+    }
+    let name = if requested == "auto" {
+        time_locale_from_env()?
+    } else {
+        requested.to_string()
+    };
+
+    let locale = parse_locale(&name);
+    if locale.is_none() && cli.verbose {
+        diag::info(
+            Code::UnknownLocale,
+            format!("Unknown date locale '{}'; using English month names", name),
+        );
+    }
+    locale
+}
+
+/// Decide whether output uses colors and box-drawing borders.
+///
+/// `--no-color` and `--color never` turn colors off, `--color always` forces them on.
+/// Output piped elsewhere (except `--fzf`) or written to an `--out` file is uncolored
+/// unless `--color always`.
+/// With `--color auto`, a terminal that cannot render ANSI escapes (legacy Windows
+/// consoles) gets plain output with ASCII borders instead of escape garbage.
+fn styling(cli: &Cli) -> Styling {
+    if cli.no_color || cli.color == ColorChoice::Never {
+        if cli.verbose {
+            diag::info(Code::TerminalColors, "Colors disabled on request");
+        }
+        return Styling {
+            color: false,
+            ascii: false,
+        };
+    }
+    // A file is no terminal; only --color always puts escapes into it
+    if cli.output_file.is_some() && cli.color == ColorChoice::Auto {
+        if cli.verbose {
+            diag::info(Code::TerminalColors, "Colors disabled for the --out file");
+        }
+        return Styling {
+            color: false,
+            ascii: false,
+        };
+    }
+
+    // Likewise for a pipe, so `bestls | less` or a script never sees escapes; fzf lines
+    // are made for piping into `fzf --ansi`, so they keep their colors
+    if cli.color == ColorChoice::Auto
+        && !matches!(cli.effective_format(), OutputFormat::Fzf)
+        && !std::io::IsTerminal::is_terminal(&std::io::stdout())
+    {
+        if cli.verbose {
+            diag::info(
+                Code::TerminalColors,
+                "Colors disabled: standard output is not a terminal",
+            );
+        }
+        return Styling {
+            color: false,
+            ascii: false,
+        };
+    }
+
+    match platform::enable_ansi() {
+        Ok(()) => {
+            if cli.verbose {
+                diag::info(
+                    Code::TerminalColors,
+                    "Terminal supports ANSI escape sequences",
+                );
+            }
+        }
+        Err(e) if cli.color == ColorChoice::Auto => {
+            if cli.verbose {
+                diag::info(
+                    Code::TerminalColors,
+                    format!(
+                        "Terminal does not support ANSI escape sequences ({}); using plain ASCII output",
+                        e
+                    ),
+                );
+            }
+            return Styling {
+                color: false,
+                ascii: true,
+            };
+        }
+        Err(e) => {
+            if cli.verbose {
+                diag::info(
+                    Code::TerminalColors,
+                    format!(
+                        "Terminal does not support ANSI escape sequences ({}); coloring anyway (--color always)",
+                        e
+                    ),
+                );
+            }
+        }
+    }
+
+    Styling {
+        color: true,
+        ascii: false,
+    }
+}
+
+/// Whether errors on stderr are colored: when it is a terminal, unless colors are
+/// turned off (`--color always` colors them anyway)
+fn stderr_color(cli: &Cli) -> bool {
+    match cli.color {
+        _ if cli.no_color => false,
+        ColorChoice::Never => false,
+        ColorChoice::Always => true,
+        ColorChoice::Auto => {
+            std::io::IsTerminal::is_terminal(&std::io::stderr()) && platform::enable_ansi().is_ok()
+        }
+    }
+}
+
+/// Rank for `--sort extension`: directories, then files without an extension, then the rest
+fn extension_group(entry: &FileEntry) -> u8 {
+    match (&entry.e_type, &entry.extension) {
+        (FileType::Directory, _) => 0,
+        (_, None) => 1,
+        (_, Some(_)) => 2,
+    }
+}
+
+/// How names compare when sorting (`--ignore-case`, `--collate`)
+#[derive(Default)]
+struct NameOrder {
+    ignore_case: bool,
+    /// Locale collation (`--collate`); byte order when `None`
+    collator: Option<NameCollator>,
+}
+
+impl NameOrder {
+    /// Compare two names.
+    ///
+    /// Names differing only in case (or equal under the collation) still compare by
+    /// their original bytes, so the order stays deterministic.
+    fn cmp(&self, a: &str, b: &str) -> Ordering {
+        match &self.collator {
+            Some(collator) => collator.compare(a, b),
+            None if self.ignore_case => a
+                .to_lowercase()
+                .cmp(&b.to_lowercase())
+                .then_with(|| a.cmp(b)),
+            None => a.cmp(b),
+        }
+    }
+}
+
+/// Resolve `--ignore-case`, `--collate`, and `--locale` into a name order.
+///
+/// A locale that cannot be used falls back to byte order with a warning.
+fn name_order(cli: &Cli) -> NameOrder {
+    let collator = cli.collate.then(|| {
+        let locale = cli
+            .locale
+            .clone()
+            .or_else(collate_locale_from_env)
+            .unwrap_or_else(|| "C".to_string());
+        NameCollator::new(&locale, cli.ignore_case)
+            .map_err(|e| {
+                diag::warning(
+                    Code::CollationUnavailable,
+                    None,
+                    format!("--collate: {}; sorting names by bytes", e),
+                )
+            })
+            .ok()
+    });
+    NameOrder {
+        ignore_case: cli.ignore_case,
+        collator: collator.flatten(),
+    }
+}
+
+/// Compare two entries by a single sort attribute, without tie-breaking
+fn key_cmp(a: &FileEntry, b: &FileEntry, by: &SortBy, names: &NameOrder) -> Ordering {
+    match by {
+        SortBy::Name => names.cmp(&a.name, &b.name),
+        SortBy::Natural if names.ignore_case => {
+            natural_cmp(&a.name.to_lowercase(), &b.name.to_lowercase())
+                .then_with(|| natural_cmp(&a.name, &b.name))
+        }
+        SortBy::Natural => natural_cmp(&a.name, &b.name),
+        SortBy::Size => a.len_bytes.cmp(&b.len_bytes),
+        // Compare raw times; the display string depends on --time-style/--date-locale
+        SortBy::Date => a.modified_time.cmp(&b.modified_time),
+        SortBy::Created => a.created_time.cmp(&b.created_time),
+        SortBy::Accessed => a.accessed_time.cmp(&b.accessed_time),
+        SortBy::Depth => a.depth.cmp(&b.depth),
+        SortBy::Type => a.e_type.sort_rank().cmp(&b.e_type.sort_rank()),
+        SortBy::Extension => extension_group(a)
+            .cmp(&extension_group(b))
+            .then_with(|| a.extension.cmp(&b.extension)),
+        SortBy::None => Ordering::Equal,
+    }
+}
+
+/// Sort entries by the `--sort` keys in order, with the order flipped for `--reverse`.
+///
+/// Entries equal on every key are ordered by name. Entries without a creation or
+/// access time sort after the others for `created` and `accessed` in either direction.
+///
+/// `--sort none` skips sorting altogether, leaving the directory order (and ignoring
+/// `--reverse`); combined with other keys it has no effect.
+fn sort_entries(files: &mut [FileEntry], keys: &[SortKey], reverse: bool, names: &NameOrder) {
+    if keys.iter().all(|key| matches!(key.by, SortBy::None)) {
+        return;
+    }
+    let directed = |ordering: Ordering, descending: bool| {
+        if descending != reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+    files.sort_by(|a, b| {
+        keys.iter()
+            .map(|key| {
+                let missing = match key.by {
+                    SortBy::Created => a.created_time.is_none().cmp(&b.created_time.is_none()),
+                    SortBy::Accessed => a.accessed_time.is_none().cmp(&b.accessed_time.is_none()),
+                    _ => Ordering::Equal,
+                };
+                missing.then_with(|| directed(key_cmp(a, b, &key.by, names), key.descending))
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| directed(names.cmp(&a.name, &b.name), false))
+    });
+}
+
+/// Move hidden entries before (`first`, `--hidden-first`) or after (`--hidden-last`)
+/// the others.
+///
+/// The sort is stable, so each group keeps the order `--sort` produced. With
+/// `--group-directories-first` as well, directories still come first and the hidden
+/// entries are grouped within the directories and within the rest.
+fn group_hidden(files: &mut [FileEntry], first: bool) {
+    files.sort_by_key(|entry| entry.is_hidden != first);
+}
+
+/// Move directories ahead of (`--group-directories-first`) or behind (`last`,
+/// `--dirs-last`) the other entries.
+///
+/// The sort is stable, so each group keeps the order `--sort` produced. Symlinks
+/// stay with the files unless `dereference` is set and the link resolves to a
+/// directory.
+fn group_directories(files: &mut [FileEntry], last: bool, dereference: bool) {
+    files.sort_by_key(|entry| {
+        let is_dir = match entry.e_type {
+            FileType::Directory => true,
+            FileType::Symlink => dereference && entry.path.is_dir(),
+            FileType::File => false,
+        };
+        is_dir == last
+    });
+}
+
+/// Keep the first `limit` entries (all of them for 0) and return how many were dropped.
+fn limit_entries(files: &mut Vec<FileEntry>, limit: usize) -> usize {
+    if limit == 0 || files.len() <= limit {
+        return 0;
+    }
+    let omitted = files.len() - limit;
+    files.truncate(limit);
+    omitted
+}
+
+/// Format a count with `,` between groups of three digits (`4,310`)
+fn group_thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Per-path configuration a [`ListingContext`] borrows.
+///
+/// Settings come from the listed directory's `.bestls.toml`, so each path given on
+/// the command line gets its own.
+struct ListingConfig {
+    filter: Filter,
+    list_opts: ListOptions,
+    exec_columns: Vec<ExecColumn>,
+    /// JSON field selection (`--fields`)
+    fields: Option<FieldSelection>,
+    /// Parsed `--format-string`
+    template: Option<Template>,
+    /// Table columns (`--columns`)
+    columns: Option<Vec<TableColumn>>,
+    /// Table style from `--style` or the config files
+    table_style: Option<TableStyle>,
+}
+
+impl ListingConfig {
+    /// Resolve the options for listing `path`, exiting on invalid ones
+    fn new(cli: &Cli, path: &Path) -> Self {
+        let settings = load_settings(path);
+        // Precompute filter configuration once
+        let filter =
+            Filter::from_cli(cli, &settings).unwrap_or_else(|e| ConfigError::from(e).exit());
+        let list_opts = ListOptions {
+            include_hidden: cli.all,
+            preview: cli.preview,
+            // -H always wins, so the root is followed whatever else is configured
+            no_dereference: cli.no_dereference && !cli.dereference_command_line,
+            directory: cli.directory,
+            respect_dot_hidden: cli.respect_dot_hidden || settings.respect_dot_hidden(),
+            timestamps: TimestampFormat {
+                style: cli.time_style.clone().unwrap_or_default(),
+                locale: date_locale(cli),
             },
+            max_entries: Some(cli.max_entries).filter(|max| *max > 0),
+            exclude: filter.exclude().clone(),
+            probe_empty: cli.empty,
+            total_size: cli.total_size,
+            count_items: cli.count_items,
+            relative_names: cli.recursive && !cli.group_by_dir,
+            follow_symlinks: cli.follow_symlinks,
+            one_file_system: cli.one_file_system,
+            mime: (!cli.mime.is_empty()).then_some(if cli.deep_mime {
+                MimeDetection::Content
+            } else {
+                MimeDetection::Name
+            }),
+        };
+        let exec_columns = exec_columns(cli, &settings).unwrap_or_else(|e| e.exit());
+
+        let fields = match cli.fields.as_deref().map(|spec| {
+            let labels: Vec<String> = exec_columns.iter().map(|c| c.label.clone()).collect();
+            FieldSelection::parse(spec, &labels).map_err(ConfigError::InvalidFields)
+        }) {
+            Some(Err(e)) => e.exit(),
+            Some(Ok(fields)) => Some(fields),
             None => None,
         };
+        let template = cli.format_string.as_deref().map(|spec| {
+            let labels: Vec<String> = exec_columns.iter().map(|c| c.label.clone()).collect();
+            Template::parse(spec, &labels).unwrap_or_else(|e| ConfigError::FormatString(e).exit())
+        });
+        let columns = cli.columns.as_deref().map(|spec| {
+            let labels: Vec<String> = exec_columns.iter().map(|c| c.label.clone()).collect();
+            let columns = table::parse_columns(spec, &labels)
+                .unwrap_or_else(|e| ConfigError::InvalidColumns(e).exit());
+            // These columns are only filled in when their flag gathers the data
+            for (column, name, flag, given) in [
+                (
+                    TableColumn::Items,
+                    "items",
+                    "--count-items",
+                    cli.count_items,
+                ),
+                (
+                    TableColumn::LastCommit,
+                    "last_commit",
+                    "--git-log",
+                    cli.git_log,
+                ),
+                (TableColumn::Preview, "preview", "--preview", cli.preview),
+            ] {
+                if !given && columns.contains(&column) {
+                    ConfigError::InvalidColumns(format!("column '{}' needs {}", name, flag)).exit();
+                }
+            }
+            columns
+        });
+
+        if let Some(kind) = vfs::virtual_fs_kind(path) {
+            diag::note(
+                Code::VirtualFilesystem,
+                Some(path),
+                format!(
+                    "'{}' is on a virtual filesystem ({}); sizes and file contents are not read",
+                    platform::display_path(path).display(),
+                    kind
+                ),
+            );
+        }
+
+        Self {
+            filter,
+            list_opts,
+            exec_columns,
+            fields,
+            template,
+            columns,
+            table_style: cli.style.or(settings.table_style()),
+        }
+    }
+
+    /// The context listing `path` with this configuration
+    fn context<'a>(
+        &'a self,
+        cli: &'a Cli,
+        path: &'a Path,
+        styling: Styling,
+        names: &'a NameOrder,
+    ) -> ListingContext<'a> {
+        ListingContext {
+            cli,
+            path,
+            list_opts: &self.list_opts,
+            filter: &self.filter,
+            exec_columns: &self.exec_columns,
+            fields: self.fields.as_ref(),
+            template: self.template.as_ref(),
+            columns: self.columns.as_deref(),
+            // Consoles without ANSI support get ASCII borders unless a style is chosen
+            table_style: self.table_style.unwrap_or(if styling.ascii {
+                TableStyle::Ascii
+            } else {
+                TableStyle::Rounded
+            }),
+            styling,
+            names,
+        }
+    }
+}
+
+/// The rendered listing of one command line path
+struct Listing {
+    path: PathBuf,
+    output: String,
+    /// NUL-terminated names written instead of `output` with `--print0`
+    raw: Vec<u8>,
+    /// Entries left out by `--limit`
+    omitted: usize,
+    /// Entries listed, after filters and `--limit`
+    entries: usize,
+}
 
-        // Parse size strings once
-        let min_size = if let Some(min_str) = cli.min_size.as_deref() {
-            match parse_size(min_str) {
-                Ok(size) => Some(size),
-                Err(e) => return Err(ConfigError::InvalidMinSize(e.to_string())),
+/// List the paths themselves as one listing (`--directory`), sorted and limited
+/// together like `ls -d a b`; returns `false` when a path could not be listed.
+///
+/// The current directory's settings apply, as that is where the entries are shown.
+fn list_directories(
+    cli: &Cli,
+    paths: &[PathBuf],
+    styling: Styling,
+    names: &NameOrder,
+    theme: &Theme,
+) -> (Option<Listing>, bool) {
+    let here = Path::new(".");
+    let config = ListingConfig::new(cli, here);
+    let mut entries = Vec::new();
+    let mut listed = true;
+    for path in paths {
+        match config.context(cli, path, styling, names).list() {
+            Ok((listing, _)) => entries.extend(listing.entries),
+            Err(e) => {
+                report_unreadable(path, &e);
+                listed = false;
             }
-        } else {
-            None
+        }
+    }
+    if entries.is_empty() && !listed {
+        return (None, false);
+    }
+
+    let ctx = config.context(cli, here, styling, names);
+    ctx.order(&mut entries);
+    let omitted = limit_entries(&mut entries, cli.limit);
+    (
+        Some(ctx.listing(here, &entries, omitted, &[], theme)),
+        listed,
+    )
+}
+
+/// Split `--stdin` input into paths, dropping empty lines and repeated paths.
+///
+/// Paths repeat when they only differ in `.` components or trailing slashes (`d`,
+/// `./d`, and `d/`); the first spelling is kept. Lines may end in `\r\n`. With `nul`,
+/// paths are separated by NUL bytes instead and kept exactly, so names may contain
+/// newlines.
+fn parse_path_list(input: &[u8], nul: bool) -> Vec<PathBuf> {
+    let separator = if nul { b'\0' } else { b'\n' };
+    let mut seen = HashSet::new();
+    input
+        .split(|byte| *byte == separator)
+        .map(|path| {
+            if nul {
+                path
+            } else {
+                path.strip_suffix(b"\r").unwrap_or(path)
+            }
+        })
+        .filter(|path| !path.is_empty())
+        .map(platform::path_from_bytes)
+        .filter(|path| {
+            // Comparing components already ignores inner `.` and trailing slashes
+            let key: PathBuf = path
+                .components()
+                .skip_while(|c| *c == std::path::Component::CurDir)
+                .collect();
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// The paths to list: read from standard input with `--stdin`, else from the command
+/// line.
+///
+/// `--stdin` paths that do not exist are reported and left out; the returned flag says
+/// whether there were any.
+fn listing_paths(cli: &Cli) -> (Vec<PathBuf>, bool) {
+    if !cli.stdin {
+        return (cli.paths(), false);
+    }
+    let mut input = Vec::new();
+    if let Err(e) = std::io::stdin().lock().read_to_end(&mut input) {
+        diag::error(
+            Code::StdinUnreadable,
+            None,
+            format!("cannot read paths from standard input: {}", e),
+        );
+        std::process::exit(1);
+    }
+
+    let mut missing = false;
+    let mut paths = parse_path_list(&input, cli.null);
+    paths.retain(|path| match platform::long_path(path).symlink_metadata() {
+        Ok(_) => true,
+        Err(e) => {
+            diag::warning(
+                Code::PathNotFound,
+                Some(path),
+                format!(
+                    "skipping '{}': {}",
+                    platform::display_path(path).display(),
+                    e
+                ),
+            );
+            missing = true;
+            false
+        }
+    });
+    (paths, missing)
+}
+
+/// Join the listings of the command line paths into one output.
+///
+/// A single listing is printed as is. Several get a `PATH:` header each, separated by
+/// a blank line like `ls` does; JSON, YAML, and TOML instead become one mapping keyed
+/// by path, fzf and NDJSON lines are simply concatenated, CSV and TSV rows share the
+/// first listing's header, and HTML tables get an `<h2>` heading each. `--group-by-dir`
+/// tables already start with their directory's heading, so they only get the blank
+/// line.
+fn combine_listings(cli: &Cli, listings: &[Listing]) -> String {
+    if let [listing] = listings {
+        return listing.output.clone();
+    }
+    let label = |listing: &Listing| platform::display_path(&listing.path).display().to_string();
+
+    match cli.effective_format() {
+        format @ (OutputFormat::Json | OutputFormat::JsonPretty) => {
+            let indent = matches!(format, OutputFormat::JsonPretty).then_some(cli.indent);
+            let outputs: Vec<(String, &str)> = listings
+                .iter()
+                .map(|listing| (label(listing), listing.output.as_str()))
+                .collect();
+            json::by_path(&outputs, indent).unwrap_or_else(|_| "cannot parse to JSON".into())
+        }
+        OutputFormat::Yaml => {
+            let outputs: Vec<(String, &str)> = listings
+                .iter()
+                .map(|listing| (label(listing), listing.output.as_str()))
+                .collect();
+            json::yaml_by_path(&outputs).unwrap_or_else(|_| "cannot serialize to YAML".into())
+        }
+        OutputFormat::Toml => {
+            let outputs: Vec<(String, &str)> = listings
+                .iter()
+                .map(|listing| (label(listing), listing.output.as_str()))
+                .collect();
+            json::toml_by_path(&outputs).unwrap_or_else(|_| "cannot serialize to TOML".into())
+        }
+        OutputFormat::Fzf | OutputFormat::Ndjson => listings
+            .iter()
+            .map(|listing| listing.output.as_str())
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        // The header is a single line; rows may span several in quoted fields
+        OutputFormat::Csv | OutputFormat::Tsv => listings
+            .iter()
+            .enumerate()
+            .map(|(i, listing)| match (i, cli.no_header) {
+                (0, _) | (_, true) => listing.output.as_str(),
+                _ => listing.output.split_once('\n').map_or("", |(_, rows)| rows),
+            })
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        // Grouped HTML already has an <h2> per directory
+        OutputFormat::Html if cli.group_by_dir => listings
+            .iter()
+            .map(|listing| listing.output.as_str())
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Html => listings
+            .iter()
+            .map(|listing| {
+                let heading = output::html::escape(&label(listing));
+                format!("<h2>{}</h2>\n{}", heading, listing.output)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Table | OutputFormat::Markdown if cli.group_by_dir => listings
+            .iter()
+            .map(|listing| listing.output.as_str())
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        OutputFormat::Table
+        | OutputFormat::Markdown
+        | OutputFormat::Oneline
+        | OutputFormat::Long
+        | OutputFormat::Grid
+        | OutputFormat::Template
+        | OutputFormat::Tree => listings
+            .iter()
+            .map(|listing| format!("{}:\n{}", label(listing), listing.output))
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    }
+}
+
+/// Everything needed to produce one listing, so `--watch` can repeat it
+struct ListingContext<'a> {
+    cli: &'a Cli,
+    path: &'a Path,
+    list_opts: &'a ListOptions,
+    filter: &'a Filter,
+    exec_columns: &'a [ExecColumn],
+    /// JSON field selection (`--fields`)
+    fields: Option<&'a FieldSelection>,
+    /// Parsed `--format-string`
+    template: Option<&'a Template>,
+    /// Table columns (`--columns`)
+    columns: Option<&'a [TableColumn]>,
+    table_style: TableStyle,
+    styling: Styling,
+    /// Name comparison for sorting (`--ignore-case`, `--collate`)
+    names: &'a NameOrder,
+}
+
+impl ListingContext<'_> {
+    /// Load, filter, annotate, and sort the entries, keeping the first `--limit` of them.
+    ///
+    /// Also returns the number of entries the limit left out. The listing's errors are
+    /// what could not be read below the path.
+    fn list(&self) -> std::io::Result<(ListResult, usize)> {
+        let cli = self.cli;
+
+        // Get files (tree or flat)
+        let ListResult {
+            entries: mut files,
+            errors,
+        } = load_files(cli, self.path, self.list_opts)?;
+
+        // Apply all configured filters
+        files.retain(|f| self.filter.matches(f));
+
+        if cli.git_log {
+            attach_git_log(&mut files, self.path, cli.git_log_depth);
+        }
+
+        run_exec_columns(
+            &mut files,
+            self.exec_columns,
+            cli.exec_jobs,
+            std::time::Duration::from_secs(cli.exec_timeout),
+        );
+
+        self.order(&mut files);
+        let omitted = limit_entries(&mut files, cli.limit);
+        Ok((
+            ListResult {
+                entries: files,
+                errors,
+            },
+            omitted,
+        ))
+    }
+
+    /// Whether the listing can be written while the directory is read: NDJSON, `-1`,
+    /// `--format-string`, and `--compact` lines stand on their own, so only what needs
+    /// the whole listing first rules it out (sorting other than `--sort none`,
+    /// grouping, `--limit`, recursion, git and exec columns, footers), and the lines go
+    /// to stdout.
+    ///
+    /// `several` says this is one of several paths, whose line formats other than
+    /// NDJSON need a heading per path.
+    fn streams(&self, several: bool) -> bool {
+        let cli = self.cli;
+        let line_format = match cli.effective_format() {
+            OutputFormat::Ndjson => true,
+            OutputFormat::Oneline | OutputFormat::Template => !several,
+            OutputFormat::Table => cli.compact && !several,
+            _ => false,
         };
+        line_format
+            && cli.sort_by.iter().all(|key| matches!(key.by, SortBy::None))
+            && !(cli.hidden_first || cli.hidden_last)
+            && !(cli.group_directories_first || cli.dirs_last)
+            && cli.limit == 0
+            && !(cli.tree || cli.recursive || cli.archive)
+            && !cli.git_log
+            && self.exec_columns.is_empty()
+            && !cli.summary
+            && !cli.disk_usage_footer
+            && cli.output_file.is_none()
+            && !cli.quiet
+    }
 
-        let max_size = if let Some(max_str) = cli.max_size.as_deref() {
-            match parse_size(max_str) {
-                Ok(size) => Some(size),
-                Err(e) => return Err(ConfigError::InvalidMaxSize(e.to_string())),
+    /// Write the filtered entries to stdout as they are read, one line (or `--print0`
+    /// name) each, flushing after every batch [`fsops::stream_files`] reads. A closed
+    /// stdout (e.g. `| head`) ends the listing quietly.
+    fn stream(&self, theme: &Theme) -> std::io::Result<Vec<ListError>> {
+        let mut stream = fsops::stream_files(self.path, self.list_opts)?;
+        let mut stdout = std::io::BufWriter::new(std::io::stdout().lock());
+        let mut errors = Vec::new();
+        while let Some(entry) = stream.next() {
+            let written = match entry {
+                Ok(entry) if self.filter.matches(&entry) => {
+                    let entry = std::slice::from_ref(&entry);
+                    if self.cli.print0 {
+                        stdout.write_all(&output::print0(entry, self.cli.full_path))
+                    } else {
+                        let line = self.render(entry, 0, &[], theme, Vec::new());
+                        writeln!(stdout, "{}", line)
+                    }
+                }
+                Ok(_) => Ok(()),
+                Err(error) => {
+                    errors.push(error);
+                    Ok(())
+                }
+            };
+            let flushed = written.and_then(|()| {
+                if stream.batch_drained() {
+                    stdout.flush()
+                } else {
+                    Ok(())
+                }
+            });
+            if !stream_write_ok(flushed) {
+                return Ok(errors);
             }
+        }
+        stream_write_ok(stdout.flush());
+        Ok(errors)
+    }
+
+    /// Sort entries and apply the hidden/directory grouping
+    fn order(&self, files: &mut [FileEntry]) {
+        let cli = self.cli;
+        sort_entries(files, &cli.sort_by, cli.reverse, self.names);
+        if cli.hidden_first || cli.hidden_last {
+            group_hidden(files, cli.hidden_first);
+        }
+        if cli.group_directories_first || cli.dirs_last {
+            group_directories(files, cli.dirs_last, cli.dereference_dirs);
+        }
+    }
+
+    /// The output of `path` for the main listing: rendered text, or the raw names with
+    /// `--print0`
+    fn listing(
+        &self,
+        path: &Path,
+        files: &[FileEntry],
+        omitted: usize,
+        errors: &[ListError],
+        theme: &Theme,
+    ) -> Listing {
+        let (output, raw) = if self.cli.quiet {
+            (String::new(), Vec::new())
+        } else if self.cli.print0 {
+            (String::new(), output::print0(files, self.cli.full_path))
         } else {
-            None
+            let output = self.render(files, omitted, errors, theme, Vec::new());
+            (output, Vec::new())
         };
+        Listing {
+            path: path.to_path_buf(),
+            output,
+            raw,
+            omitted,
+            entries: files.len(),
+        }
+    }
 
-        // Validate that min_size <= max_size
-        if let (Some(min), Some(max)) = (min_size, max_size) {
-            if min > max {
-                return Err(ConfigError::SizeRangeInvalid(format!(
-                    "--min-size ({}) must be less than or equal to --max-size ({})",
-                    min, max
-                )));
-            }
+    /// Render entries in the effective output format; `omitted` entries were left out
+    /// by `--limit`, and `errors` go into the `--envelope`
+    fn render(
+        &self,
+        files: &[FileEntry],
+        omitted: usize,
+        errors: &[ListError],
+        theme: &Theme,
+        highlights: Vec<Option<RowHighlight>>,
+    ) -> String {
+        let cli = self.cli;
+        if cli.summary {
+            return self.render_summary(files);
         }
 
-        Ok(FilterConfig {
-            exts,
-            name_pattern,
-            min_size,
-            max_size,
+        let filesystem = cli
+            .disk_usage_footer
+            .then(|| self.filesystem_usage())
+            .flatten();
+        let meta = cli.envelope.then(|| ListingMeta {
+            partial: interrupt::is_cancelled() || interrupt::was_truncated(),
+            truncated: interrupt::was_truncated(),
+            total: (cli.limit > 0).then(|| files.len() + omitted),
+            filesystem,
+            errors: errors.iter().map(json::ListingError::from).collect(),
+        });
+
+        // Like `ls`, `-1` and `--grid` names are only colored on a terminal unless
+        // --color always, and a grid falls back to one name per line off a terminal
+        let to_terminal =
+            cli.output_file.is_none() && std::io::IsTerminal::is_terminal(&std::io::stdout());
+        let oneline_plain = matches!(
+            cli.effective_format(),
+            OutputFormat::Oneline | OutputFormat::Grid
+        ) && cli.color != ColorChoice::Always
+            && !to_terminal;
+        let width = match cli.width {
+            Some(width) => Some(usize::from(width)),
+            None if to_terminal => terminal_size::terminal_size().map(|(w, _)| usize::from(w.0)),
+            None => None,
+        };
+        let ctx = RenderContext {
+            theme,
+            color: self.styling.color && !oneline_plain,
+            columns: self.columns,
+            compact: cli.compact,
+            table: TableOptions {
+                git_log: cli.git_log,
+                preview: cli.preview,
+                exec_columns: self.exec_columns.iter().map(|c| c.label.clone()).collect(),
+                highlights,
+                size_bars: cli.size_bars,
+                total_size: cli.total_size,
+                ascii: self.styling.ascii,
+                markdown: false,
+                owner_format: cli.owner_format,
+                omit_same_group: cli.omit_same_group,
+                // Grouped tables already say where each entry is
+                depth: cli.tree || (cli.recursive && !cli.group_by_dir),
+                created: cli.created,
+                items: cli.count_items,
+                search: cli.search.clone(),
+                no_header: cli.no_header,
+                style: self.table_style,
+                name_width: match (cli.max_name_width, cli.wrap) {
+                    (Some(max), _) => Some(NameWidth {
+                        wrap: false,
+                        max: max.map(usize::from),
+                    }),
+                    (None, Some(max)) => Some(NameWidth {
+                        wrap: true,
+                        max: max.map(usize::from),
+                    }),
+                    (None, None) => None,
+                },
+                terminal_width: width,
+            },
+            tree: TreeOptions {
+                root: platform::display_path(self.path).display().to_string(),
+                sizes: cli.sizes,
+            },
+            group_by_dir: cli.group_by_dir,
+            fields: self.fields,
+            template: self.template,
+            indent: cli.indent,
+            envelope: meta,
+            filesystem,
+            header: !cli.no_header,
+            full_path: cli.full_path,
+            width,
+        };
+        // Legacy --json/--json-pretty flags are folded into the effective format
+        output::render(cli.effective_format(), files, &ctx).unwrap_or_else(|e| {
+            diag::error(
+                Code::OutputWriteFailed,
+                None,
+                format!("cannot render output: {}", e),
+            );
+            std::process::exit(1);
         })
     }
+
+    /// Render the totals of the entries (`--summary`), as JSON for the JSON formats
+    fn render_summary(&self, files: &[FileEntry]) -> String {
+        let summary = summary::summarize(files);
+        let json = match self.cli.effective_format() {
+            OutputFormat::Json => json::write_json(&summary, None),
+            OutputFormat::JsonPretty => json::write_json(&summary, Some(self.cli.indent)),
+            OutputFormat::Yaml => {
+                return serde_yaml::to_string(&summary)
+                    .map(|yaml| yaml.trim_end_matches('\n').to_string())
+                    .unwrap_or_else(|_| "cannot serialize to YAML".into());
+            }
+            OutputFormat::Toml => {
+                return json::write_toml(&summary)
+                    .unwrap_or_else(|_| "cannot serialize to TOML".into());
+            }
+            _ => return summary::format_summary(&summary),
+        };
+        json.unwrap_or_else(|_| "cannot parse to JSON".into())
+    }
+
+    /// Query the listed path's filesystem; failures only get a `--verbose` note
+    fn filesystem_usage(&self) -> Option<disk::FilesystemUsage> {
+        match disk::filesystem_usage(self.path) {
+            Ok(usage) => Some(usage),
+            Err(e) => {
+                if self.cli.verbose {
+                    diag::info(
+                        Code::DiskUsageUnavailable,
+                        format!(
+                            "no disk usage footer: cannot query the filesystem of '{}': {}",
+                            platform::display_path(self.path).display(),
+                            e
+                        ),
+                    );
+                }
+                None
+            }
+        }
+    }
 }
 
-/// Apply all filters to a file entry based on precomputed filter configuration
-fn passes_filters(f: &FileEntry, cfg: &FilterConfig) -> bool {
-    // Extension filter
-    if let Some(ref exts) = cfg.exts {
-        if !matches_extension(&f.name, exts) {
-            return false;
+/// Whether a streamed write succeeded; `false` when the reader went away (broken
+/// pipe), and any other failure exits with an error
+fn stream_write_ok(written: std::io::Result<()>) -> bool {
+    match written {
+        Ok(()) => true,
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => false,
+        Err(e) => {
+            diag::error(Code::OutputWriteFailed, None, e);
+            std::process::exit(1);
         }
     }
+}
+
+/// Write output to the `--out` file or stdout
+fn write_output(cli: &Cli, output: &str) {
+    if cli.output_file.is_some() {
+        write_out_file(cli, format!("{}\n", output).as_bytes());
+    } else {
+        println!("{}", output);
+    }
+}
 
-    // Name pattern filter
-    if let Some(ref name_pattern) = cfg.name_pattern {
-        if !matches_pattern(&f.name, name_pattern) {
-            return false;
+/// Write bytes as they are, without a final newline (`--print0`), to the `--out`
+/// file or stdout. A closed stdout (e.g. `| head -z`) is not an error.
+fn write_raw(cli: &Cli, bytes: &[u8]) {
+    if cli.output_file.is_some() {
+        return write_out_file(cli, bytes);
+    }
+    let mut stdout = std::io::stdout().lock();
+    match stdout.write_all(bytes).and_then(|()| stdout.flush()) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+            diag::error(Code::OutputWriteFailed, None, e);
+            std::process::exit(1);
         }
+        _ => {}
     }
+}
 
-    // Minimum size filter
-    if let Some(min) = cfg.min_size {
-        if f.len_bytes < min {
-            return false;
+/// Replace (or with `--append`, extend) the `--out` file with `contents`
+fn write_out_file(cli: &Cli, contents: &[u8]) {
+    let Some(file_path) = &cli.output_file else {
+        return;
+    };
+    let written = create_out_dirs(cli, file_path).and_then(|()| {
+        if cli.append {
+            output::file::append_locked(file_path, contents)
+        } else {
+            output::file::write_atomic(file_path, contents)
         }
+    });
+    if let Err(e) = written {
+        diag::error(Code::OutputWriteFailed, Some(file_path), e);
+        std::process::exit(1);
+    }
+}
+
+/// Create the missing parent directories of an output file with `--mkdir`
+fn create_out_dirs(cli: &Cli, path: &Path) -> Result<(), output::file::OutputError> {
+    if cli.mkdir {
+        output::file::create_parent_dirs(path)
+    } else {
+        Ok(())
     }
+}
+
+/// Re-list the directory whenever it changes, highlighting changes (`--watch`),
+/// until Ctrl-C exits with status 130.
+///
+/// With `--watch-log`, changes are appended as NDJSON events (to `--out` when given,
+/// one locked append per refresh) instead of redrawing the screen.
+fn run_watch(ctx: &ListingContext, theme: &Theme) -> ! {
+    let cli = ctx.cli;
+    let log_file = cli.output_file.as_deref().filter(|_| cli.watch_log);
+    let mut previous: Option<Snapshot> = None;
+    let watcher = match watch::Watcher::new(ctx.path, cli.tree || cli.recursive) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            diag::warning(
+                Code::WatchUnavailable,
+                Some(ctx.path),
+                format!(
+                    "cannot watch '{}' for changes ({}); re-listing every second instead",
+                    platform::display_path(ctx.path).display(),
+                    e
+                ),
+            );
+            None
+        }
+    };
 
-    // Maximum size filter
-    if let Some(max) = cfg.max_size {
-        if f.len_bytes > max {
-            return false;
+    loop {
+        match ctx.list() {
+            Ok((listing, omitted)) => {
+                // Each refresh clears the screen, so errors are only shown in the envelope
+                let files = listing.entries;
+                if cli.watch_log {
+                    if let Some(previous) = &previous {
+                        let now = chrono::Utc::now();
+                        let mut events = String::new();
+                        for change in previous.diff(&files) {
+                            match watch::event_line(&change, now, ctx.fields) {
+                                Ok(line) => {
+                                    events.push_str(&line);
+                                    events.push('\n');
+                                }
+                                Err(e) => diag::warning(
+                                    Code::EventEncodingFailed,
+                                    None,
+                                    format!("cannot encode watch event: {}", e),
+                                ),
+                            }
+                        }
+                        match log_file {
+                            Some(path) if !events.is_empty() => {
+                                let appended = create_out_dirs(cli, path).and_then(|()| {
+                                    output::file::append_locked(path, events.as_bytes())
+                                });
+                                if let Err(e) = appended {
+                                    diag::error(Code::OutputWriteFailed, Some(path), e);
+                                    std::process::exit(1);
+                                }
+                            }
+                            Some(_) => {}
+                            None => {
+                                print!("{}", events);
+                                let _ = std::io::stdout().flush();
+                            }
+                        }
+                    }
+                } else {
+                    let (rows, highlights) = match &previous {
+                        Some(previous) => watch::highlight_rows(previous, &files),
+                        None => (files.clone(), Vec::new()),
+                    };
+                    // Clear the screen and move the cursor home before redrawing
+                    print!("\x1b[2J\x1b[H");
+                    println!(
+                        "{}",
+                        ctx.render(&rows, omitted, &listing.errors, theme, highlights)
+                    );
+                }
+                previous = Some(Snapshot::new(&files));
+            }
+            Err(e) => report_unreadable(ctx.path, &e),
+        }
+
+        let changed = match &watcher {
+            Some(watcher) => watcher.wait_for_change(),
+            None => {
+                std::thread::sleep(watch::POLL_INTERVAL);
+                !interrupt::is_cancelled()
+            }
+        };
+        if !changed {
+            std::process::exit(interrupt::EXIT_INTERRUPTED);
         }
     }
+}
 
-    true
+/// Let the user pick entries and print their paths (`--select`).
+///
+/// Cancelling prints nothing and exits with status 130, like an interrupted command.
+fn run_select(ctx: &ListingContext, theme: &Theme) -> ! {
+    let (listing, _) = ctx.list().unwrap_or_else(|e| {
+        report_unreadable(ctx.path, &e);
+        std::process::exit(1);
+    });
+    report_list_errors(&listing.errors);
+    let files = listing.entries;
+
+    match select::run(&files, theme, ctx.styling.color) {
+        Ok(Some(chosen)) => {
+            let terminator = if ctx.cli.null { '\0' } else { '\n' };
+            let paths: String = chosen
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{}{}",
+                        platform::display_path(&entry.path).display(),
+                        terminator
+                    )
+                })
+                .collect();
+            print!("{}", paths);
+            let _ = std::io::stdout().flush();
+            std::process::exit(0);
+        }
+        Ok(None) => std::process::exit(interrupt::EXIT_INTERRUPTED),
+        Err(e) => {
+            diag::error(
+                Code::SelectUnavailable,
+                None,
+                format!("cannot run --select: {}", e),
+            );
+            std::process::exit(1);
+        }
+    }
 }
 
-/// Load files from the specified path (tree or flat)
-fn load_files(cli: &Cli, path: &Path, include_hidden: bool) -> std::io::Result<Vec<FileEntry>> {
-    if cli.tree {
-        get_files_recursive(path, include_hidden, cli.depth)
+/// Parse the command line, reporting usage errors as JSON under `--error-format json`.
+///
+/// Parsing failed, so the format is taken from the raw arguments.
+fn parse_cli() -> Cli {
+    let err = match Cli::try_parse() {
+        Ok(mut cli) => {
+            cli.resolve_paths();
+            return cli;
+        }
+        Err(err) => err,
+    };
+
+    let args: Vec<String> = std::env::args().collect();
+    let json_errors = args.iter().enumerate().any(|(i, arg)| {
+        arg == "--error-format=json"
+            || (arg == "--error-format" && args.get(i + 1).is_some_and(|next| next == "json"))
+    });
+    // --help and --version also arrive as "errors" and go to stdout
+    if !json_errors || !err.use_stderr() {
+        err.exit();
+    }
+
+    diag::set_format(cli::ErrorFormat::Json);
+    let rendered = err.to_string();
+    let message = rendered
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("error: ");
+    // Size values are parsed by clap but keep their own codes
+    let code = match err.get(clap::error::ContextKind::InvalidArg) {
+        Some(clap::error::ContextValue::String(arg)) if arg.starts_with("--min-size") => {
+            Code::InvalidMinSize
+        }
+        Some(clap::error::ContextValue::String(arg)) if arg.starts_with("--max-size") => {
+            Code::InvalidMaxSize
+        }
+        _ => Code::InvalidArgument,
+    };
+    diag::error(code, None, message);
+    std::process::exit(err.exit_code());
+}
+
+/// After gathering stopped early, mark the output that was written as partial and
+/// exit with status 130 (Ctrl-C) or 3 (`--max-entries`)
+fn exit_if_partial(max_entries: usize) {
+    if interrupt::is_cancelled() {
+        diag::warning(
+            Code::Interrupted,
+            None,
+            "(interrupted \u{2014} partial results)",
+        );
+        std::process::exit(interrupt::EXIT_INTERRUPTED);
+    }
+    if interrupt::was_truncated() {
+        diag::warning(
+            Code::EntryLimitReached,
+            None,
+            format!(
+                "stopped after {} entries (partial results); raise the cap with --max-entries N or pass --max-entries 0 for no limit",
+                max_entries
+            ),
+        );
+        std::process::exit(interrupt::EXIT_TRUNCATED);
+    }
+}
+
+/// Exit status of `--quiet`: like `grep -q`, a match wins over read errors
+fn quiet_status(matched: bool, failed: bool) -> i32 {
+    match (matched, failed) {
+        (true, _) => 0,
+        (false, false) => 1,
+        (false, true) => 2,
+    }
+}
+
+/// Report a listing root that cannot be read
+fn report_unreadable(path: &Path, e: &std::io::Error) {
+    let path_text = platform::display_path(path);
+    let message = if e.kind() == std::io::ErrorKind::NotFound {
+        format!("cannot access '{}': {}", path_text.display(), e)
     } else {
-        get_files(path, include_hidden)
+        format!("failed to read directory '{}': {}", path_text.display(), e)
+    };
+    diag::error(Code::PathUnreadable, Some(path), message);
+}
+
+/// Report what could not be read below a listed path; returns whether anything was
+fn report_list_errors(errors: &[ListError]) -> bool {
+    for error in errors {
+        diag::error(error.code, Some(&error.path), error);
     }
+    !errors.is_empty()
 }
 
 /// Handle theme management commands
@@ -226,18 +1560,30 @@ fn handle_theme_command(subcommand: &ThemeSubcommand) {
                 if *show {
                     match std::fs::read_to_string(&path) {
                         Ok(content) => println!("\n{}", content),
-                        Err(e) => eprintln!("Error reading config: {}", e),
+                        Err(e) => diag::error(
+                            Code::ThemeConfigFailed,
+                            Some(&path),
+                            format!("cannot read config: {}", e),
+                        ),
                     }
                 }
             }
-            Err(e) => eprintln!("Error creating config: {}", e),
+            Err(e) => diag::error(
+                Code::ThemeConfigFailed,
+                None,
+                format!("cannot create config: {}", e),
+            ),
         },
         ThemeSubcommand::Path => {
             if let Some(config_dir) = dirs::config_dir() {
                 let config_path = config_dir.join("bestls").join("config.toml");
                 println!("{}", config_path.display());
             } else {
-                eprintln!("Could not determine config directory");
+                diag::error(
+                    Code::ThemeConfigFailed,
+                    None,
+                    "could not determine config directory",
+                );
             }
         }
         ThemeSubcommand::Reset => {
@@ -246,13 +1592,21 @@ fn handle_theme_command(subcommand: &ThemeSubcommand) {
                 if config_path.exists() {
                     match std::fs::remove_file(&config_path) {
                         Ok(_) => println!("Theme reset to default (config file removed)"),
-                        Err(e) => eprintln!("Error removing config: {}", e),
+                        Err(e) => diag::error(
+                            Code::ThemeConfigFailed,
+                            Some(&config_path),
+                            format!("cannot remove config: {}", e),
+                        ),
                     }
                 } else {
                     println!("Theme already at default (no config file found)");
                 }
             } else {
-                eprintln!("Could not determine config directory");
+                diag::error(
+                    Code::ThemeConfigFailed,
+                    None,
+                    "could not determine config directory",
+                );
             }
         }
     }
@@ -268,7 +1622,11 @@ fn handle_theme_command(subcommand: &ThemeSubcommand) {
 /// 5. Sorts the entries according to the specified criteria
 /// 6. Outputs the results in the requested format (table or JSON)
 fn main() {
-    let cli: Cli = Cli::parse();
+    let cli: Cli = parse_cli();
+    diag::set_format(cli.error_format);
+    diag::set_color(stderr_color(&cli));
+    // If the handler cannot be installed, Ctrl-C simply kills the process as before
+    let _ = interrupt::install_handler();
 
     if let Some(command) = &cli.command {
         match command {
@@ -280,85 +1638,738 @@ fn main() {
                 handle_theme_command(subcommand);
                 return;
             }
+            Commands::Errors => {
+                println!("{}", diag::code_listing());
+                return;
+            }
+            Commands::Stale {
+                path,
+                older_than,
+                recursive,
+                all,
+                access_time,
+                json,
+            } => {
+                let path = path.clone().unwrap_or_else(|| PathBuf::from("."));
+                let opts = ListOptions {
+                    include_hidden: *all,
+                    max_entries: Some(cli.max_entries).filter(|max| *max > 0),
+                    ..Default::default()
+                };
+                let listing = if *recursive {
+                    get_files_recursive(&path, &opts, None)
+                } else {
+                    get_files(&path, &opts)
+                };
+                let listing = match listing {
+                    Ok(listing) => listing,
+                    Err(e) => {
+                        report_unreadable(&path, &e);
+                        std::process::exit(1);
+                    }
+                };
+                let failed = report_list_errors(&listing.errors);
+                let files = listing.entries;
+
+                let report = stale::find_stale(
+                    files,
+                    &path,
+                    *older_than,
+                    std::time::SystemTime::now(),
+                    *access_time,
+                );
+                let output = if *json {
+                    serde_json::to_string_pretty(&report)
+                        .unwrap_or_else(|_| "cannot parse to JSON".into())
+                } else {
+                    let styling = styling(&cli);
+                    stale::format_report(&report, styling.color, styling.ascii, &load_theme())
+                };
+                write_output(&cli, &output);
+                exit_if_partial(cli.max_entries);
+                if failed {
+                    std::process::exit(1);
+                }
+                return;
+            }
+            Commands::Top {
+                path,
+                by,
+                count,
+                all,
+                json,
+            } => {
+                let path = path.clone().unwrap_or_else(|| PathBuf::from("."));
+                let opts = ListOptions {
+                    include_hidden: *all,
+                    max_entries: Some(cli.max_entries).filter(|max| *max > 0),
+                    ..Default::default()
+                };
+                let listing = match get_files_recursive(&path, &opts, None) {
+                    Ok(listing) => listing,
+                    Err(e) => {
+                        report_unreadable(&path, &e);
+                        std::process::exit(1);
+                    }
+                };
+                let failed = report_list_errors(&listing.errors);
+
+                let report = top::find_top(listing.entries, &path, *by, *count);
+                let output = if *json {
+                    serde_json::to_string_pretty(&report)
+                        .unwrap_or_else(|_| "cannot parse to JSON".into())
+                } else {
+                    top::format_report(&report, styling(&cli).color, &load_theme())
+                };
+                write_output(&cli, &output);
+                exit_if_partial(cli.max_entries);
+                if failed {
+                    std::process::exit(1);
+                }
+                return;
+            }
         }
     }
 
     // Load theme for color output
     let theme = load_theme();
+    let styling = styling(&cli);
+    let names = name_order(&cli);
 
-    let path: PathBuf = cli
-        .path
-        .as_deref()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| PathBuf::from("."));
-    let include_hidden: bool = cli.all;
-
-    // Precompute filter configuration once
-    let filter_cfg = match FilterConfig::from_cli(&cli) {
-        Ok(cfg) => cfg,
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            std::process::exit(2);
+    let (paths, mut failed) = listing_paths(&cli);
+    if paths.len() > 1 {
+        if cli.watch {
+            ConfigError::SinglePathOnly("--watch").exit();
         }
-    };
+        if cli.select {
+            ConfigError::SinglePathOnly("--select").exit();
+        }
+    }
 
-    // Get files (tree or flat)
-    let get_result = load_files(&cli, &path, include_hidden);
+    let mut listings: Vec<Listing> = Vec::new();
+    if cli.directory {
+        let (listing, listed) = list_directories(&cli, &paths, styling, &names, &theme);
+        listings.extend(listing);
+        failed |= !listed;
+    }
+    for path in paths.iter().filter(|_| !cli.directory) {
+        // After Ctrl-C, the paths not started yet are left out of the partial output
+        if interrupt::is_cancelled() {
+            break;
+        }
+        let config = ListingConfig::new(&cli, path);
+        let ctx = config.context(&cli, path, styling, &names);
 
-    match get_result {
-        Ok(mut files) => {
-            // Apply all configured filters
-            files.retain(|f| passes_filters(f, &filter_cfg));
+        if cli.watch {
+            run_watch(&ctx, &theme);
+        }
+        if cli.select {
+            run_select(&ctx, &theme);
+        }
 
-            // Apply sorting
-            match cli.sort_by {
-                SortBy::Name => {
-                    files.sort_by(|a: &fsops::FileEntry, b: &fsops::FileEntry| a.name.cmp(&b.name))
+        if ctx.streams(paths.len() > 1) {
+            // Buffered listings of earlier paths come first
+            if !listings.is_empty() {
+                write_output(&cli, &combine_listings(&cli, &listings));
+                listings.clear();
+            }
+            match ctx.stream(&theme) {
+                Ok(errors) => failed |= report_list_errors(&errors),
+                Err(e) => {
+                    report_unreadable(path, &e);
+                    failed = true;
                 }
-                SortBy::Size => files.sort_by(|a: &fsops::FileEntry, b: &fsops::FileEntry| {
-                    a.len_bytes.cmp(&b.len_bytes)
-                }),
-                SortBy::Date => files.sort_by(|a: &fsops::FileEntry, b: &fsops::FileEntry| {
-                    a.modified.cmp(&b.modified)
-                }),
             }
+            continue;
+        }
 
-            // Generate output based on effective format, normalizing legacy flags to a single source of truth
-            let effective_format = cli.effective_format();
-            let output = match effective_format {
-                OutputFormat::Json => {
-                    serde_json::to_string(&files).unwrap_or_else(|_| "cannot parse to JSON".into())
-                }
-                OutputFormat::JsonPretty => serde_json::to_string_pretty(&files)
-                    .unwrap_or_else(|_| "cannot parse to JSON".into()),
-                OutputFormat::Table => {
-                    // Format table/compact output as string
-                    format_table(
-                        &files,
-                        cli.columns.clone(),
-                        cli.compact,
-                        !cli.no_color,
-                        Some(&theme),
-                    )
+        match ctx.list() {
+            Ok((listing, omitted)) => {
+                failed |= report_list_errors(&listing.errors);
+                let files = &listing.entries;
+                if files.is_empty() && config.filter.has_globs() && !cli.quiet {
+                    diag::note(Code::NoGlobMatches, Some(path), "0 entries matched --glob");
                 }
+                listings.push(ctx.listing(path, files, omitted, &listing.errors, &theme));
+            }
+            Err(e) => {
+                report_unreadable(path, &e);
+                failed = true;
+            }
+        }
+    }
+
+    if cli.quiet {
+        let matched = listings.iter().any(|listing| listing.entries > 0);
+        // A match answers the question even if the listing stopped early
+        if !matched {
+            exit_if_partial(cli.max_entries);
+        }
+        std::process::exit(quiet_status(matched, failed));
+    }
+    if cli.print0 {
+        write_raw(
+            &cli,
+            &listings
+                .iter()
+                .flat_map(|l| l.raw.clone())
+                .collect::<Vec<_>>(),
+        );
+    } else if !listings.is_empty() {
+        write_output(&cli, &combine_listings(&cli, &listings));
+    }
+    for listing in listings.iter().filter(|listing| listing.omitted > 0) {
+        diag::note(
+            Code::ListingLimited,
+            Some(&listing.path),
+            format!(
+                "\u{2026}and {} more entries (use --limit 0 for all)",
+                group_thousands(listing.omitted)
+            ),
+        );
+    }
+    exit_if_partial(cli.max_entries);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// An ascending sort key
+    fn key(by: SortBy) -> SortKey {
+        SortKey {
+            by,
+            descending: false,
+        }
+    }
+
+    #[test]
+    fn test_quiet_status() {
+        assert_eq!(quiet_status(true, false), 0);
+        assert_eq!(quiet_status(true, true), 0);
+        assert_eq!(quiet_status(false, false), 1);
+        assert_eq!(quiet_status(false, true), 2);
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        let paths = |input: &[u8], nul| parse_path_list(input, nul);
+        assert_eq!(
+            paths(b"a/node_modules\r\nb\n\na/node_modules\n", false),
+            [PathBuf::from("a/node_modules"), PathBuf::from("b")]
+        );
+        // NUL-separated names may contain newlines
+        assert_eq!(
+            paths(b"line\nbreak\0b\0", true),
+            [PathBuf::from("line\nbreak"), PathBuf::from("b")]
+        );
+        assert_eq!(
+            paths(b"d\nd\n./d\nd/\n././d\n./e/./f\ne/f\n", false),
+            [PathBuf::from("d"), PathBuf::from("./e/./f")]
+        );
+        assert!(paths(b"", false).is_empty());
+    }
+
+    #[test]
+    fn test_limit_entries() {
+        let entries = |names: &[&str]| -> Vec<FileEntry> {
+            names
+                .iter()
+                .map(|name| FileEntry {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        let mut files = entries(&["a", "b", "c"]);
+        assert_eq!(limit_entries(&mut files, 10), 0);
+        assert_eq!(names(&files), ["a", "b", "c"]);
+        assert_eq!(limit_entries(&mut files, 0), 0);
+        assert_eq!(files.len(), 3);
+
+        // The first entries of the sorted listing are kept
+        assert_eq!(limit_entries(&mut files, 1), 2);
+        assert_eq!(names(&files), ["a"]);
+
+        assert_eq!(group_thousands(4310), "4,310");
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_sort_by_size_reverse() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, len) in [("medium", 20), ("large", 300), ("small", 1)] {
+            std::fs::write(dir.path().join(name), "x".repeat(len)).unwrap();
+        }
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        let mut files = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Size)],
+            false,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["small", "medium", "large"]);
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Size)],
+            true,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["large", "medium", "small"]);
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Name)],
+            true,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["small", "medium", "large"]);
+
+        let cli = Cli::try_parse_from(["bestls", "-r", "--sort", "size"]).unwrap();
+        assert!(cli.reverse);
+    }
+
+    #[test]
+    fn test_sort_none_keeps_directory_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..200 {
+            std::fs::write(dir.path().join(format!("f{}", i)), "x".repeat(i)).unwrap();
+        }
+        let read_order: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+
+        let mut files = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&files), read_order);
+
+        let cli = Cli::try_parse_from(["bestls", "--sort", "none", "-r"]).unwrap();
+        sort_entries(&mut files, &cli.sort_by, cli.reverse, &NameOrder::default());
+        assert_eq!(names(&files), read_order);
+    }
+
+    #[test]
+    fn test_sort_by_multiple_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        // Ties at every level: two empty directories, two files per size
+        std::fs::create_dir(dir.path().join("beta")).unwrap();
+        std::fs::create_dir(dir.path().join("alpha")).unwrap();
+        for (name, len) in [
+            ("big1", 300),
+            ("mid", 20),
+            ("small_b", 1),
+            ("big0", 300),
+            ("small_a", 1),
+        ] {
+            std::fs::write(dir.path().join(name), "x".repeat(len)).unwrap();
+        }
+        let sorted = |args: &[&str], reverse: bool| {
+            let cli = Cli::try_parse_from([&["bestls"], args].concat()).unwrap();
+            let mut files = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries;
+            sort_entries(&mut files, &cli.sort_by, reverse, &NameOrder::default());
+            files.into_iter().map(|f| f.name).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(&["--sort", "type,-size,name"], false),
+            ["alpha", "beta", "big0", "big1", "mid", "small_a", "small_b"]
+        );
+        assert_eq!(
+            sorted(&["--sort=type,-size,-name"], false),
+            ["beta", "alpha", "big1", "big0", "mid", "small_b", "small_a"]
+        );
+        // Without a final name key, ties still fall back to name order
+        assert_eq!(
+            sorted(&["-s", "type,size"], false),
+            ["alpha", "beta", "small_a", "small_b", "mid", "big0", "big1"]
+        );
+        // --reverse flips the whole chain, per-key directions included
+        assert_eq!(
+            sorted(&["-s", "type,-size"], true),
+            ["small_b", "small_a", "mid", "big1", "big0", "beta", "alpha"]
+        );
+        // A leading - is a key, not a flag; directory sizes depend on the filesystem
+        assert_eq!(sorted(&["-s", "-size"], false)[5..], ["small_a", "small_b"]);
+
+        let err = Cli::try_parse_from(["bestls", "--sort", "type,bogus"]).unwrap_err();
+        assert!(err.to_string().contains("unknown sort key 'bogus'"));
+    }
+
+    #[test]
+    fn test_group_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, len) in [("medium", 20), ("large", 300), ("small", 1)] {
+            std::fs::write(dir.path().join(name), "x".repeat(len)).unwrap();
+        }
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("sub/inner"), "x".repeat(5000)).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("sub", dir.path().join("link")).unwrap();
+
+        let listed = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["bestls"], args].concat()).unwrap();
+            let mut files = get_files(dir.path(), &ListOptions::default())
+                .unwrap()
+                .entries;
+            sort_entries(&mut files, &cli.sort_by, cli.reverse, &name_order(&cli));
+            if cli.group_directories_first || cli.dirs_last {
+                group_directories(&mut files, cli.dirs_last, cli.dereference_dirs);
+            }
+            files.into_iter().map(|f| f.name).collect::<Vec<_>>()
+        };
+
+        let mut expected = vec!["empty", "sub", "large", "medium", "small"];
+        if cfg!(unix) {
+            expected.insert(3, "link");
+        }
+        assert_eq!(listed(&["--group-directories-first"]), expected);
+
+        // Directories come first even when --reverse puts larger entries first; the
+        // directories' own sizes depend on the filesystem
+        let by_size = listed(&["--sort", "size", "--reverse", "--group-directories-first"]);
+        let mut dirs = by_size[..2].to_vec();
+        dirs.sort();
+        assert_eq!(dirs, ["empty", "sub"]);
+        let mut expected = vec!["large", "medium", "small"];
+        if cfg!(unix) {
+            // The link's own size is the length of its target, "sub"
+            expected.insert(2, "link");
+        }
+        assert_eq!(by_size[2..], expected);
+
+        #[cfg(unix)]
+        assert_eq!(
+            listed(&["--group-directories-first", "--dereference-dirs"]),
+            ["empty", "link", "sub", "large", "medium", "small"]
+        );
+
+        // --dirs-last always keeps symlinks with the files
+        let mut expected = vec!["large", "medium", "small", "empty", "sub"];
+        if cfg!(unix) {
+            expected.insert(1, "link");
+        }
+        assert_eq!(listed(&["--dirs-last"]), expected);
+        assert!(
+            Cli::try_parse_from(["bestls", "--dirs-last", "--group-directories-first"]).is_err()
+        );
+        assert!(Cli::try_parse_from(["bestls", "--dirs-last", "--dereference-dirs"]).is_err());
+        assert!(Cli::try_parse_from(["bestls", "--dereference-dirs"]).is_err());
+    }
+
+    #[test]
+    fn test_sort_by_optional_times_puts_unknown_last() {
+        let at = |name: &str, created: Option<u64>| FileEntry {
+            name: name.to_string(),
+            created_time: created.map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(s)),
+            // Access times run the other way, so both keys are checked independently
+            accessed_time: created.map(|s| SystemTime::UNIX_EPOCH + Duration::from_secs(1000 - s)),
+            ..Default::default()
+        };
+        let mut files = vec![
+            at("unknown_b", None),
+            at("new", Some(300)),
+            at("unknown_a", None),
+            at("old", Some(100)),
+        ];
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Created)],
+            false,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["old", "new", "unknown_a", "unknown_b"]);
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Created)],
+            true,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["new", "old", "unknown_b", "unknown_a"]);
+
+        let cli = Cli::try_parse_from(["bestls", "--sort", "-created"]).unwrap();
+        sort_entries(&mut files, &cli.sort_by, false, &NameOrder::default());
+        assert_eq!(names(&files), ["new", "old", "unknown_a", "unknown_b"]);
+
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Accessed)],
+            false,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["new", "old", "unknown_a", "unknown_b"]);
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Accessed)],
+            true,
+            &NameOrder::default(),
+        );
+        assert_eq!(names(&files), ["old", "new", "unknown_b", "unknown_a"]);
+    }
+
+    #[test]
+    fn test_sort_collate() {
+        let sorted = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["bestls"], args].concat()).unwrap();
+            let mut files: Vec<FileEntry> = ["Zebra", "ähnlich", "apfel", "Äpfel", "Öl"]
+                .into_iter()
+                .map(|name| FileEntry {
+                    name: name.to_string(),
+                    ..Default::default()
+                })
+                .collect();
+            sort_entries(&mut files, &cli.sort_by, cli.reverse, &name_order(&cli));
+            files.into_iter().map(|f| f.name).collect::<Vec<_>>()
+        };
+
+        // Byte order stays the default
+        assert_eq!(sorted(&[]), ["Zebra", "apfel", "Äpfel", "Öl", "ähnlich"]);
+        if cfg!(feature = "collate") {
+            assert_eq!(
+                sorted(&["--collate", "--locale", "de-DE"]),
+                ["ähnlich", "apfel", "Äpfel", "Öl", "Zebra"]
+            );
+            assert_eq!(
+                sorted(&["--collate", "--locale", "de-DE", "-r"]),
+                ["Zebra", "Öl", "Äpfel", "apfel", "ähnlich"]
+            );
+        }
+        assert!(Cli::try_parse_from(["bestls", "--locale", "de-DE"]).is_err());
+    }
+
+    #[test]
+    fn test_hidden_first_and_last() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, len) in [
+            (".big", 300),
+            ("medium", 20),
+            (".tiny", 1),
+            ("large", 200),
+            (".mid", 30),
+            ("small", 2),
+        ] {
+            std::fs::write(dir.path().join(name), "x".repeat(len)).unwrap();
+        }
+        let listed = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["bestls", "-a"], args].concat()).unwrap();
+            let opts = ListOptions {
+                include_hidden: cli.all,
+                ..Default::default()
             };
+            let mut files = get_files(dir.path(), &opts).unwrap().entries;
+            sort_entries(&mut files, &cli.sort_by, cli.reverse, &name_order(&cli));
+            group_hidden(&mut files, cli.hidden_first);
+            files.into_iter().map(|f| f.name).collect::<Vec<_>>()
+        };
 
-            // Write output to file or stdout
-            if let Some(file_path) = &cli.output_file {
-                match File::create(file_path) {
-                    Ok(mut file) => {
-                        if let Err(e) = writeln!(file, "{}", output) {
-                            eprintln!("{}: {}", "Failed to write to file".red(), e);
-                        }
-                    }
-                    Err(e) => {
-                        eprintln!("{}: {}", "Failed to create output file".red(), e);
-                    }
-                }
-            } else {
-                println!("{}", output);
+        assert_eq!(
+            listed(&["--hidden-first", "--sort", "size"]),
+            [".tiny", ".mid", ".big", "small", "medium", "large"]
+        );
+        assert_eq!(
+            listed(&["--hidden-last", "--sort", "size", "-r"]),
+            ["large", "medium", "small", ".big", ".mid", ".tiny"]
+        );
+        assert!(Cli::try_parse_from(["bestls", "--hidden-first", "--hidden-last"]).is_err());
+    }
+
+    #[test]
+    fn test_sort_ignore_case() {
+        let ignore_case = NameOrder {
+            ignore_case: true,
+            ..Default::default()
+        };
+        let entry = |name: &str| {
+            let (extension, stem) = fsops::split_name(name, &FileType::File);
+            FileEntry {
+                name: name.to_string(),
+                extension,
+                stem,
+                ..Default::default()
             }
+        };
+        let mut files: Vec<FileEntry> = [
+            "README.md",
+            "cargo.toml",
+            "Ärger",
+            "apple",
+            "Apple",
+            "zebra",
+            "ärmel",
+        ]
+        .into_iter()
+        .map(entry)
+        .collect();
+        let names = |files: &[FileEntry]| files.iter().map(|f| f.name.clone()).collect::<Vec<_>>();
+
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Name)],
+            false,
+            &NameOrder::default(),
+        );
+        assert_eq!(
+            names(&files),
+            [
+                "Apple",
+                "README.md",
+                "apple",
+                "cargo.toml",
+                "zebra",
+                "Ärger",
+                "ärmel"
+            ]
+        );
+
+        // Case-only differences fall back to byte order: "Apple" before "apple"
+        sort_entries(&mut files, &[key(SortBy::Name)], false, &ignore_case);
+        assert_eq!(
+            names(&files),
+            [
+                "Apple",
+                "apple",
+                "cargo.toml",
+                "README.md",
+                "zebra",
+                "Ärger",
+                "ärmel"
+            ]
+        );
+
+        sort_entries(&mut files, &[key(SortBy::Name)], true, &ignore_case);
+        assert_eq!(
+            names(&files),
+            [
+                "ärmel",
+                "Ärger",
+                "zebra",
+                "README.md",
+                "cargo.toml",
+                "apple",
+                "Apple"
+            ]
+        );
+
+        // Extension groups stay in place; names within a group ignore case
+        sort_entries(&mut files, &[key(SortBy::Extension)], false, &ignore_case);
+        assert_eq!(
+            names(&files),
+            [
+                "Apple",
+                "apple",
+                "zebra",
+                "Ärger",
+                "ärmel",
+                "README.md",
+                "cargo.toml"
+            ]
+        );
+
+        let mut files: Vec<FileEntry> = ["File10", "file2", "FILE1"]
+            .into_iter()
+            .map(entry)
+            .collect();
+        sort_entries(&mut files, &[key(SortBy::Natural)], false, &ignore_case);
+        assert_eq!(names(&files), ["FILE1", "file2", "File10"]);
+
+        let cli = Cli::try_parse_from(["bestls", "--ignore-case"]).unwrap();
+        assert!(cli.ignore_case);
+    }
+
+    #[test]
+    fn test_sort_by_extension() {
+        let entry = |name: &str, e_type: FileType| {
+            let (extension, stem) = fsops::split_name(name, &e_type);
+            FileEntry {
+                name: name.to_string(),
+                e_type,
+                extension,
+                stem,
+                ..Default::default()
+            }
+        };
+        let mut files = vec![
+            entry("main.RS", FileType::File),
+            entry("archive.tar.gz", FileType::File),
+            entry("Cargo.toml", FileType::File),
+            entry("src", FileType::Directory),
+            entry(".gitignore", FileType::File),
+            entry("lib.rs", FileType::File),
+            entry("Makefile", FileType::File),
+            entry("site.d", FileType::Directory),
+            entry("notes.gz", FileType::File),
+        ];
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Extension)],
+            false,
+            &NameOrder::default(),
+        );
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "site.d",
+                "src",
+                ".gitignore",
+                "Makefile",
+                "archive.tar.gz",
+                "notes.gz",
+                "lib.rs",
+                "main.RS",
+                "Cargo.toml",
+            ]
+        );
+
+        let cli = Cli::try_parse_from(["bestls", "--sort", "ext"]).unwrap();
+        assert!(matches!(cli.sort_by[0].by, SortBy::Extension));
+    }
+
+    #[test]
+    fn test_sort_by_type() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "x").unwrap();
+        std::fs::write(dir.path().join("z.txt"), "x").unwrap();
+        std::fs::create_dir(dir.path().join("zdir")).unwrap();
+        std::fs::create_dir(dir.path().join("bdir")).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("a.txt", dir.path().join("link")).unwrap();
+
+        let mut files = get_files(dir.path(), &ListOptions::default())
+            .unwrap()
+            .entries;
+        sort_entries(
+            &mut files,
+            &[key(SortBy::Type)],
+            false,
+            &NameOrder::default(),
+        );
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        if cfg!(unix) {
+            assert_eq!(names, ["bdir", "zdir", "link", "a.txt", "z.txt"]);
+        } else {
+            assert_eq!(names, ["bdir", "zdir", "a.txt", "z.txt"]);
         }
-        Err(e) => eprintln!("{}: {}", "Failed to read directory".red(), e),
+
+        // JSON is rendered from the same sorted entries
+        let json = json::to_json(&files, None, None, None).unwrap();
+        assert!(json.find("\"zdir\"").unwrap() < json.find("\"a.txt\"").unwrap());
     }
 }
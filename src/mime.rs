@@ -0,0 +1,429 @@
+//! # MIME Type Module
+//!
+//! Guesses the content type of entries for `--mime`. Detection is only run when a
+//! listing asks for it, so the default listing never opens files:
+//!
+//! - **By name** (default): a table of well-known extensions, compound ones such as
+//!   `.tar.gz` first
+//! - **By content** (`--deep-mime`): names the table does not know are sniffed from
+//!   their first bytes, recognizing common magic numbers, `#!` scripts, and text
+//!
+//! Directories and special files get the `inode/*` types used by shared-mime-info
+//! (`inode/directory`, `inode/symlink`, `inode/fifo`, ...). Anything else that stays
+//! unknown is `application/octet-stream`.
+
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// How MIME types are detected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MimeDetection {
+    /// From the file name only
+    Name,
+    /// From the file name, then from the leading bytes for unknown names (`--deep-mime`)
+    Content,
+}
+
+/// Type of files whose name and content say nothing more specific
+pub const UNKNOWN: &str = "application/octet-stream";
+
+/// Number of leading bytes read when sniffing content
+const SNIFF_BYTES: usize = 512;
+
+/// Compound extensions, checked before the last extension alone
+const COMPOUND_EXTENSIONS: &[(&str, &str)] = &[
+    (".tar.gz", "application/x-compressed-tar"),
+    (".tar.bz2", "application/x-bzip-compressed-tar"),
+    (".tar.xz", "application/x-xz-compressed-tar"),
+    (".tar.zst", "application/x-zstd-compressed-tar"),
+];
+
+/// Types of single (lowercase) extensions
+const EXTENSIONS: &[(&str, &str)] = &[
+    // Text and source code
+    ("txt", "text/plain"),
+    ("log", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("ts", "text/x-typescript"),
+    ("rs", "text/rust"),
+    ("py", "text/x-python"),
+    ("rb", "text/x-ruby"),
+    ("pl", "text/x-perl"),
+    ("sh", "text/x-shellscript"),
+    ("bash", "text/x-shellscript"),
+    ("zsh", "text/x-shellscript"),
+    ("c", "text/x-csrc"),
+    ("h", "text/x-chdr"),
+    ("cpp", "text/x-c++src"),
+    ("hpp", "text/x-c++hdr"),
+    ("go", "text/x-go"),
+    ("java", "text/x-java"),
+    // Structured data
+    ("json", "application/json"),
+    ("toml", "application/toml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("xml", "application/xml"),
+    // Images
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+    ("bmp", "image/bmp"),
+    ("ico", "image/vnd.microsoft.icon"),
+    ("tif", "image/tiff"),
+    ("tiff", "image/tiff"),
+    // Audio and video
+    ("mp3", "audio/mpeg"),
+    ("wav", "audio/wav"),
+    ("flac", "audio/flac"),
+    ("ogg", "audio/ogg"),
+    ("mp4", "video/mp4"),
+    ("mkv", "video/x-matroska"),
+    ("webm", "video/webm"),
+    ("mov", "video/quicktime"),
+    ("avi", "video/x-msvideo"),
+    // Fonts
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    // Documents, archives, and binaries
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("jar", "application/java-archive"),
+    ("tar", "application/x-tar"),
+    ("tgz", "application/x-compressed-tar"),
+    ("gz", "application/gzip"),
+    ("bz2", "application/x-bzip2"),
+    ("xz", "application/x-xz"),
+    ("zst", "application/zstd"),
+    ("7z", "application/x-7z-compressed"),
+    ("wasm", "application/wasm"),
+    ("exe", "application/vnd.microsoft.portable-executable"),
+    ("dll", "application/vnd.microsoft.portable-executable"),
+];
+
+/// Magic numbers at the start of a file
+const MAGIC_NUMBERS: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\xfd7zXZ\x00", "application/x-xz"),
+    (b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"\x00asm", "application/wasm"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"MZ", "application/vnd.microsoft.portable-executable"),
+];
+
+/// Script types by interpreter name (`python3` matches `python`)
+const INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "text/x-shellscript"),
+    ("bash", "text/x-shellscript"),
+    ("dash", "text/x-shellscript"),
+    ("zsh", "text/x-shellscript"),
+    ("ksh", "text/x-shellscript"),
+    ("fish", "text/x-shellscript"),
+    ("python", "text/x-python"),
+    ("perl", "text/x-perl"),
+    ("ruby", "text/x-ruby"),
+    ("node", "text/javascript"),
+];
+
+/// Detect the MIME type of the entry `name` at `path`.
+///
+/// `file_type` is the entry's own (non-followed) type; only regular files are ever
+/// read, so FIFOs and devices cannot block a listing.
+pub fn detect(
+    name: &str,
+    path: &Path,
+    file_type: fs::FileType,
+    detection: MimeDetection,
+) -> String {
+    if file_type.is_dir() {
+        return "inode/directory".to_string();
+    }
+    if file_type.is_symlink() {
+        return "inode/symlink".to_string();
+    }
+    if !file_type.is_file() {
+        return special_file_type(file_type).to_string();
+    }
+    if let Some(mime) = from_name(name) {
+        return mime.to_string();
+    }
+    match detection {
+        MimeDetection::Content => read_head(path)
+            .map(|head| from_content(&head))
+            .unwrap_or(UNKNOWN)
+            .to_string(),
+        MimeDetection::Name => UNKNOWN.to_string(),
+    }
+}
+
+/// Type of a file from its extension, if the table knows it
+pub fn from_name(name: &str) -> Option<&'static str> {
+    let lower = name.to_lowercase();
+    if let Some((_, mime)) = COMPOUND_EXTENSIONS
+        .iter()
+        .find(|(suffix, _)| lower.len() > suffix.len() && lower.ends_with(suffix))
+    {
+        return Some(mime);
+    }
+    let (stem, extension) = lower.rsplit_once('.')?;
+    if stem.is_empty() {
+        // A dotfile such as `.bashrc` has no extension
+        return None;
+    }
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, mime)| *mime)
+}
+
+/// Type of a file from its leading bytes
+fn from_content(head: &[u8]) -> &'static str {
+    if head.is_empty() {
+        return "application/x-zerosize";
+    }
+    if let Some((_, mime)) = MAGIC_NUMBERS
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+    {
+        return mime;
+    }
+    if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        return "image/webp";
+    }
+    if head.contains(&0) {
+        return UNKNOWN;
+    }
+    // A multi-byte character may be cut at the read limit
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => text,
+        Err(e) if e.error_len().is_none() => {
+            std::str::from_utf8(&head[..e.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return UNKNOWN,
+    };
+    text.strip_prefix("#!")
+        .and_then(|shebang| script_type(shebang.lines().next().unwrap_or_default()))
+        .unwrap_or("text/plain")
+}
+
+/// Script type from a `#!` line, looking through `env` (`/usr/bin/env -S python3 -u`)
+fn script_type(shebang: &str) -> Option<&'static str> {
+    let mut words = shebang.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
+    }
+    INTERPRETERS
+        .iter()
+        .find(|(interpreter, _)| {
+            program
+                .strip_prefix(interpreter)
+                .is_some_and(|version| version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        })
+        .map(|(_, mime)| *mime)
+}
+
+/// `inode/*` type of a FIFO, socket, or device
+fn special_file_type(file_type: fs::FileType) -> &'static str {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return "inode/fifo";
+        }
+        if file_type.is_socket() {
+            return "inode/socket";
+        }
+        if file_type.is_block_device() {
+            return "inode/blockdevice";
+        }
+        if file_type.is_char_device() {
+            return "inode/chardevice";
+        }
+    }
+    let _ = file_type;
+    UNKNOWN
+}
+
+/// Read up to [`SNIFF_BYTES`] from the start of a file
+fn read_head(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    fs::File::open(crate::platform::long_path(path))
+        .ok()?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(head)
+}
+
+/// A `--mime` pattern: an exact type (`text/plain`) or a whole top-level type (`image/*`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MimePattern {
+    kind: String,
+    /// `None` for `type/*`
+    subtype: Option<String>,
+}
+
+impl MimePattern {
+    /// Whether `mime` matches, ignoring case
+    pub fn matches(&self, mime: &str) -> bool {
+        let Some((kind, subtype)) = mime.split_once('/') else {
+            return false;
+        };
+        kind.eq_ignore_ascii_case(&self.kind)
+            && self
+                .subtype
+                .as_deref()
+                .is_none_or(|wanted| subtype.eq_ignore_ascii_case(wanted))
+    }
+}
+
+impl fmt::Display for MimePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            self.kind,
+            self.subtype.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+/// Parse a `--mime` pattern (`text/plain` or `image/*`).
+///
+/// # Errors
+///
+/// Returns a message for patterns without exactly one `/`, with an empty part, or
+/// with a wildcard anywhere but the whole subtype.
+pub fn parse_mime_pattern(s: &str) -> Result<MimePattern, String> {
+    let invalid = || {
+        format!(
+            "invalid MIME type '{}': expected TYPE/SUBTYPE or TYPE/*, like text/plain or image/*",
+            s
+        )
+    };
+    let (kind, subtype) = s.trim().split_once('/').ok_or_else(invalid)?;
+    if kind.is_empty() || subtype.is_empty() || subtype.contains('/') || kind.contains('*') {
+        return Err(invalid());
+    }
+    let subtype = match subtype {
+        "*" => None,
+        _ if subtype.contains('*') => return Err(invalid()),
+        _ => Some(subtype.to_lowercase()),
+    };
+    Ok(MimePattern {
+        kind: kind.to_lowercase(),
+        subtype,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(
+            from_name("backup.tar.gz"),
+            Some("application/x-compressed-tar")
+        );
+        assert_eq!(
+            from_name("BACKUP.TAR.GZ"),
+            Some("application/x-compressed-tar")
+        );
+        assert_eq!(from_name("notes.gz"), Some("application/gzip"));
+        assert_eq!(from_name("photo.JPG"), Some("image/jpeg"));
+        assert_eq!(from_name("main.rs"), Some("text/rust"));
+        assert_eq!(from_name(".bashrc"), None);
+        assert_eq!(from_name("Makefile"), None);
+        assert_eq!(from_name("data.unknownext"), None);
+    }
+
+    #[test]
+    fn test_from_content() {
+        assert_eq!(from_content(b"#!/bin/sh\necho hi\n"), "text/x-shellscript");
+        assert_eq!(from_content(b"#!/usr/bin/env python3\n"), "text/x-python");
+        assert_eq!(
+            from_content(b"#!/usr/bin/env -S node --flag\n"),
+            "text/javascript"
+        );
+        assert_eq!(from_content(b"#!/opt/custom-tool\n"), "text/plain");
+        assert_eq!(from_content(b"just some words\n"), "text/plain");
+        assert_eq!(from_content(b"\x89PNG\r\n\x1a\n\0\0"), "image/png");
+        assert_eq!(from_content(b"\x7fELF\x02\x01"), "application/x-executable");
+        assert_eq!(from_content(b""), "application/x-zerosize");
+        // Unknown binaries stay unknown
+        assert_eq!(from_content(b"\x01\x02\x00\x03\xfe"), UNKNOWN);
+        assert_eq!(from_content(b"\xff\xfe\xfd"), UNKNOWN);
+    }
+
+    #[test]
+    fn test_detect_reads_only_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("deploy");
+        fs::write(&script, "#!/bin/bash\nset -e\n").unwrap();
+        let blob = dir.path().join("blob");
+        fs::write(&blob, [0u8, 159, 146, 150]).unwrap();
+        let archive = dir.path().join("site.tar.gz");
+        fs::write(&archive, "not really gzip").unwrap();
+
+        let detect_at = |path: &Path, detection| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            let file_type = fs::symlink_metadata(path).unwrap().file_type();
+            detect(name, path, file_type, detection)
+        };
+        assert_eq!(detect_at(&script, MimeDetection::Name), UNKNOWN);
+        assert_eq!(
+            detect_at(&script, MimeDetection::Content),
+            "text/x-shellscript"
+        );
+        assert_eq!(detect_at(&blob, MimeDetection::Content), UNKNOWN);
+        // A known name wins over the content
+        assert_eq!(
+            detect_at(&archive, MimeDetection::Content),
+            "application/x-compressed-tar"
+        );
+        assert_eq!(
+            detect_at(dir.path(), MimeDetection::Name),
+            "inode/directory"
+        );
+    }
+
+    #[test]
+    fn test_mime_pattern() {
+        let image = parse_mime_pattern("image/*").unwrap();
+        assert!(image.matches("image/png"));
+        assert!(image.matches("IMAGE/svg+xml"));
+        assert!(!image.matches("text/plain"));
+
+        let plain = parse_mime_pattern("Text/Plain").unwrap();
+        assert_eq!(plain.to_string(), "text/plain");
+        assert!(plain.matches("text/plain"));
+        assert!(!plain.matches("text/plainer"));
+
+        for invalid in ["text", "/plain", "text/", "*/*", "image/p*", "a/b/c"] {
+            assert!(parse_mime_pattern(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
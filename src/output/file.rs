@@ -0,0 +1,239 @@
+//! # Output File Writes
+//!
+//! This module writes `--out` files safely.
+//!
+//! ## Replacing (default)
+//!
+//! The output is written to a temporary file in the target's directory, synced, and
+//! renamed over the target. An interrupted run (Ctrl-C, full disk) leaves the
+//! previous file untouched instead of a truncated one, and readers never see a
+//! partially written file. An existing target keeps its permissions.
+//!
+//! ## Appending (`--append`)
+//!
+//! The output is appended to the target while holding an exclusive advisory lock, so
+//! concurrent writers of line-oriented output (NDJSON) never interleave their lines.
+//! There is no rename in this mode; tailing readers keep following the same file.
+//!
+//! ## Missing Directories (`--mkdir`)
+//!
+//! A target in a directory that does not exist is an error unless `--mkdir` asks
+//! for the missing parents to be created first ([`create_parent_dirs`]).
+
+use crate::platform::lock_exclusive;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Error type for output file writes
+#[derive(Debug)]
+pub enum OutputError {
+    /// The target path is an existing directory
+    IsDirectory(PathBuf),
+    /// A filesystem step failed
+    Io {
+        action: &'static str,
+        path: PathBuf,
+        source: io::Error,
+    },
+}
+
+impl fmt::Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::IsDirectory(path) => {
+                write!(f, "'{}' is a directory, not a file", path.display())
+            }
+            OutputError::Io {
+                action,
+                path,
+                source,
+            } => write!(f, "cannot {} '{}': {}", action, path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for OutputError {}
+
+fn io_error(action: &'static str, path: &Path) -> impl FnOnce(io::Error) -> OutputError {
+    let path = path.to_path_buf();
+    move |source| OutputError::Io {
+        action,
+        path,
+        source,
+    }
+}
+
+/// Refuse directories and resolve a symlinked target, so the link itself survives
+fn resolve_target(path: &Path) -> Result<PathBuf, OutputError> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Err(OutputError::IsDirectory(path.to_path_buf())),
+        Ok(_) => fs::canonicalize(path).map_err(io_error("resolve", path)),
+        Err(_) => Ok(path.to_path_buf()),
+    }
+}
+
+/// Atomically replace `path` with `contents`.
+///
+/// # Errors
+///
+/// Returns an error if `path` is a directory or any step (creating the temporary
+/// file, writing, syncing, renaming) fails; the target is untouched in that case.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), OutputError> {
+    let target = resolve_target(path)?;
+    let dir = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let prefix = format!(".{}.", file_name);
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(&prefix).suffix(".tmp");
+    #[cfg(unix)]
+    {
+        // Same default mode as File::create (the umask still applies)
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+    let mut temp = builder
+        .tempfile_in(dir)
+        .map_err(io_error("create a temporary file in", dir))?;
+
+    temp.write_all(contents)
+        .and_then(|()| temp.flush())
+        .and_then(|()| temp.as_file().sync_all())
+        .map_err(io_error("write", temp.path()))?;
+    if let Ok(metadata) = fs::metadata(&target) {
+        fs::set_permissions(temp.path(), metadata.permissions())
+            .map_err(io_error("copy permissions to", temp.path()))?;
+    }
+
+    // On failure the temporary file is dropped, which deletes it
+    temp.persist(&target)
+        .map_err(|e| io_error("replace", &target)(e.error))?;
+    Ok(())
+}
+
+/// Create the missing parent directories of `path`.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be created.
+pub fn create_parent_dirs(path: &Path) -> Result<(), OutputError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(parent).map_err(io_error("create directory", parent))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Append `contents` to `path` (creating it) under an exclusive advisory lock.
+///
+/// # Errors
+///
+/// Returns an error if `path` is a directory or it cannot be opened, locked, or written.
+pub fn append_locked(path: &Path, contents: &[u8]) -> Result<(), OutputError> {
+    if path.is_dir() {
+        return Err(OutputError::IsDirectory(path.to_path_buf()));
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io_error("open", path))?;
+    lock_exclusive(&file).map_err(io_error("lock", path))?;
+    // The lock is released when the file is closed
+    file.write_all(contents)
+        .and_then(|()| file.flush())
+        .map_err(io_error("append to", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leftover_temp_files(dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.to_string_lossy().ends_with(".tmp"))
+            .collect()
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("listing.json");
+        fs::write(&target, "old contents that are longer").unwrap();
+
+        write_atomic(&target, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        assert!(leftover_temp_files(dir.path()).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_keeps_permissions_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("listing.json");
+        fs::write(&target, "old").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        let link = dir.path().join("latest.json");
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        write_atomic(&link, b"new").unwrap();
+        assert!(fs::symlink_metadata(&link).unwrap().is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new");
+        let mode = fs::metadata(&target).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[test]
+    fn test_create_parent_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("reports/daily/listing.csv");
+        assert!(write_atomic(&target, b"x").is_err());
+
+        create_parent_dirs(&target).unwrap();
+        write_atomic(&target, b"x").unwrap();
+        assert_eq!(fs::read_to_string(&target).unwrap(), "x");
+        // Existing directories and bare file names are fine
+        create_parent_dirs(&target).unwrap();
+        create_parent_dirs(Path::new("listing.csv")).unwrap();
+    }
+
+    #[test]
+    fn test_refuses_directory_target() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            write_atomic(dir.path(), b"x"),
+            Err(OutputError::IsDirectory(_))
+        ));
+        assert!(matches!(
+            append_locked(dir.path(), b"x"),
+            Err(OutputError::IsDirectory(_))
+        ));
+    }
+
+    #[test]
+    fn test_append_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("events.ndjson");
+
+        append_locked(&target, b"{\"a\":1}\n").unwrap();
+        append_locked(&target, b"{\"a\":2}\n").unwrap();
+        assert_eq!(
+            fs::read_to_string(&target).unwrap(),
+            "{\"a\":1}\n{\"a\":2}\n"
+        );
+    }
+}
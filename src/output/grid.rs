@@ -0,0 +1,131 @@
+//! # Grid Layout
+//!
+//! `--grid` lays names out in columns like plain `ls` on a terminal:
+//!
+//! ```text
+//! Cargo.lock  README.md  docs  target
+//! Cargo.toml  build.rs   src   tests
+//! ```
+//!
+//! Names run down each column, then across (column-major), and the layout uses the
+//! fewest rows whose columns, two spaces apart, fit the width. Widths are measured
+//! in terminal cells on the uncolored names, so wide characters take two cells and
+//! color escapes take none. A name wider than the terminal gets a column of its own.
+
+use crate::color::{get_file_color, Theme};
+use crate::fsops::FileEntry;
+use unicode_width::UnicodeWidthStr;
+
+/// Spaces between two columns
+const GAP: usize = 2;
+
+/// Render entry names in columns fitting `width` terminal cells, or one per line
+/// without a width. No trailing newline; lines carry no trailing spaces.
+pub fn format_grid(
+    entries: &[FileEntry],
+    width: Option<usize>,
+    use_color: bool,
+    theme: &Theme,
+) -> String {
+    let widths: Vec<usize> = entries.iter().map(|e| e.name.width()).collect();
+    let rows = width.map_or(entries.len(), |width| row_count(&widths, width));
+    let paint = |entry: &FileEntry| {
+        if use_color {
+            get_file_color(entry, theme)
+                .to_tabled_color()
+                .colorize(&entry.name)
+        } else {
+            entry.name.clone()
+        }
+    };
+
+    let columns = column_widths(&widths, rows.max(1));
+    let mut lines = Vec::with_capacity(rows);
+    for row in 0..rows {
+        let mut line = String::new();
+        let cells: Vec<usize> = (row..entries.len()).step_by(rows).collect();
+        for (column, &i) in cells.iter().enumerate() {
+            line.push_str(&paint(&entries[i]));
+            if column + 1 < cells.len() {
+                line.push_str(&" ".repeat(columns[column] - widths[i] + GAP));
+            }
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// The fewest rows whose column-major layout fits `width` cells
+fn row_count(widths: &[usize], width: usize) -> usize {
+    (1..widths.len())
+        .find(|&rows| {
+            let columns = column_widths(widths, rows);
+            columns.iter().sum::<usize>() + GAP * (columns.len() - 1) <= width
+        })
+        .unwrap_or(widths.len())
+}
+
+/// Width of each column when `widths` fill `rows` rows column by column
+fn column_widths(widths: &[usize], rows: usize) -> Vec<usize> {
+    widths
+        .chunks(rows)
+        .map(|column| column.iter().copied().max().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(names: &[&str]) -> Vec<FileEntry> {
+        names
+            .iter()
+            .map(|name| FileEntry {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn grid(names: &[&str], width: usize) -> String {
+        format_grid(&entries(names), Some(width), false, &Theme::default())
+    }
+
+    #[test]
+    fn test_column_major_layout() {
+        let names = ["a", "bbb", "cc", "dddd", "e"];
+        assert_eq!(grid(&names, 80), "a  bbb  cc  dddd  e");
+        // Three columns of "a bbb", "cc dddd", "e" need 3 + 2 + 4 + 2 + 1 cells
+        assert_eq!(grid(&names, 12), "a    cc    e\nbbb  dddd");
+        assert_eq!(grid(&names, 4), "a\nbbb\ncc\ndddd\ne");
+    }
+
+    #[test]
+    fn test_wide_characters_take_two_cells() {
+        // "日本" is four cells wide though it is two characters
+        assert_eq!(grid(&["日本", "a", "b", "c"], 9), "日本  b\na     c");
+    }
+
+    #[test]
+    fn test_name_wider_than_terminal() {
+        assert_eq!(grid(&["a-very-long-name", "b"], 5), "a-very-long-name\nb");
+    }
+
+    #[test]
+    fn test_colors_do_not_count() {
+        let colored = format_grid(&entries(&["a", "b"]), Some(5), true, &Theme::default());
+        let lines: Vec<&str> = colored.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(colored.contains("\x1b["));
+    }
+
+    #[test]
+    fn test_without_width_one_per_line() {
+        let names = entries(&["a", "b", "c"]);
+        assert_eq!(
+            format_grid(&names, None, false, &Theme::default()),
+            "a\nb\nc"
+        );
+        assert_eq!(format_grid(&[], Some(80), false, &Theme::default()), "");
+    }
+}
@@ -0,0 +1,148 @@
+//! # HTML Tables
+//!
+//! `--format html` renders a listing as a standalone `<table>` for static reports:
+//!
+//! ```text
+//! <table class="bestls">
+//! <thead>
+//! <tr><th>Name</th><th>Type</th><th>Size</th>...</tr>
+//! </thead>
+//! <tbody>
+//! <tr class="dir"><td>src</td><td>Directory</td><td data-bytes="128">128 B</td>...</tr>
+//! </tbody>
+//! </table>
+//! ```
+//!
+//! Rows carry a `file`, `dir`, or `symlink` class so a stylesheet can color them like
+//! the terminal theme, and size cells keep the exact byte count in `data-bytes` for
+//! sorting scripts. No styles are embedded. Every cell is HTML-escaped.
+
+use crate::fsops::{FileEntry, FileType};
+
+/// Renders one cell of an entry
+type Cell = fn(&FileEntry) -> String;
+
+/// Table columns in table order: header and cell of each
+const COLUMNS: [(&str, Cell); 7] = [
+    ("Name", |e| e.name.clone()),
+    ("Type", |e| e.e_type.to_string()),
+    ("Size", |e| e.human_size.clone()),
+    ("Modified", |e| e.modified.clone()),
+    ("Permissions", |e| e.permissions.clone()),
+    ("Owner", |e| e.owner.clone()),
+    ("Group", |e| e.group.clone()),
+];
+
+/// Render entries as a `<table>` with a header row and one row per entry, without a
+/// trailing newline.
+pub fn format_html(entries: &[FileEntry]) -> String {
+    let mut html = String::from("<table class=\"bestls\">\n<thead>\n<tr>");
+    for (header, _) in COLUMNS {
+        html.push_str(&format!("<th>{}</th>", header));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for entry in entries {
+        html.push_str(&format!("<tr class=\"{}\">", row_class(&entry.e_type)));
+        for (header, cell) in COLUMNS {
+            let text = escape(&cell(entry));
+            if header == "Size" {
+                html.push_str(&format!(
+                    "<td data-bytes=\"{}\">{}</td>",
+                    entry.len_bytes, text
+                ));
+            } else {
+                html.push_str(&format!("<td>{}</td>", text));
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>");
+    html
+}
+
+/// Row class of an entry type
+fn row_class(e_type: &FileType) -> &'static str {
+    match e_type {
+        FileType::File => "file",
+        FileType::Directory => "dir",
+        FileType::Symlink => "symlink",
+    }
+}
+
+/// Escape text for use in element content and double- or single-quoted attributes
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, e_type: FileType) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            e_type,
+            len_bytes: 42,
+            human_size: "42 B".to_string(),
+            modified: "Thu 22 Aug 2024 17:44:23".to_string(),
+            permissions: "rw-r--r--".to_string(),
+            owner: "user".to_string(),
+            group: "staff".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<b>Tom & "Jerry's"</b>"#),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&#39;s&quot;&lt;/b&gt;"
+        );
+        assert_eq!(escape("plain.txt"), "plain.txt");
+    }
+
+    #[test]
+    fn test_format_html() {
+        let entries = [
+            entry("a<b>&\"c\".txt", FileType::File),
+            entry("src", FileType::Directory),
+            entry("link", FileType::Symlink),
+        ];
+        let html = format_html(&entries);
+        let rows: Vec<&str> = html.lines().collect();
+        assert_eq!(rows[0], "<table class=\"bestls\">");
+        assert_eq!(
+            rows[2],
+            "<tr><th>Name</th><th>Type</th><th>Size</th><th>Modified</th>\
+             <th>Permissions</th><th>Owner</th><th>Group</th></tr>"
+        );
+        assert_eq!(
+            rows[5],
+            "<tr class=\"file\"><td>a&lt;b&gt;&amp;&quot;c&quot;.txt</td><td>File</td>\
+             <td data-bytes=\"42\">42 B</td><td>Thu 22 Aug 2024 17:44:23</td>\
+             <td>rw-r--r--</td><td>user</td><td>staff</td></tr>"
+        );
+        assert!(rows[6].starts_with("<tr class=\"dir\"><td>src</td>"));
+        assert!(rows[7].starts_with("<tr class=\"symlink\"><td>link</td>"));
+        assert_eq!(rows[8..], ["</tbody>", "</table>"]);
+    }
+
+    #[test]
+    fn test_empty_table_keeps_header() {
+        let html = format_html(&[]);
+        assert!(html.contains("<thead>"));
+        assert!(html.ends_with("<tbody>\n</tbody>\n</table>"));
+    }
+}
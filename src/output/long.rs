@@ -0,0 +1,191 @@
+//! # Long Listings
+//!
+//! `-l/--long` prints the classic `ls -l` columns instead of a boxed table:
+//!
+//! ```text
+//! total 12
+//! -rw-r--r--  1 user staff 1.1 KB Thu 22 Aug 2024 17:44:23 Cargo.toml
+//! drwxr-xr-x 12 user staff  128 B Thu 22 Aug 2024 17:44:23 src
+//! ```
+//!
+//! Columns are separated by one space and padded to the widest value of the listing:
+//! link counts and sizes are right-aligned, owners, groups and dates left-aligned.
+//! Widths are terminal columns, so wide characters in localized dates or owner names
+//! keep the columns aligned.
+//! Only the name is colored. The `total` line adds up the allocated blocks in
+//! 1 KiB units like GNU ls; where the platform does not report blocks (Windows,
+//! archive members) each entry counts its size rounded up to whole KiB.
+
+use crate::color::{get_file_color, Theme};
+use crate::fsops::{FileEntry, FileType};
+use unicode_width::UnicodeWidthStr;
+
+/// Render entries as a `total` line followed by one aligned line per entry, without a
+/// trailing newline.
+///
+/// `total_line` is false for `--no-header`, which leaves just the entry lines.
+pub fn format_long(
+    entries: &[FileEntry],
+    total_line: bool,
+    use_color: bool,
+    theme: &Theme,
+) -> String {
+    let links: Vec<String> = entries
+        .iter()
+        .map(|e| e.nlink.map_or_else(|| "-".to_string(), |n| n.to_string()))
+        .collect();
+    let links_width = width(links.iter());
+    let owner_width = width(entries.iter().map(|e| &e.owner));
+    let group_width = width(entries.iter().map(|e| &e.group));
+    let size_width = width(entries.iter().map(|e| &e.human_size));
+    let date_width = width(entries.iter().map(|e| &e.modified));
+
+    let mut lines = Vec::with_capacity(entries.len() + 1);
+    if total_line {
+        lines.push(format!("total {}", total_kib(entries)));
+    }
+    for (entry, links) in entries.iter().zip(&links) {
+        let name = if use_color {
+            get_file_color(entry, theme)
+                .to_tabled_color()
+                .colorize(&entry.name)
+        } else {
+            entry.name.clone()
+        };
+        lines.push(format!(
+            "{}{} {:>links_width$} {} {} {:>size_width$} {} {}",
+            type_char(&entry.e_type),
+            entry.permissions,
+            links,
+            pad(&entry.owner, owner_width),
+            pad(&entry.group, group_width),
+            entry.human_size,
+            pad(&entry.modified, date_width),
+            name,
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Width of the widest value of a column, in terminal columns
+fn width<'a>(values: impl Iterator<Item = &'a String>) -> usize {
+    values.map(|v| v.width()).max().unwrap_or(0)
+}
+
+/// `value` left-aligned in `width` terminal columns (`{:<}` would count characters)
+fn pad(value: &str, width: usize) -> String {
+    format!(
+        "{}{}",
+        value,
+        " ".repeat(width.saturating_sub(value.width()))
+    )
+}
+
+/// Size of the listing for the `total` line, in 1 KiB units
+fn total_kib(entries: &[FileEntry]) -> u64 {
+    let mut blocks = 0u64;
+    let mut kib = 0u64;
+    for entry in entries {
+        match entry.blocks {
+            Some(b) => blocks = blocks.saturating_add(b),
+            None => kib = kib.saturating_add(entry.len_bytes.div_ceil(1024)),
+        }
+    }
+    // Blocks are 512 bytes; GNU ls rounds the sum up
+    kib.saturating_add(blocks.div_ceil(2))
+}
+
+/// First character of the mode string
+fn type_char(e_type: &FileType) -> char {
+    match e_type {
+        FileType::File => '-',
+        FileType::Directory => 'd',
+        FileType::Symlink => 'l',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, human_size: &str, nlink: Option<u64>, blocks: Option<u64>) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            human_size: human_size.to_string(),
+            modified: "Thu 22 Aug 2024 17:44:23".to_string(),
+            permissions: "rw-r--r--".to_string(),
+            owner: "user".to_string(),
+            group: "staff".to_string(),
+            nlink,
+            blocks,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_total_rounds_blocks_up() {
+        let entries = [
+            entry("a", "1 B", Some(1), Some(3)),
+            entry("b", "1 B", Some(1), Some(2)),
+        ];
+        assert_eq!(total_kib(&entries), 3);
+        assert!(format_long(&entries, true, false, &Theme::default()).starts_with("total 3\n"));
+    }
+
+    #[test]
+    fn test_total_without_blocks_uses_sizes() {
+        let mut member = entry("a", "2.0 KB", None, None);
+        member.len_bytes = 1025;
+        assert_eq!(total_kib(&[member]), 2);
+    }
+
+    #[test]
+    fn test_missing_link_count() {
+        let long = format_long(
+            &[entry("a", "1 B", None, None)],
+            true,
+            false,
+            &Theme::default(),
+        );
+        assert_eq!(
+            long,
+            "total 0\n-rw-r--r-- - user staff 1 B Thu 22 Aug 2024 17:44:23 a"
+        );
+    }
+
+    #[test]
+    fn test_empty_listing() {
+        assert_eq!(format_long(&[], true, false, &Theme::default()), "total 0");
+        assert_eq!(format_long(&[], false, false, &Theme::default()), "");
+    }
+
+    #[test]
+    fn test_without_total_line() {
+        let long = format_long(
+            &[entry("a", "1 B", None, None)],
+            false,
+            false,
+            &Theme::default(),
+        );
+        assert_eq!(
+            long,
+            "-rw-r--r-- - user staff 1 B Thu 22 Aug 2024 17:44:23 a"
+        );
+    }
+
+    #[test]
+    fn test_wide_values_stay_aligned() {
+        let mut wide = entry("a", "1 B", Some(1), None);
+        wide.owner = "田中太郎".to_string();
+        wide.modified = "木 22 8月 2024 17:44:23".to_string();
+        let narrow = entry("b", "1 B", Some(1), None);
+        let long = format_long(&[wide, narrow], false, false, &Theme::default());
+        let widths: Vec<usize> = long.lines().map(|line| line.width()).collect();
+        assert_eq!(widths[0], widths[1], "{}", long);
+        assert!(
+            long.ends_with(" user     staff 1 B Thu 22 Aug 2024 17:44:23 b"),
+            "{}",
+            long
+        );
+    }
+}
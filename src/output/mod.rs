@@ -0,0 +1,982 @@
+//! # Output Module
+//!
+//! Turns a finished listing into text. Every `--format` has an [`OutputFormatter`]
+//! implementation, and [`formatter`] maps the format to it; [`render`] is the single
+//! pipeline all of them go through, so shared concerns (the `--disk-usage-footer`
+//! line, color state) are handled in one place.
+//!
+//! ## Adding a Format
+//!
+//! Add the `--format` value to [`OutputFormat`], implement [`OutputFormatter`] for it,
+//! and return it from [`formatter`]. Nothing in `main.rs` changes.
+//!
+//! ## Output Files
+//!
+//! [`file`] writes `--out` files (atomic replace or locked append).
+//!
+//! ## HTML
+//!
+//! [`html`] renders `--format html` tables.
+//!
+//! ## Grids
+//!
+//! [`grid`] lays out the name columns of `--grid`.
+//!
+//! ## Long Listings
+//!
+//! [`long`] renders the `ls -l` style columns of `-l`.
+
+pub mod file;
+pub mod grid;
+pub mod html;
+pub mod long;
+
+use crate::cli::{OutputFormat, TableStyle};
+use crate::color::{get_file_color, Theme};
+use crate::disk::{self, FilesystemUsage};
+use crate::fsops::FileEntry;
+use crate::json::{self, FieldSelection, ListingMeta};
+use crate::platform::display_path;
+use crate::table::{self, TableOptions};
+use crate::template::Template;
+use crate::tree::{self, TreeOptions};
+use std::io::{self, Write};
+
+/// Everything a formatter may need besides the entries
+pub struct RenderContext<'a> {
+    pub theme: &'a Theme,
+    /// Emit ANSI colors (already resolved from `--color`, `--no-color`, and the terminal)
+    pub color: bool,
+    /// Table columns in display order (`--columns`); the default layout if None
+    pub columns: Option<&'a [table::TableColumn]>,
+    /// Names only, one per line (`--compact`)
+    pub compact: bool,
+    /// Optional table columns and border style
+    pub table: TableOptions,
+    /// Root label and sizes of the `--tree` view
+    pub tree: TreeOptions,
+    /// Split a recursive table into one table per directory (`--group-by-dir`)
+    pub group_by_dir: bool,
+    /// JSON field selection (`--fields`)
+    pub fields: Option<&'a FieldSelection>,
+    /// Indentation width of pretty JSON (`--indent`)
+    pub indent: usize,
+    /// Listing metadata when JSON is wrapped in an envelope (`--envelope`)
+    pub envelope: Option<ListingMeta>,
+    /// Filesystem usage for the footer (`--disk-usage-footer`)
+    pub filesystem: Option<FilesystemUsage>,
+    /// Start CSV output with a header row and `-l` with its `total` line (cleared by
+    /// `--no-header`)
+    pub header: bool,
+    /// Print absolute paths instead of names with `-1` (`--full-path`)
+    pub full_path: bool,
+    /// Terminal width `--grid` fills; `None` lists names one per line
+    pub width: Option<usize>,
+    /// Line template of `--format-string`
+    pub template: Option<&'a Template>,
+}
+
+/// An output backend for one `--format`
+pub trait OutputFormatter {
+    /// Write `entries` to `out`, without a trailing newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to `out` fails.
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()>;
+
+    /// Whether the `--disk-usage-footer` line belongs below this format's output
+    fn shows_footer(&self) -> bool {
+        false
+    }
+}
+
+/// Bordered table, or a name list with `--compact`; with `--group-by-dir`, one per
+/// directory under a `DIR:` heading. The Markdown variant has the same columns, never
+/// colors, and ignores `--compact` and `--no-header`.
+struct TableFormatter {
+    markdown: bool,
+}
+
+impl TableFormatter {
+    fn table(&self, entries: &[FileEntry], ctx: &RenderContext) -> String {
+        let options = TableOptions {
+            markdown: self.markdown,
+            style: if self.markdown {
+                TableStyle::Markdown
+            } else {
+                ctx.table.style
+            },
+            // Without its header row, Markdown would not be a table at all
+            no_header: ctx.table.no_header && !self.markdown,
+            ..ctx.table.clone()
+        };
+        table::format_table(
+            entries,
+            ctx.columns,
+            ctx.compact && !self.markdown,
+            ctx.color,
+            Some(ctx.theme),
+            &options,
+        )
+    }
+}
+
+impl OutputFormatter for TableFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        if !ctx.group_by_dir {
+            return out.write_all(self.table(entries, ctx).as_bytes());
+        }
+        for (i, (dir, group)) in table::group_by_directory(entries).iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n\n")?;
+            }
+            writeln!(out, "{}:", display_path(dir).display())?;
+            out.write_all(self.table(group, ctx).as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn shows_footer(&self) -> bool {
+        !self.markdown
+    }
+}
+
+/// A JSON array of entries (or an `--envelope` object)
+struct JsonFormatter {
+    pretty: bool,
+}
+
+impl OutputFormatter for JsonFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let indent = self.pretty.then_some(ctx.indent);
+        let json = json::to_json(entries, ctx.fields, indent, ctx.envelope.as_ref())
+            .unwrap_or_else(|_| "cannot parse to JSON".into());
+        out.write_all(json.as_bytes())
+    }
+}
+
+/// A standalone `<table>`; with `--group-by-dir`, one per directory under an `<h2>`
+struct HtmlFormatter;
+
+impl OutputFormatter for HtmlFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        if !ctx.group_by_dir {
+            return out.write_all(html::format_html(entries).as_bytes());
+        }
+        for (i, (dir, group)) in table::group_by_directory(entries).iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n")?;
+            }
+            let heading = html::escape(&display_path(dir).display().to_string());
+            writeln!(out, "<h2>{}</h2>", heading)?;
+            out.write_all(html::format_html(group).as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// One compact JSON object per line
+struct NdjsonFormatter;
+
+impl OutputFormatter for NdjsonFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let ndjson =
+            json::to_ndjson(entries, ctx.fields).unwrap_or_else(|_| "cannot parse to JSON".into());
+        out.write_all(ndjson.as_bytes())
+    }
+}
+
+/// The JSON documents as YAML
+struct YamlFormatter;
+
+impl OutputFormatter for YamlFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let yaml = json::to_yaml(entries, ctx.fields, ctx.envelope.as_ref())
+            .unwrap_or_else(|_| "cannot serialize to YAML".into());
+        out.write_all(yaml.as_bytes())
+    }
+}
+
+/// The JSON documents as TOML
+struct TomlFormatter;
+
+impl OutputFormatter for TomlFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let toml = json::to_toml(entries, ctx.fields, ctx.envelope.as_ref())
+            .unwrap_or_else(|_| "cannot serialize to TOML".into());
+        out.write_all(toml.as_bytes())
+    }
+}
+
+/// Names (or `--full-path` paths) one per line, colored by type; no table layout
+struct OnelineFormatter;
+
+impl OutputFormatter for OnelineFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        for (i, entry) in entries.iter().enumerate() {
+            if i > 0 {
+                out.write_all(b"\n")?;
+            }
+            let text = if ctx.full_path {
+                let path = std::path::absolute(&entry.path).unwrap_or_else(|_| entry.path.clone());
+                display_path(&path).display().to_string()
+            } else {
+                entry.name.clone()
+            };
+            if ctx.color {
+                let color = get_file_color(entry, ctx.theme).to_tabled_color();
+                out.write_all(color.colorize(&text).as_bytes())?;
+            } else {
+                out.write_all(text.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Names in terminal-wide columns, like plain `ls`
+struct GridFormatter;
+
+impl OutputFormatter for GridFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        out.write_all(grid::format_grid(entries, ctx.width, ctx.color, ctx.theme).as_bytes())
+    }
+}
+
+/// One `--format-string` line per entry
+struct TemplateFormatter;
+
+impl OutputFormatter for TemplateFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let Some(template) = ctx.template else {
+            return Ok(());
+        };
+        let lines: Vec<String> = entries
+            .iter()
+            .map(|entry| template.render(entry, ctx.color, ctx.theme))
+            .collect();
+        out.write_all(lines.join("\n").as_bytes())
+    }
+}
+
+/// `ls -l` style columns with a `total` line (left out with `--no-header`)
+struct LongFormatter;
+
+impl OutputFormatter for LongFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let long = long::format_long(entries, ctx.header, ctx.color, ctx.theme);
+        out.write_all(long.as_bytes())
+    }
+
+    fn shows_footer(&self) -> bool {
+        true
+    }
+}
+
+/// `name<TAB>size<TAB>modified` lines for `fzf --ansi`
+struct FzfFormatter;
+
+impl OutputFormatter for FzfFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        out.write_all(table::format_fzf(entries, ctx.color, ctx.theme).as_bytes())
+    }
+}
+
+/// Renders one CSV field of an entry
+type CsvCell = fn(&FileEntry) -> String;
+
+/// CSV columns in table order: header and cell of each
+const CSV_COLUMNS: [(&str, CsvCell); 8] = [
+    ("Name", |e| e.name.clone()),
+    ("Type", |e| e.e_type.to_string()),
+    ("Size (bytes)", |e| e.len_bytes.to_string()),
+    ("Size", |e| e.human_size.clone()),
+    ("Modified", |e| e.modified.clone()),
+    ("Permissions", |e| e.permissions.clone()),
+    ("Owner", |e| e.owner.clone()),
+    ("Group", |e| e.group.clone()),
+];
+
+/// A header row and one row per entry. CSV quotes fields with commas, quotes, or
+/// newlines; TSV never quotes and escapes instead (see [`escape_tsv`]).
+///
+/// With `--fields`, the columns are the selected JSON fields instead, headed by their
+/// JSON names.
+struct CsvFormatter {
+    tsv: bool,
+}
+
+impl OutputFormatter for CsvFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut builder = csv::WriterBuilder::new();
+        if self.tsv {
+            builder.delimiter(b'\t').quote_style(csv::QuoteStyle::Never);
+        }
+        let mut writer = builder.from_writer(Vec::new());
+        if ctx.header {
+            match ctx.fields {
+                Some(fields) => writer.write_record(fields.names())?,
+                None => writer.write_record(CSV_COLUMNS.iter().map(|(header, _)| header))?,
+            }
+        }
+        for entry in entries {
+            let cells: Vec<String> = match ctx.fields {
+                Some(fields) => {
+                    let object = json::project(entry, fields)?;
+                    let cell = |field: &String| {
+                        object.get(field).map(json::plain_value).unwrap_or_default()
+                    };
+                    fields.names().iter().map(cell).collect()
+                }
+                None => CSV_COLUMNS.iter().map(|(_, cell)| cell(entry)).collect(),
+            };
+            if self.tsv {
+                writer.write_record(cells.iter().map(|cell| escape_tsv(cell)))?;
+            } else {
+                writer.write_record(cells)?;
+            }
+        }
+        let csv = writer.into_inner().map_err(|e| e.into_error())?;
+        out.write_all(csv.strip_suffix(b"\n").unwrap_or(&csv))
+    }
+}
+
+/// Escape a TSV field so every entry stays on one line with a fixed number of columns.
+///
+/// Backslashes, tabs, line feeds, and carriage returns become `\\`, `\t`, `\n`, and
+/// `\r`; nothing else is changed. Scripts rely on this, so it must not change.
+fn escape_tsv(field: &str) -> String {
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Indented tree of a recursive listing (`--tree`)
+struct TreeFormatter;
+
+impl OutputFormatter for TreeFormatter {
+    fn write(
+        &self,
+        entries: &[FileEntry],
+        ctx: &RenderContext,
+        out: &mut dyn Write,
+    ) -> io::Result<()> {
+        let tree = tree::format_tree(entries, &ctx.tree, ctx.color, ctx.table.ascii, ctx.theme);
+        out.write_all(tree.as_bytes())
+    }
+
+    fn shows_footer(&self) -> bool {
+        true
+    }
+}
+
+/// Names (or `--full-path` paths) each followed by a NUL byte (`--print0`).
+///
+/// Names are written as the bytes the filesystem stores, not the lossily decoded
+/// [`FileEntry::name`], so `xargs -0` gets names it can open even when they are not
+/// valid UTF-8. Nothing is escaped or colored.
+pub fn print0(entries: &[FileEntry], full_path: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    for entry in entries {
+        if full_path {
+            let path = std::path::absolute(&entry.path).unwrap_or_else(|_| entry.path.clone());
+            out.extend_from_slice(display_path(&path).as_os_str().as_encoded_bytes());
+        } else {
+            out.extend_from_slice(&raw_name(entry));
+        }
+        out.push(0);
+    }
+    out
+}
+
+/// The bytes of an entry's name. A recursive listing names entries by their path
+/// below the listed directory, which is the last `depth + 1` components of the path;
+/// entries whose name is not taken from their path (archive members) keep it.
+fn raw_name(entry: &FileEntry) -> Vec<u8> {
+    let components: Vec<_> = entry.path.components().collect();
+    let start = components.len().saturating_sub(entry.depth + 1);
+    let relative: std::path::PathBuf = components[start..].iter().collect();
+    let relative = relative.as_os_str();
+    let lossy = relative
+        .to_string_lossy()
+        .replace(std::path::MAIN_SEPARATOR, "/");
+    if lossy != entry.name {
+        return entry.name.as_bytes().to_vec();
+    }
+    let bytes = relative.as_encoded_bytes();
+    if std::path::MAIN_SEPARATOR == '/' {
+        bytes.to_vec()
+    } else {
+        // The names of a recursive listing are `/`-separated on every platform
+        bytes
+            .iter()
+            .map(|b| {
+                if *b == std::path::MAIN_SEPARATOR as u8 {
+                    b'/'
+                } else {
+                    *b
+                }
+            })
+            .collect()
+    }
+}
+
+/// The formatter implementing `format`
+pub fn formatter(format: OutputFormat) -> &'static dyn OutputFormatter {
+    match format {
+        OutputFormat::Table => &TableFormatter { markdown: false },
+        OutputFormat::Markdown => &TableFormatter { markdown: true },
+        OutputFormat::Json => &JsonFormatter { pretty: false },
+        OutputFormat::JsonPretty => &JsonFormatter { pretty: true },
+        OutputFormat::Fzf => &FzfFormatter,
+        OutputFormat::Oneline => &OnelineFormatter,
+        OutputFormat::Long => &LongFormatter,
+        OutputFormat::Grid => &GridFormatter,
+        OutputFormat::Template => &TemplateFormatter,
+        OutputFormat::Csv => &CsvFormatter { tsv: false },
+        OutputFormat::Tsv => &CsvFormatter { tsv: true },
+        OutputFormat::Yaml => &YamlFormatter,
+        OutputFormat::Toml => &TomlFormatter,
+        OutputFormat::Ndjson => &NdjsonFormatter,
+        OutputFormat::Html => &HtmlFormatter,
+        OutputFormat::Tree => &TreeFormatter,
+    }
+}
+
+/// Render `entries` in `format`, followed by the footer where the format has one.
+///
+/// # Errors
+///
+/// Returns an error if the formatter fails.
+pub fn render(
+    format: OutputFormat,
+    entries: &[FileEntry],
+    ctx: &RenderContext,
+) -> io::Result<String> {
+    let formatter = formatter(format);
+    let mut out = Vec::new();
+    formatter.write(entries, ctx, &mut out)?;
+
+    if let (true, Some(usage)) = (formatter.shows_footer(), &ctx.filesystem) {
+        writeln!(out)?;
+        let footer = disk::format_footer(usage, ctx.color, &ctx.theme.disk_usage);
+        out.write_all(footer.as_bytes())?;
+    }
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsops::FileType;
+    use crate::table::NameWidth;
+
+    /// Entries with fixed metadata, so the rendered output is stable
+    fn fixture() -> Vec<FileEntry> {
+        let entry = |name: &str, e_type, human_size: &str, permissions: &str, group: &str| {
+            let (extension, stem) = crate::fsops::split_name(name, &e_type);
+            FileEntry {
+                name: name.to_string(),
+                e_type,
+                human_size: human_size.to_string(),
+                modified: "Thu 22 Aug 2024 17:44:23".to_string(),
+                modified_epoch: Some(1_724_348_663),
+                modified_iso: Some("2024-08-22T17:44:23Z".to_string()),
+                permissions: permissions.to_string(),
+                owner: "user".to_string(),
+                group: group.to_string(),
+                extension,
+                stem,
+                ..Default::default()
+            }
+        };
+        let mut entries = vec![
+            entry("Cargo.toml", FileType::File, "1.1 KB", "rw-r--r--", "staff"),
+            entry("src", FileType::Directory, "128 B", "rwxr-xr-x", "staff"),
+            entry("run.sh", FileType::File, "42 B", "rwxr-xr-x", "user"),
+        ];
+        for (entry, (len_bytes, nlink)) in entries.iter_mut().zip([(1126, 1), (128, 12), (42, 1)]) {
+            entry.len_bytes = len_bytes;
+            entry.nlink = Some(nlink);
+            entry.blocks = Some(8);
+        }
+        entries
+    }
+
+    fn context(theme: &Theme) -> RenderContext<'_> {
+        RenderContext {
+            theme,
+            color: false,
+            columns: None,
+            compact: false,
+            table: TableOptions::default(),
+            tree: TreeOptions::default(),
+            group_by_dir: false,
+            fields: None,
+            indent: 2,
+            envelope: None,
+            filesystem: None,
+            header: true,
+            full_path: false,
+            width: None,
+            template: None,
+        }
+    }
+
+    fn usage() -> FilesystemUsage {
+        FilesystemUsage {
+            total_bytes: 2048,
+            used_bytes: 1024,
+            available_bytes: 1024,
+            percent_used: 50,
+        }
+    }
+
+    fn assert_snapshot(format: OutputFormat, ctx: &RenderContext, snapshot: &str) {
+        assert_eq!(render(format, &fixture(), ctx).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_table_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/table.txt"),
+        );
+
+        ctx.color = true;
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/table_color.txt"),
+        );
+
+        ctx.filesystem = Some(usage());
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/table_footer.txt"),
+        );
+
+        ctx.filesystem = None;
+        ctx.compact = true;
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/compact.txt"),
+        );
+    }
+
+    #[test]
+    fn test_table_columns_snapshot() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        let columns = table::parse_columns("size,name", &[]).unwrap();
+        ctx.columns = Some(&columns);
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/table_columns.txt"),
+        );
+    }
+
+    #[test]
+    fn test_table_no_header_snapshot() {
+        // The first entry keeps its colors and no header line follows it
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.color = true;
+        ctx.table.no_header = true;
+        assert_snapshot(
+            OutputFormat::Table,
+            &ctx,
+            include_str!("snapshots/table_no_header.txt"),
+        );
+
+        // Markdown keeps its header row
+        let markdown = render(OutputFormat::Markdown, &fixture(), &ctx).unwrap();
+        assert!(markdown.starts_with("| Name"), "{}", markdown);
+    }
+
+    #[test]
+    fn test_table_style_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        for (style, snapshot) in [
+            (TableStyle::Ascii, include_str!("snapshots/table_ascii.txt")),
+            (
+                TableStyle::Modern,
+                include_str!("snapshots/table_modern.txt"),
+            ),
+            (TableStyle::Psql, include_str!("snapshots/table_psql.txt")),
+            // Uncolored, the style matches --format markdown
+            (TableStyle::Markdown, include_str!("snapshots/markdown.txt")),
+            (TableStyle::Blank, include_str!("snapshots/table_blank.txt")),
+            (
+                TableStyle::Compact,
+                include_str!("snapshots/table_compact.txt"),
+            ),
+        ] {
+            ctx.table.style = style;
+            assert_eq!(
+                render(OutputFormat::Table, &fixture(), &ctx).unwrap(),
+                snapshot,
+                "{:?}",
+                style
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_name_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        let mut entries = fixture();
+        entries[0].name = format!("{}.tar.gz", "0123456789".repeat(12));
+        for (name_width, terminal_width, snapshot) in [
+            (
+                NameWidth {
+                    wrap: true,
+                    max: Some(40),
+                },
+                None,
+                include_str!("snapshots/table_wrap.txt"),
+            ),
+            (
+                NameWidth {
+                    wrap: false,
+                    max: Some(40),
+                },
+                None,
+                include_str!("snapshots/table_max_name_width.txt"),
+            ),
+            // Without a width, the names give way until the table fits the terminal
+            (
+                NameWidth {
+                    wrap: true,
+                    max: None,
+                },
+                Some(100),
+                include_str!("snapshots/table_wrap_auto.txt"),
+            ),
+        ] {
+            ctx.table.name_width = Some(name_width);
+            ctx.table.terminal_width = terminal_width;
+            let table = render(OutputFormat::Table, &entries, &ctx).unwrap();
+            assert_eq!(table, snapshot, "{:?}", name_width);
+        }
+        // An automatic width leaves tables that fit alone
+        ctx.table.terminal_width = Some(500);
+        assert!(render(OutputFormat::Table, &entries, &ctx)
+            .unwrap()
+            .contains(&entries[0].name));
+    }
+
+    #[test]
+    fn test_json_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        // JSON ignores colors and the footer
+        ctx.color = true;
+        ctx.filesystem = Some(usage());
+        assert_snapshot(OutputFormat::Json, &ctx, include_str!("snapshots/json.txt"));
+        assert_snapshot(
+            OutputFormat::JsonPretty,
+            &ctx,
+            include_str!("snapshots/json_pretty.txt"),
+        );
+
+        ctx.indent = 4;
+        ctx.envelope = Some(ListingMeta {
+            filesystem: Some(usage()),
+            ..Default::default()
+        });
+        assert_snapshot(
+            OutputFormat::JsonPretty,
+            &ctx,
+            include_str!("snapshots/json_envelope.txt"),
+        );
+    }
+
+    /// A recursive walk of `/work`, named by relative path (`-R`) or file name
+    fn walk_fixture(relative_names: bool) -> Vec<FileEntry> {
+        // Walk order: a directory's entries, then each subdirectory's
+        let entry = |path: &str, depth, e_type, human_size: &str| FileEntry {
+            name: if relative_names {
+                path.to_string()
+            } else {
+                path.rsplit('/').next().unwrap().to_string()
+            },
+            path: std::path::Path::new("/work").join(path),
+            e_type,
+            depth,
+            human_size: human_size.to_string(),
+            ..Default::default()
+        };
+        vec![
+            entry("Cargo.toml", 0, FileType::File, "1.1 KB"),
+            entry("src", 0, FileType::Directory, "128 B"),
+            entry("run.sh", 0, FileType::File, "42 B"),
+            entry("src/bin", 1, FileType::Directory, "96 B"),
+            entry("src/main.rs", 1, FileType::File, "12.3 KB"),
+            entry("src/bin/tool.rs", 2, FileType::File, "2.0 KB"),
+        ]
+    }
+
+    #[test]
+    fn test_recursive_table_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.table.depth = true;
+        let flat = render(OutputFormat::Table, &walk_fixture(true), &ctx).unwrap();
+        assert_eq!(flat, include_str!("snapshots/recursive_flat.txt"));
+
+        ctx.table.depth = false;
+        ctx.group_by_dir = true;
+        let grouped = render(OutputFormat::Table, &walk_fixture(false), &ctx).unwrap();
+        assert_eq!(grouped, include_str!("snapshots/recursive_grouped.txt"));
+    }
+
+    #[test]
+    fn test_tree_snapshots() {
+        let entries = walk_fixture(false);
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.tree.root = ".".to_string();
+        let render_tree = |ctx: &RenderContext| render(OutputFormat::Tree, &entries, ctx).unwrap();
+
+        assert_eq!(render_tree(&ctx), include_str!("snapshots/tree.txt"));
+        ctx.tree.sizes = true;
+        assert_eq!(render_tree(&ctx), include_str!("snapshots/tree_sizes.txt"));
+        ctx.table.ascii = true;
+        assert_eq!(render_tree(&ctx), include_str!("snapshots/tree_ascii.txt"));
+        ctx.table.ascii = false;
+        ctx.color = true;
+        assert_eq!(render_tree(&ctx), include_str!("snapshots/tree_color.txt"));
+    }
+
+    #[test]
+    fn test_fzf_snapshot() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.color = true;
+        ctx.filesystem = Some(usage());
+        assert_snapshot(OutputFormat::Fzf, &ctx, include_str!("snapshots/fzf.txt"));
+    }
+
+    #[test]
+    fn test_grid_snapshots() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.width = Some(20);
+        assert_snapshot(OutputFormat::Grid, &ctx, include_str!("snapshots/grid.txt"));
+
+        ctx.color = true;
+        assert_snapshot(
+            OutputFormat::Grid,
+            &ctx,
+            include_str!("snapshots/grid_color.txt"),
+        );
+    }
+
+    #[test]
+    fn test_long_snapshot() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        ctx.filesystem = Some(usage());
+        assert_snapshot(OutputFormat::Long, &ctx, include_str!("snapshots/long.txt"));
+
+        // Only names are colored, so the columns stay aligned
+        ctx.color = true;
+        ctx.filesystem = None;
+        let colored = render(OutputFormat::Long, &fixture(), &ctx).unwrap();
+        let plain = render(OutputFormat::Long, &fixture(), &context(&theme)).unwrap();
+        for (colored, plain) in colored.lines().zip(plain.lines()).skip(1) {
+            let (columns, name) = plain.rsplit_once(' ').unwrap();
+            assert!(colored.starts_with(columns));
+            assert!(colored.contains("\x1b["));
+            assert!(colored.contains(name));
+        }
+    }
+
+    #[test]
+    fn test_markdown_snapshot() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        // Neither colors, --compact, nor the footer apply to Markdown
+        ctx.color = true;
+        ctx.compact = true;
+        ctx.filesystem = Some(usage());
+        assert_snapshot(
+            OutputFormat::Markdown,
+            &ctx,
+            include_str!("snapshots/markdown.txt"),
+        );
+    }
+
+    #[test]
+    fn test_print0() {
+        assert_eq!(print0(&fixture(), false), b"Cargo.toml\0src\0run.sh\0");
+
+        // Names not taken from the path are written as they are
+        let mut member = FileEntry {
+            name: "docs/a.md".to_string(),
+            path: std::path::PathBuf::from("archive.zip"),
+            ..Default::default()
+        };
+        assert_eq!(print0(std::slice::from_ref(&member), false), b"docs/a.md\0");
+        member.path = std::path::PathBuf::from("/listed/docs/a.md");
+        member.depth = 1;
+        assert_eq!(print0(&[member], false), b"docs/a.md\0");
+    }
+
+    #[test]
+    fn test_csv_snapshot() {
+        let theme = Theme::default();
+        let mut ctx = context(&theme);
+        // Neither colors nor the footer apply to CSV
+        ctx.color = true;
+        ctx.filesystem = Some(usage());
+        assert_snapshot(OutputFormat::Csv, &ctx, include_str!("snapshots/csv.txt"));
+
+        ctx.header = false;
+        let rows = render(OutputFormat::Csv, &fixture(), &ctx).unwrap();
+        assert!(rows.starts_with("Cargo.toml,File,1126,"));
+    }
+
+    #[test]
+    fn test_csv_fields() {
+        let theme = Theme::default();
+        let fields = FieldSelection::parse("len_bytes,name,preview,is_hidden", &[]).unwrap();
+        let mut ctx = context(&theme);
+        ctx.fields = Some(&fields);
+        // An unset optional field is an empty cell
+        assert_eq!(
+            render(OutputFormat::Csv, &fixture(), &ctx).unwrap(),
+            "len_bytes,name,preview,is_hidden\n\
+             1126,Cargo.toml,,false\n\
+             128,src,,false\n\
+             42,run.sh,,false"
+        );
+        assert_eq!(
+            render(OutputFormat::Tsv, &fixture()[..1], &ctx).unwrap(),
+            "len_bytes\tname\tpreview\tis_hidden\n1126\tCargo.toml\t\tfalse"
+        );
+    }
+
+    #[test]
+    fn test_tsv_snapshot() {
+        let theme = Theme::default();
+        let ctx = context(&theme);
+        assert_snapshot(OutputFormat::Tsv, &ctx, include_str!("snapshots/tsv.txt"));
+    }
+
+    #[test]
+    fn test_escape_tsv() {
+        assert_eq!(escape_tsv("plain, \"quoted\""), "plain, \"quoted\"");
+        assert_eq!(
+            escape_tsv("tab\there\nnew\r\\back"),
+            "tab\\there\\nnew\\r\\\\back"
+        );
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut entries = fixture();
+        let names = ["comma, separated", "\"quoted\" name", "multi\nline\r\nname"];
+        for (entry, name) in entries.iter_mut().zip(names) {
+            entry.name = name.to_string();
+        }
+        let theme = Theme::default();
+        let csv = render(OutputFormat::Csv, &entries, &context(&theme)).unwrap();
+
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let headers: Vec<String> = reader.headers().unwrap().iter().map(String::from).collect();
+        assert_eq!(
+            headers,
+            CSV_COLUMNS.map(|(header, _)| header.to_string()).to_vec()
+        );
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), entries.len());
+        for (row, entry) in rows.iter().zip(&entries) {
+            let expected: Vec<String> = CSV_COLUMNS.iter().map(|(_, cell)| cell(entry)).collect();
+            assert_eq!(row.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+}
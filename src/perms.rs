@@ -0,0 +1,251 @@
+//! # Permission Filter Module
+//!
+//! This module parses `--perm` specs, which follow `find -perm`:
+//!
+//! - `644` / `u=rw,go=r`: the permission bits are exactly these
+//! - `-220` / `-ug+w`: all of these bits are set (`-4000`: setuid)
+//! - `/222` / `/o+w`: any of these bits is set
+//!
+//! Symbolic specs are evaluated starting from no bits at all, so `u+w` is `200`.
+//! Who letters are `u`, `g`, `o`, and `a` (the default), permission letters are
+//! `r`, `w`, `x`, `s` (setuid/setgid), and `t` (sticky).
+//!
+//! Unlike `find`, a symbolic spec without a prefix only constrains the bits its
+//! clauses mention: `o+w` matches world-writable entries, `g-x` entries the group
+//! cannot execute, and `u=rw` entries whose owner bits are exactly `rw-`.
+
+use std::fmt;
+
+/// All bits `--perm` compares (permissions plus setuid, setgid, sticky)
+const PERM_MASK: u32 = 0o7777;
+
+/// How the spec bits are compared against an entry's mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermMatch {
+    /// Mode bits equal the spec bits
+    Exact,
+    /// Every spec bit is set (`-` prefix)
+    All,
+    /// At least one spec bit is set (`/` prefix); an empty spec matches everything
+    Any,
+    /// The mode bits under the mask equal the spec bits (symbolic spec without prefix)
+    Masked(u32),
+}
+
+/// A parsed `--perm` spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PermSpec {
+    pub bits: u32,
+    pub kind: PermMatch,
+}
+
+/// Error type for `--perm` spec parsing
+#[derive(Debug, Clone)]
+pub enum PermParseError {
+    Empty,
+    InvalidOctal(String),
+    InvalidSymbolic(String),
+}
+
+impl fmt::Display for PermParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermParseError::Empty => write!(f, "empty permission spec provided"),
+            PermParseError::InvalidOctal(s) => {
+                write!(
+                    f,
+                    "invalid octal mode '{}' (expected up to 4 octal digits)",
+                    s
+                )
+            }
+            PermParseError::InvalidSymbolic(s) => write!(
+                f,
+                "invalid symbolic mode '{}' (expected e.g. u+w, g=rx, a+r)",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PermParseError {}
+
+impl PermSpec {
+    /// Whether a raw `st_mode` value satisfies this spec
+    pub fn matches(&self, mode: u32) -> bool {
+        let mode = mode & PERM_MASK;
+        match self.kind {
+            PermMatch::Exact => mode == self.bits,
+            PermMatch::All => mode & self.bits == self.bits,
+            PermMatch::Any => self.bits == 0 || mode & self.bits != 0,
+            PermMatch::Masked(mask) => mode & mask == self.bits,
+        }
+    }
+}
+
+/// Parse a `--perm` spec (e.g., "644", "/o+w", "-220", "g=rx")
+pub fn parse_perm(spec: &str) -> Result<PermSpec, PermParseError> {
+    let spec = spec.trim();
+    let (kind, body) = if let Some(rest) = spec.strip_prefix('/') {
+        (PermMatch::Any, rest)
+    } else if let Some(rest) = spec.strip_prefix('-') {
+        (PermMatch::All, rest)
+    } else {
+        (PermMatch::Exact, spec)
+    };
+
+    if body.is_empty() {
+        return Err(PermParseError::Empty);
+    }
+
+    if body.chars().all(|c| c.is_ascii_digit()) {
+        let bits = parse_octal(body)?;
+        return Ok(PermSpec { bits, kind });
+    }
+    let (bits, mentioned) = parse_symbolic(body)?;
+    let kind = match kind {
+        PermMatch::Exact => PermMatch::Masked(mentioned),
+        kind => kind,
+    };
+    Ok(PermSpec { bits, kind })
+}
+
+fn parse_octal(body: &str) -> Result<u32, PermParseError> {
+    if body.len() > 4 {
+        return Err(PermParseError::InvalidOctal(body.to_string()));
+    }
+    u32::from_str_radix(body, 8).map_err(|_| PermParseError::InvalidOctal(body.to_string()))
+}
+
+/// Evaluate comma-separated `[ugoa]*[+-=][rwxst]*` clauses starting from no bits.
+///
+/// Returns the resulting bits and the mask of bits the clauses mention (`=` mentions
+/// every bit of its classes).
+fn parse_symbolic(body: &str) -> Result<(u32, u32), PermParseError> {
+    let invalid = || PermParseError::InvalidSymbolic(body.to_string());
+    let mut bits = 0u32;
+    let mut mentioned = 0u32;
+
+    for clause in body.split(',') {
+        let op_pos = clause.find(['+', '-', '=']).ok_or_else(invalid)?;
+        let (who, rest) = clause.split_at(op_pos);
+        let mut chars = rest.chars();
+        let op = chars.next().ok_or_else(invalid)?;
+        let perms: &str = chars.as_str();
+
+        // Bits the named classes may touch (including their special bit)
+        let mut class_mask = 0u32;
+        for c in if who.is_empty() { "a" } else { who }.chars() {
+            class_mask |= match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let mut perm_bits = 0u32;
+        for c in perms.chars() {
+            perm_bits |= match c {
+                'r' => 0o444,
+                'w' => 0o222,
+                'x' => 0o111,
+                's' => 0o6000,
+                't' => 0o1000,
+                _ => return Err(invalid()),
+            };
+        }
+        let clause_bits = perm_bits & class_mask;
+
+        match op {
+            '+' => bits |= clause_bits,
+            '-' => bits &= !clause_bits,
+            '=' => bits = (bits & !class_mask) | clause_bits,
+            _ => return Err(invalid()),
+        }
+        mentioned |= if op == '=' { class_mask } else { clause_bits };
+    }
+
+    Ok((bits, mentioned))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_octal_specs() {
+        assert_eq!(
+            parse_perm("644").unwrap(),
+            PermSpec {
+                bits: 0o644,
+                kind: PermMatch::Exact
+            }
+        );
+        assert_eq!(parse_perm("/222").unwrap().kind, PermMatch::Any);
+        assert_eq!(parse_perm("-220").unwrap().kind, PermMatch::All);
+        assert_eq!(parse_perm("4755").unwrap().bits, 0o4755);
+
+        assert!(parse_perm("").is_err());
+        assert!(parse_perm("/").is_err());
+        assert!(parse_perm("888").is_err());
+        assert!(parse_perm("12345").is_err());
+    }
+
+    #[test]
+    fn test_parse_symbolic_specs() {
+        assert_eq!(parse_perm("u+w").unwrap().bits, 0o200);
+        assert_eq!(parse_perm("g=rx").unwrap().bits, 0o050);
+        assert_eq!(parse_perm("/o+w").unwrap().bits, 0o002);
+        assert_eq!(parse_perm("u=rw,go=r").unwrap().bits, 0o644);
+        assert_eq!(parse_perm("+x").unwrap().bits, 0o111);
+        assert_eq!(parse_perm("a+rwx,o-w").unwrap().bits, 0o775);
+        assert_eq!(parse_perm("u+s").unwrap().bits, 0o4000);
+        assert_eq!(parse_perm("+t").unwrap().bits, 0o1000);
+
+        assert!(parse_perm("u+q").is_err());
+        assert!(parse_perm("z+w").is_err());
+        assert!(parse_perm("uw").is_err());
+    }
+
+    #[test]
+    fn test_perm_matching() {
+        let world_writable = parse_perm("/o+w").unwrap();
+        assert!(world_writable.matches(0o100666));
+        assert!(!world_writable.matches(0o100644));
+
+        let exact = parse_perm("644").unwrap();
+        assert!(exact.matches(0o100644));
+        assert!(!exact.matches(0o100664));
+
+        let all = parse_perm("-220").unwrap();
+        assert!(all.matches(0o664));
+        assert!(!all.matches(0o644));
+
+        assert!(parse_perm("/000").unwrap().matches(0o600));
+    }
+
+    #[test]
+    fn test_bare_symbolic_checks_mentioned_bits() {
+        let world_writable = parse_perm("o+w").unwrap();
+        assert_eq!(world_writable.kind, PermMatch::Masked(0o002));
+        assert!(world_writable.matches(0o100666));
+        assert!(world_writable.matches(0o100777));
+        assert!(!world_writable.matches(0o100644));
+
+        let group_no_exec = parse_perm("g-x").unwrap();
+        assert!(group_no_exec.matches(0o100744));
+        assert!(!group_no_exec.matches(0o100754));
+
+        assert!(parse_perm("u+s").unwrap().matches(0o104755));
+        assert!(!parse_perm("u+s").unwrap().matches(0o100755));
+
+        // `=` fixes every bit of its classes, so this is an exact match again
+        let exact = parse_perm("u=rw,go=r").unwrap();
+        assert!(exact.matches(0o100644));
+        assert!(!exact.matches(0o100664));
+        let owner = parse_perm("u=rw").unwrap();
+        assert!(owner.matches(0o100600) && owner.matches(0o100677));
+        assert!(!owner.matches(0o100700));
+    }
+}
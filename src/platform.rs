@@ -0,0 +1,421 @@
+//! # Platform Support Module
+//!
+//! Small helpers that paper over operating system differences.
+//!
+//! ## Windows Paths
+//!
+//! Win32 APIs reject paths longer than `MAX_PATH` (260 characters) unless they use
+//! the extended-length form (`\\?\C:\...` or `\\?\UNC\server\share\...`).
+//! [`long_path`] converts a path to that form right before it is handed to the
+//! filesystem, and [`display_path`] strips it again for anything shown to the user.
+//! On other platforms both are no-ops.
+//!
+//! ## Terminal Colors
+//!
+//! Legacy Windows consoles print ANSI escapes literally (`←[36m`) unless virtual
+//! terminal processing is switched on for the output handle. [`enable_ansi`] tries to
+//! do that at startup; when it fails, `--color auto` falls back to plain ASCII output.
+//!
+//! ## File Locks
+//!
+//! [`lock_exclusive`] takes an advisory whole-file lock (`flock` on Unix, `LockFileEx`
+//! on Windows) that is released when the file is closed.
+//!
+//! ## Home Directories
+//!
+//! [`expand_tilde`] expands a leading `~` or `~user` for path values the shell did not
+//! expand (for example `-p=~/src`, or values from config files). `~user` is looked up
+//! in the user database on Unix and as a sibling of the current profile on Windows.
+//!
+//! ## Directory Identity
+//!
+//! [`file_id`] identifies the directory a path leads to, whatever links it goes
+//! through: the (device, inode) pair on Unix, the canonical path elsewhere (which
+//! resolves Windows junctions and directory symlinks). `--follow-symlinks` uses it to
+//! notice a link leading back into a directory being walked.
+//!
+//! [`device_id`] names the filesystem a path is on, for `--one-file-system`: `st_dev`
+//! on Unix, the volume (the prefix of the canonical path) on Windows.
+//!
+//! ## Raw Paths
+//!
+//! [`path_from_bytes`] turns path bytes read from a pipe (`--stdin`) into a path. Unix
+//! paths are arbitrary bytes and are kept exactly; elsewhere they must be UTF-8.
+
+use std::borrow::Cow;
+use std::path::{Component, Path, PathBuf};
+
+/// Longest path Win32 accepts without the extended-length prefix
+#[cfg(windows)]
+const MAX_PATH: usize = 260;
+
+/// Extended-length prefix for drive paths
+#[cfg(windows)]
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// Extended-length prefix for UNC paths (replaces the leading `\\`)
+#[cfg(windows)]
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// Return the form of `path` to pass to filesystem calls.
+///
+/// On Windows, absolute paths at or over `MAX_PATH` and all UNC paths are turned into
+/// their extended-length form; paths that already carry the prefix are kept as is.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    let text = path.to_string_lossy();
+    if text.starts_with(VERBATIM_PREFIX) {
+        return Cow::Borrowed(path);
+    }
+
+    let Ok(absolute) = std::path::absolute(path) else {
+        return Cow::Borrowed(path);
+    };
+    let absolute_text = absolute.to_string_lossy();
+
+    if let Some(rest) = absolute_text.strip_prefix(r"\\") {
+        // UNC paths behave inconsistently across APIs unless made verbatim
+        return Cow::Owned(PathBuf::from(format!("{}{}", VERBATIM_UNC_PREFIX, rest)));
+    }
+    if absolute_text.len() >= MAX_PATH {
+        return Cow::Owned(PathBuf::from(format!(
+            "{}{}",
+            VERBATIM_PREFIX, absolute_text
+        )));
+    }
+
+    Cow::Borrowed(path)
+}
+
+/// Return the form of `path` to pass to filesystem calls (unchanged on this platform).
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Return `path` without an extended-length prefix, for display and serialization.
+#[cfg(windows)]
+pub fn display_path(path: &Path) -> Cow<'_, Path> {
+    let text = path.to_string_lossy();
+    if let Some(rest) = text.strip_prefix(VERBATIM_UNC_PREFIX) {
+        Cow::Owned(PathBuf::from(format!(r"\\{}", rest)))
+    } else if let Some(rest) = text.strip_prefix(VERBATIM_PREFIX) {
+        Cow::Owned(PathBuf::from(rest))
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+/// Return `path` without an extended-length prefix (unchanged on this platform).
+#[cfg(not(windows))]
+pub fn display_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Make sure stdout renders ANSI escape sequences.
+///
+/// Enables `ENABLE_VIRTUAL_TERMINAL_PROCESSING` on the stdout console handle; fails when
+/// the console is too old or stdout is not a console.
+#[cfg(windows)]
+pub fn enable_ansi() -> std::io::Result<()> {
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_OUTPUT_HANDLE,
+    };
+
+    // SAFETY: plain Win32 calls on the process' own stdout handle; `mode` outlives the call
+    unsafe {
+        let handle = GetStdHandle(STD_OUTPUT_HANDLE);
+        if handle == INVALID_HANDLE_VALUE || handle.is_null() {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        let mut mode = 0;
+        if GetConsoleMode(handle, &mut mode) == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0 {
+            return Ok(());
+        }
+        if SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Make sure stdout renders ANSI escape sequences (always true on this platform).
+#[cfg(not(windows))]
+pub fn enable_ansi() -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Expand a leading `~` (the current user's home) or `~user` (that user's home).
+///
+/// Paths that don't start with a tilde, and `~user` forms naming an unknown user, are
+/// returned unchanged.
+pub fn expand_tilde(path: &Path) -> Cow<'_, Path> {
+    let mut components = path.components();
+    let Some(Component::Normal(first)) = components.next() else {
+        return Cow::Borrowed(path);
+    };
+    let Some(user) = first.to_str().and_then(|first| first.strip_prefix('~')) else {
+        return Cow::Borrowed(path);
+    };
+
+    let home = if user.is_empty() {
+        dirs::home_dir()
+    } else {
+        user_home(user)
+    };
+    match home {
+        // Joining an empty rest would add a trailing separator
+        Some(home) if components.as_path().as_os_str().is_empty() => Cow::Owned(home),
+        Some(home) => Cow::Owned(home.join(components.as_path())),
+        None => Cow::Borrowed(path),
+    }
+}
+
+/// Home directory of `user` from the user database
+#[cfg(unix)]
+fn user_home(user: &str) -> Option<PathBuf> {
+    nix::unistd::User::from_name(user)
+        .ok()
+        .flatten()
+        .map(|user| user.dir)
+}
+
+/// Profile directory of `user`, next to the current user's profile (`C:\Users\user`)
+#[cfg(windows)]
+fn user_home(user: &str) -> Option<PathBuf> {
+    let profile = dirs::home_dir()?.parent()?.join(user);
+    profile.is_dir().then_some(profile)
+}
+
+/// Other users' home directories are unknown on this platform.
+#[cfg(not(any(unix, windows)))]
+fn user_home(_user: &str) -> Option<PathBuf> {
+    None
+}
+
+/// Identity of a file or directory, equal for every path that leads to it
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileId {
+    dev: u64,
+    ino: u64,
+}
+
+/// Identity of a file or directory, equal for every path that leads to it
+#[cfg(not(unix))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileId(PathBuf);
+
+/// The identity of what `path` leads to, following symlinks.
+#[cfg(unix)]
+pub fn file_id(path: &Path) -> std::io::Result<FileId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path)?;
+    Ok(FileId {
+        dev: metadata.dev(),
+        ino: metadata.ino(),
+    })
+}
+
+/// The identity of what `path` leads to, following symlinks and junctions.
+#[cfg(not(unix))]
+pub fn file_id(path: &Path) -> std::io::Result<FileId> {
+    std::fs::canonicalize(long_path(path)).map(FileId)
+}
+
+/// Identity of the filesystem (device or volume) holding a path
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceId(u64);
+
+/// Identity of the filesystem (device or volume) holding a path
+#[cfg(windows)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceId(std::ffi::OsString);
+
+/// Identity of the filesystem (device or volume) holding a path
+#[cfg(not(any(unix, windows)))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceId;
+
+/// The filesystem `path` is on, following symlinks.
+#[cfg(unix)]
+pub fn device_id(path: &Path) -> std::io::Result<DeviceId> {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::metadata(path).map(|metadata| DeviceId(metadata.dev()))
+}
+
+/// The volume `path` is on, following symlinks and junctions.
+///
+/// Volumes mounted in a folder canonicalize to their own `\\?\Volume{...}` prefix.
+#[cfg(windows)]
+pub fn device_id(path: &Path) -> std::io::Result<DeviceId> {
+    let canonical = std::fs::canonicalize(long_path(path))?;
+    match canonical.components().next() {
+        Some(Component::Prefix(prefix)) => Ok(DeviceId(prefix.as_os_str().to_os_string())),
+        _ => Ok(DeviceId(std::ffi::OsString::new())),
+    }
+}
+
+/// Filesystems cannot be told apart on this platform; every path is on the same one.
+#[cfg(not(any(unix, windows)))]
+pub fn device_id(_path: &Path) -> std::io::Result<DeviceId> {
+    Ok(DeviceId)
+}
+
+/// The path spelled by `bytes`, kept byte for byte.
+#[cfg(unix)]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+/// The path spelled by `bytes`; invalid UTF-8 is replaced.
+#[cfg(not(unix))]
+pub fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Block until an exclusive advisory lock on `file` is held.
+#[cfg(unix)]
+pub fn lock_exclusive(file: &std::fs::File) -> std::io::Result<()> {
+    use nix::fcntl::{flock, FlockArg};
+    use std::os::unix::io::AsRawFd;
+
+    flock(file.as_raw_fd(), FlockArg::LockExclusive).map_err(std::io::Error::from)
+}
+
+/// Block until an exclusive advisory lock on `file` is held.
+#[cfg(windows)]
+pub fn lock_exclusive(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::windows::io::AsRawHandle;
+    use windows_sys::Win32::Storage::FileSystem::{LockFileEx, LOCKFILE_EXCLUSIVE_LOCK};
+    use windows_sys::Win32::System::IO::OVERLAPPED;
+
+    // SAFETY: the handle is owned by `file` for the duration of the call, and a zeroed
+    // OVERLAPPED (offset 0) is valid for a synchronous lock of the whole file
+    unsafe {
+        let mut overlapped: OVERLAPPED = std::mem::zeroed();
+        if LockFileEx(
+            file.as_raw_handle(),
+            LOCKFILE_EXCLUSIVE_LOCK,
+            0,
+            u32::MAX,
+            u32::MAX,
+            &mut overlapped,
+        ) == 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Advisory locks are not supported on this platform; writes proceed unlocked.
+#[cfg(not(any(unix, windows)))]
+pub fn lock_exclusive(_file: &std::fs::File) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tilde() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde(Path::new("~")), home);
+        assert_eq!(
+            expand_tilde(Path::new("~/src/app")),
+            home.join("src").join("app")
+        );
+        assert_eq!(expand_tilde(Path::new("src/~")), Path::new("src/~"));
+        assert_eq!(
+            expand_tilde(Path::new("~no-such-user-here/x")),
+            Path::new("~no-such-user-here/x")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_tilde_user() {
+        let user = nix::unistd::User::from_uid(nix::unistd::getuid())
+            .unwrap()
+            .unwrap();
+        let path = PathBuf::from(format!("~{}/notes", user.name));
+        assert_eq!(expand_tilde(&path), user.dir.join("notes"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_expand_tilde_backslash() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde(Path::new(r"~\src")), home.join("src"));
+        let user = home.file_name().unwrap().to_string_lossy().into_owned();
+        assert_eq!(
+            expand_tilde(&PathBuf::from(format!(r"~{}\src", user))),
+            home.join("src")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_path_prefixes() {
+        assert_eq!(
+            long_path(Path::new(r"\\server\share\dir")),
+            Path::new(r"\\?\UNC\server\share\dir")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\?\C:\already")),
+            Path::new(r"\\?\C:\already")
+        );
+        assert_eq!(long_path(Path::new(r"C:\short")), Path::new(r"C:\short"));
+
+        let long = format!(r"C:\{}", "a".repeat(MAX_PATH));
+        assert_eq!(
+            long_path(Path::new(&long)),
+            PathBuf::from(format!(r"\\?\{}", long))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_display_path_strips_prefix() {
+        assert_eq!(
+            display_path(Path::new(r"\\?\UNC\server\share")),
+            Path::new(r"\\server\share")
+        );
+        assert_eq!(display_path(Path::new(r"\\?\C:\dir")), Path::new(r"C:\dir"));
+        assert_eq!(display_path(Path::new(r"C:\dir")), Path::new(r"C:\dir"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_list_deeply_nested_directory() {
+        let root = tempfile::tempdir().unwrap();
+        let mut deepest = root.path().to_path_buf();
+        while deepest.as_os_str().len() <= MAX_PATH + 20 {
+            deepest.push("a_fairly_long_directory_name");
+        }
+        std::fs::create_dir_all(long_path(&deepest)).unwrap();
+        std::fs::write(long_path(&deepest.join("leaf.txt")), "x").unwrap();
+
+        let files = crate::fsops::get_files(&deepest, &crate::fsops::ListOptions::default())
+            .unwrap()
+            .entries;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "leaf.txt");
+
+        let json = serde_json::to_string(&files).unwrap();
+        assert!(!json.contains('?'));
+    }
+}
@@ -0,0 +1,387 @@
+//! # Interactive Selection Module
+//!
+//! `--select` shows the listing as a picker and prints the chosen entries' paths, so
+//! bestls can feed other commands: `vim $(bestls --select)`.
+//!
+//! ## Keys
+//!
+//! - `↑`/`↓` or `k`/`j`: move the cursor (`PgUp`/`PgDn`, `Home`/`End` jump)
+//! - `Space`: toggle the entry under the cursor
+//! - `/`: type a filter (case-insensitive substring of the name); `Enter` keeps it,
+//!   `Esc` clears it
+//! - `Enter`: print the toggled entries, or the one under the cursor if none is toggled
+//! - `Esc`, `q`, or `Ctrl-C`: cancel without output (exit status 130)
+//!
+//! ## Terminal Handling
+//!
+//! The picker draws on stderr in the alternate screen, so stdout carries nothing but
+//! the chosen paths. The terminal is restored when the picker ends, including on a
+//! panic (the release profile aborts on panic, so a panic hook does it rather than
+//! unwinding).
+
+use crate::color::{get_file_color, Theme};
+use crate::fsops::FileEntry;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, queue};
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Write};
+use std::sync::Once;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Rows above the entries: the prompt line and the key help line
+const HEADER_ROWS: usize = 2;
+
+/// What the picker does after a key press
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Continue,
+    Accept,
+    Cancel,
+}
+
+/// Picker state, independent of the terminal
+pub struct Picker<'a> {
+    entries: &'a [FileEntry],
+    /// Indices of the entries matching the filter, in listing order
+    visible: Vec<usize>,
+    /// Position of the cursor in `visible`
+    cursor: usize,
+    /// Toggled entries (indices into `entries`)
+    selected: BTreeSet<usize>,
+    filter: String,
+    /// Typed characters go to the filter instead of being commands
+    filtering: bool,
+}
+
+impl<'a> Picker<'a> {
+    pub fn new(entries: &'a [FileEntry]) -> Self {
+        Self {
+            entries,
+            visible: (0..entries.len()).collect(),
+            cursor: 0,
+            selected: BTreeSet::new(),
+            filter: String::new(),
+            filtering: false,
+        }
+    }
+
+    /// Apply a key press.
+    ///
+    /// `page` is the number of entry rows on screen, used by `PgUp`/`PgDn`.
+    pub fn handle_key(&mut self, key: KeyEvent, page: usize) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Cancel;
+        }
+
+        match key.code {
+            KeyCode::Up => self.move_by(-1),
+            KeyCode::Down => self.move_by(1),
+            KeyCode::PageUp => self.move_by(-(page.max(1) as isize)),
+            KeyCode::PageDown => self.move_by(page.max(1) as isize),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.visible.len().saturating_sub(1),
+            KeyCode::Enter if self.filtering => self.filtering = false,
+            KeyCode::Esc if self.filtering => {
+                self.filtering = false;
+                self.set_filter(String::new());
+            }
+            KeyCode::Backspace if self.filtering => {
+                let mut filter = self.filter.clone();
+                filter.pop();
+                self.set_filter(filter);
+            }
+            KeyCode::Char(c) if self.filtering => {
+                let mut filter = self.filter.clone();
+                filter.push(c);
+                self.set_filter(filter);
+            }
+            // Nothing to print while the filter hides everything
+            KeyCode::Enter if !self.accepted().is_empty() => return Action::Accept,
+            KeyCode::Esc | KeyCode::Char('q') => return Action::Cancel,
+            KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Char(' ') => {
+                if let Some(&index) = self.visible.get(self.cursor) {
+                    if !self.selected.remove(&index) {
+                        self.selected.insert(index);
+                    }
+                    self.move_by(1);
+                }
+            }
+            KeyCode::Char('/') => self.filtering = true,
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// The toggled entries in listing order, or the entry under the cursor if none is
+    pub fn accepted(&self) -> Vec<&'a FileEntry> {
+        let entries = self.entries;
+        if self.selected.is_empty() {
+            return self
+                .visible
+                .get(self.cursor)
+                .map(|&index| &entries[index])
+                .into_iter()
+                .collect();
+        }
+        self.selected.iter().map(|&index| &entries[index]).collect()
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        let needle = filter.to_lowercase();
+        self.visible = (0..self.entries.len())
+            .filter(|&index| self.entries[index].name.to_lowercase().contains(&needle))
+            .collect();
+        self.filter = filter;
+        self.cursor = self.cursor.min(self.visible.len().saturating_sub(1));
+    }
+
+    /// Draw the picker into a `width` x `height` screen
+    fn draw(
+        &self,
+        out: &mut impl Write,
+        width: usize,
+        height: usize,
+        theme: &Theme,
+        color: bool,
+    ) -> io::Result<()> {
+        queue!(out, cursor::MoveTo(0, 0), terminal::Clear(ClearType::All))?;
+
+        let prompt = if self.filtering || !self.filter.is_empty() {
+            format!("/{}", self.filter)
+        } else {
+            format!("{} entries", self.entries.len())
+        };
+        let status = format!("{}  ({} selected)", prompt, self.selected.len());
+        // Lines are separated rather than terminated, so the last row doesn't scroll
+        write!(out, "{}\r\n", clip(&status, width))?;
+        queue!(out, SetAttribute(Attribute::Dim))?;
+        write!(
+            out,
+            "{}",
+            clip(
+                "j/k move · space toggle · / filter · enter accept · esc cancel",
+                width
+            )
+        )?;
+        queue!(out, SetAttribute(Attribute::Reset))?;
+
+        let rows = height.saturating_sub(HEADER_ROWS).max(1);
+        // Keep the cursor on screen by scrolling it to the last row
+        let offset = (self.cursor + 1).saturating_sub(rows);
+        for (row, &index) in self.visible.iter().enumerate().skip(offset).take(rows) {
+            let entry = &self.entries[index];
+            let marker = if self.selected.contains(&index) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            // The size column needs room too
+            let name = clip(&entry.name, width.saturating_sub(marker.len() + 14));
+            let name = if color {
+                get_file_color(entry, theme)
+                    .to_tabled_color()
+                    .colorize(&name)
+            } else {
+                name
+            };
+
+            write!(out, "\r\n")?;
+            if row == self.cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            write!(out, "{} {}  {}", marker, name, entry.human_size)?;
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        out.flush()
+    }
+}
+
+/// Cut `text` to at most `width` terminal columns, between graphemes
+fn clip(text: &str, width: usize) -> String {
+    let mut used = 0;
+    text.graphemes(true)
+        .take_while(|grapheme| {
+            used += grapheme.width();
+            used <= width
+        })
+        .collect()
+}
+
+/// Put the terminal back into its normal state
+fn restore_terminal() {
+    let _ = terminal::disable_raw_mode();
+    let _ = crossterm::execute!(io::stderr(), cursor::Show, terminal::LeaveAlternateScreen);
+}
+
+/// Raw mode and the alternate screen on stderr, undone on drop
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        static HOOK: Once = Once::new();
+        HOOK.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore_terminal();
+                previous(info);
+            }));
+        });
+
+        terminal::enable_raw_mode()?;
+        let guard = TerminalGuard;
+        crossterm::execute!(io::stderr(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Run the picker on stderr; returns the accepted entries, or `None` when cancelled.
+///
+/// # Errors
+///
+/// Returns an error if stderr is not a terminal or the terminal cannot be controlled.
+pub fn run<'a>(
+    entries: &'a [FileEntry],
+    theme: &Theme,
+    color: bool,
+) -> io::Result<Option<Vec<&'a FileEntry>>> {
+    if !io::stderr().is_terminal() {
+        return Err(io::Error::other("--select needs a terminal on stderr"));
+    }
+
+    let mut picker = Picker::new(entries);
+    let _guard = TerminalGuard::enter()?;
+    let mut stderr = io::stderr();
+    loop {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (usize::from(width), usize::from(height));
+        picker.draw(&mut stderr, width, height, theme, color)?;
+
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match picker.handle_key(key, height.saturating_sub(HEADER_ROWS)) {
+                Action::Continue => {}
+                Action::Accept => return Ok(Some(picker.accepted())),
+                Action::Cancel => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(names: &[&str]) -> Vec<FileEntry> {
+        names
+            .iter()
+            .map(|name| FileEntry {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Action {
+        picker.handle_key(KeyEvent::new(code, KeyModifiers::NONE), 10)
+    }
+
+    fn accepted_names(picker: &Picker) -> Vec<String> {
+        picker.accepted().iter().map(|e| e.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_cursor_and_toggle() {
+        let files = entries(&["a", "b", "c"]);
+        let mut picker = Picker::new(&files);
+
+        press(&mut picker, KeyCode::Char('k'));
+        assert_eq!(accepted_names(&picker), ["a"]);
+        press(&mut picker, KeyCode::Char('j'));
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Down);
+        assert_eq!(accepted_names(&picker), ["c"]);
+
+        // Space toggles and moves on; output keeps listing order
+        press(&mut picker, KeyCode::Char(' '));
+        press(&mut picker, KeyCode::Home);
+        press(&mut picker, KeyCode::Char(' '));
+        assert_eq!(accepted_names(&picker), ["a", "c"]);
+        assert_eq!(press(&mut picker, KeyCode::Enter), Action::Accept);
+    }
+
+    #[test]
+    fn test_filter() {
+        let files = entries(&["main.rs", "README.md", "lib.rs"]);
+        let mut picker = Picker::new(&files);
+
+        press(&mut picker, KeyCode::Char('/'));
+        // Letters go to the filter, not to the commands
+        for c in "RS".chars() {
+            assert_eq!(press(&mut picker, KeyCode::Char(c)), Action::Continue);
+        }
+        press(&mut picker, KeyCode::Enter);
+        press(&mut picker, KeyCode::Char('j'));
+        assert_eq!(accepted_names(&picker), ["lib.rs"]);
+
+        press(&mut picker, KeyCode::Char('/'));
+        press(&mut picker, KeyCode::Char('q'));
+        assert!(picker.accepted().is_empty());
+        press(&mut picker, KeyCode::Enter);
+        assert_eq!(press(&mut picker, KeyCode::Enter), Action::Continue);
+        press(&mut picker, KeyCode::Char('/'));
+        press(&mut picker, KeyCode::Esc);
+        assert_eq!(picker.visible.len(), 3);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let files = entries(&["a"]);
+        let mut picker = Picker::new(&files);
+        assert_eq!(press(&mut picker, KeyCode::Esc), Action::Cancel);
+        assert_eq!(press(&mut picker, KeyCode::Char('q')), Action::Cancel);
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(picker.handle_key(ctrl_c, 10), Action::Cancel);
+    }
+
+    #[test]
+    fn test_draw_marks_selection() {
+        let files = entries(&["a", "b"]);
+        let mut picker = Picker::new(&files);
+        press(&mut picker, KeyCode::Char(' '));
+
+        let mut screen = Vec::new();
+        picker
+            .draw(&mut screen, 80, 10, &Theme::default(), false)
+            .unwrap();
+        let screen = String::from_utf8(screen).unwrap();
+        assert!(screen.contains("2 entries  (1 selected)"));
+        assert!(screen.contains("[x] a"));
+        assert!(screen.contains("[ ] b"));
+    }
+
+    #[test]
+    fn test_clip_counts_terminal_columns() {
+        assert_eq!(clip("plain.txt", 5), "plain");
+        assert_eq!(clip("日本語.txt", 5), "日本");
+        assert_eq!(clip("cafe\u{301}.md", 4), "cafe\u{301}");
+        assert_eq!(clip("short", 80), "short");
+    }
+}
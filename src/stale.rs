@@ -0,0 +1,197 @@
+//! # Stale Files Report Module
+//!
+//! This module implements `bestls stale`, a cleanup report listing the entries that
+//! were not modified (or, with `--access-time`, not accessed) within a time window.
+//!
+//! ## Report
+//!
+//! - Only files and symlinks are reported; a directory's own timestamp says nothing
+//!   about its contents
+//! - Entries are sorted oldest first and show their age in whole days
+//! - The footer (and the JSON `total_bytes`) is the reclaimable size of all entries
+
+use crate::color::Theme;
+use crate::fsops::{FileEntry, FileType};
+use bytesize::ByteSize;
+use serde::Serialize;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tabled::builder::Builder;
+use tabled::settings::object::{Columns, Rows};
+use tabled::settings::Style;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// A stale entry with its age
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleEntry {
+    /// Path relative to the listed directory
+    pub path: String,
+    /// Whole days since the entry was last modified (or accessed)
+    pub age_days: u64,
+    #[serde(flatten)]
+    pub entry: FileEntry,
+    /// Timestamp the age was computed from (used for sorting)
+    #[serde(skip)]
+    time: SystemTime,
+}
+
+/// The `bestls stale` result
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleReport {
+    pub entries: Vec<StaleEntry>,
+    pub count: usize,
+    /// Cumulative size of all stale entries in bytes
+    pub total_bytes: u64,
+    /// Human-readable cumulative size
+    pub total_size: String,
+}
+
+/// Select the entries of a listing that are older than `older_than`, oldest first.
+///
+/// # Arguments
+///
+/// * `entries` - Entries from a flat or recursive listing of `root`
+/// * `root` - Listed directory, used to compute relative paths
+/// * `older_than` - Minimum age of a reported entry
+/// * `now` - Reference time for ages
+/// * `use_atime` - Use the access time instead of the modification time; entries
+///   without a recorded access time fall back to their modification time
+pub fn find_stale(
+    entries: Vec<FileEntry>,
+    root: &Path,
+    older_than: Duration,
+    now: SystemTime,
+    use_atime: bool,
+) -> StaleReport {
+    let mut stale: Vec<StaleEntry> = entries
+        .into_iter()
+        .filter(|e| e.e_type != FileType::Directory)
+        .filter_map(|entry| {
+            let time = if use_atime {
+                entry.accessed_time.or(entry.modified_time)
+            } else {
+                entry.modified_time
+            }?;
+            // Timestamps in the future are never stale
+            let age = now.duration_since(time).ok()?;
+            if age < older_than {
+                return None;
+            }
+
+            let path = entry
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&entry.path)
+                .to_string_lossy()
+                .to_string();
+            Some(StaleEntry {
+                path,
+                age_days: age.as_secs() / SECONDS_PER_DAY,
+                entry,
+                time,
+            })
+        })
+        .collect();
+
+    stale.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.path.cmp(&b.path)));
+
+    let total_bytes = stale.iter().map(|s| s.entry.len_bytes).sum();
+    StaleReport {
+        count: stale.len(),
+        entries: stale,
+        total_bytes,
+        total_size: ByteSize(total_bytes).to_string(),
+    }
+}
+
+/// Render the report as a table followed by a reclaimable-size footer
+pub fn format_report(report: &StaleReport, use_color: bool, ascii: bool, theme: &Theme) -> String {
+    let footer = format!(
+        "{} stale {}, {} reclaimable",
+        report.count,
+        if report.count == 1 {
+            "entry"
+        } else {
+            "entries"
+        },
+        report.total_size
+    );
+    if report.entries.is_empty() {
+        return footer;
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(["Path", "Age (days)", "Size", "Modified"]);
+    for stale in &report.entries {
+        builder.push_record([
+            stale.path.clone(),
+            stale.age_days.to_string(),
+            stale.entry.human_size.clone(),
+            stale.entry.modified.clone(),
+        ]);
+    }
+
+    let mut table = builder.build();
+    if ascii {
+        table.with(Style::ascii());
+    } else {
+        table.with(Style::rounded());
+    }
+    if use_color {
+        table.modify(Columns::first(), theme.table.name.to_tabled_color());
+        table.modify(Columns::one(2), theme.table.size.to_tabled_color());
+        table.modify(Columns::one(3), theme.table.date.to_tabled_color());
+        table.modify(Rows::first(), theme.table.header.to_tabled_color());
+    }
+
+    format!("{}\n{}", table, footer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    fn entry(name: &str, e_type: FileType, len_bytes: u64, age_days: u32) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            e_type,
+            len_bytes,
+            path: PathBuf::from("/root/dir").join(name),
+            modified_time: Some(now() - DAY * age_days),
+            accessed_time: Some(now() - DAY),
+            ..Default::default()
+        }
+    }
+
+    fn now() -> SystemTime {
+        SystemTime::UNIX_EPOCH + DAY * 20_000
+    }
+
+    #[test]
+    fn test_find_stale_oldest_first() {
+        let entries = vec![
+            entry("recent.txt", FileType::File, 1, 10),
+            entry("old.txt", FileType::File, 100, 200),
+            entry("older.txt", FileType::File, 50, 400),
+            entry("olddir", FileType::Directory, 4096, 500),
+        ];
+
+        let report = find_stale(entries, Path::new("/root/dir"), DAY * 180, now(), false);
+        let paths: Vec<&str> = report.entries.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["older.txt", "old.txt"]);
+        assert_eq!(report.entries[0].age_days, 400);
+        assert_eq!(report.count, 2);
+        assert_eq!(report.total_bytes, 150);
+    }
+
+    #[test]
+    fn test_find_stale_access_time() {
+        let entries = vec![entry("old.txt", FileType::File, 1, 200)];
+        let report = find_stale(entries, Path::new("/root/dir"), DAY * 180, now(), true);
+        assert!(report.entries.is_empty());
+    }
+}
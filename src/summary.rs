@@ -0,0 +1,103 @@
+//! # Summary Module
+//!
+//! `--summary` replaces the listing with its totals:
+//!
+//! ```text
+//! 128 files, 14 directories, 3 symlinks, 2.4 GB total
+//! ```
+//!
+//! The totals are taken after filtering, so `bestls --summary --filter-ext rs` counts
+//! the Rust files in a directory and `-R` counts a whole tree. The size total covers
+//! files and symlinks; a directory's own size (or its `--total-size` total, which
+//! would count its contents twice in a recursive listing) is left out. With `--json`,
+//! the totals are one JSON object.
+
+use crate::fsops::{FileEntry, FileType};
+use bytesize::ByteSize;
+use serde::Serialize;
+
+/// Totals of a listing
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub files: u64,
+    pub directories: u64,
+    pub symlinks: u64,
+    /// Cumulative size of the files and symlinks in bytes
+    pub total_bytes: u64,
+    /// Human-readable cumulative size
+    pub total_size: String,
+}
+
+/// Count the entries of a listing by type and add up their sizes.
+pub fn summarize(entries: &[FileEntry]) -> Summary {
+    let mut summary = Summary::default();
+    for entry in entries {
+        match entry.e_type {
+            FileType::File => summary.files += 1,
+            FileType::Directory => summary.directories += 1,
+            FileType::Symlink => summary.symlinks += 1,
+        }
+        if entry.e_type != FileType::Directory {
+            summary.total_bytes = summary.total_bytes.saturating_add(entry.len_bytes);
+        }
+    }
+    summary.total_size = ByteSize(summary.total_bytes).to_string();
+    summary
+}
+
+/// Render the summary as a single line.
+pub fn format_summary(summary: &Summary) -> String {
+    format!(
+        "{}, {}, {}, {} total",
+        count(summary.files, "file", "files"),
+        count(summary.directories, "directory", "directories"),
+        count(summary.symlinks, "symlink", "symlinks"),
+        summary.total_size
+    )
+}
+
+fn count(n: u64, singular: &str, plural: &str) -> String {
+    format!("{} {}", n, if n == 1 { singular } else { plural })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(e_type: FileType, len_bytes: u64) -> FileEntry {
+        FileEntry {
+            e_type,
+            len_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summarize_counts_by_type() {
+        let entries = [
+            entry(FileType::File, 1000),
+            entry(FileType::File, 500),
+            entry(FileType::Directory, 4096),
+            entry(FileType::Symlink, 12),
+        ];
+        let summary = summarize(&entries);
+        assert_eq!(
+            (summary.files, summary.directories, summary.symlinks),
+            (2, 1, 1)
+        );
+        // Directory sizes are not part of the total
+        assert_eq!(summary.total_bytes, 1512);
+        assert_eq!(
+            format_summary(&summary),
+            "2 files, 1 directory, 1 symlink, 1.5 KB total"
+        );
+    }
+
+    #[test]
+    fn test_empty_summary() {
+        assert_eq!(
+            format_summary(&summarize(&[])),
+            "0 files, 0 directories, 0 symlinks, 0 B total"
+        );
+    }
+}
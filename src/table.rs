@@ -6,13 +6,14 @@
 //!
 //! ## Key Components
 //!
-//! - [`DisplayEntry`]: Internal struct for table formatting (derived from [`FileEntry`])
+//! - [`TableColumn`]: The columns a table can show, selectable with `--columns`
 //! - [`format_table`]: Main function for rendering and formatting tables as strings
+//! - [`format_fzf`]: Tab-separated lines for `--format fzf`
 //!
 //! ## Features
 //!
 //! * **Colorized Output**: Different colors for columns (names, sizes, dates, headers)
-//! * **Rounded Borders**: Uses rounded table style for modern appearance
+//! * **Border Styles**: Rounded borders by default, others picked with `--style`
 //! * **Custom Column Names**: User-friendly column headers
 //! * **Flexible Formatting**: Automatically adjusts to terminal width
 //!
@@ -31,11 +32,11 @@
 //!
 //! ```rust
 //! use bestls::table::format_table;
-//! use bestls::fsops::{get_files, FileEntry};
+//! use bestls::fsops::{get_files, FileEntry, ListOptions};
 //! use std::path::Path;
 //!
 //! let path = Path::new(".");
-//! let files = get_files(&path, false)?;
+//! let files = get_files(&path, &ListOptions::default())?.entries;
 //!
 //! // Format and print table
 //! let output = format_table(&files, None, false, true);
@@ -57,14 +58,15 @@
 //!
 //! ## Design Choices
 //!
-//! ### Internal DisplayEntry Struct
+//! ### Dynamic Rows
 //!
-//! The module uses an internal [`DisplayEntry`] struct rather than implementing [`Tabled`]
-//! directly on [`FileEntry`] for several reasons:
+//! Rows are built cell by cell from a list of [`TableColumn`]s rather than from a
+//! fixed `Tabled` struct, so `--columns` and the optional column flags can pick and
+//! order the columns freely without affecting the core data:
 //!
 //! 1. **Separation of Concerns**: Keeps formatting logic separate from data structures
-//! 2. **Customization**: Allows custom column names and ordering without affecting core data
-//! 3. **Future Flexibility**: Easy to modify display without changing serialization
+//! 2. **Customization**: Column names and order are chosen at run time
+//! 3. **Coloring by Kind**: Colors follow the column, wherever it ends up
 //!
 //! ### Color Selection
 //!
@@ -73,63 +75,521 @@
 //! - Help users quickly identify different types of information
 //! - Maintain professional appearance while being visually helpful
 
-use crate::color::Theme;
-use crate::fsops::FileEntry;
-use std::collections::HashSet;
-use tabled::settings::object::{Columns, Rows};
-use tabled::settings::Style;
-use tabled::{Table, Tabled};
-
-/// Internal representation of a file entry optimized for table display.
-///
-/// This struct is derived from [`FileEntry`] and is specifically designed for use with
-/// the `tabled` crate. It provides custom column names and ordering for optimal
-/// table presentation.
-///
-/// # Fields
-///
-/// All fields are strings optimized for display:
-///
-/// * `name` - Filename (displayed as "Name")
-/// * `e_type` - File type as string (displayed as "Type")
-/// * `human_size` - Human-readable size (displayed as "Size")
-/// * `modified` - Formatted modification time (displayed as "Modified")
-/// * `permissions` - Permission string (displayed as "Permissions")
-/// * `owner` - Owner name (displayed as "Owner")
-/// * `group` - Group name (displayed as "Group")
-///
-/// # Design
-///
-/// This struct is intentionally kept private as it's an implementation detail.
-/// External code should use [`format_table`] to create output strings that can be printed.
-///
-/// # Attributes
-///
-/// Uses `tabled(rename = "...")` attributes to provide user-friendly column headers
-/// that are more readable than the field names.
-#[derive(Tabled)]
-struct DisplayEntry {
-    /// Filename or directory name
-    #[tabled(rename = "Name")]
-    name: String,
-    /// File type (File, Directory, or Symlink)
-    #[tabled(rename = "Type")]
-    e_type: String,
-    /// Human-readable file size
-    #[tabled(rename = "Size")]
-    human_size: String,
-    /// Formatted modification date and time
-    #[tabled(rename = "Modified")]
-    modified: String,
-    /// File permissions string
-    #[tabled(rename = "Permissions")]
-    permissions: String,
-    /// File owner name
-    #[tabled(rename = "Owner")]
-    owner: String,
-    /// File group name
-    #[tabled(rename = "Group")]
-    group: String,
+use crate::cli::{OwnerFormat, SizeBarScale, TableStyle};
+use crate::color::{get_file_color, Theme};
+use crate::fsops::{search_matches, FileEntry, FileType};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tabled::builder::Builder;
+use tabled::grid::records::PeekableRecords;
+use tabled::settings::object::{Columns, Rows, Segment};
+use tabled::settings::themes::Theme as Borders;
+use tabled::settings::{Color, Format, Style};
+use tabled::Table;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// A table column, as named by `--columns`.
+///
+/// The extra columns are in the default layout only when their flag is given; a
+/// `--columns` list shows exactly the named columns, in its order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TableColumn {
+    /// Filename or directory name (`name`)
+    Name,
+    /// File, Directory, or Symlink (`type`)
+    Type,
+    /// Human-readable size (`size`)
+    Size,
+    /// Proportional size bar of `--size-bars` (`size_bar`)
+    SizeBar,
+    /// Directory entry count of `--count-items` (`items`)
+    Items,
+    /// Formatted modification time (`modified`, or `date`)
+    Modified,
+    /// Formatted creation time of `--created` (`created`)
+    Created,
+    /// Permission string (`permissions`)
+    Permissions,
+    /// Owner name, or `owner:group` with `--owner-format combined` (`owner`)
+    Owner,
+    /// Group name (`group`)
+    Group,
+    /// Nesting depth of flat recursive listings (`depth`)
+    Depth,
+    /// Last commit of `--git-log` (`last_commit`)
+    LastCommit,
+    /// First line of `--preview` (`preview`)
+    Preview,
+    /// An `--exec-column`, by label
+    Exec(String),
+}
+
+/// `--columns` names of the built-in columns, in their default order
+pub const COLUMN_NAMES: &[&str] = &[
+    "name",
+    "type",
+    "size",
+    "size_bar",
+    "items",
+    "modified",
+    "created",
+    "permissions",
+    "owner",
+    "group",
+    "depth",
+    "last_commit",
+    "preview",
+];
+
+impl TableColumn {
+    /// The built-in column with a `--columns` name (case-insensitive)
+    fn from_name(name: &str) -> Option<Self> {
+        let column = match name.to_lowercase().as_str() {
+            "name" => TableColumn::Name,
+            "type" => TableColumn::Type,
+            "size" => TableColumn::Size,
+            "size_bar" => TableColumn::SizeBar,
+            "items" => TableColumn::Items,
+            "modified" | "date" => TableColumn::Modified,
+            "created" => TableColumn::Created,
+            "permissions" => TableColumn::Permissions,
+            "owner" => TableColumn::Owner,
+            "group" => TableColumn::Group,
+            "depth" => TableColumn::Depth,
+            "last_commit" => TableColumn::LastCommit,
+            "preview" => TableColumn::Preview,
+            _ => return None,
+        };
+        Some(column)
+    }
+
+    /// Header cell
+    fn header(&self) -> &str {
+        match self {
+            TableColumn::Name => "Name",
+            TableColumn::Type => "Type",
+            TableColumn::Size => "Size",
+            TableColumn::SizeBar => "",
+            TableColumn::Items => "Items",
+            TableColumn::Modified => "Modified",
+            TableColumn::Created => "Created",
+            TableColumn::Permissions => "Permissions",
+            TableColumn::Owner => "Owner",
+            TableColumn::Group => "Group",
+            TableColumn::Depth => "Depth",
+            TableColumn::LastCommit => "Last Commit",
+            TableColumn::Preview => "Preview",
+            TableColumn::Exec(label) => label,
+        }
+    }
+}
+
+/// Parse a comma-separated `--columns` list such as `name,size,modified`.
+///
+/// `exec_labels` holds the `--exec-column` labels, which are valid names too. Repeated
+/// names are shown once.
+///
+/// # Errors
+///
+/// Returns a message listing the valid names for an unknown one.
+pub fn parse_columns(spec: &str, exec_labels: &[String]) -> Result<Vec<TableColumn>, String> {
+    let mut columns: Vec<TableColumn> = Vec::new();
+    for name in spec.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let column = match exec_labels.iter().find(|label| *label == name) {
+            Some(label) => TableColumn::Exec(label.clone()),
+            None => TableColumn::from_name(name).ok_or_else(|| {
+                let valid: Vec<&str> = COLUMN_NAMES
+                    .iter()
+                    .copied()
+                    .chain(exec_labels.iter().map(String::as_str))
+                    .collect();
+                format!(
+                    "unknown column '{}' (expected one of: {})",
+                    name,
+                    valid.join(", ")
+                )
+            })?,
+        };
+        if !columns.contains(&column) {
+            columns.push(column);
+        }
+    }
+
+    if columns.is_empty() {
+        return Err("--columns needs at least one column name".to_string());
+    }
+    Ok(columns)
+}
+
+/// The columns shown without `--columns`: the standard seven plus those the flags in
+/// `options` turn on
+fn default_columns(options: &TableOptions) -> Vec<TableColumn> {
+    let mut columns = vec![TableColumn::Name, TableColumn::Type, TableColumn::Size];
+    if options.size_bars.is_some() {
+        columns.push(TableColumn::SizeBar);
+    }
+    if options.items {
+        columns.push(TableColumn::Items);
+    }
+    columns.push(TableColumn::Modified);
+    if options.created {
+        columns.push(TableColumn::Created);
+    }
+    columns.extend([TableColumn::Permissions, TableColumn::Owner]);
+    // Group is either folded into Owner or dropped
+    if options.owner_format == OwnerFormat::Separate {
+        columns.push(TableColumn::Group);
+    }
+    if options.depth {
+        columns.push(TableColumn::Depth);
+    }
+    if options.git_log {
+        columns.push(TableColumn::LastCommit);
+    }
+    if options.preview {
+        columns.push(TableColumn::Preview);
+    }
+    columns.extend(options.exec_columns.iter().cloned().map(TableColumn::Exec));
+    columns
+}
+
+/// Optional table columns toggled from the command line.
+#[derive(Debug, Default, Clone)]
+pub struct TableOptions {
+    /// Append a "Last Commit" column (`--git-log`)
+    pub git_log: bool,
+    /// Append a "Preview" column with the first line of text files (`--preview`)
+    pub preview: bool,
+    /// Labels of custom `--exec-column` columns, in display order
+    pub exec_columns: Vec<String>,
+    /// Per-row change markers (`--watch`), indexed like the entries; empty for none
+    pub highlights: Vec<Option<RowHighlight>>,
+    /// Add a proportional size bar column next to Size (`--size-bars`)
+    pub size_bars: Option<SizeBarScale>,
+    /// Directory sizes are the totals below them (`--total-size`), so they get bars too
+    pub total_size: bool,
+    /// Draw `--tree` connectors with plain ASCII instead of box-drawing characters
+    pub ascii: bool,
+    /// Border style (`--style`)
+    pub style: TableStyle,
+    /// Render a GitHub-flavored Markdown table (`--format markdown`)
+    pub markdown: bool,
+    /// How the Owner and Group columns are laid out (`--owner-format`)
+    pub owner_format: OwnerFormat,
+    /// With the combined format, show just the owner when the group has the same name
+    pub omit_same_group: bool,
+    /// Append a "Depth" column with each entry's nesting depth (`--tree`)
+    pub depth: bool,
+    /// Add a "Created" column after Modified (`--created`)
+    pub created: bool,
+    /// Add an "Items" column after Size with each directory's entry count
+    /// (`--count-items`)
+    pub items: bool,
+    /// `--search` terms to highlight in names when colors are enabled
+    pub search: Vec<String>,
+    /// Leave out the header row (`--no-header`)
+    pub no_header: bool,
+    /// How long names are fitted into the Name column (`--max-name-width`, `--wrap`)
+    pub name_width: Option<NameWidth>,
+    /// Width of the terminal the table is shown on, for an automatic [`NameWidth`]
+    pub terminal_width: Option<usize>,
+}
+
+/// Width limit of the Name column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NameWidth {
+    /// Wrap long names onto further lines instead of cutting them (`--wrap`)
+    pub wrap: bool,
+    /// Column width in terminal columns; `None` narrows the column just enough for the
+    /// table to fit the terminal
+    pub max: Option<usize>,
+}
+
+/// Narrowest Name column an automatic [`NameWidth`] leaves
+const MIN_NAME_WIDTH: usize = 8;
+
+/// Items cell: the entry count of a directory, `?` when it could not be read
+fn items_cell(entry: &FileEntry) -> String {
+    match (entry.items, &entry.e_type) {
+        (Some(items), _) => items.to_string(),
+        (None, FileType::Directory) => "?".to_string(),
+        (None, _) => "-".to_string(),
+    }
+}
+
+/// Cut a cell `value` (a file name or commit summary) to at most `max` terminal columns,
+/// ending it with `…` when anything was left out.
+///
+/// Widths are measured per grapheme, so wide (CJK, emoji) characters count twice and
+/// combining marks and emoji sequences are never split.
+fn truncate_cell(value: &str, max: usize) -> Cow<'_, str> {
+    if value.width() <= max {
+        return Cow::Borrowed(value);
+    }
+    // Room for the ellipsis
+    let budget = max.saturating_sub(1);
+    let mut width = 0;
+    let mut cut = String::new();
+    for grapheme in value.graphemes(true) {
+        width += grapheme.width();
+        if width > budget {
+            break;
+        }
+        cut.push_str(grapheme);
+    }
+    cut.push('…');
+    Cow::Owned(cut)
+}
+
+/// Break `name` into lines of at most `max` terminal columns.
+///
+/// Lines break between graphemes, measured like [`truncate_cell`]; a grapheme wider
+/// than `max` gets a line of its own.
+fn wrap_name(name: &str, max: usize) -> String {
+    let mut wrapped = String::with_capacity(name.len());
+    let mut width = 0;
+    for grapheme in name.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        if width > 0 && width + grapheme_width > max {
+            wrapped.push('\n');
+            width = 0;
+        }
+        wrapped.push_str(grapheme);
+        width += grapheme_width;
+    }
+    wrapped
+}
+
+/// Width of the Name column (`column`) that makes `table` fit `terminal` columns, or
+/// `None` if it fits already or the column cannot narrow any further
+fn fitted_name_width(table: &Table, column: usize, terminal: usize) -> Option<usize> {
+    let overflow = table.total_width().checked_sub(terminal)?;
+    let records = table.get_records();
+    let widest = (0..table.count_rows())
+        .map(|row| records.get_width((row, column).into()))
+        .max()?;
+    let fitted = widest.saturating_sub(overflow).max(MIN_NAME_WIDTH);
+    (overflow > 0 && fitted < widest).then_some(fitted)
+}
+
+/// Bold and underline, switched off without resetting the Name column's color
+const SEARCH_HIGHLIGHT: (&str, &str) = ("\u{1b}[1;4m", "\u{1b}[22;24m");
+
+/// Wrap the parts of `name` matching a `--search` term in [`SEARCH_HIGHLIGHT`]
+fn highlight_search(name: &str, terms: &[String]) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut copied = 0;
+    for range in search_matches(name, terms) {
+        out.push_str(&name[copied..range.start]);
+        out.push_str(SEARCH_HIGHLIGHT.0);
+        out.push_str(&name[range.clone()]);
+        out.push_str(SEARCH_HIGHLIGHT.1);
+        copied = range.end;
+    }
+    out.push_str(&name[copied..]);
+    out
+}
+
+/// Render the single "Owner" cell of `--owner-format combined`
+fn owner_group_cell(entry: &FileEntry, omit_same_group: bool) -> String {
+    if omit_same_group && entry.owner == entry.group {
+        entry.owner.clone()
+    } else {
+        format!("{}:{}", entry.owner, entry.group)
+    }
+}
+
+/// How a row changed since the previous `--watch` refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowHighlight {
+    /// Entry appeared since the previous refresh (green)
+    Added,
+    /// Entry size or modification time changed (yellow)
+    Changed,
+    /// Entry disappeared; shown dimmed and struck through for one refresh
+    Removed,
+}
+
+impl RowHighlight {
+    /// Marker appended to the name when colors are disabled
+    fn marker(self) -> &'static str {
+        match self {
+            RowHighlight::Added => "[new]",
+            RowHighlight::Changed => "[changed]",
+            RowHighlight::Removed => "[removed]",
+        }
+    }
+
+    /// Row color used when colors are enabled
+    fn color(self) -> Color {
+        match self {
+            RowHighlight::Added => Color::FG_GREEN,
+            RowHighlight::Changed => Color::FG_YELLOW,
+            RowHighlight::Removed => Color::new("\u{1b}[2;9m", "\u{1b}[0m"),
+        }
+    }
+}
+
+/// Maximum number of terminal columns of a commit summary shown in the table
+const COMMIT_SUMMARY_WIDTH: usize = 40;
+
+/// Render the compact "Last Commit" cell for an entry ("-" for untracked entries)
+fn last_commit_cell(entry: &FileEntry) -> String {
+    match (&entry.last_commit_hash, &entry.last_commit_date) {
+        (Some(hash), Some(date)) => {
+            let summary = entry.last_commit_summary.as_deref().unwrap_or_default();
+            let summary = truncate_cell(summary, COMMIT_SUMMARY_WIDTH);
+            format!("{} {} {}", hash, date, summary)
+        }
+        _ => "-".to_string(),
+    }
+}
+
+/// Width of a full `--size-bars` bar, in cells
+const SIZE_BAR_WIDTH: usize = 10;
+
+/// Render the size bar cells for every entry.
+///
+/// Directories have no meaningful size of their own, so they get an empty cell and
+/// are left out of the scale, unless `total_size` made their size the total below
+/// them. In that case the total scale only adds up the top-level entries, whose
+/// sizes already cover everything listed under them.
+fn size_bar_cells(entries: &[FileEntry], scale: SizeBarScale, total_size: bool) -> Vec<String> {
+    let has_bar = |e: &FileEntry| total_size || e.e_type != FileType::Directory;
+    let sized = entries.iter().filter(|e| has_bar(e));
+    let reference = match scale {
+        SizeBarScale::Max => sized.map(|e| e.len_bytes).max().unwrap_or(0),
+        SizeBarScale::Total if total_size => {
+            sized.filter(|e| e.depth == 0).map(|e| e.len_bytes).sum()
+        }
+        SizeBarScale::Total => sized.map(|e| e.len_bytes).sum(),
+    };
+
+    entries
+        .iter()
+        .map(|e| {
+            if !has_bar(e) || reference == 0 {
+                return String::new();
+            }
+            let cells = (e.len_bytes as f64 / reference as f64 * SIZE_BAR_WIDTH as f64).round();
+            // Any non-empty entry gets at least one cell so it stands out from empty ones
+            let cells = if e.len_bytes > 0 {
+                (cells as usize).max(1)
+            } else {
+                0
+            };
+            "█".repeat(cells)
+        })
+        .collect()
+}
+
+/// Keep a Markdown table cell on one line and out of the column structure: pipes are
+/// escaped and line breaks become spaces
+fn escape_markdown_cell(cell: &str) -> String {
+    cell.replace('|', "\\|").replace(['\r', '\n'], " ")
+}
+
+/// The `tabled` borders of a `--style`
+fn borders(style: TableStyle) -> Borders {
+    match style {
+        TableStyle::Rounded => Borders::from_style(Style::rounded()),
+        TableStyle::Ascii => Borders::from_style(Style::ascii()),
+        TableStyle::Modern => Borders::from_style(Style::modern()),
+        TableStyle::Psql => Borders::from_style(Style::psql()),
+        TableStyle::Markdown => Borders::from_style(Style::markdown()),
+        TableStyle::Blank => Borders::from_style(Style::blank()),
+        TableStyle::Compact => Borders::from_style(Style::empty()),
+    }
+}
+
+/// Format compact output as string (internal helper)
+fn format_compact_inner(entries: &[FileEntry]) -> String {
+    entries
+        .iter()
+        .map(|f| f.name.clone())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Split a recursive listing into one group per directory (`--group-by-dir`).
+///
+/// Entries keep their order within each group, so a sorted listing gives sorted
+/// groups. Groups come in `ls -R` order: the listed directory first, then each
+/// subdirectory right after its parent's group, in the parent's order. Directories
+/// whose own entry was filtered out follow at the end.
+pub fn group_by_directory(entries: &[FileEntry]) -> Vec<(PathBuf, Vec<FileEntry>)> {
+    let mut groups: Vec<(PathBuf, Vec<FileEntry>)> = Vec::new();
+    let mut index: HashMap<PathBuf, usize> = HashMap::new();
+    for entry in entries {
+        let parent = entry.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let i = *index.entry(parent.clone()).or_insert_with(|| {
+            groups.push((parent, Vec::new()));
+            groups.len() - 1
+        });
+        groups[i].1.push(entry.clone());
+    }
+
+    // The listed directory holds the shallowest entries
+    let root = entries
+        .iter()
+        .min_by_key(|e| e.depth)
+        .and_then(|e| e.path.parent())
+        .and_then(|root| index.get(root).copied());
+    let mut placed = vec![false; groups.len()];
+    let mut order = Vec::with_capacity(groups.len());
+    for start in root.into_iter().chain(0..groups.len()) {
+        let mut stack = vec![start];
+        while let Some(i) = stack.pop() {
+            if std::mem::replace(&mut placed[i], true) {
+                continue;
+            }
+            order.push(i);
+            // Reversed, so the first subdirectory is popped first
+            let children = groups[i]
+                .1
+                .iter()
+                .filter_map(|e| index.get(&e.path).copied());
+            stack.extend(children.rev());
+        }
+    }
+
+    let mut groups: Vec<Option<(PathBuf, Vec<FileEntry>)>> = groups.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| groups[i].take()).collect()
+}
+
+/// Escape the characters that would split an fzf line or field
+fn fzf_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Format entries as `name<TAB>size<TAB>modified` lines for `fzf --ansi`.
+///
+/// There is no header, border, or footer, and the name comes first so fzf's
+/// default matching works on it. Backslashes, tabs, and newlines in names are
+/// escaped (`\\`, `\t`, `\n`) so every entry stays on one line with three fields.
+pub fn format_fzf(entries: &[FileEntry], use_color: bool, theme: &Theme) -> String {
+    entries
+        .iter()
+        .map(|e| {
+            let fields = [fzf_field(&e.name), e.human_size.clone(), e.modified.clone()];
+            if !use_color {
+                return fields.join("\t");
+            }
+            let colors = [get_file_color(e, theme), theme.table.size, theme.table.date];
+            fields
+                .iter()
+                .zip(colors)
+                .map(|(field, color)| color.to_tabled_color().colorize(field))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 /// Display a collection of file entries as a colorized, formatted table.
@@ -138,10 +598,6 @@ struct DisplayEntry {
 /// colorized table with rounded borders. It's the primary output method for bestls when
 /// not using JSON format.
 ///
-/// # Arguments
-///
-/// * `entries` - Vector of file entries to display
-///
 /// # Output Format
 ///
 /// The table includes the following columns:
@@ -154,9 +610,13 @@ struct DisplayEntry {
 /// 6. **Owner** - File owner name
 /// 7. **Group** - File group name
 ///
+/// Flags add further columns, and `columns` (`--columns`) replaces the layout with
+/// the listed [`TableColumn`]s in their order.
+///
 /// # Styling
 ///
-/// * **Borders**: Rounded style with Unicode box-drawing characters
+/// * **Borders**: Rounded style with Unicode box-drawing characters, or the
+///   [`TableStyle`] in `options`
 /// * **Headers**: Bright green for clear separation
 /// * **Colors**: Carefully chosen for readability on various terminal themes
 ///
@@ -165,16 +625,16 @@ struct DisplayEntry {
 /// ## Basic Table Display
 ///
 /// ```rust
-/// use bestls::table::format_table;
-/// use bestls::fsops::{get_files, FileEntry, FileType};
+/// use bestls::table::{format_table, TableOptions};
+/// use bestls::fsops::{get_files, FileEntry, FileType, ListOptions};
 /// use std::path::Path;
 ///
 /// // Get files from current directory
 /// let path = Path::new(".");
-/// let files = get_files(&path, false)?;
+/// let files = get_files(&path, &ListOptions::default())?.entries;
 ///
 /// // Format as a table string with default theme
-/// let output = format_table(&files, None, false, true, None);
+/// let output = format_table(&files, None, false, true, None, &TableOptions::default());
 /// println!("{}", output);
 /// # Ok::<(), std::io::Error>(())
 /// ```
@@ -182,7 +642,7 @@ struct DisplayEntry {
 /// ## With Custom File Entries
 ///
 /// ```rust
-/// use bestls::table::format_table;
+/// use bestls::table::{format_table, TableOptions};
 /// use bestls::fsops::{FileEntry, FileType};
 ///
 /// let entries = vec![
@@ -198,7 +658,7 @@ struct DisplayEntry {
 ///     }
 /// ];
 ///
-/// let output = format_table(&entries, None, false, true, None);
+/// let output = format_table(&entries, None, false, true, None, &TableOptions::default());
 /// println!("{}", output);
 /// ```
 ///
@@ -210,7 +670,8 @@ struct DisplayEntry {
 ///
 /// # Terminal Compatibility
 ///
-/// The table uses Unicode box-drawing characters which are supported by most modern terminals.
+/// The default borders use Unicode box-drawing characters, which are supported by most
+/// modern terminals; `--style ascii` avoids them.
 /// Colors use ANSI escape codes that work with virtually all terminal emulators.
 ///
 /// # Empty Input
@@ -222,75 +683,133 @@ struct DisplayEntry {
 /// │ Name │ Type │ Size │ Modified │ Permissions │ Owner │ Group │
 /// ╰──────┴──────┴──────┴──────────┴─────────────┴───────┴───────╯
 /// ```
-/// Parse column names from comma-separated string.
-///
-/// # Arguments
-/// * `cols` - Comma-separated column specification (e.g., "name,size,date")
-///
-/// # Returns
-/// A HashSet of valid column names
-///
-/// # Note
-/// This function is reserved for future column filtering implementation.
-#[allow(dead_code)]
-pub fn parse_columns(cols: &str) -> HashSet<String> {
-    cols.split(',')
-        .map(|s| s.trim().to_lowercase())
-        .filter(|s| {
-            matches!(
-                s.as_str(),
-                "name" | "type" | "size" | "date" | "permissions" | "owner" | "group"
-            )
-        })
-        .collect()
-}
-
-/// Format compact output as string (internal helper)
-fn format_compact_inner(entries: &[FileEntry]) -> String {
-    entries
-        .iter()
-        .map(|f| f.name.clone())
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
-/// Format table output as a string
 ///
 /// # Arguments
 /// * `entries` - Vector of file entries to format
-/// * `columns` - Optional column selection (reserved for future use)
+/// * `columns` - Columns to show, in order (`--columns`); the default layout if None
 /// * `compact` - If true, return single-column format
 /// * `use_color` - If true, apply color styling
 /// * `theme` - Optional theme for colors (uses default if None)
+/// * `options` - Optional extra columns
 pub fn format_table(
     entries: &[FileEntry],
-    columns: Option<String>,
+    columns: Option<&[TableColumn]>,
     compact: bool,
     use_color: bool,
     theme: Option<&Theme>,
+    options: &TableOptions,
 ) -> String {
     if compact {
         return format_compact_inner(entries);
     }
 
-    // Column selection reserved for future implementation; not currently wired up
-    let _ = columns;
+    // Markdown is pasted elsewhere, where escape codes would show up as text
+    let use_color = use_color && !options.markdown;
 
-    let data: Vec<DisplayEntry> = entries
-        .iter()
-        .map(|e: &FileEntry| DisplayEntry {
-            name: e.name.clone(),
-            e_type: e.e_type.to_string(),
-            human_size: e.human_size.clone(),
-            modified: e.modified.clone(),
-            permissions: e.permissions.clone(),
-            owner: e.owner.clone(),
-            group: e.group.clone(),
-        })
-        .collect();
+    let layout = match columns {
+        Some(columns) => columns.to_vec(),
+        None => default_columns(options),
+    };
+    let size_bars = layout.contains(&TableColumn::SizeBar).then(|| {
+        let scale = options.size_bars.unwrap_or(SizeBarScale::Max);
+        size_bar_cells(entries, scale, options.total_size)
+    });
+    let cell = |column: &TableColumn, i: usize, e: &FileEntry| -> String {
+        match column {
+            TableColumn::Name => {
+                let name = match options.name_width {
+                    Some(NameWidth {
+                        wrap: false,
+                        max: Some(max),
+                    }) => truncate_cell(&e.name, max),
+                    // A row of Markdown cannot span several lines
+                    Some(NameWidth {
+                        wrap: true,
+                        max: Some(max),
+                    }) if options.style != TableStyle::Markdown => {
+                        Cow::Owned(wrap_name(&e.name, max))
+                    }
+                    _ => Cow::Borrowed(e.name.as_str()),
+                };
+                let mut name = if use_color && !options.search.is_empty() {
+                    highlight_search(&name, &options.search)
+                } else {
+                    name.into_owned()
+                };
+                if e.encrypted == Some(true) {
+                    name.push_str(" [encrypted]");
+                }
+                if let (false, Some(Some(highlight))) = (use_color, options.highlights.get(i)) {
+                    name = format!("{} {}", name, highlight.marker());
+                }
+                name
+            }
+            TableColumn::Type if e.is_broken == Some(true) => format!("{} (broken)", e.e_type),
+            TableColumn::Type => e.e_type.to_string(),
+            TableColumn::Size => e.human_size.clone(),
+            TableColumn::SizeBar => size_bars
+                .as_ref()
+                .map(|bars| bars[i].clone())
+                .unwrap_or_default(),
+            TableColumn::Items => items_cell(e),
+            TableColumn::Modified => e.modified.clone(),
+            TableColumn::Created => e.created.clone().unwrap_or_else(|| "-".to_string()),
+            TableColumn::Permissions => e.permissions.clone(),
+            TableColumn::Owner => match options.owner_format {
+                OwnerFormat::Combined => owner_group_cell(e, options.omit_same_group),
+                OwnerFormat::Separate | OwnerFormat::OwnerOnly => e.owner.clone(),
+            },
+            TableColumn::Group => e.group.clone(),
+            TableColumn::Depth => e.depth.to_string(),
+            TableColumn::LastCommit => last_commit_cell(e),
+            TableColumn::Preview => e.preview.clone().unwrap_or_else(|| "-".to_string()),
+            TableColumn::Exec(label) => e
+                .custom_columns
+                .get(label)
+                .cloned()
+                .flatten()
+                .unwrap_or_else(|| "-".to_string()),
+        }
+    };
 
-    let mut table: Table = Table::new(data);
-    table.with(Style::rounded());
+    let mut builder = Builder::default();
+    if !options.no_header {
+        builder.push_record(layout.iter().map(TableColumn::header));
+    }
+    // Row index of the first entry
+    let first_row = usize::from(!options.no_header);
+    for (i, e) in entries.iter().enumerate() {
+        builder.push_record(layout.iter().map(|column| cell(column, i, e)));
+    }
+
+    let mut table: Table = builder.build();
+    let mut borders = borders(options.style);
+    // Drop the line under the header, unless it is one of the lines between all rows
+    if options.no_header && !borders.borders_has_horizontal() {
+        borders.remove_horizontal_lines();
+    }
+    let right_border = borders.borders_has_right();
+    table.with(borders);
+    if options.style == TableStyle::Markdown {
+        table.modify(Segment::all(), Format::content(escape_markdown_cell));
+    }
+
+    let name_column = layout.iter().position(|c| *c == TableColumn::Name);
+    if let (Some(fit @ NameWidth { max: None, .. }), Some(column), Some(terminal)) =
+        (options.name_width, name_column, options.terminal_width)
+    {
+        // Names are cut or wrapped while the cells are made, so start over with the width
+        if let Some(max) = fitted_name_width(&table, column, terminal) {
+            let options = TableOptions {
+                name_width: Some(NameWidth {
+                    max: Some(max),
+                    ..fit
+                }),
+                ..options.clone()
+            };
+            return format_table(entries, columns, compact, use_color, theme, &options);
+        }
+    }
 
     if use_color {
         // Use provided theme or create a default one with longer lifetime
@@ -303,11 +822,357 @@ pub fn format_table(
             }
         };
 
-        table.modify(Columns::first(), active_theme.table.name.to_tabled_color());
-        table.modify(Columns::one(2), active_theme.table.size.to_tabled_color());
-        table.modify(Columns::one(3), active_theme.table.date.to_tabled_color());
-        table.modify(Rows::first(), active_theme.table.header.to_tabled_color());
+        for (index, column) in layout.iter().enumerate() {
+            let color = match column {
+                TableColumn::Name => active_theme.table.name,
+                TableColumn::Size | TableColumn::Items => active_theme.table.size,
+                TableColumn::SizeBar => active_theme.table.size_bar,
+                TableColumn::Modified | TableColumn::Created => active_theme.table.date,
+                _ => continue,
+            };
+            table.modify(Columns::one(index), color.to_tabled_color());
+        }
+        if !options.no_header {
+            table.modify(Rows::first(), active_theme.table.header.to_tabled_color());
+        }
+
+        let type_column = layout.iter().position(|c| *c == TableColumn::Type);
+        for (i, e) in entries.iter().enumerate() {
+            if let (Some(column), Some(true)) = (type_column, e.is_broken) {
+                table.modify(
+                    (i + first_row, column),
+                    active_theme.file_types.broken_symlink.to_tabled_color(),
+                );
+            }
+        }
+
+        for (i, highlight) in options.highlights.iter().enumerate() {
+            if let Some(highlight) = highlight {
+                table.modify(Rows::one(i + first_row), highlight.color());
+            }
+        }
     }
 
+    if !right_border {
+        // Without a right border, the padding would end every line in spaces
+        return table
+            .to_string()
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
     table.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn entry(name: &str, e_type: FileType, len_bytes: u64) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            e_type,
+            len_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_owner_group_cell() {
+        let mut e = entry("a", FileType::File, 1);
+        e.owner = "alice".to_string();
+        e.group = "staff".to_string();
+        assert_eq!(owner_group_cell(&e, true), "alice:staff");
+
+        e.group = "alice".to_string();
+        assert_eq!(owner_group_cell(&e, false), "alice:alice");
+        assert_eq!(owner_group_cell(&e, true), "alice");
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let labels = ["Lines".to_string()];
+        assert_eq!(
+            parse_columns("Size, name,date,name,Lines", &labels).unwrap(),
+            [
+                TableColumn::Size,
+                TableColumn::Name,
+                TableColumn::Modified,
+                TableColumn::Exec("Lines".to_string())
+            ]
+        );
+
+        let error = parse_columns("name,sise", &labels).unwrap_err();
+        assert!(error.contains("unknown column 'sise'"), "{}", error);
+        assert!(error.contains("permissions"), "{}", error);
+        assert!(error.contains("Lines"), "{}", error);
+        assert!(parse_columns(" , ", &[]).is_err());
+    }
+
+    #[test]
+    fn test_columns_replace_default_layout() {
+        let mut e = entry("a.txt", FileType::File, 1);
+        e.group = "staff".to_string();
+        e.created = Some("Mon 15 Jan 2024 14:30:25".to_string());
+        // Flags only shape the default layout; the selection shows Group anyway
+        let options = TableOptions {
+            owner_format: OwnerFormat::OwnerOnly,
+            preview: true,
+            ..Default::default()
+        };
+        let columns = [TableColumn::Group, TableColumn::Created, TableColumn::Name];
+        let table = format_table(&[e], Some(&columns), false, false, None, &options);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[1], "│ Group │ Created                  │ Name  │");
+        assert_eq!(lines[3], "│ staff │ Mon 15 Jan 2024 14:30:25 │ a.txt │");
+    }
+
+    #[test]
+    fn test_truncate_cell() {
+        assert_eq!(truncate_cell("short.txt", 9), "short.txt");
+        assert_eq!(truncate_cell("longer.txt", 9), "longer.t…");
+        // CJK characters are two columns wide; a half-fitting one is dropped whole
+        assert_eq!(truncate_cell("日本語のファイル.txt", 8), "日本語…");
+        assert_eq!(truncate_cell("日本語のファイル.txt", 9), "日本語の…");
+        // Combining marks and emoji sequences stay with their base character
+        assert_eq!(
+            truncate_cell("e\u{301}e\u{301}e\u{301}.txt", 3),
+            "e\u{301}e\u{301}…"
+        );
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        let name = format!("{}party.txt", family);
+        assert_eq!(truncate_cell(&name, 4), format!("{}p…", family));
+        assert_eq!(truncate_cell(&name, 2), "…");
+        assert_eq!(truncate_cell("🎉🎉🎉", 4), "🎉…");
+    }
+
+    #[test]
+    fn test_wrap_name() {
+        assert_eq!(wrap_name("abcdefg", 3), "abc\ndef\ng");
+        assert_eq!(wrap_name("日本語.txt", 5), "日本\n語.tx\nt");
+        assert_eq!(wrap_name("cafe\u{301}.md", 4), "cafe\u{301}\n.md");
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(
+            wrap_name(&format!("{}.png", family), 3),
+            format!("{}.\npng", family)
+        );
+        // Too wide for a line of its own, yet never split
+        assert_eq!(wrap_name("日本", 1), "日\n本");
+    }
+
+    #[test]
+    fn test_max_name_width_cuts_only_the_name() {
+        let options = TableOptions {
+            name_width: Some(NameWidth {
+                wrap: false,
+                max: Some(6),
+            }),
+            ..Default::default()
+        };
+        let entries = [entry("a-very-long-name.txt", FileType::File, 1)];
+        let columns = [TableColumn::Name, TableColumn::Type];
+        let table = format_table(&entries, Some(&columns), false, false, None, &options);
+        assert_eq!(table.lines().nth(3), Some("│ a-ver… │ File │"));
+    }
+
+    #[test]
+    fn test_markdown_escapes_cells() {
+        let options = TableOptions {
+            markdown: true,
+            style: TableStyle::Markdown,
+            ..Default::default()
+        };
+        let entries = [entry("a|b\nc.txt", FileType::File, 1)];
+        let table = format_table(&entries, None, false, true, None, &options);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("|---"));
+        assert!(lines[2].starts_with("| a\\|b c.txt |"));
+        assert!(!table.contains('\x1b'));
+    }
+
+    #[test]
+    fn test_format_fzf() {
+        let mut dir = entry("src", FileType::Directory, 4096);
+        dir.human_size = "4.0 KiB".to_string();
+        dir.modified = "Mon 15 Jan 2024 14:30:25".to_string();
+        let odd = entry("tab\there\\", FileType::File, 0);
+        let theme = Theme::default();
+
+        assert_eq!(
+            format_fzf(&[dir.clone(), odd], false, &theme),
+            "src\t4.0 KiB\tMon 15 Jan 2024 14:30:25\ntab\\there\\\\\t\t"
+        );
+
+        let colored = format_fzf(&[dir], true, &theme);
+        assert_eq!(colored.split('\t').count(), 3);
+        assert!(colored.starts_with(theme.file_types.directory.to_tabled_color().get_prefix()));
+    }
+
+    #[test]
+    fn test_broken_symlink_marker() {
+        let mut broken = entry("dangling", FileType::Symlink, 4);
+        broken.is_broken = Some(true);
+        let mut working = entry("link", FileType::Symlink, 4);
+        working.is_broken = Some(false);
+        let entries = [broken, working];
+        let options = TableOptions::default();
+
+        let plain = format_table(&entries, None, false, false, None, &options);
+        assert_eq!(plain.matches("Symlink (broken)").count(), 1);
+
+        let theme = Theme::default();
+        let colored = format_table(&entries, None, false, true, Some(&theme), &options);
+        let red = theme.file_types.broken_symlink.to_tabled_color();
+        assert!(colored.contains(&format!("{}Symlink (broken)", red.get_prefix())));
+    }
+
+    #[test]
+    fn test_search_highlight() {
+        let options = TableOptions {
+            search: vec!["voice".to_string()],
+            ..Default::default()
+        };
+        let entries = [entry("Invoice.pdf", FileType::File, 1)];
+
+        let colored = format_table(&entries, None, false, true, None, &options);
+        assert!(colored.contains("In\u{1b}[1;4mvoice\u{1b}[22;24m.pdf"));
+        // Escapes do not count towards the column width
+        let widths: HashSet<usize> = colored
+            .lines()
+            .map(tabled::grid::util::string::get_line_width)
+            .collect();
+        assert_eq!(widths.len(), 1);
+
+        let plain = format_table(&entries, None, false, false, None, &options);
+        assert!(plain.contains("│ Invoice.pdf │"));
+    }
+
+    #[test]
+    fn test_created_column_follows_modified() {
+        let mut known = entry("known", FileType::File, 1);
+        known.modified = "Tue 16 Jan 2024 08:00:00".to_string();
+        known.created = Some("Mon 15 Jan 2024 14:30:25".to_string());
+        let unknown = entry("unknown", FileType::File, 1);
+        let options = TableOptions {
+            created: true,
+            size_bars: Some(SizeBarScale::Max),
+            ..Default::default()
+        };
+
+        let table = format_table(&[known, unknown], None, false, false, None, &options);
+        let cells = |line: &str| {
+            // Skip the text before the left border
+            line.split('│')
+                .skip(1)
+                .map(|c| c.trim().to_string())
+                .collect::<Vec<_>>()
+        };
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(cells(lines[1])[3..6], ["", "Modified", "Created"]);
+        assert_eq!(
+            cells(lines[3])[4..6],
+            ["Tue 16 Jan 2024 08:00:00", "Mon 15 Jan 2024 14:30:25"]
+        );
+        assert_eq!(cells(lines[4])[5], "-");
+    }
+
+    #[test]
+    fn test_items_column() {
+        let mut dir = entry("dir", FileType::Directory, 4096);
+        dir.items = Some(12);
+        let unreadable = entry("locked", FileType::Directory, 4096);
+        let file = entry("file", FileType::File, 1);
+        let options = TableOptions {
+            items: true,
+            ..Default::default()
+        };
+
+        let table = format_table(&[dir, unreadable, file], None, false, false, None, &options);
+        let column: Vec<String> = table
+            .lines()
+            .filter(|line| line.starts_with('│'))
+            .map(|line| line.split('│').nth(4).unwrap().trim().to_string())
+            .collect();
+        assert_eq!(column, ["Items", "12", "?", "-"]);
+    }
+
+    #[test]
+    fn test_size_bar_cells() {
+        let entries = [
+            entry("big", FileType::File, 1000),
+            entry("half", FileType::File, 500),
+            entry("tiny", FileType::File, 1),
+            entry("empty", FileType::File, 0),
+            entry("dir", FileType::Directory, 4096),
+        ];
+
+        let max = size_bar_cells(&entries, SizeBarScale::Max, false);
+        assert_eq!(max[0].chars().count(), SIZE_BAR_WIDTH);
+        assert_eq!(max[1].chars().count(), 5);
+        assert_eq!(max[2].chars().count(), 1);
+        assert_eq!(max[3], "");
+        assert_eq!(max[4], "");
+
+        let total = size_bar_cells(&entries, SizeBarScale::Total, false);
+        assert_eq!(total[0].chars().count(), 7);
+        assert_eq!(total[1].chars().count(), 3);
+    }
+
+    #[test]
+    fn test_size_bar_cells_with_total_size() {
+        let entries = [
+            entry("small", FileType::File, 250),
+            entry("dir", FileType::Directory, 750),
+            FileEntry {
+                depth: 1,
+                ..entry("dir/inner", FileType::File, 750)
+            },
+        ];
+
+        let max = size_bar_cells(&entries, SizeBarScale::Max, true);
+        assert_eq!(max[0].chars().count(), 3);
+        assert_eq!(max[1].chars().count(), SIZE_BAR_WIDTH);
+
+        // `dir/inner` is already part of `dir`, so the total is 1000 bytes, not 1750
+        let total = size_bar_cells(&entries, SizeBarScale::Total, true);
+        assert_eq!(total[0].chars().count(), 3);
+        assert_eq!(total[1].chars().count(), 8);
+        assert_eq!(total[2].chars().count(), 8);
+    }
+
+    #[test]
+    fn test_group_by_directory_preorder() {
+        let at = |path: &str, depth, e_type| FileEntry {
+            path: PathBuf::from("/w").join(path),
+            depth,
+            ..entry(path, e_type, 0)
+        };
+        // Sorted by name across the whole walk; `x/gone` was filtered out
+        let entries = [
+            at("a", 0, FileType::Directory),
+            at("a/f", 1, FileType::File),
+            at("b", 0, FileType::Directory),
+            at("b/c", 1, FileType::Directory),
+            at("b/c/g", 2, FileType::File),
+            at("x/gone/h", 2, FileType::File),
+            at("z", 0, FileType::File),
+        ];
+        let dirs: Vec<_> = group_by_directory(&entries)
+            .into_iter()
+            .map(|(dir, group)| (dir, group.len()))
+            .collect();
+        assert_eq!(
+            dirs,
+            [
+                (PathBuf::from("/w"), 3),
+                (PathBuf::from("/w/a"), 1),
+                (PathBuf::from("/w/b"), 1),
+                (PathBuf::from("/w/b/c"), 1),
+                (PathBuf::from("/w/x/gone"), 1),
+            ]
+        );
+    }
+}
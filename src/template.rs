@@ -0,0 +1,294 @@
+//! # Format String Module
+//!
+//! `--format-string` prints one line per entry from a template:
+//!
+//! ```text
+//! bestls --format-string '{name:color}\t{human_size}\t{modified}'
+//! ```
+//!
+//! ## Syntax
+//!
+//! - `{field}` is replaced by a field of the entry's JSON object (see
+//!   [`ENTRY_FIELDS`]) or by `{path}`, the entry's full path. Strings are written
+//!   as they are, numbers and booleans in JSON notation, and unset optional fields
+//!   (`null` or absent) as nothing.
+//! - `{field:color}` colors the value like the entry's name.
+//! - `{{` and `}}` are literal braces; `\t`, `\n`, and `\\` are a tab, a line feed,
+//!   and a backslash. Any other backslash is kept as is, so Windows paths survive.
+//!
+//! Templates are parsed once, up front: unknown placeholders, unknown `:` specs,
+//! and unbalanced braces are errors, and an unknown placeholder's error lists the
+//! valid names.
+
+use crate::color::{get_file_color, Theme};
+use crate::diag::Code;
+use crate::fsops::FileEntry;
+use crate::json::{plain_value, ENTRY_FIELDS};
+use crate::platform::display_path;
+use std::fmt;
+
+/// Placeholder for the entry's full path, next to the JSON field names
+const PATH_FIELD: &str = "path";
+
+/// Error returned when a `--format-string` template is invalid
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// A placeholder names no field; carries the name and the valid names
+    UnknownPlaceholder(String, Vec<String>),
+    /// `{field:spec}` with a spec other than `color`
+    UnknownSpec(String),
+    /// A `{` without its `}`
+    Unclosed,
+    /// A `}` without its `{` (a literal one is written `}}`)
+    UnmatchedClose,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnknownPlaceholder(name, valid) => write!(
+                f,
+                "invalid --format-string: unknown placeholder '{{{}}}' (expected one of: {})",
+                name,
+                valid.join(", ")
+            ),
+            TemplateError::UnknownSpec(spec) => write!(
+                f,
+                "invalid --format-string: unknown placeholder spec ':{}' (expected ':color')",
+                spec
+            ),
+            TemplateError::Unclosed => {
+                write!(f, "invalid --format-string: '{{' is not closed (write '{{{{' for a literal brace)")
+            }
+            TemplateError::UnmatchedClose => {
+                write!(
+                    f,
+                    "invalid --format-string: unmatched '}}' (write '}}}}' for a literal brace)"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl TemplateError {
+    /// Stable diagnostic code for `--error-format json`
+    pub fn code(&self) -> Code {
+        Code::InvalidFormatString
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Field { name: String, color: bool },
+}
+
+/// A parsed `--format-string` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template(Vec<Segment>);
+
+impl Template {
+    /// Parse a template.
+    ///
+    /// `extra` holds additional valid placeholders, such as `--exec-column` labels.
+    pub fn parse(spec: &str, extra: &[String]) -> Result<Self, TemplateError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = spec.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '}' => return Err(TemplateError::UnmatchedClose),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some('{') | None => return Err(TemplateError::Unclosed),
+                            Some(c) => placeholder.push(c),
+                        }
+                    }
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(parse_placeholder(&placeholder, extra)?);
+                }
+                '\\' => match chars.peek() {
+                    Some('t') => {
+                        chars.next();
+                        text.push('\t');
+                    }
+                    Some('n') => {
+                        chars.next();
+                        text.push('\n');
+                    }
+                    Some('\\') => {
+                        chars.next();
+                        text.push('\\');
+                    }
+                    _ => text.push('\\'),
+                },
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Template(segments))
+    }
+
+    /// Fill in the template for one entry.
+    ///
+    /// `use_color` turns `:color` placeholders into colored values.
+    pub fn render(&self, entry: &FileEntry, use_color: bool, theme: &Theme) -> String {
+        let object = serde_json::to_value(entry).unwrap_or_default();
+        let mut line = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Text(text) => line.push_str(text),
+                Segment::Field { name, color } => {
+                    let value = if name == PATH_FIELD {
+                        display_path(&entry.path).display().to_string()
+                    } else {
+                        object.get(name).map(plain_value).unwrap_or_default()
+                    };
+                    if *color && use_color {
+                        let color = get_file_color(entry, theme).to_tabled_color();
+                        line.push_str(&color.colorize(&value));
+                    } else {
+                        line.push_str(&value);
+                    }
+                }
+            }
+        }
+        line
+    }
+}
+
+/// Parse the inside of a `{...}` placeholder
+fn parse_placeholder(placeholder: &str, extra: &[String]) -> Result<Segment, TemplateError> {
+    let (name, spec) = match placeholder.split_once(':') {
+        Some((name, spec)) => (name.trim(), Some(spec.trim())),
+        None => (placeholder.trim(), None),
+    };
+    let known = name == PATH_FIELD
+        || ENTRY_FIELDS.contains(&name)
+        || extra.iter().any(|label| label == name);
+    if !known {
+        let valid = ENTRY_FIELDS
+            .iter()
+            .copied()
+            .chain([PATH_FIELD])
+            .map(String::from)
+            .chain(extra.iter().cloned())
+            .collect();
+        return Err(TemplateError::UnknownPlaceholder(name.to_string(), valid));
+    }
+    match spec {
+        None => Ok(Segment::Field {
+            name: name.to_string(),
+            color: false,
+        }),
+        Some("color") => Ok(Segment::Field {
+            name: name.to_string(),
+            color: true,
+        }),
+        Some(spec) => Err(TemplateError::UnknownSpec(spec.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsops::FileType;
+    use std::path::PathBuf;
+
+    fn entry() -> FileEntry {
+        FileEntry {
+            name: "main.rs".to_string(),
+            e_type: FileType::File,
+            len_bytes: 1126,
+            human_size: "1.1 KB".to_string(),
+            modified: "Thu 22 Aug 2024 17:44:23".to_string(),
+            path: PathBuf::from("src").join("main.rs"),
+            ..Default::default()
+        }
+    }
+
+    fn render(spec: &str) -> String {
+        Template::parse(spec, &[])
+            .unwrap()
+            .render(&entry(), false, &Theme::default())
+    }
+
+    #[test]
+    fn test_fields_and_escapes() {
+        assert_eq!(
+            render(r"{name}\t{human_size}\t{modified}"),
+            "main.rs\t1.1 KB\tThu 22 Aug 2024 17:44:23"
+        );
+        assert_eq!(render(r"{len_bytes} {is_hidden}\n"), "1126 false\n");
+        assert_eq!(render(r"a\\b C:\dir"), r"a\b C:\dir");
+        // Unset optional fields are empty
+        assert_eq!(render("[{preview}]"), "[]");
+        assert_eq!(
+            render("{path}"),
+            PathBuf::from("src").join("main.rs").display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_literal_braces() {
+        assert_eq!(render("{{{name}}}"), "{main.rs}");
+        assert_eq!(render("{{name}}"), "{name}");
+        assert_eq!(render("}}{{"), "}{");
+    }
+
+    #[test]
+    fn test_color_spec() {
+        let template = Template::parse("{name:color} {human_size}", &[]).unwrap();
+        let theme = Theme::default();
+        let colored = template.render(&entry(), true, &theme);
+        let color = get_file_color(&entry(), &theme).to_tabled_color();
+        assert_eq!(colored, format!("{} 1.1 KB", color.colorize("main.rs")));
+        assert_eq!(template.render(&entry(), false, &theme), "main.rs 1.1 KB");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let error = Template::parse("{name} {nmae}", &[]).unwrap_err();
+        assert!(matches!(&error, TemplateError::UnknownPlaceholder(name, _) if name == "nmae"));
+        assert!(error.to_string().contains("human_size"));
+        assert!(error.to_string().contains("path"));
+
+        assert_eq!(
+            Template::parse("{name:bold}", &[]),
+            Err(TemplateError::UnknownSpec("bold".to_string()))
+        );
+        assert_eq!(Template::parse("{name", &[]), Err(TemplateError::Unclosed));
+        assert_eq!(
+            Template::parse("{na{me}", &[]),
+            Err(TemplateError::Unclosed)
+        );
+        assert_eq!(
+            Template::parse("name}", &[]),
+            Err(TemplateError::UnmatchedClose)
+        );
+    }
+
+    #[test]
+    fn test_extra_placeholders() {
+        let labels = ["Lines".to_string()];
+        assert!(Template::parse("{Lines}", &labels).is_ok());
+        assert!(Template::parse("{Lines}", &[]).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+//! # Top Files Module
+//!
+//! This module implements `bestls top`, which answers "what are the ten biggest (or
+//! newest) files under here": a recursive walk, ranked descending by size or
+//! modification date and cut off after `-n` entries.
+//!
+//! ## Report
+//!
+//! - Only files and symlinks are ranked; a directory's own size and timestamp say
+//!   nothing about its contents
+//! - Ties are broken by path, so the cutoff is stable between runs
+//! - Each line shows the size, the modification date and the path relative to the
+//!   scanned directory
+
+use crate::cli::TopBy;
+use crate::color::{get_file_color, ColorValue, Theme};
+use crate::fsops::{FileEntry, FileType};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::path::Path;
+
+/// A ranked entry
+#[derive(Debug, Clone, Serialize)]
+pub struct TopEntry {
+    /// Path relative to the scanned directory
+    pub path: String,
+    #[serde(flatten)]
+    pub entry: FileEntry,
+}
+
+/// The `bestls top` result
+#[derive(Debug, Clone, Serialize)]
+pub struct TopReport {
+    pub entries: Vec<TopEntry>,
+    pub count: usize,
+}
+
+/// Rank the files of a listing and keep the first `count`.
+///
+/// # Arguments
+///
+/// * `entries` - Entries from a recursive listing of `root`
+/// * `root` - Scanned directory, used to compute relative paths
+/// * `by` - Largest or most recently modified first; files without a recorded
+///   modification time are left out of a date ranking
+/// * `count` - Number of entries to keep
+pub fn find_top(entries: Vec<FileEntry>, root: &Path, by: TopBy, count: usize) -> TopReport {
+    let mut top: Vec<TopEntry> = entries
+        .into_iter()
+        .filter(|e| e.e_type != FileType::Directory)
+        .filter(|e| by != TopBy::Date || e.modified_time.is_some())
+        .map(|entry| TopEntry {
+            path: entry
+                .path
+                .strip_prefix(root)
+                .unwrap_or(&entry.path)
+                .to_string_lossy()
+                .to_string(),
+            entry,
+        })
+        .collect();
+
+    match by {
+        TopBy::Size => top.sort_by(|a, b| {
+            b.entry
+                .len_bytes
+                .cmp(&a.entry.len_bytes)
+                .then_with(|| a.path.cmp(&b.path))
+        }),
+        TopBy::Date => top.sort_by_key(|t| (Reverse(t.entry.modified_time), t.path.clone())),
+    }
+    top.truncate(count);
+
+    TopReport {
+        count: top.len(),
+        entries: top,
+    }
+}
+
+/// Render the report as one `size  date  path` line per entry, sizes right-aligned
+pub fn format_report(report: &TopReport, use_color: bool, theme: &Theme) -> String {
+    let width = report
+        .entries
+        .iter()
+        .map(|t| t.entry.human_size.chars().count())
+        .max()
+        .unwrap_or(0);
+    let paint = |text: &str, color: ColorValue| {
+        if use_color {
+            color.to_tabled_color().colorize(text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    report
+        .entries
+        .iter()
+        .map(|t| {
+            let size = format!("{:>width$}", t.entry.human_size);
+            format!(
+                "{}  {}  {}",
+                paint(&size, theme.table.size),
+                paint(&t.entry.modified, theme.table.date),
+                paint(&t.path, get_file_color(&t.entry, theme)),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    fn entry(name: &str, e_type: FileType, len_bytes: u64, modified_secs: u64) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            e_type,
+            len_bytes,
+            human_size: format!("{} B", len_bytes),
+            modified: format!("t{}", modified_secs),
+            path: PathBuf::from("/root/dir").join(name),
+            modified_time: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(modified_secs)),
+            ..Default::default()
+        }
+    }
+
+    fn paths(report: &TopReport) -> Vec<&str> {
+        report.entries.iter().map(|t| t.path.as_str()).collect()
+    }
+
+    fn fixture() -> Vec<FileEntry> {
+        vec![
+            entry("small.txt", FileType::File, 1, 300),
+            entry("sub/big.bin", FileType::File, 500, 100),
+            entry("sub", FileType::Directory, 4096, 400),
+            entry("mid.txt", FileType::File, 50, 200),
+            entry("also-mid.txt", FileType::File, 50, 200),
+        ]
+    }
+
+    #[test]
+    fn test_top_by_size() {
+        let report = find_top(fixture(), Path::new("/root/dir"), TopBy::Size, 3);
+        // Directories are not ranked; equal sizes keep path order
+        assert_eq!(paths(&report), ["sub/big.bin", "also-mid.txt", "mid.txt"]);
+        assert_eq!(report.count, 3);
+    }
+
+    #[test]
+    fn test_top_by_date() {
+        let report = find_top(fixture(), Path::new("/root/dir"), TopBy::Date, 10);
+        assert_eq!(
+            paths(&report),
+            ["small.txt", "also-mid.txt", "mid.txt", "sub/big.bin"]
+        );
+        assert!(find_top(fixture(), Path::new("/root/dir"), TopBy::Date, 0)
+            .entries
+            .is_empty());
+    }
+
+    #[test]
+    fn test_format_report_aligns_sizes() {
+        let report = find_top(fixture(), Path::new("/root/dir"), TopBy::Size, 2);
+        assert_eq!(
+            format_report(&report, false, &Theme::default()),
+            "500 B  t100  sub/big.bin\n 50 B  t200  also-mid.txt"
+        );
+    }
+}
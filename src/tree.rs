@@ -0,0 +1,288 @@
+//! # Tree Module
+//!
+//! Renders a `--tree` listing as an indented tree, like the `tree` command:
+//!
+//! ```text
+//! .
+//! ├── Cargo.toml
+//! └── src
+//!     ├── main.rs
+//!     └── table.rs
+//! ```
+//!
+//! The recursive walk returns a flat list; [`format_tree`] rebuilds the hierarchy
+//! from each entry's path. Siblings keep the order of that list, so `--sort` applies
+//! within each directory. When a filter drops a directory but keeps entries inside
+//! it (`--tree --glob '*.rs'`), the directory is still drawn, by name only, so the
+//! kept entries have somewhere to hang.
+//!
+//! With `--sizes`, each line starts with the entry's size in brackets. Terminals
+//! without box-drawing support get ASCII connectors (`|--`, `` `-- ``).
+
+use crate::color::{get_file_color, ColorValue, Theme};
+use crate::fsops::FileEntry;
+use crate::platform::display_path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Options of the tree renderer
+#[derive(Debug, Default, Clone)]
+pub struct TreeOptions {
+    /// First line of the tree: the listed path as given
+    pub root: String,
+    /// Show each entry's size before its name (`--sizes`)
+    pub sizes: bool,
+}
+
+/// Connector strings for one drawing style
+struct Connectors {
+    branch: &'static str,
+    last: &'static str,
+    pipe: &'static str,
+    blank: &'static str,
+}
+
+const UNICODE: Connectors = Connectors {
+    branch: "├── ",
+    last: "└── ",
+    pipe: "│   ",
+    blank: "    ",
+};
+
+const ASCII: Connectors = Connectors {
+    branch: "|-- ",
+    last: "`-- ",
+    pipe: "|   ",
+    blank: "    ",
+};
+
+/// A line of the tree: a listed entry, or a directory a filter dropped
+enum Node<'a> {
+    Entry(&'a FileEntry),
+    Placeholder(String),
+}
+
+/// The entries grouped under the path of their parent directory
+struct Hierarchy<'a> {
+    children: HashMap<PathBuf, Vec<(PathBuf, Node<'a>)>>,
+}
+
+impl<'a> Hierarchy<'a> {
+    /// Group `entries` by parent, adding placeholders for missing directories
+    /// between an entry and `root`
+    fn new(entries: &'a [FileEntry], root: &Path) -> Self {
+        let mut children: HashMap<PathBuf, Vec<(PathBuf, Node<'a>)>> = HashMap::new();
+        let listed: HashSet<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+
+        for entry in entries {
+            let mut path = entry.path.as_path();
+            let mut node = Node::Entry(entry);
+            while let Some(parent) = path.parent().filter(|_| path != root) {
+                let siblings = children.entry(parent.to_path_buf()).or_default();
+                let placeholder = matches!(node, Node::Placeholder(_));
+                if placeholder && siblings.iter().any(|(p, _)| p == path) {
+                    break;
+                }
+                siblings.push((path.to_path_buf(), node));
+                if parent == root || listed.contains(parent) {
+                    break;
+                }
+                let name = parent
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                node = Node::Placeholder(name);
+                path = parent;
+            }
+        }
+        Self { children }
+    }
+}
+
+/// The directory the listing started from, found from any entry's depth
+fn listing_root(entries: &[FileEntry]) -> Option<PathBuf> {
+    let entry = entries.iter().min_by_key(|e| e.depth)?;
+    let mut root = entry.path.parent()?;
+    for _ in 0..entry.depth {
+        root = root.parent()?;
+    }
+    Some(root.to_path_buf())
+}
+
+/// Render `entries` of a recursive listing as a tree.
+///
+/// Entry names are drawn colored by type when `use_color` is set; `ascii` selects
+/// ASCII connectors.
+pub fn format_tree(
+    entries: &[FileEntry],
+    options: &TreeOptions,
+    use_color: bool,
+    ascii: bool,
+    theme: &Theme,
+) -> String {
+    // A file given as the listed path is a tree of its own (`bestls --tree Cargo.toml`)
+    if let [entry] = entries {
+        if display_path(&entry.path) == Path::new(&options.root) {
+            return paint(&options.root, get_file_color(entry, theme), use_color);
+        }
+    }
+
+    let mut lines = vec![paint(&options.root, theme.file_types.directory, use_color)];
+    let Some(root) = listing_root(entries) else {
+        return lines.join("\n");
+    };
+
+    let hierarchy = Hierarchy::new(entries, &root);
+    let size_width = entries
+        .iter()
+        .map(|e| e.human_size.chars().count())
+        .max()
+        .unwrap_or(0);
+    let renderer = Renderer {
+        hierarchy: &hierarchy,
+        options,
+        use_color,
+        connectors: if ascii { &ASCII } else { &UNICODE },
+        theme,
+        size_width,
+    };
+    renderer.draw(&root, "", &mut lines);
+    lines.join("\n")
+}
+
+struct Renderer<'a> {
+    hierarchy: &'a Hierarchy<'a>,
+    options: &'a TreeOptions,
+    use_color: bool,
+    connectors: &'a Connectors,
+    theme: &'a Theme,
+    size_width: usize,
+}
+
+impl Renderer<'_> {
+    /// Append the lines below `dir`, each starting with `prefix`
+    fn draw(&self, dir: &Path, prefix: &str, lines: &mut Vec<String>) {
+        let Some(children) = self.hierarchy.children.get(dir) else {
+            return;
+        };
+        for (i, (path, node)) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let connector = if last {
+                self.connectors.last
+            } else {
+                self.connectors.branch
+            };
+            lines.push(format!("{}{}{}", prefix, connector, self.label(node)));
+
+            let indent = if last {
+                self.connectors.blank
+            } else {
+                self.connectors.pipe
+            };
+            self.draw(path, &format!("{}{}", prefix, indent), lines);
+        }
+    }
+
+    /// Size (with `--sizes`) and colored name of a line
+    fn label(&self, node: &Node) -> String {
+        let (name, color, size) = match node {
+            Node::Entry(entry) => (
+                entry.name.clone(),
+                get_file_color(entry, self.theme),
+                entry.human_size.as_str(),
+            ),
+            Node::Placeholder(name) => (name.clone(), self.theme.file_types.directory, ""),
+        };
+        let name = paint(&name, color, self.use_color);
+        if !self.options.sizes {
+            return name;
+        }
+        let size = format!("[{:>width$}]", size, width = self.size_width);
+        format!(
+            "{}  {}",
+            paint(&size, self.theme.table.size, self.use_color),
+            name
+        )
+    }
+}
+
+fn paint(text: &str, color: ColorValue, use_color: bool) -> String {
+    if use_color {
+        color.to_tabled_color().colorize(text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsops::FileType;
+
+    fn entry(path: &str, depth: usize, e_type: FileType) -> FileEntry {
+        let path = Path::new("/work").join(path);
+        FileEntry {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            e_type,
+            path,
+            depth,
+            human_size: "1 B".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn plain(entries: &[FileEntry]) -> String {
+        let options = TreeOptions {
+            root: ".".to_string(),
+            sizes: false,
+        };
+        format_tree(entries, &options, false, false, &Theme::default())
+    }
+
+    #[test]
+    fn test_siblings_keep_listing_order() {
+        // The walk lists a directory's entries before descending
+        let entries = [
+            entry("b.txt", 0, FileType::File),
+            entry("a", 0, FileType::Directory),
+            entry("a/z.txt", 1, FileType::File),
+            entry("a/y.txt", 1, FileType::File),
+        ];
+        assert_eq!(
+            plain(&entries),
+            ".\n├── b.txt\n└── a\n    ├── z.txt\n    └── y.txt"
+        );
+    }
+
+    #[test]
+    fn test_dropped_directories_become_placeholders() {
+        let entries = [
+            entry("src/bin/tool.rs", 2, FileType::File),
+            entry("src/main.rs", 1, FileType::File),
+        ];
+        assert_eq!(
+            plain(&entries),
+            ".\n└── src\n    ├── bin\n    │   └── tool.rs\n    └── main.rs"
+        );
+    }
+
+    #[test]
+    fn test_empty_listing() {
+        assert_eq!(plain(&[]), ".");
+    }
+
+    #[test]
+    fn test_file_root_is_a_single_line() {
+        let file = FileEntry {
+            name: "Cargo.toml".to_string(),
+            path: PathBuf::from("Cargo.toml"),
+            ..Default::default()
+        };
+        let options = TreeOptions {
+            root: "Cargo.toml".to_string(),
+            sizes: false,
+        };
+        let tree = format_tree(&[file], &options, false, false, &Theme::default());
+        assert_eq!(tree, "Cargo.toml");
+    }
+}
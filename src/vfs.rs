@@ -0,0 +1,126 @@
+//! # Virtual Filesystem Detection Module
+//!
+//! Kernel pseudo filesystems such as `/proc`, `/sys`, and `/dev` report meaningless
+//! sizes (often 0 or 4096, sometimes absurdly large) and some of their files block
+//! or take a long time when read. This module detects them so listings can skip
+//! size computation and any content-based feature (like `--preview`) in those trees.
+//!
+//! ## Detection
+//!
+//! - **Linux/Android**: `statfs` magic numbers, plus the mount table to tell
+//!   `devtmpfs` apart from a regular `tmpfs` (both share the same magic)
+//! - **macOS/FreeBSD**: the filesystem type name reported by `statfs`
+//! - **Other platforms**: never detected
+
+use std::path::Path;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::sync::OnceLock;
+
+/// Return the kind of virtual filesystem `path` lives on, if any (e.g. `"proc"`).
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn virtual_fs_kind(path: &Path) -> Option<&'static str> {
+    use nix::sys::statfs::{
+        statfs, BPF_FS_MAGIC, CGROUP2_SUPER_MAGIC, CGROUP_SUPER_MAGIC, DEBUGFS_MAGIC,
+        DEVPTS_SUPER_MAGIC, PROC_SUPER_MAGIC, SECURITYFS_MAGIC, SYSFS_MAGIC, TMPFS_MAGIC,
+        TRACEFS_MAGIC,
+    };
+
+    let fs_type = statfs(path).ok()?.filesystem_type();
+    let known = [
+        (PROC_SUPER_MAGIC, "proc"),
+        (SYSFS_MAGIC, "sysfs"),
+        (DEVPTS_SUPER_MAGIC, "devpts"),
+        (DEBUGFS_MAGIC, "debugfs"),
+        (TRACEFS_MAGIC, "tracefs"),
+        (SECURITYFS_MAGIC, "securityfs"),
+        (CGROUP_SUPER_MAGIC, "cgroup"),
+        (CGROUP2_SUPER_MAGIC, "cgroup2"),
+        (BPF_FS_MAGIC, "bpf"),
+    ];
+
+    if let Some((_, kind)) = known.iter().find(|(magic, _)| *magic == fs_type) {
+        return Some(kind);
+    }
+
+    if fs_type == TMPFS_MAGIC && mount_fs_type(path) == Some("devtmpfs") {
+        return Some("devtmpfs");
+    }
+
+    None
+}
+
+/// Return the kind of virtual filesystem `path` lives on, if any (e.g. `"devfs"`).
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+pub fn virtual_fs_kind(path: &Path) -> Option<&'static str> {
+    let stat = nix::sys::statfs::statfs(path).ok()?;
+    match stat.filesystem_type_name() {
+        "devfs" => Some("devfs"),
+        "procfs" | "linprocfs" => Some("procfs"),
+        "linsysfs" => Some("sysfs"),
+        "fdescfs" => Some("fdescfs"),
+        _ => None,
+    }
+}
+
+/// Virtual filesystem detection is not available on this platform.
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+)))]
+pub fn virtual_fs_kind(_path: &Path) -> Option<&'static str> {
+    None
+}
+
+/// Look up the filesystem type of the mount containing `path` in `/proc/self/mounts`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn mount_fs_type(path: &Path) -> Option<&'static str> {
+    let path = path.canonicalize().ok()?;
+    mounts()
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type.as_str())
+}
+
+/// The mount table as `(mount point, filesystem type)` pairs, read once per run since
+/// a recursive listing asks about every tmpfs directory
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn mounts() -> &'static [(String, String)] {
+    static MOUNTS: OnceLock<Vec<(String, String)>> = OnceLock::new();
+    MOUNTS.get_or_init(|| {
+        std::fs::read_to_string("/proc/self/mounts")
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let _device = fields.next()?;
+                // Spaces and other special characters are octal-escaped (e.g. "\040")
+                let mount_point = fields.next()?.replace("\\040", " ");
+                let fs_type = fields.next()?;
+                Some((mount_point, fs_type.to_string()))
+            })
+            .collect()
+    })
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proc_is_virtual() {
+        assert_eq!(virtual_fs_kind(Path::new("/proc")), Some("proc"));
+        assert_eq!(virtual_fs_kind(Path::new("/proc/self")), Some("proc"));
+        assert_eq!(mount_fs_type(Path::new("/proc")), Some("proc"));
+    }
+
+    #[test]
+    fn test_regular_directory_is_not_virtual() {
+        // Even on a tmpfs, which shares its magic number with devtmpfs
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(virtual_fs_kind(dir.path()), None);
+    }
+}
@@ -0,0 +1,342 @@
+//! # Watch Mode Module
+//!
+//! This module keeps the state behind `--watch`: a snapshot of the previous listing
+//! keyed by entry name, and the diff between that snapshot and a fresh listing.
+//!
+//! ## Change Detection
+//!
+//! - **Added**: the name was not present in the previous snapshot
+//! - **Removed**: the name disappeared; the old entry is kept for one more redraw
+//! - **Changed**: raw `len_bytes` or modification time differ (formatted strings
+//!   are not compared, so sub-second updates are still noticed)
+//!
+//! Each change is either rendered as a row highlight or, with `--watch-log`,
+//! written as one NDJSON event line.
+//!
+//! ## Refreshes
+//!
+//! The directory is re-listed when the filesystem reports a change below it
+//! ([`Watcher`]), once no further change arrived for [`DEBOUNCE`], so a build writing
+//! hundreds of files redraws once. Reads (including bestls's own) are not changes.
+//! Where notifications are unavailable, the directory is re-listed every
+//! [`POLL_INTERVAL`] instead.
+
+use crate::fsops::FileEntry;
+use crate::interrupt;
+use crate::json::{EntryJson, FieldSelection};
+use crate::table::RowHighlight;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// Delay between two listings of the watched directory without notifications
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Quiet time after a change before re-listing
+pub const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How often a wait for changes checks for Ctrl-C
+const CANCEL_CHECK: Duration = Duration::from_millis(100);
+
+/// Filesystem change notifications for the watched directory
+pub struct Watcher {
+    // Dropping the watcher stops the notifications
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl Watcher {
+    /// Watch `path`, including everything below it when `recursive` (`--tree`, `-R`)
+    pub fn new(path: &Path, recursive: bool) -> notify::Result<Self> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(path, mode)?;
+        Ok(Watcher {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Block until the directory changed and then stayed quiet for [`DEBOUNCE`].
+    ///
+    /// Returns `false` after Ctrl-C.
+    pub fn wait_for_change(&self) -> bool {
+        // A failed notification may hide a change, so it counts as one
+        wait_debounced(
+            &self.events,
+            DEBOUNCE,
+            |event| !matches!(event, Ok(event) if matches!(event.kind, EventKind::Access(_))),
+        )
+    }
+}
+
+/// Wait for a message `is_change` accepts, then until none arrived for `quiet`.
+///
+/// Returns `false` after Ctrl-C. If the sender is gone, waits [`POLL_INTERVAL`] so
+/// the caller falls back to polling.
+fn wait_debounced<T>(
+    events: &Receiver<T>,
+    quiet: Duration,
+    is_change: impl Fn(&T) -> bool,
+) -> bool {
+    let mut timeout = CANCEL_CHECK;
+    let mut changed = false;
+    loop {
+        if interrupt::is_cancelled() {
+            return false;
+        }
+        match events.recv_timeout(timeout) {
+            Ok(event) if is_change(&event) => {
+                changed = true;
+                timeout = quiet;
+            }
+            Ok(_) => {}
+            Err(RecvTimeoutError::Timeout) if changed => return !interrupt::is_cancelled(),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                std::thread::sleep(POLL_INTERVAL);
+                return !interrupt::is_cancelled();
+            }
+        }
+    }
+}
+
+/// Kind of change between two consecutive listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// A single entry change between two listings
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Current entry (previous entry for removals)
+    pub entry: FileEntry,
+}
+
+/// One `--watch-log` NDJSON line
+#[derive(Serialize)]
+struct WatchEvent<'a> {
+    event: ChangeKind,
+    /// RFC 3339 time at which the change was detected
+    time: String,
+    entry: EntryJson<'a>,
+}
+
+/// The previous listing, keyed by entry name
+#[derive(Debug, Default)]
+pub struct Snapshot {
+    entries: HashMap<String, FileEntry>,
+}
+
+impl Snapshot {
+    /// Record a listing so the next refresh can be compared against it
+    pub fn new(entries: &[FileEntry]) -> Self {
+        Snapshot {
+            entries: entries
+                .iter()
+                .map(|e| (e.name.clone(), e.clone()))
+                .collect(),
+        }
+    }
+
+    /// Compare a fresh listing against this snapshot.
+    ///
+    /// Additions and modifications follow the order of `current`, removals are
+    /// reported last in name order.
+    pub fn diff(&self, current: &[FileEntry]) -> Vec<Change> {
+        let mut changes: Vec<Change> = current
+            .iter()
+            .filter_map(|entry| {
+                let kind = match self.entries.get(&entry.name) {
+                    None => ChangeKind::Added,
+                    Some(old)
+                        if old.len_bytes != entry.len_bytes
+                            || old.modified_time != entry.modified_time =>
+                    {
+                        ChangeKind::Changed
+                    }
+                    Some(_) => return None,
+                };
+                Some(Change {
+                    kind,
+                    entry: entry.clone(),
+                })
+            })
+            .collect();
+
+        let current_names: std::collections::HashSet<&str> =
+            current.iter().map(|e| e.name.as_str()).collect();
+        let mut removed: Vec<&FileEntry> = self
+            .entries
+            .values()
+            .filter(|e| !current_names.contains(e.name.as_str()))
+            .collect();
+        removed.sort_by(|a, b| a.name.cmp(&b.name));
+        changes.extend(removed.into_iter().map(|entry| Change {
+            kind: ChangeKind::Removed,
+            entry: entry.clone(),
+        }));
+
+        changes
+    }
+}
+
+/// Build the rows to display for a refresh along with their highlights.
+///
+/// Removed entries are appended after the current listing so they stay visible
+/// (struck through) for exactly one redraw.
+pub fn highlight_rows(
+    previous: &Snapshot,
+    current: &[FileEntry],
+) -> (Vec<FileEntry>, Vec<Option<RowHighlight>>) {
+    let changes = previous.diff(current);
+    let kind_of: HashMap<&str, ChangeKind> = changes
+        .iter()
+        .filter(|c| c.kind != ChangeKind::Removed)
+        .map(|c| (c.entry.name.as_str(), c.kind))
+        .collect();
+
+    let mut rows: Vec<FileEntry> = current.to_vec();
+    let mut highlights: Vec<Option<RowHighlight>> = current
+        .iter()
+        .map(|e| match kind_of.get(e.name.as_str()) {
+            Some(ChangeKind::Added) => Some(RowHighlight::Added),
+            Some(ChangeKind::Changed) => Some(RowHighlight::Changed),
+            _ => None,
+        })
+        .collect();
+
+    for change in changes
+        .into_iter()
+        .filter(|c| c.kind == ChangeKind::Removed)
+    {
+        rows.push(change.entry);
+        highlights.push(Some(RowHighlight::Removed));
+    }
+
+    (rows, highlights)
+}
+
+/// Serialize a change as a single NDJSON line (without the trailing newline).
+///
+/// `fields` restricts the embedded entry like `--fields` does for JSON output.
+pub fn event_line(
+    change: &Change,
+    now: chrono::DateTime<chrono::Utc>,
+    fields: Option<&FieldSelection>,
+) -> serde_json::Result<String> {
+    serde_json::to_string(&WatchEvent {
+        event: change.kind,
+        time: now.to_rfc3339(),
+        entry: EntryJson {
+            entry: &change.entry,
+            fields,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fsops::FileType;
+    use std::time::SystemTime;
+
+    fn entry(name: &str, len_bytes: u64, mtime_secs: u64) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            e_type: FileType::File,
+            len_bytes,
+            modified_time: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(mtime_secs)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_detects_changes() {
+        let previous = Snapshot::new(&[
+            entry("same", 1, 1),
+            entry("grown", 1, 1),
+            entry("gone", 1, 1),
+        ]);
+        let current = [
+            entry("same", 1, 1),
+            entry("grown", 2, 1),
+            entry("new", 1, 1),
+        ];
+
+        let changes: Vec<(ChangeKind, String)> = previous
+            .diff(&current)
+            .into_iter()
+            .map(|c| (c.kind, c.entry.name))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (ChangeKind::Changed, "grown".to_string()),
+                (ChangeKind::Added, "new".to_string()),
+                (ChangeKind::Removed, "gone".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_compares_raw_mtime() {
+        let previous = Snapshot::new(&[entry("a", 1, 1)]);
+        let changes = previous.diff(&[entry("a", 1, 2)]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+    }
+
+    #[test]
+    fn test_highlight_rows_keeps_removed_for_one_refresh() {
+        let previous = Snapshot::new(&[entry("a", 1, 1), entry("b", 1, 1)]);
+        let current = [entry("a", 1, 1)];
+
+        let (rows, highlights) = highlight_rows(&previous, &current);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].name, "b");
+        assert_eq!(highlights, vec![None, Some(RowHighlight::Removed)]);
+
+        // Next refresh compares against the real listing, so the marker fades
+        let (rows, highlights) = highlight_rows(&Snapshot::new(&current), &current);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(highlights, vec![None]);
+    }
+
+    #[test]
+    fn test_event_line() {
+        let change = Change {
+            kind: ChangeKind::Added,
+            entry: entry("a", 1, 1),
+        };
+        let line = event_line(&change, chrono::Utc::now(), None).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["event"], "added");
+        assert_eq!(value["entry"]["name"], "a");
+        assert!(!line.contains('\n'));
+    }
+
+    #[test]
+    fn test_wait_debounced_drains_a_burst() {
+        let (sender, events) = mpsc::channel();
+        // 0 stands for an access event, which is not a change
+        for event in [0, 1, 2, 0] {
+            sender.send(event).unwrap();
+        }
+        assert!(wait_debounced(&events, Duration::from_millis(20), |e| *e != 0));
+        assert!(events.try_recv().is_err());
+    }
+}
@@ -0,0 +1,65 @@
+//! `--color` decides on ANSI colors, and `-v/--verbose` explains the decision
+
+mod common;
+
+use common::{bestls, fixture, stdout};
+
+const FILES: [(&str, &str); 1] = [("a.txt", "hello")];
+
+#[test]
+fn test_color_when() {
+    let dir = fixture(FILES);
+    let table =
+        |args: &[&str]| stdout(bestls(&[&["--columns", "name"], args].concat(), dir.path()));
+
+    assert!(table(&["--color", "always"]).contains("\x1b["));
+    for args in [
+        &["--color", "never"][..],
+        &["--no-color"],
+        &["--no-color", "--color", "always"],
+    ] {
+        let plain = table(args);
+        assert!(!plain.contains('\x1b'), "{:?}", args);
+        // Without colors, the borders stay box-drawing characters
+        assert!(plain.starts_with('╭'), "{:?}", args);
+    }
+
+    let output = bestls(&["--color", "sometimes"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("possible values: auto, always, never"));
+}
+
+#[test]
+fn test_auto_is_plain_when_piped() {
+    let dir = fixture(FILES);
+    for args in [&[][..], &["--color", "auto"], &["-l"], &["--grid"]] {
+        let piped = stdout(bestls(args, dir.path()));
+        assert!(!piped.contains('\x1b'), "{:?}: {:?}", args, piped);
+    }
+    let top = stdout(common::bestls(&["top", "-p"], dir.path()));
+    assert!(!top.contains('\x1b'), "{:?}", top);
+    // Except fzf lines, which are meant for `fzf --ansi`
+    assert!(stdout(bestls(&["--fzf"], dir.path())).contains("\x1b["));
+}
+
+#[test]
+fn test_verbose_reports_color_decision() {
+    let dir = fixture(FILES);
+    let stderr = |args: &[&str]| {
+        let output = bestls(args, dir.path());
+        assert_eq!(output.status.code(), Some(0));
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    assert!(stderr(&["-v", "--color", "never"]).contains("Colors disabled on request"));
+    assert!(stderr(&["--verbose", "--no-color"]).contains("Colors disabled on request"));
+    if cfg!(unix) {
+        assert!(stderr(&["-v", "--color", "always"])
+            .contains("Terminal supports ANSI escape sequences"));
+    }
+    assert!(stderr(&["-v"]).contains("standard output is not a terminal"));
+    // Quiet without --verbose
+    assert!(stderr(&["--color", "never"]).is_empty());
+}
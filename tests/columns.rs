@@ -0,0 +1,58 @@
+//! `--columns` picks the table columns and their order
+
+mod common;
+
+use common::fixture;
+use std::process::Output;
+
+const FILES: [(&str, &str); 2] = [("a.txt", "hello"), ("b.rs", "fn main() {}")];
+
+fn run_columns(args: &[&str], dir: &std::path::Path) -> Output {
+    common::bestls(&[&["--no-color", "--sort", "name"], args].concat(), dir)
+}
+
+#[test]
+fn test_two_columns_in_order() {
+    let dir = fixture(FILES);
+    let output = run_columns(&["--columns", "size,name"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "╭──────┬───────╮\n\
+         │ Size │ Name  │\n\
+         ├──────┼───────┤\n\
+         │ 5 B  │ a.txt │\n\
+         │ 12 B │ b.rs  │\n\
+         ╰──────┴───────╯\n"
+    );
+
+    // Markdown tables follow the selection too
+    let output = run_columns(&["--columns", "name", "--format", "markdown"], dir.path());
+    let markdown = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(markdown.lines().next(), Some("| Name  |"));
+}
+
+#[test]
+fn test_unknown_column_lists_valid_names() {
+    let dir = fixture(FILES);
+    let output = run_columns(&["--columns", "name,sizee"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("unknown column 'sizee'"), "{}", stderr);
+    assert!(stderr.contains("name, type, size"), "{}", stderr);
+}
+
+#[test]
+fn test_column_needs_its_flag() {
+    let dir = fixture(FILES);
+    let output = run_columns(&["--columns", "name,preview"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("needs --preview"), "{}", stderr);
+
+    let output = run_columns(&["--columns", "name,preview", "--preview"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("│ a.txt │ hello        │"), "{}", stdout);
+}
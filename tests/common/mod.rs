@@ -0,0 +1,50 @@
+//! Helpers shared by the integration tests, pulled into each test file with
+//! `mod common;`
+
+// Every test file is its own crate and uses only some of the helpers
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Run bestls with `args` followed by `paths`
+pub fn bestls_paths(args: &[&str], paths: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(args)
+        .args(paths)
+        .output()
+        .expect("failed to run bestls")
+}
+
+/// Run bestls with `args` followed by the one path to list
+pub fn bestls(args: &[&str], dir: &Path) -> Output {
+    bestls_paths(args, &[dir])
+}
+
+/// Standard output of a run that must succeed
+pub fn stdout(output: Output) -> String {
+    assert_eq!(output.status.code(), Some(0), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// A temporary directory holding `files`, given as relative path and contents.
+///
+/// Parent directories are created as needed, and a path ending in `/` becomes an
+/// empty directory.
+pub fn fixture<P: AsRef<str>, C: AsRef<[u8]>>(
+    files: impl IntoIterator<Item = (P, C)>,
+) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (path, contents) in files {
+        let path = dir.path().join(path.as_ref());
+        if path.to_string_lossy().ends_with('/') {
+            std::fs::create_dir_all(&path).unwrap();
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap();
+        }
+        std::fs::write(&path, contents).unwrap();
+    }
+    dir
+}
@@ -0,0 +1,71 @@
+//! `--format csv` parses back with a CSV reader, whatever the file names contain
+
+mod common;
+
+use common::{bestls_paths, fixture, stdout};
+
+fn run_csv(args: &[&str], dirs: &[&std::path::Path]) -> String {
+    stdout(bestls_paths(
+        &[&["--format", "csv", "--sort", "name"], args].concat(),
+        dirs,
+    ))
+}
+
+fn records(csv: &str, has_headers: bool) -> Vec<csv::StringRecord> {
+    csv::ReaderBuilder::new()
+        .has_headers(has_headers)
+        .from_reader(csv.as_bytes())
+        .records()
+        .map(Result::unwrap)
+        .collect()
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let mut names = vec!["a, b.txt", "plain.txt", "say \"hi\".txt"];
+    if cfg!(unix) {
+        names.push("two\nlines.txt");
+    }
+    let dir = fixture(names.iter().map(|name| (name, "12345")));
+
+    let csv = run_csv(&[], &[dir.path()]);
+    let mut reader = csv::Reader::from_reader(csv.as_bytes());
+    assert_eq!(
+        reader.headers().unwrap(),
+        vec![
+            "Name",
+            "Type",
+            "Size (bytes)",
+            "Size",
+            "Modified",
+            "Permissions",
+            "Owner",
+            "Group"
+        ]
+    );
+
+    let rows = records(&csv, true);
+    let listed: Vec<&str> = rows.iter().map(|row| &row[0]).collect();
+    assert_eq!(listed, names);
+    for row in &rows {
+        assert_eq!((&row[1], &row[2], &row[3]), ("File", "5", "5 B"));
+    }
+}
+
+#[test]
+fn test_no_header() {
+    let dir = fixture([("only.txt", "x")]);
+    let rows = records(&run_csv(&["--no-header"], &[dir.path()]), false);
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][0], "only.txt");
+}
+
+#[test]
+fn test_several_paths_share_one_header() {
+    let first = fixture([("one.txt", "x")]);
+    let second = fixture([("two.txt", "x")]);
+
+    let rows = records(&run_csv(&[], &[first.path(), second.path()]), true);
+    let listed: Vec<&str> = rows.iter().map(|row| &row[0]).collect();
+    assert_eq!(listed, ["one.txt", "two.txt"]);
+}
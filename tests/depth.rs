@@ -0,0 +1,79 @@
+//! `--min-depth` and `--max-depth` on a four-level tree
+
+mod common;
+
+use common::fixture;
+use std::process::Output;
+
+/// `top.txt`, `a/a.txt`, `a/b/b.txt` and `a/b/c/c.txt`: depths 0 to 3
+const FILES: [(&str, &str); 4] = [
+    ("top.txt", "x"),
+    ("a/a.txt", "x"),
+    ("a/b/b.txt", "x"),
+    ("a/b/c/c.txt", "x"),
+];
+
+fn run_recursive(dir: &tempfile::TempDir, args: &[&str]) -> Output {
+    common::bestls(
+        &[&["-R", "--compact", "--no-color"], args].concat(),
+        dir.path(),
+    )
+}
+
+fn listed(dir: &tempfile::TempDir, args: &[&str]) -> Vec<String> {
+    let output = run_recursive(dir, args);
+    assert_eq!(output.status.code(), Some(0));
+    let mut names: Vec<String> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_min_depth_counts_from_the_directory_entries() {
+    let dir = fixture(FILES);
+    let all = [
+        "a",
+        "a/a.txt",
+        "a/b",
+        "a/b/b.txt",
+        "a/b/c",
+        "a/b/c/c.txt",
+        "top.txt",
+    ];
+    assert_eq!(listed(&dir, &["--min-depth", "0"]), all);
+    assert_eq!(
+        listed(&dir, &["--min-depth", "1"]),
+        ["a/a.txt", "a/b", "a/b/b.txt", "a/b/c", "a/b/c/c.txt"]
+    );
+    // Shallower directories are still walked, only not listed
+    assert_eq!(
+        listed(&dir, &["--min-depth", "2"]),
+        ["a/b/b.txt", "a/b/c", "a/b/c/c.txt"]
+    );
+    assert_eq!(listed(&dir, &["--min-depth", "3"]), ["a/b/c/c.txt"]);
+    assert!(listed(&dir, &["--min-depth", "4"]).is_empty());
+}
+
+#[test]
+fn test_depth_window() {
+    let dir = fixture(FILES);
+    assert_eq!(
+        listed(&dir, &["--min-depth", "1", "--max-depth", "1"]),
+        ["a/a.txt", "a/b"]
+    );
+    assert_eq!(
+        listed(&dir, &["--min-depth", "1", "--max-depth", "2"]),
+        ["a/a.txt", "a/b", "a/b/b.txt", "a/b/c"]
+    );
+
+    let output = run_recursive(&dir, &["--min-depth", "2", "--max-depth", "1"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("--min-depth (2) must be less than or equal to --max-depth (1)"));
+}
@@ -0,0 +1,47 @@
+//! `-d` lists the given paths themselves, as one listing
+
+use std::process::Command;
+
+#[test]
+fn test_directories_are_single_entries() {
+    let dir = tempfile::tempdir().unwrap();
+    for sub in ["beta", "alpha"] {
+        std::fs::create_dir(dir.path().join(sub)).unwrap();
+        std::fs::write(dir.path().join(sub).join("inside.txt"), "x").unwrap();
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["-d", "--json", "beta", "alpha"])
+        .current_dir(dir.path())
+        .output()
+        .expect("failed to run bestls");
+    assert_eq!(output.status.code(), Some(0));
+
+    // One array for both paths, in sort order
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entries = json.as_array().unwrap();
+    let names: Vec<&str> = entries
+        .iter()
+        .map(|e| e["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["alpha", "beta"]);
+
+    let metadata = std::fs::metadata(dir.path().join("alpha")).unwrap();
+    assert_eq!(entries[0]["e_type"], "Directory");
+    assert_eq!(entries[0]["len_bytes"], metadata.len());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode();
+        let expected: String = (0..9)
+            .map(|bit| {
+                if mode & (0o400 >> bit) == 0 {
+                    '-'
+                } else {
+                    ['r', 'w', 'x'][bit % 3]
+                }
+            })
+            .collect();
+        assert_eq!(entries[0]["permissions"], expected);
+    }
+}
@@ -0,0 +1,77 @@
+//! `--dirs-last` lists directories after the other entries, keeping the sort order
+
+mod common;
+
+use common::{bestls, fixture, stdout};
+
+const FILES: [(&str, &str); 4] = [
+    ("big.txt", "12345"),
+    ("small.txt", "1"),
+    ("adir/", ""),
+    ("zdir/", ""),
+];
+
+fn lines(args: &[&str], dir: &std::path::Path) -> Vec<String> {
+    stdout(bestls(&[&["-1"], args].concat(), dir))
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_directories_follow_files() {
+    let dir = fixture(FILES);
+    assert_eq!(
+        lines(&["--dirs-last"], dir.path()),
+        ["big.txt", "small.txt", "adir", "zdir"]
+    );
+    assert_eq!(
+        lines(&["--dirs-last", "--sort", "-name"], dir.path()),
+        ["small.txt", "big.txt", "zdir", "adir"]
+    );
+    assert_eq!(
+        lines(
+            &["--dirs-last", "--sort", "size", "--filter-ext", "txt"],
+            dir.path()
+        ),
+        ["small.txt", "big.txt"]
+    );
+
+    // Structured output has the same order
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout(bestls(&["--dirs-last", "--json"], dir.path()))).unwrap();
+    let names: Vec<&str> = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["big.txt", "small.txt", "adir", "zdir"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinks_to_directories_stay_with_files() {
+    let dir = fixture(FILES);
+    std::os::unix::fs::symlink("zdir", dir.path().join("link")).unwrap();
+    assert_eq!(
+        lines(&["--dirs-last"], dir.path()),
+        ["big.txt", "link", "small.txt", "adir", "zdir"]
+    );
+}
+
+#[test]
+fn test_conflicts_with_group_directories_first() {
+    let dir = fixture(FILES);
+    for args in [
+        &["--dirs-last", "--group-directories-first"][..],
+        &["--dirs-last", "--dereference-dirs"],
+    ] {
+        assert_eq!(
+            bestls(args, dir.path()).status.code(),
+            Some(2),
+            "{:?}",
+            args
+        );
+    }
+}
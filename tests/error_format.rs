@@ -0,0 +1,46 @@
+//! `--error-format json` diagnostics as seen by a wrapping script
+
+mod common;
+
+fn stderr_records(args: &[&str]) -> (Option<i32>, Vec<serde_json::Value>) {
+    let output = common::bestls_paths(args, &[]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let records = stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("stderr line is not JSON"))
+        .collect();
+    (output.status.code(), records)
+}
+
+#[test]
+fn test_unreadable_path_is_reported_as_json() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing");
+
+    let (status, records) =
+        stderr_records(&["--error-format", "json", "-p", missing.to_str().unwrap()]);
+    assert_eq!(status, Some(1));
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["level"], "error");
+    assert_eq!(records[0]["code"], "path-unreadable");
+    assert_eq!(records[0]["path"], missing.to_str().unwrap());
+    assert!(records[0]["message"].as_str().unwrap().contains("missing"));
+}
+
+#[test]
+fn test_invalid_filters_are_reported_as_json() {
+    let (status, records) = stderr_records(&["--error-format=json", "--perm", "zz"]);
+    assert_eq!(status, Some(2));
+    assert_eq!(records[0]["code"], "invalid-argument");
+    assert!(records[0]["path"].is_null());
+
+    let (status, records) = stderr_records(&[
+        "--error-format=json",
+        "--min-size",
+        "5KB",
+        "--max-size",
+        "1KB",
+    ]);
+    assert_eq!(status, Some(2));
+    assert_eq!(records[0]["code"], "invalid-size-range");
+}
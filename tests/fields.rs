@@ -0,0 +1,93 @@
+//! `--fields` restricts every structured output to the selected fields
+
+mod common;
+
+use common::{bestls, fixture, stdout};
+use std::collections::BTreeSet;
+
+fn keys(object: &serde_json::Value) -> Vec<&str> {
+    object
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect()
+}
+
+const FILES: [(&str, &str); 2] = [("sub/", ""), ("a.txt", "hello")];
+
+const FIELDS: &str = "name,len_bytes,modified";
+
+#[test]
+fn test_json_keys() {
+    let dir = fixture(FILES);
+    for format in ["json", "json-pretty"] {
+        let json: serde_json::Value = serde_json::from_str(&stdout(bestls(
+            &["--format", format, "--fields", FIELDS],
+            dir.path(),
+        )))
+        .unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        for entry in entries {
+            assert_eq!(keys(entry), ["name", "len_bytes", "modified"]);
+        }
+    }
+}
+
+#[test]
+fn test_ndjson_and_yaml_keys() {
+    let dir = fixture(FILES);
+    let ndjson = stdout(bestls(
+        &["--format", "ndjson", "--fields", FIELDS],
+        dir.path(),
+    ));
+    for line in ndjson.lines() {
+        let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(keys(&entry), ["name", "len_bytes", "modified"]);
+    }
+
+    let yaml = stdout(bestls(
+        &["--format", "yaml", "--fields", "len_bytes,name"],
+        dir.path(),
+    ));
+    let entries: Vec<serde_yaml::Mapping> = serde_yaml::from_str(&yaml).unwrap();
+    for entry in entries {
+        let keys: Vec<&str> = entry.keys().map(|k| k.as_str().unwrap()).collect();
+        assert_eq!(keys, ["len_bytes", "name"]);
+    }
+}
+
+#[test]
+fn test_csv_columns() {
+    let dir = fixture(FILES);
+    let csv = stdout(bestls(
+        &[
+            "--format",
+            "csv",
+            "--fields",
+            "name,len_bytes",
+            "--sort",
+            "name",
+        ],
+        dir.path(),
+    ));
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("name,len_bytes"));
+    let rows: BTreeSet<&str> = lines.collect();
+    assert!(rows.contains("a.txt,5"), "{}", csv);
+    assert_eq!(rows.len(), 2);
+}
+
+#[test]
+fn test_unknown_field_is_rejected() {
+    let dir = fixture(FILES);
+    let output = bestls(
+        &["--format", "json", "--fields", "name,len_byts"],
+        dir.path(),
+    );
+    assert_ne!(output.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown field 'len_byts'"), "{}", stderr);
+    assert!(stderr.contains("len_bytes"), "{}", stderr);
+}
@@ -0,0 +1,49 @@
+//! A file given as the path is listed as that single entry, like `ls FILE`
+
+mod common;
+
+use common::bestls;
+
+#[test]
+fn test_file_is_a_one_row_listing() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("notes.txt");
+    std::fs::write(&file, "abc").unwrap();
+
+    let output = bestls(&["--compact"], &file);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "notes.txt\n");
+
+    let output = bestls(&["--json"], &file);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["name"], "notes.txt");
+    assert_eq!(json[0]["len_bytes"], 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_to_file_is_listed_as_the_link() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "abc").unwrap();
+    let link = dir.path().join("link");
+    std::os::unix::fs::symlink("notes.txt", &link).unwrap();
+
+    let output = bestls(&["--json"], &link);
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 1);
+    assert_eq!(json[0]["name"], "link");
+    assert_eq!(json[0]["e_type"], "Symlink");
+}
+
+#[test]
+fn test_missing_path_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = bestls(&["--compact"], &dir.path().join("missing"));
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("missing"));
+}
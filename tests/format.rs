@@ -0,0 +1,57 @@
+//! `--format` chooses the output; `--json` and `--json-pretty` are hidden aliases
+
+mod common;
+
+use common::{bestls, bestls_paths, fixture};
+
+const FILES: [(&str, &str); 2] = [("sub/", ""), ("a.txt", "hello")];
+
+#[test]
+fn test_json_aliases_are_byte_identical() {
+    let dir = fixture(FILES);
+    for (alias, format) in [
+        (&["--json"][..], "json"),
+        (&["-j"], "json"),
+        (&["--json-pretty"], "json-pretty"),
+    ] {
+        let legacy = bestls(alias, dir.path());
+        let current = bestls(&["--format", format], dir.path());
+        assert_eq!(legacy.status.code(), Some(0));
+        assert_eq!(legacy.stdout, current.stdout, "{:?}", alias);
+    }
+}
+
+#[test]
+fn test_conflicting_formats_are_rejected() {
+    let dir = fixture(FILES);
+    for args in [
+        &["--json", "--json-pretty"][..],
+        &["--json", "--format", "csv"],
+        &["--json-pretty", "--format", "table"],
+        &["--fzf", "--format", "json"],
+    ] {
+        let output = bestls(args, dir.path());
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn test_help_and_completions_list_formats() {
+    let help = String::from_utf8(bestls_paths(&["--help"], &[]).stdout).unwrap();
+    assert!(help.contains("--format <FORMAT>"));
+    assert!(!help.contains("--json-pretty"));
+
+    let completion = String::from_utf8(bestls_paths(&["completion", "bash"], &[]).stdout).unwrap();
+    for value in [
+        "json-pretty",
+        "csv",
+        "tsv",
+        "yaml",
+        "ndjson",
+        "markdown",
+        "html",
+    ] {
+        assert!(completion.contains(value), "missing {}", value);
+    }
+}
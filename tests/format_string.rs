@@ -0,0 +1,61 @@
+//! `--format-string` prints one templated line per entry
+
+mod common;
+
+use common::fixture;
+use std::process::Output;
+
+const FILES: [(&str, &str); 2] = [("a.txt", "hello"), ("b.rs", "fn main() {}")];
+
+fn run_sorted(args: &[&str], dir: &std::path::Path) -> Output {
+    common::bestls(&[&["--no-color", "--sort", "name"], args].concat(), dir)
+}
+
+#[test]
+fn test_template_lines() {
+    let dir = fixture(FILES);
+    let output = run_sorted(
+        &["--format-string", r"{name}\t{len_bytes}\t{extension}"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a.txt\t5\ttxt\nb.rs\t12\trs\n"
+    );
+}
+
+#[test]
+fn test_escaped_braces_and_path() {
+    let dir = fixture(FILES);
+    let output = run_sorted(&["--format-string", "{{{name}}} {path}"], dir.path());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let first = stdout.lines().next().unwrap();
+    let path = dir.path().join("a.txt");
+    assert_eq!(first, format!("{{a.txt}} {}", path.display()));
+}
+
+#[test]
+fn test_unknown_placeholder_lists_valid_names() {
+    let dir = fixture(FILES);
+    let output = run_sorted(&["--format-string", "{name} {size}"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("unknown placeholder '{size}'"),
+        "{}",
+        stderr
+    );
+    assert!(stderr.contains("human_size"), "{}", stderr);
+}
+
+#[test]
+fn test_conflicts_with_format() {
+    let dir = fixture(FILES);
+    let output = run_sorted(
+        &["--format-string", "{name}", "--format", "csv"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,63 @@
+//! `--grid` lays names out in columns, and lists one per line when piped
+
+mod common;
+
+use common::{bestls, fixture, stdout};
+
+const NAMES: [&str; 7] = [
+    "alpha",
+    "beta",
+    "delta",
+    "epsilon",
+    "eta",
+    "gamma",
+    "日本語.txt",
+];
+
+#[test]
+fn test_grid_fills_width_column_major() {
+    let dir = fixture(NAMES.map(|name| (name, "x")));
+    assert_eq!(
+        stdout(bestls(&["--grid", "--width", "30"], dir.path())),
+        "alpha  epsilon  日本語.txt\nbeta   eta\ndelta  gamma\n"
+    );
+    assert_eq!(
+        stdout(bestls(&["--grid", "--width", "20"], dir.path())),
+        "alpha    eta\nbeta     gamma\ndelta    日本語.txt\nepsilon\n"
+    );
+}
+
+#[test]
+fn test_grid_without_terminal_lists_one_per_line() {
+    let dir = fixture(NAMES.map(|name| (name, "x")));
+    assert_eq!(
+        stdout(bestls(&["--grid"], dir.path())),
+        "alpha\nbeta\ndelta\nepsilon\neta\ngamma\n日本語.txt\n"
+    );
+}
+
+#[test]
+fn test_grid_colors_with_color_always() {
+    let dir = fixture(NAMES.map(|name| (name, "x")));
+    let grid = stdout(bestls(
+        &["--grid", "--width", "200", "--color", "always"],
+        dir.path(),
+    ));
+    assert_eq!(grid.lines().count(), 1);
+    assert!(grid.contains("\x1b["));
+}
+
+#[test]
+fn test_width_requires_grid() {
+    let dir = fixture(NAMES.map(|name| (name, "x")));
+    assert_eq!(
+        bestls(&["--width", "80"], dir.path()).status.code(),
+        Some(2)
+    );
+    assert_eq!(
+        bestls(&["--grid", "--width", "0"], dir.path())
+            .status
+            .code(),
+        Some(2)
+    );
+}
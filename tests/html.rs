@@ -0,0 +1,29 @@
+//! `--format html` escapes file names and classes rows by entry type
+
+#![cfg(unix)]
+
+mod common;
+
+#[test]
+fn test_names_are_escaped() {
+    let dir = common::fixture([("<script>&\"'.txt", "x"), ("sub/", "")]);
+    std::os::unix::fs::symlink("sub", dir.path().join("link")).unwrap();
+
+    let html = common::stdout(common::bestls(
+        &["--format", "html", "--sort", "name", "--color", "always"],
+        dir.path(),
+    ));
+
+    assert!(html.starts_with("<table class=\"bestls\">\n<thead>\n"));
+    assert!(html.ends_with("</tbody>\n</table>\n"));
+    assert!(!html.contains('\x1b'));
+    assert!(!html.contains("<script>"));
+    let rows: Vec<&str> = html
+        .lines()
+        .filter(|line| line.starts_with("<tr class="))
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0].starts_with("<tr class=\"file\"><td>&lt;script&gt;&amp;&quot;&#39;.txt</td>"));
+    assert!(rows[1].starts_with("<tr class=\"symlink\"><td>link</td>"));
+    assert!(rows[2].starts_with("<tr class=\"dir\"><td>sub</td>"));
+}
@@ -0,0 +1,34 @@
+//! Ctrl-C during a long listing still produces valid, partial output
+
+#![cfg(unix)]
+
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_sigint_renders_partial_results() {
+    // Walking the whole filesystem takes far longer than the delay below
+    let child = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["-p", "/", "--tree", "--json", "--envelope"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run bestls");
+
+    thread::sleep(Duration::from_millis(300));
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(130));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["partial"], true);
+    assert!(json["entries"].is_array());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("partial results"));
+}
@@ -0,0 +1,75 @@
+//! `-l` prints aligned `ls -l` style columns after a `total` line
+
+#![cfg(unix)]
+
+mod common;
+
+use common::bestls;
+use std::os::unix::fs::MetadataExt;
+use std::time::{Duration, SystemTime};
+
+fn long_fixture() -> tempfile::TempDir {
+    let dir = common::fixture([
+        ("sub/", &b""[..]),
+        ("a.txt", b"x"),
+        ("big-file-name.bin", &[0u8; 5000]),
+    ]);
+    std::fs::hard_link(dir.path().join("a.txt"), dir.path().join("b.txt")).unwrap();
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_724_348_663);
+    for name in ["sub", "a.txt", "big-file-name.bin"] {
+        let file = std::fs::File::open(dir.path().join(name)).unwrap();
+        file.set_modified(modified).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_long_columns_are_aligned() {
+    let dir = long_fixture();
+    let output = bestls(&["--no-color", "-l", "--time-style", "iso"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+
+    let blocks: u64 = ["sub", "a.txt", "b.txt", "big-file-name.bin"]
+        .iter()
+        .map(|name| std::fs::metadata(dir.path().join(name)).unwrap().blocks())
+        .sum();
+    assert_eq!(lines[0], format!("total {}", blocks.div_ceil(2)));
+    assert_eq!(lines.len(), 5);
+
+    // Every line has the same columns up to the name, which starts at the same offset
+    let name_start = |line: &str| line.rfind(' ').unwrap() + 1;
+    let start = name_start(lines[1]);
+    for line in &lines[1..] {
+        assert_eq!(name_start(line), start, "misaligned: {:?}", lines);
+    }
+    let a = lines.iter().find(|l| l.ends_with(" a.txt")).unwrap();
+    assert!(a.starts_with("-rw-"), "{}", a);
+    assert_eq!(a.split_whitespace().nth(1), Some("2"));
+    let sub = lines.iter().find(|l| l.ends_with(" sub")).unwrap();
+    assert!(sub.starts_with('d'), "{}", sub);
+}
+
+#[test]
+fn test_long_colors_only_names() {
+    let dir = long_fixture();
+    let output = bestls(&["-l", "--color", "always"], dir.path());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    for line in stdout.lines().skip(1) {
+        let escape = line.find('\x1b').expect("name is colored");
+        assert!(
+            line[escape..].contains("sub")
+                || line[escape..].contains(".txt")
+                || line[escape..].contains(".bin")
+        );
+        assert_eq!(line[..escape].chars().last(), Some(' '));
+    }
+}
+
+#[test]
+fn test_long_conflicts_with_json() {
+    let dir = long_fixture();
+    let output = bestls(&["-l", "--json"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+}
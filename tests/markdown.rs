@@ -0,0 +1,32 @@
+//! `--format markdown` sorts like the table and never emits colors
+
+mod common;
+
+#[test]
+fn test_markdown_rows_follow_the_sort() {
+    let files = [("big.txt", 300), ("small.txt", 1), ("mid|dle.txt", 20)];
+    let dir = common::fixture(files.map(|(name, len)| (name, "x".repeat(len))));
+
+    let stdout = common::stdout(common::bestls(
+        &[
+            "--format", "markdown", "--sort", "size", "--color", "always",
+        ],
+        dir.path(),
+    ));
+    assert!(!stdout.contains('\x1b'));
+
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert!(rows[0].starts_with("| Name "));
+    assert!(rows[1].starts_with("|---"));
+    let names: Vec<&str> = rows[2..]
+        .iter()
+        .map(|row| {
+            row.trim_start_matches("| ")
+                .split(" | ")
+                .next()
+                .unwrap()
+                .trim()
+        })
+        .collect();
+    assert_eq!(names, ["small.txt", "mid\\|dle.txt", "big.txt"]);
+}
@@ -0,0 +1,70 @@
+//! `--max-name-width` cuts and `--wrap` wraps table names, leaving machine-readable
+//! output alone
+
+mod common;
+
+use std::process::Output;
+
+const LONG_NAME: &str = "日本語のファイル名-🎉-report.txt";
+
+fn run_plain(args: &[&str], dir: &std::path::Path) -> Output {
+    common::bestls(&[&["--no-color"], args].concat(), dir)
+}
+
+fn plain_stdout(args: &[&str], dir: &std::path::Path) -> String {
+    common::stdout(run_plain(args, dir))
+}
+
+fn cut_names(args: &[&str], dir: &std::path::Path) -> String {
+    plain_stdout(&[&["--max-name-width", "10"], args].concat(), dir)
+}
+
+fn long_name_fixture() -> tempfile::TempDir {
+    common::fixture([(LONG_NAME, "x"), ("a.txt", "x")])
+}
+
+#[test]
+fn test_table_names_are_cut() {
+    let dir = long_name_fixture();
+    let table = cut_names(&["--columns", "name"], dir.path());
+    // A two-column character does not fit the tenth column, so the name takes nine
+    assert!(table.contains("│ 日本語の… │"), "{}", table);
+    assert!(table.contains("│ a.txt     │"), "{}", table);
+    assert!(!table.contains(LONG_NAME));
+}
+
+#[test]
+fn test_full_names_in_json_and_csv() {
+    let dir = long_name_fixture();
+    let json: serde_json::Value =
+        serde_json::from_str(&cut_names(&["--format", "json"], dir.path())).unwrap();
+    assert!(json
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry["name"] == LONG_NAME));
+
+    let csv = cut_names(&["--format", "csv"], dir.path());
+    assert!(csv.contains(LONG_NAME), "{}", csv);
+}
+
+#[test]
+fn test_wrap() {
+    let dir = long_name_fixture();
+    let table = plain_stdout(&["--columns", "name,size", "--wrap", "10"], dir.path());
+    // Wide characters count twice, so a line can end a column short
+    for line in [
+        "│ 日本語のフ │ 1 B  │",
+        "│ ァイル名-  │      │",
+        "│ 🎉-report. │      │",
+    ] {
+        assert!(table.contains(line), "{}", table);
+    }
+
+    // Off a terminal, there is no width to fit, so names stay whole
+    let table = plain_stdout(&["--columns", "name", "--wrap", "--"], dir.path());
+    assert!(table.contains(LONG_NAME), "{}", table);
+
+    let output = run_plain(&["--wrap", "10", "--max-name-width", "10"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+}
@@ -0,0 +1,73 @@
+//! Several paths on the command line or from `--stdin`, each listed on its own
+
+mod common;
+
+use common::{bestls_paths, fixture};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const FILES: [(&str, &str); 2] = [("one/a.txt", "x"), ("two/b.txt", "x")];
+
+#[test]
+fn test_each_path_gets_a_header() {
+    let dir = fixture(FILES);
+    let one = dir.path().join("one");
+    let two = dir.path().join("two");
+
+    let output = bestls_paths(&["--compact", "--no-color"], &[&two, &one]);
+    assert_eq!(output.status.code(), Some(0));
+    let expected = format!("{}:\nb.txt\n\n{}:\na.txt\n", two.display(), one.display());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+}
+
+#[test]
+fn test_json_is_keyed_by_path() {
+    let dir = fixture(FILES);
+    let one = dir.path().join("one");
+    let missing = dir.path().join("missing");
+    let two = dir.path().join("two");
+
+    // The unreadable path is reported but does not stop the others
+    let output = bestls_paths(&["--json"], &[&one, &missing, &two]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("missing"));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let listings = json.as_object().unwrap();
+    let keys: Vec<&String> = listings.keys().collect();
+    assert_eq!(keys, [one.to_str().unwrap(), two.to_str().unwrap()]);
+    assert_eq!(listings[one.to_str().unwrap()][0]["name"], "a.txt");
+}
+
+#[test]
+fn test_stdin_paths() {
+    let dir = fixture(FILES);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["--stdin", "--compact", "--no-color"])
+        .current_dir(dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run bestls");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"one\nmissing\ntwo\none\n./one\ntwo/\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    // Missing paths are skipped with a warning, repeated ones are listed once, however
+    // they are spelled
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("Warning: skipping 'missing'"));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "one:\na.txt\n\ntwo:\nb.txt\n"
+    );
+}
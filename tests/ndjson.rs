@@ -0,0 +1,97 @@
+//! `--format ndjson` writes one JSON object per line, buffered or streamed
+
+mod common;
+
+use serde_json::Value;
+
+fn many_files() -> tempfile::TempDir {
+    let files = (0..50).map(|i| format!("file{:02}.txt", i));
+    common::fixture(
+        files
+            .chain(["image.png".into(), "sub/".into()])
+            .map(|name| (name, "x")),
+    )
+}
+
+/// Run bestls and parse every output line as its own JSON object
+fn lines(args: &[&str], dir: &std::path::Path) -> Vec<Value> {
+    let stdout = common::stdout(common::bestls(
+        &[&["--format", "ndjson"], args].concat(),
+        dir,
+    ));
+    assert!(stdout.ends_with("}\n"));
+    stdout
+        .lines()
+        .map(|line| {
+            let value: Value = serde_json::from_str(line).unwrap();
+            assert!(value.is_object(), "{}", line);
+            value
+        })
+        .collect()
+}
+
+fn names(lines: &[Value]) -> Vec<String> {
+    lines
+        .iter()
+        .map(|line| line["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_sorted_lines() {
+    let dir = many_files();
+    let sorted = lines(&[], dir.path());
+    assert_eq!(sorted.len(), 52);
+    assert_eq!(sorted[0]["name"], "file00.txt");
+    assert_eq!(sorted[0]["len_bytes"], 1);
+    assert_eq!(sorted[51]["name"], "sub");
+}
+
+#[test]
+fn test_streamed_lines_match_buffered() {
+    let dir = many_files();
+    let mut streamed = names(&lines(&["--sort", "none"], dir.path()));
+    streamed.sort();
+    assert_eq!(streamed, names(&lines(&[], dir.path())));
+
+    // Filters and --fields apply to streamed entries too
+    let filtered = lines(
+        &["--sort", "none", "--filter-ext", "png", "--fields", "name"],
+        dir.path(),
+    );
+    assert_eq!(filtered, [serde_json::json!({ "name": "image.png" })]);
+}
+
+#[test]
+fn test_empty_listing_prints_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    for sort in ["name", "none"] {
+        let output = common::bestls(&["--format", "ndjson", "--sort", sort], dir.path());
+        assert_eq!(output.status.code(), Some(0));
+        assert!(output.stdout.iter().all(|b| *b == b'\n'));
+    }
+}
+
+#[test]
+fn test_raw_timestamps_match_display() {
+    let dir = many_files();
+    // `+%s` renders the display column as epoch seconds, which must agree
+    for line in lines(&["--time-style", "+%s"], dir.path()) {
+        let epoch = line["modified_epoch"].as_i64().unwrap();
+        assert_eq!(line["modified"], epoch.to_string());
+        let iso = line["modified_iso"].as_str().unwrap();
+        assert!(iso.ends_with('Z'), "{}", iso);
+    }
+
+    let projected = lines(
+        &[
+            "--filter-ext",
+            "png",
+            "--fields",
+            "name,modified_epoch,modified_iso",
+        ],
+        dir.path(),
+    );
+    assert!(projected[0]["modified_epoch"].is_i64());
+    assert!(projected[0]["modified_iso"].is_string());
+}
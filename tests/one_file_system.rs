@@ -0,0 +1,26 @@
+//! `--one-file-system` lists mount points without entering them
+
+#![cfg(unix)]
+
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_proc_is_listed_but_not_entered() {
+    let (Ok(root), Ok(proc)) = (Path::new("/").metadata(), Path::new("/proc").metadata()) else {
+        return;
+    };
+    if root.dev() == proc.dev() {
+        return;
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["-R", "--depth", "2", "--one-file-system", "--compact", "/"])
+        .output()
+        .expect("failed to run bestls");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let names: Vec<&str> = stdout.lines().collect();
+    assert!(names.contains(&"proc"));
+    assert!(!names.iter().any(|name| name.starts_with("proc/")));
+}
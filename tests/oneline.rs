@@ -0,0 +1,58 @@
+//! `-1` prints one name per line, honoring sorting, filters, and hidden files
+
+mod common;
+
+fn sized_files() -> tempfile::TempDir {
+    let files = [
+        ("b.rs", 30),
+        ("a.txt", 10),
+        ("c.rs", 20),
+        (".hidden", 1),
+        ("sub/", 0),
+    ];
+    common::fixture(files.map(|(name, len)| (name, "x".repeat(len))))
+}
+
+/// Run bestls with its output piped, returning the lines
+fn lines(args: &[&str], dir: &std::path::Path) -> Vec<String> {
+    common::stdout(common::bestls(args, dir))
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn test_one_name_per_line() {
+    let dir = sized_files();
+    // Piped output is never colored
+    assert_eq!(lines(&["-1"], dir.path()), ["a.txt", "b.rs", "c.rs", "sub"]);
+    assert_eq!(lines(&["-1", "-a"], dir.path()).len(), 5);
+    assert_eq!(
+        lines(
+            &["--oneline", "--sort", "size", "--filter-ext", "rs"],
+            dir.path()
+        ),
+        ["c.rs", "b.rs"]
+    );
+}
+
+#[test]
+fn test_color_always() {
+    let dir = sized_files();
+    let colored = lines(&["-1", "--color", "always"], dir.path());
+    assert_eq!(colored.len(), 4);
+    assert!(colored.iter().all(|line| line.starts_with('\x1b')));
+}
+
+#[test]
+fn test_full_path() {
+    let dir = sized_files();
+    let paths = lines(&["-1", "--full-path", "--filter-ext", "rs"], dir.path());
+    assert_eq!(paths.len(), 2);
+    for (line, name) in paths.iter().zip(["b.rs", "c.rs"]) {
+        let path = std::path::Path::new(line);
+        assert!(path.is_absolute(), "{}", line);
+        assert!(path.ends_with(name));
+        assert!(path.exists());
+    }
+}
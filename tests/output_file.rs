@@ -0,0 +1,99 @@
+//! `-o/--out` replaces the target file atomically and writes it uncolored
+
+mod common;
+
+use common::{bestls, fixture};
+
+const FILES: [(&str, &str); 1] = [("a.txt", "hello")];
+
+/// Names in the output directory other than the target, such as leftover temp files
+fn others(dir: &std::path::Path, target: &str) -> Vec<String> {
+    std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name != target)
+        .collect()
+}
+
+#[test]
+fn test_output_replaces_target() {
+    let dir = fixture(FILES);
+    let out = tempfile::tempdir().unwrap();
+    let target = out.path().join("report.csv");
+    std::fs::write(
+        &target,
+        "a much longer previous report that must disappear\n".repeat(50),
+    )
+    .unwrap();
+
+    let output = bestls(
+        &["--format", "csv", "-o", target.to_str().unwrap()],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+    let report = std::fs::read_to_string(&target).unwrap();
+    assert!(report.starts_with("Name,Type,"), "{}", report);
+    assert!(report.contains("a.txt,File,5,"));
+    assert!(!report.contains("previous report"));
+    assert!(others(out.path(), "report.csv").is_empty());
+
+    // The long name writes the same file
+    let output = bestls(
+        &["--format", "json", "--output", target.to_str().unwrap()],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&target).unwrap()).unwrap();
+    assert_eq!(json[0]["name"], "a.txt");
+}
+
+#[test]
+fn test_output_is_uncolored() {
+    let dir = fixture(FILES);
+    let out = tempfile::tempdir().unwrap();
+    let target = out.path().join("listing.txt");
+    let output = bestls(&["-o", target.to_str().unwrap()], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    let listing = std::fs::read_to_string(&target).unwrap();
+    assert!(listing.contains("a.txt"));
+    assert!(!listing.contains('\x1b'), "{:?}", listing);
+
+    bestls(
+        &["-o", target.to_str().unwrap(), "--color", "always"],
+        dir.path(),
+    );
+    assert!(std::fs::read_to_string(&target).unwrap().contains('\x1b'));
+}
+
+#[test]
+fn test_mkdir_creates_parents() {
+    let dir = fixture(FILES);
+    let out = tempfile::tempdir().unwrap();
+    let target = out.path().join("reports/daily/listing.json");
+    let target = target.to_str().unwrap();
+
+    let output = bestls(&["--format", "json", "-o", target], dir.path());
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("cannot create a temporary file in"),
+        "{}",
+        stderr
+    );
+
+    let output = bestls(&["--format", "json", "-o", target, "--mkdir"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    assert!(std::fs::read_to_string(target).unwrap().contains("a.txt"));
+}
+
+#[test]
+fn test_unwritable_destination_fails() {
+    let dir = fixture(FILES);
+    // A directory cannot be replaced by the report
+    let output = bestls(&["-o", dir.path().to_str().unwrap()], dir.path());
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("is a directory"), "{}", stderr);
+}
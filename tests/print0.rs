@@ -0,0 +1,68 @@
+//! `-1 --print0` writes exact file name bytes, each terminated by NUL
+
+#![cfg(unix)]
+
+mod common;
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+
+fn print0(args: &[&str], dir: &std::path::Path) -> Vec<u8> {
+    let output = common::bestls(
+        &[
+            &["-1", "--print0", "--sort", "name", "--color", "always"],
+            args,
+        ]
+        .concat(),
+        dir,
+    );
+    assert_eq!(output.status.code(), Some(0));
+    output.stdout
+}
+
+/// Split a NUL-terminated stream into its names
+fn split(stream: &[u8]) -> Vec<&[u8]> {
+    assert_eq!(stream.last(), Some(&0), "the last name is terminated too");
+    stream[..stream.len() - 1].split(|b| *b == 0).collect()
+}
+
+#[test]
+fn test_names_split_on_nul() {
+    let dir = tempfile::tempdir().unwrap();
+    let names: [&[u8]; 4] = [b"a\xff\xfe.bin", b"line\nbreak", b"with space", b"zz"];
+    for name in names {
+        // Some filesystems (e.g. APFS) refuse names that are not valid UTF-8
+        if std::fs::write(dir.path().join(OsStr::from_bytes(name)), "x").is_err() {
+            return;
+        }
+    }
+
+    let stream = print0(&[], dir.path());
+    assert!(!stream.contains(&0x1b), "--print0 is never colored");
+    assert_eq!(split(&stream), names);
+
+    // Every name opens the file it came from
+    for name in split(&stream) {
+        assert!(dir.path().join(OsStr::from_bytes(name)).exists());
+    }
+}
+
+#[test]
+fn test_recursive_and_full_paths() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    std::fs::write(dir.path().join("sub/two\nlines"), "x").unwrap();
+
+    assert_eq!(
+        split(&print0(&["-R"], dir.path())),
+        [&b"sub"[..], b"sub/two\nlines"]
+    );
+
+    let stream = print0(&["-R", "--full-path"], dir.path());
+    let paths = split(&stream);
+    assert_eq!(paths.len(), 2);
+    for path in paths {
+        let path = std::path::Path::new(OsStr::from_bytes(path));
+        assert!(path.is_absolute() && path.exists(), "{:?}", path);
+    }
+}
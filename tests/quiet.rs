@@ -0,0 +1,66 @@
+//! `-q/--quiet` prints nothing and answers through the exit status
+
+mod common;
+
+use common::{bestls_paths, fixture};
+
+const FILES: [(&str, &str); 2] = [("Cargo.lock", "x"), ("notes.txt", "x")];
+
+#[test]
+fn test_match_exits_zero() {
+    let dir = fixture(FILES);
+    for args in [
+        &["-q", "--glob", "*.lock"][..],
+        &["--quiet", "--filter-ext", "txt"],
+        // Streamable formats are not written either
+        &["-q", "--format", "ndjson", "--sort", "none"],
+    ] {
+        let output = bestls_paths(args, &[dir.path()]);
+        assert_eq!(output.status.code(), Some(0), "{:?}", args);
+        assert!(output.stdout.is_empty(), "{:?}", args);
+        assert!(output.stderr.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_no_match_exits_one() {
+    let dir = fixture(FILES);
+    let output = bestls_paths(&["-q", "--glob", "*.zip"], &[dir.path()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    // No "0 entries matched" note either
+    assert!(output.stderr.is_empty());
+
+    let empty = tempfile::tempdir().unwrap();
+    assert_eq!(
+        bestls_paths(&["-q"], &[empty.path()]).status.code(),
+        Some(1)
+    );
+}
+
+#[test]
+fn test_read_error_exits_two() {
+    let dir = fixture(FILES);
+    let missing = dir.path().join("missing");
+    let output = bestls_paths(&["-q"], &[&missing]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("cannot access"));
+
+    // A match elsewhere still answers yes, like grep -q
+    let output = bestls_paths(&["-q", "--glob", "*.lock"], &[dir.path(), &missing]);
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_conflicts_with_watch() {
+    let dir = fixture(FILES);
+    assert_eq!(
+        bestls_paths(&["-q", "--watch"], &[dir.path()])
+            .status
+            .code(),
+        Some(2)
+    );
+}
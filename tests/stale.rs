@@ -0,0 +1,42 @@
+//! `bestls stale` reports old entries and honors the global `--max-entries` cap
+
+mod common;
+
+fn twenty_files() -> tempfile::TempDir {
+    let files = (0..10).flat_map(|i| [format!("f{}.txt", i), format!("sub/g{}.txt", i)]);
+    common::fixture(files.map(|name| (name, "x")))
+}
+
+fn stale_count(args: &[&str], dir: &std::path::Path) -> (Option<i32>, u64, String) {
+    let output = common::bestls(
+        &[args, &["stale", "--older-than", "0d", "-R", "--json", "-p"]].concat(),
+        dir,
+    );
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    (
+        output.status.code(),
+        json["count"].as_u64().unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn test_stale_recursive() {
+    let dir = twenty_files();
+    let (code, count, stderr) = stale_count(&[], dir.path());
+    assert_eq!(code, Some(0), "{}", stderr);
+    assert_eq!(count, 20);
+}
+
+#[test]
+fn test_max_entries_marks_stale_partial() {
+    let dir = twenty_files();
+    let (code, count, stderr) = stale_count(&["--max-entries", "5"], dir.path());
+    assert_eq!(code, Some(3));
+    assert!(count <= 5, "{}", count);
+    assert!(
+        stderr.contains("stopped after 5 entries (partial results)"),
+        "{}",
+        stderr
+    );
+}
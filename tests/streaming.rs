@@ -0,0 +1,79 @@
+//! With `--sort none`, line formats are written while the directory is read and match
+//! the buffered output
+
+mod common;
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+fn batch_fixture() -> tempfile::TempDir {
+    // More than one streaming batch
+    let files = (0..600).map(|i| format!("file{:03}.txt", i));
+    common::fixture(
+        files
+            .chain([".hidden".into(), "sub/".into()])
+            .map(|name| (name, "x")),
+    )
+}
+
+fn plain_output(args: &[&str], dir: &std::path::Path) -> Vec<u8> {
+    let output = common::bestls(&[&["--no-color"], args].concat(), dir);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stderr.is_empty());
+    output.stdout
+}
+
+/// Output lines (or `--print0` names), sorted so streamed and buffered output compare
+fn sorted(output: Vec<u8>, separator: u8) -> Vec<Vec<u8>> {
+    let mut items: Vec<Vec<u8>> = output
+        .split(|b| *b == separator)
+        .filter(|item| !item.is_empty())
+        .map(<[u8]>::to_vec)
+        .collect();
+    items.sort();
+    items
+}
+
+#[test]
+fn test_streamed_matches_buffered() {
+    let dir = batch_fixture();
+    for args in [
+        &["-1"][..],
+        &["--compact"],
+        &["--format-string", "{name} {len_bytes}"],
+        &["-1", "--full-path"],
+        &["-1", "--filter-ext", "txt"],
+    ] {
+        let streamed = plain_output(&[args, &["--sort", "none"]].concat(), dir.path());
+        let buffered = plain_output(args, dir.path());
+        let lines = sorted(streamed, b'\n');
+        assert_eq!(lines, sorted(buffered, b'\n'), "{:?}", args);
+        assert!(lines.len() >= 600, "{:?}", args);
+    }
+
+    let streamed = plain_output(&["-1", "--print0", "--sort", "none"], dir.path());
+    let buffered = plain_output(&["-1", "--print0"], dir.path());
+    assert!(streamed.ends_with(b"\0"));
+    assert_eq!(sorted(streamed, 0), sorted(buffered, 0));
+}
+
+#[test]
+fn test_closed_stdout_ends_quietly() {
+    let dir = batch_fixture();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["-1", "--sort", "none"])
+        .arg(dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run bestls");
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut first)
+        .unwrap();
+    assert!(!first.is_empty());
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
@@ -0,0 +1,58 @@
+//! `--style` picks the table borders, with a default from the config files
+
+mod common;
+
+use common::{fixture, stdout};
+use std::process::Output;
+
+fn run_name_size(args: &[&str], dir: &std::path::Path) -> Output {
+    common::bestls(
+        &[&["--no-color", "--columns", "name,size"], args].concat(),
+        dir,
+    )
+}
+
+#[test]
+fn test_style_from_flag_and_config() {
+    let dir = fixture([("a.txt", "hello")]);
+    assert_eq!(
+        stdout(run_name_size(&["--style", "blank"], dir.path())),
+        " Name    Size\n a.txt   5 B\n"
+    );
+
+    // The listed directory's .bestls.toml sets the default, the flag still wins
+    std::fs::write(
+        dir.path().join(".bestls.toml"),
+        "[table]\nstyle = \"psql\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        stdout(run_name_size(&[], dir.path())),
+        " Name  | Size\n-------+------\n a.txt | 5 B\n"
+    );
+    assert!(stdout(run_name_size(&["--style", "ascii"], dir.path())).starts_with("+-------+"));
+}
+
+#[test]
+fn test_unknown_style() {
+    let dir = fixture([("a.txt", "hello")]);
+    let output = run_name_size(&["--style", "fancy"], dir.path());
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("possible values: rounded, ascii"),
+        "{}",
+        stderr
+    );
+
+    // A config file with an unknown style is ignored with a warning
+    std::fs::write(
+        dir.path().join(".bestls.toml"),
+        "[table]\nstyle = \"fancy\"\n",
+    )
+    .unwrap();
+    let output = run_name_size(&[], dir.path());
+    let stderr = String::from_utf8(output.stderr.clone()).unwrap();
+    assert!(stderr.contains("ignoring invalid config"), "{}", stderr);
+    assert!(stdout(output).starts_with('╭'));
+}
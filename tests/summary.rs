@@ -0,0 +1,45 @@
+//! `--summary` prints only the totals of the filtered listing
+
+mod common;
+
+use common::{bestls, fixture};
+
+/// File names and sizes
+const FILES: [(&str, usize); 5] = [
+    ("a.rs", 10),
+    ("b.rs", 20),
+    ("c.txt", 5),
+    (".hidden.rs", 7),
+    ("sub/d.rs", 3),
+];
+
+fn files() -> impl Iterator<Item = (&'static str, String)> {
+    FILES.into_iter().map(|(file, len)| (file, "x".repeat(len)))
+}
+
+#[test]
+fn test_summary_line() {
+    let dir = fixture(files());
+    let output = bestls(&["--summary"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "3 files, 1 directory, 0 symlinks, 35 B total\n"
+    );
+}
+
+#[test]
+fn test_summary_json_respects_filters() {
+    let dir = fixture(files());
+    let output = bestls(
+        &["--summary", "--json", "-R", "-a", "--filter-ext", "rs"],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["files"], 4);
+    assert_eq!(json["directories"], 0);
+    assert_eq!(json["symlinks"], 0);
+    assert_eq!(json["total_bytes"], 40);
+    assert_eq!(json["total_size"], "40 B");
+}
@@ -0,0 +1,84 @@
+//! `--format toml` carries the JSON documents as `[[entries]]` tables
+
+mod common;
+
+use common::{bestls_paths, fixture, stdout};
+use serde_json::Value;
+
+const FILES: [(&str, &str); 2] = [("say \"hi\" = yes.md", "12345"), ("sub/inner.txt", "x")];
+
+fn run_sorted(args: &[&str], dirs: &[&std::path::Path]) -> String {
+    stdout(bestls_paths(&[&["--sort", "name"], args].concat(), dirs))
+}
+
+/// Drop `null` fields, which TOML leaves out
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+/// Parse TOML and JSON output of the same listing into comparable values
+fn both(args: &[&str], dirs: &[&std::path::Path]) -> (Value, Value) {
+    let toml = run_sorted(&[args, &["--format", "toml"]].concat(), dirs);
+    let json = run_sorted(&[args, &["--format", "json"]].concat(), dirs);
+    (
+        toml::from_str(&toml).unwrap(),
+        without_nulls(serde_json::from_str(&json).unwrap()),
+    )
+}
+
+#[test]
+fn test_toml_entries_match_json() {
+    let dir = fixture(FILES);
+    let (toml, json) = both(&[], &[dir.path()]);
+    assert_eq!(toml["entries"], json);
+
+    let entries = toml["entries"].as_array().unwrap();
+    assert_eq!(entries[0]["name"], "say \"hi\" = yes.md");
+    assert_eq!(entries[0]["len_bytes"], 5);
+    assert_eq!(entries[1]["name"], "sub");
+    // Directories have no extension, so the key is missing rather than null
+    assert!(entries[1].get("extension").is_none());
+}
+
+#[test]
+fn test_toml_envelope_and_fields() {
+    let dir = fixture(FILES);
+    let (toml, json) = both(&["--envelope", "--fields", "name,len_bytes"], &[dir.path()]);
+    assert_eq!(toml, json);
+    assert_eq!(toml["partial"], false);
+    assert_eq!(
+        toml["entries"][0],
+        serde_json::json!({ "name": "say \"hi\" = yes.md", "len_bytes": 5 })
+    );
+}
+
+#[test]
+fn test_toml_several_paths_keyed_by_path() {
+    let dir = fixture(FILES);
+    let sub = dir.path().join("sub");
+    let toml = run_sorted(&["--format", "toml"], &[dir.path(), &sub]);
+    let toml: Value = toml::from_str(&toml).unwrap();
+
+    let listings = toml.as_object().unwrap();
+    let keys: Vec<&String> = listings.keys().collect();
+    assert_eq!(keys.len(), 2);
+    assert!(keys[1].ends_with("sub"));
+    assert_eq!(listings[keys[1]]["entries"][0]["name"], "inner.txt");
+}
+
+#[test]
+fn test_empty_listing_is_an_empty_array() {
+    let dir = tempfile::tempdir().unwrap();
+    let toml = run_sorted(&["--format", "toml"], &[dir.path()]);
+    assert_eq!(toml.trim_end(), "entries = []");
+}
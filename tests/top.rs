@@ -0,0 +1,100 @@
+//! `bestls top` ranks the files of a whole tree and keeps the first `-n`
+
+mod common;
+
+use std::fs::File;
+use std::process::Output;
+use std::time::{Duration, SystemTime};
+
+fn run_top(args: &[&str], dir: &std::path::Path) -> Output {
+    common::bestls(&[&["--no-color", "top"], args, &["-p"]].concat(), dir)
+}
+
+/// Files of distinct sizes; the smaller a file, the more recently it was modified
+fn aged_files() -> tempfile::TempDir {
+    let files = [
+        ("huge.bin", 4000),
+        ("sub/deeper/large.bin", 3000),
+        ("sub/medium.txt", 2000),
+        ("small.txt", 1000),
+        ("tiny.txt", 10),
+    ];
+    let dir = common::fixture(files.map(|(file, len)| (file, "x".repeat(len))));
+    for (age_days, (file, _)) in files.iter().rev().enumerate() {
+        let modified = SystemTime::now() - Duration::from_secs(86_400 * (age_days as u64 + 1));
+        File::options()
+            .write(true)
+            .open(dir.path().join(file))
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+    dir
+}
+
+fn ranked(output: Output) -> Vec<String> {
+    assert_eq!(output.status.code(), Some(0));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let entries = json["entries"].as_array().unwrap();
+    assert_eq!(json["count"], entries.len());
+    entries
+        .iter()
+        .map(|entry| entry["path"].as_str().unwrap().replace('\\', "/"))
+        .collect()
+}
+
+#[test]
+fn test_top_by_size_keeps_the_largest() {
+    let dir = aged_files();
+    assert_eq!(
+        ranked(run_top(&["--json", "-n", "3"], dir.path())),
+        ["huge.bin", "sub/deeper/large.bin", "sub/medium.txt"]
+    );
+    // The default cutoff is ten, more than there are files; directories never count
+    assert_eq!(ranked(run_top(&["--json"], dir.path())).len(), 5);
+}
+
+#[test]
+fn test_top_by_date_keeps_the_newest() {
+    let dir = aged_files();
+    assert_eq!(
+        ranked(run_top(&["--json", "--by", "date", "-n", "2"], dir.path())),
+        ["tiny.txt", "small.txt"]
+    );
+}
+
+#[test]
+fn test_top_lines() {
+    let dir = aged_files();
+    let output = run_top(&["-n", "2"], dir.path());
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("4.0 KB  ") && lines[0].ends_with("  huge.bin"));
+    assert!(lines[1].starts_with("3.0 KB  ") && lines[1].ends_with("large.bin"));
+}
+
+#[test]
+fn test_max_entries_caps_the_walk() {
+    let dir = aged_files();
+    let output = common::bestls(
+        &[
+            "--no-color",
+            "--max-entries",
+            "3",
+            "top",
+            "--json",
+            "-n",
+            "50",
+            "-p",
+        ],
+        dir.path(),
+    );
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("stopped after 3 entries (partial results)"));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json["count"].as_u64().unwrap() <= 3);
+}
@@ -0,0 +1,51 @@
+//! Unreadable subdirectories are reported, and the rest of the walk is still listed
+
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+
+#[test]
+fn test_unreadable_subdirectory_is_reported() {
+    // Root reads the locked directory anyway
+    if nix::unistd::geteuid().is_root() {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let locked = dir.path().join("locked");
+    fs::create_dir(&locked).unwrap();
+    fs::write(locked.join("hidden.txt"), "x").unwrap();
+    fs::write(dir.path().join("shown.txt"), "x").unwrap();
+
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    let output = common::bestls(&["-R", "--envelope", "--json"], dir.path());
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Error: failed to read directory"),
+        "{}",
+        stderr
+    );
+    // Piped stderr is never colored
+    assert!(!stderr.contains('\x1b'));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names: Vec<&str> = json["entries"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"shown.txt"));
+    assert!(names.contains(&"locked"));
+    assert!(!names.contains(&"locked/hidden.txt"));
+
+    let errors = json["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["code"], "path-unreadable");
+    assert_eq!(errors[0]["path"], locked.to_str().unwrap());
+}
@@ -0,0 +1,43 @@
+//! `--format tsv` keeps one entry per line, with tabs and line breaks in names escaped
+
+#![cfg(unix)]
+
+mod common;
+
+#[test]
+fn test_tab_in_file_name_is_escaped() {
+    let dir = common::fixture(
+        [
+            "tab\there.txt",
+            "line\nbreak.txt",
+            "back\\slash.txt",
+            "plain.txt",
+        ]
+        .map(|name| (name, "abc")),
+    );
+
+    let stdout = common::stdout(common::bestls(
+        &["--format", "tsv", "--sort", "name", "--no-header"],
+        dir.path(),
+    ));
+
+    let rows: Vec<Vec<&str>> = stdout
+        .lines()
+        .map(|line| line.split('\t').collect())
+        .collect();
+    assert_eq!(rows.len(), 4);
+    for row in &rows {
+        assert_eq!(row.len(), 8, "{:?}", row);
+        assert_eq!(&row[1..4], ["File", "3", "3 B"]);
+    }
+    let names: Vec<&str> = rows.iter().map(|row| row[0]).collect();
+    assert_eq!(
+        names,
+        [
+            "back\\\\slash.txt",
+            "line\\nbreak.txt",
+            "plain.txt",
+            "tab\\there.txt"
+        ]
+    );
+}
@@ -0,0 +1,77 @@
+//! Wide characters, emoji sequences, and combining marks in names keep tables aligned
+
+mod common;
+
+use unicode_width::UnicodeWidthStr;
+
+/// CJK, a zero-width-joiner emoji family, and an `é` written as `e` plus a combining
+/// acute accent
+const NAMES: [&str; 4] = [
+    "日本語ファイル.txt",
+    "👨\u{200d}👩\u{200d}👧\u{200d}👦.png",
+    "cafe\u{301}.md",
+    "plain.txt",
+];
+
+fn plain_table(args: &[&str], dir: &std::path::Path) -> String {
+    common::stdout(common::bestls(&[&["--no-color"], args].concat(), dir))
+}
+
+fn names_fixture() -> tempfile::TempDir {
+    common::fixture(NAMES.map(|name| (name, "x")))
+}
+
+#[test]
+fn test_table_rows_have_equal_width() {
+    let dir = names_fixture();
+    for args in [
+        &[][..],
+        &["--style", "ascii"],
+        &["--style", "markdown"],
+        &["--columns", "name", "--wrap", "5"],
+        &["--columns", "name", "--wrap", "1"],
+        &["--max-name-width", "6"],
+    ] {
+        let table = plain_table(args, dir.path());
+        let widths: Vec<usize> = table.lines().map(UnicodeWidthStr::width).collect();
+        assert!(
+            widths.iter().all(|w| *w == widths[0]),
+            "{:?}: {:?}\n{}",
+            args,
+            widths,
+            table
+        );
+    }
+}
+
+#[test]
+fn test_wrapping_keeps_graphemes_whole() {
+    let dir = names_fixture();
+    let table = plain_table(&["--columns", "name", "--wrap", "5"], dir.path());
+    assert!(
+        table.contains("│ 👨\u{200d}👩\u{200d}👧\u{200d}👦.pn │"),
+        "{}",
+        table
+    );
+    assert!(table.contains("│ cafe\u{301}. │"), "{}", table);
+}
+
+#[test]
+fn test_grid_columns_line_up() {
+    let dir = names_fixture();
+    let grid = plain_table(&["--grid", "--width", "40"], dir.path());
+    let lines: Vec<&str> = grid.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", grid);
+    // The second column starts at the same terminal column on both lines
+    let starts: Vec<usize> = lines
+        .iter()
+        .map(|line| {
+            let (first, _) = line.split_once("  ").unwrap();
+            line[first.len()..]
+                .find(|c| c != ' ')
+                .map(|offset| first.width() + offset)
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(starts[0], starts[1], "{}", grid);
+}
@@ -0,0 +1,59 @@
+//! `--watch` redraws when the directory changes and exits cleanly on Ctrl-C
+
+#![cfg(unix)]
+
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Wait until a line containing `needle` arrives
+fn wait_for(lines: &mpsc::Receiver<String>, needle: &str) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+        match lines.recv_timeout(left) {
+            Ok(line) if line.contains(needle) => return true,
+            Ok(_) => {}
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+#[test]
+fn test_new_file_is_listed() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("existing.txt"), "x").unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_bestls"))
+        .args(["--watch", "--compact", "--no-color"])
+        .arg(dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run bestls");
+
+    let stdout = child.stdout.take().unwrap();
+    let (sender, lines) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    assert!(wait_for(&lines, "existing.txt"));
+    std::fs::write(dir.path().join("created.txt"), "x").unwrap();
+    let listed = wait_for(&lines, "created.txt");
+
+    let status = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let exit = child.wait().unwrap();
+    assert!(listed, "the new file was not listed");
+    assert_eq!(exit.code(), Some(130));
+}
@@ -0,0 +1,61 @@
+//! `--format yaml` carries the same documents as the JSON output
+
+mod common;
+
+use common::{bestls_paths, fixture, stdout};
+use serde_json::Value;
+
+const FILES: [(&str, &str); 2] = [("notes: draft.md", "12345"), ("sub/inner.txt", "x")];
+
+fn run_sorted(args: &[&str], dirs: &[&std::path::Path]) -> String {
+    stdout(bestls_paths(&[&["--sort", "name"], args].concat(), dirs))
+}
+
+/// Parse YAML and JSON output of the same listing into comparable values
+fn both(args: &[&str], dirs: &[&std::path::Path]) -> (Value, Value) {
+    let yaml = run_sorted(&[args, &["--format", "yaml"]].concat(), dirs);
+    let json = run_sorted(&[args, &["--format", "json"]].concat(), dirs);
+    (
+        serde_yaml::from_str(&yaml).unwrap(),
+        serde_json::from_str(&json).unwrap(),
+    )
+}
+
+#[test]
+fn test_yaml_fields_match_json() {
+    let dir = fixture(FILES);
+    let (yaml, json) = both(&[], &[dir.path()]);
+    assert_eq!(yaml, json);
+
+    let entries = yaml.as_array().unwrap();
+    assert_eq!(entries[0]["name"], "notes: draft.md");
+    assert_eq!(entries[0]["e_type"], "File");
+    assert_eq!(entries[0]["len_bytes"], 5);
+    assert_eq!(entries[1]["name"], "sub");
+}
+
+#[test]
+fn test_yaml_envelope_has_errors() {
+    let dir = fixture(FILES);
+    let (yaml, json) = both(&["--envelope", "--fields", "name"], &[dir.path()]);
+    assert_eq!(yaml, json);
+    assert_eq!(yaml["errors"], Value::Array(Vec::new()));
+    assert_eq!(
+        yaml["entries"][0],
+        serde_json::json!({ "name": "notes: draft.md" })
+    );
+}
+
+#[test]
+fn test_yaml_several_paths_keyed_by_path() {
+    let dir = fixture(FILES);
+    let sub = dir.path().join("sub");
+    let (yaml, json) = both(&[], &[dir.path(), &sub]);
+    assert_eq!(yaml, json);
+
+    let listings = yaml.as_object().unwrap();
+    let keys: Vec<&String> = listings.keys().collect();
+    assert_eq!(keys.len(), 2);
+    assert!(keys[1].ends_with("sub"));
+    assert_eq!(listings[keys[1]][0]["name"], "inner.txt");
+}